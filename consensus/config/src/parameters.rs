@@ -36,6 +36,21 @@ pub struct Parameters {
     #[serde(default = "Parameters::default_max_forward_time_drift")]
     pub max_forward_time_drift: Duration,
 
+    /// Maximum number of commits fetched from a peer in a single commit sync request. An authority
+    /// falling behind the commits voted by a quorum by more than this number fetches the certified
+    /// commits directly, instead of fetching the missing blocks one dependency at a time.
+    #[serde(default = "Parameters::default_commit_sync_batch_size")]
+    pub commit_sync_batch_size: u32,
+
+    /// Number of rounds below the last committed leader round for which commits and committed
+    /// blocks are kept in the store. Older commits, and blocks that later commits do not need,
+    /// are pruned from the store as new commits are flushed. The retained history must be long
+    /// enough for lagging peers to sync. At least `dag_state_cached_rounds` rounds are retained,
+    /// and commits the commit consumer has not handled yet are never pruned.
+    /// If unspecified, all blocks and commits are kept for the epoch.
+    #[serde(default = "Parameters::default_store_retention_depth")]
+    pub store_retention_depth: Option<u32>,
+
     /// The database path.
     /// Required.
     pub db_path: Option<PathBuf>,
@@ -62,6 +77,14 @@ impl Parameters {
         Duration::from_millis(500)
     }
 
+    pub fn default_commit_sync_batch_size() -> u32 {
+        100
+    }
+
+    pub fn default_store_retention_depth() -> Option<u32> {
+        None
+    }
+
     pub fn db_path_str_unsafe(&self) -> String {
         self.db_path
             .clone()
@@ -80,6 +103,8 @@ impl Default for Parameters {
             leader_timeout: Parameters::default_leader_timeout(),
             min_round_delay: Parameters::default_min_round_delay(),
             max_forward_time_drift: Parameters::default_max_forward_time_drift(),
+            commit_sync_batch_size: Parameters::default_commit_sync_batch_size(),
+            store_retention_depth: Parameters::default_store_retention_depth(),
             db_path: None,
             anemo: AnemoParameters::default(),
         }
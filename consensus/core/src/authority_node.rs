@@ -15,6 +15,7 @@ use crate::{
     block_verifier::SignedBlockVerifier,
     broadcaster::Broadcaster,
     commit_observer::CommitObserver,
+    commit_syncer::{CommitSyncer, CommitSyncerHandle, CommitVoteMonitor},
     context::Context,
     core::{Core, CoreSignals},
     core_thread::{ChannelCoreThreadDispatcher, CoreThreadHandle},
@@ -121,6 +122,7 @@ where
     start_time: Instant,
     transaction_client: Arc<TransactionClient>,
     synchronizer: Arc<SynchronizerHandle>,
+    commit_syncer: CommitSyncerHandle,
     leader_timeout_handle: LeaderTimeoutTaskHandle,
     core_thread_handle: CoreThreadHandle,
     // Not created when using block streaming.
@@ -176,7 +178,10 @@ where
         ));
 
         let store = Arc::new(RocksDBStore::new(&context.parameters.db_path_str_unsafe()));
-        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store.clone())));
+        let dag_state = Arc::new(RwLock::new(
+            DagState::new(context.clone(), store.clone())
+                .with_commit_consumer_monitor(commit_consumer.monitor()),
+        ));
 
         let block_verifier = Arc::new(SignedBlockVerifier::new(
             context.clone(),
@@ -186,8 +191,12 @@ where
         let block_manager =
             BlockManager::new(context.clone(), dag_state.clone(), block_verifier.clone());
 
-        let commit_observer =
-            CommitObserver::new(context.clone(), commit_consumer, dag_state.clone(), store);
+        let commit_observer = CommitObserver::new(
+            context.clone(),
+            commit_consumer,
+            dag_state.clone(),
+            store.clone(),
+        );
 
         let core = Core::new(
             context.clone(),
@@ -206,19 +215,32 @@ where
             LeaderTimeoutTask::start(core_dispatcher.clone(), &signals_receivers, context.clone());
 
         let synchronizer = Synchronizer::start(
-            network_client,
+            network_client.clone(),
             context.clone(),
             core_dispatcher.clone(),
             block_verifier.clone(),
         );
 
+        let commit_vote_monitor = Arc::new(CommitVoteMonitor::new(context.clone()));
+        let commit_syncer = CommitSyncer::new(
+            context.clone(),
+            network_client,
+            block_verifier.clone(),
+            core_dispatcher.clone(),
+            dag_state.clone(),
+            commit_vote_monitor.clone(),
+        )
+        .start();
+
         let network_service = Arc::new(AuthorityService::new(
             context.clone(),
             block_verifier,
             synchronizer.clone(),
             core_dispatcher,
             tx_block_broadcast,
+            commit_vote_monitor,
             dag_state,
+            store,
         ));
         network_manager
             .install_service(network_keypair, network_service)
@@ -229,6 +251,7 @@ where
             start_time,
             transaction_client: Arc::new(tx_client),
             synchronizer,
+            commit_syncer,
             leader_timeout_handle,
             core_thread_handle,
             broadcaster,
@@ -249,6 +272,7 @@ where
         self.core_thread_handle.stop().await;
         self.leader_timeout_handle.stop().await;
         self.synchronizer.stop().await;
+        self.commit_syncer.stop().await;
 
         self.context
             .metrics
@@ -262,9 +286,13 @@ where
     }
 }
 
+#[cfg(test)]
+#[path = "tests/simulation_tests.rs"]
+mod simulation_tests;
+
 #[cfg(test)]
 mod tests {
-    use std::{collections::BTreeSet, sync::Arc, time::Duration};
+    use std::{collections::BTreeSet, ops::Range, sync::Arc, time::Duration};
 
     use async_trait::async_trait;
     use bytes::Bytes;
@@ -284,6 +312,7 @@ mod tests {
         authority_node::AuthorityService,
         block::{timestamp_utc_ms, BlockAPI as _, BlockRef, Round, TestBlock, VerifiedBlock},
         block_verifier::NoopBlockVerifier,
+        commit::CommitIndex,
         context::Context,
         core_thread::{CoreError, CoreThreadDispatcher},
         error::ConsensusResult,
@@ -361,6 +390,14 @@ mod tests {
         ) -> ConsensusResult<Vec<Bytes>> {
             unimplemented!("Unimplemented")
         }
+        async fn fetch_commits(
+            &self,
+            _peer: AuthorityIndex,
+            _commit_range: Range<CommitIndex>,
+            _timeout: Duration,
+        ) -> ConsensusResult<(Vec<Bytes>, Vec<Bytes>)> {
+            unimplemented!("Unimplemented")
+        }
     }
 
     #[rstest]
@@ -415,20 +452,23 @@ mod tests {
         let (tx_block_broadcast, _rx_block_broadcast) = broadcast::channel(100);
         let network_client = Arc::new(FakeNetworkClient::default());
         let store = Arc::new(MemStore::new());
-        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store.clone())));
         let synchronizer = Synchronizer::start(
             network_client,
             context.clone(),
             core_dispatcher.clone(),
             block_verifier.clone(),
         );
+        let commit_vote_monitor = Arc::new(CommitVoteMonitor::new(context.clone()));
         let authority_service = Arc::new(AuthorityService::new(
             context.clone(),
             block_verifier,
             synchronizer,
             core_dispatcher.clone(),
             tx_block_broadcast,
+            commit_vote_monitor,
             dag_state,
+            store,
         ));
 
         // Test delaying blocks with time drift.
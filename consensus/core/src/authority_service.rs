@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
+    ops::Range,
     pin::{pin, Pin},
     sync::Arc,
     time::Duration,
@@ -18,11 +19,14 @@ use tracing::{info, warn};
 use crate::{
     block::{timestamp_utc_ms, BlockAPI as _, BlockRef, SignedBlock, VerifiedBlock},
     block_verifier::BlockVerifier,
+    commit::{CommitIndex, TrustedCommit},
+    commit_syncer::{load_certified_commits, CommitVoteMonitor},
     context::Context,
     core_thread::CoreThreadDispatcher,
     dag_state::DagState,
     error::{ConsensusError, ConsensusResult},
     network::{BlockStream, NetworkService},
+    storage::Store,
     synchronizer::SynchronizerHandle,
     Round,
 };
@@ -34,17 +38,22 @@ pub(crate) struct AuthorityService<C: CoreThreadDispatcher> {
     synchronizer: Arc<SynchronizerHandle>,
     core_dispatcher: Arc<C>,
     tx_block_broadcaster: broadcast::Sender<VerifiedBlock>,
+    commit_vote_monitor: Arc<CommitVoteMonitor>,
     dag_state: Arc<RwLock<DagState>>,
+    store: Arc<dyn Store>,
 }
 
 impl<C: CoreThreadDispatcher> AuthorityService<C> {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         context: Arc<Context>,
         block_verifier: Arc<dyn BlockVerifier>,
         synchronizer: Arc<SynchronizerHandle>,
         core_dispatcher: Arc<C>,
         tx_block_broadcaster: broadcast::Sender<VerifiedBlock>,
+        commit_vote_monitor: Arc<CommitVoteMonitor>,
         dag_state: Arc<RwLock<DagState>>,
+        store: Arc<dyn Store>,
     ) -> Self {
         Self {
             context,
@@ -52,7 +61,9 @@ impl<C: CoreThreadDispatcher> AuthorityService<C> {
             synchronizer,
             core_dispatcher,
             tx_block_broadcaster,
+            commit_vote_monitor,
             dag_state,
+            store,
         }
     }
 
@@ -92,6 +103,9 @@ impl<C: CoreThreadDispatcher> AuthorityService<C> {
         }
         let verified_block = VerifiedBlock::new_verified(signed_block, serialized_block);
 
+        // Commit votes tell whether this authority is falling behind.
+        self.commit_vote_monitor.observe(&verified_block);
+
         // Reject block with timestamp too far in the future.
         let forward_time_drift = Duration::from_millis(
             verified_block
@@ -205,6 +219,14 @@ impl<C: CoreThreadDispatcher> NetworkService for AuthorityService<C> {
 
         Ok(result)
     }
+
+    async fn handle_fetch_commits(
+        &self,
+        _peer: AuthorityIndex,
+        commit_range: Range<CommitIndex>,
+    ) -> ConsensusResult<(Vec<TrustedCommit>, Vec<VerifiedBlock>)> {
+        load_certified_commits(&self.context, self.store.as_ref(), commit_range)
+    }
 }
 
 /// Each broadcasted block stream wraps a broadcast receiver for blocks.
@@ -187,7 +187,11 @@ impl Broadcaster {
 
 #[cfg(test)]
 mod test {
-    use std::{collections::BTreeMap, ops::DerefMut, time::Duration};
+    use std::{
+        collections::BTreeMap,
+        ops::{DerefMut, Range},
+        time::Duration,
+    };
 
     use async_trait::async_trait;
     use bytes::Bytes;
@@ -197,6 +201,7 @@ mod test {
     use super::*;
     use crate::{
         block::{BlockRef, TestBlock},
+        commit::CommitIndex,
         core::CoreSignals,
         network::BlockStream,
         Round,
@@ -254,6 +259,14 @@ mod test {
         ) -> ConsensusResult<Vec<Bytes>> {
             unimplemented!("Unimplemented")
         }
+        async fn fetch_commits(
+            &self,
+            _peer: AuthorityIndex,
+            _commit_range: Range<CommitIndex>,
+            _timeout: Duration,
+        ) -> ConsensusResult<(Vec<Bytes>, Vec<Bytes>)> {
+            unimplemented!("Unimplemented")
+        }
     }
 
     #[tokio::test(flavor = "current_thread", start_paused = true)]
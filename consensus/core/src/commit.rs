@@ -5,7 +5,10 @@ use std::{
     fmt::{self, Display, Formatter},
     hash::{Hash, Hasher},
     ops::Deref,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
 };
 
 use bytes::Bytes;
@@ -241,6 +244,12 @@ pub struct CommitRef {
     pub digest: CommitDigest,
 }
 
+impl fmt::Debug for CommitRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "C{}({})", self.index, self.digest)
+    }
+}
+
 /// The output of consensus is an ordered list of [`CommittedSubDag`]. The application
 /// can arbitrarily sort the blocks within each sub-dag (but using a deterministic algorithm).
 #[derive(Clone, PartialEq)]
@@ -348,6 +357,8 @@ pub struct CommitConsumer {
     // First commit in the replayed sequence will have index last_processed_commit_index + 1.
     // Set 0 to replay from the start (as generated commit sequence starts at index = 1).
     pub last_processed_commit_index: CommitIndex,
+    // Progress of the consumer in handling commits, reported by the consumer.
+    monitor: Arc<CommitConsumerMonitor>,
 }
 
 impl CommitConsumer {
@@ -360,8 +371,40 @@ impl CommitConsumer {
             sender,
             last_processed_commit_round,
             last_processed_commit_index,
+            monitor: Arc::new(CommitConsumerMonitor::new(last_processed_commit_index)),
         }
     }
+
+    /// The monitor the consumer reports its progress to.
+    pub fn monitor(&self) -> Arc<CommitConsumerMonitor> {
+        self.monitor.clone()
+    }
+}
+
+/// Tracks the highest commit the consumer has handled, so that commits the consumer may still
+/// need to recover after a restart are not pruned from the store.
+pub struct CommitConsumerMonitor {
+    highest_handled_commit: AtomicU32,
+}
+
+impl CommitConsumerMonitor {
+    pub(crate) fn new(last_handled_commit: CommitIndex) -> Self {
+        Self {
+            highest_handled_commit: AtomicU32::new(last_handled_commit),
+        }
+    }
+
+    /// Index of the highest commit the consumer has handled.
+    pub fn highest_handled_commit(&self) -> CommitIndex {
+        self.highest_handled_commit.load(Ordering::Acquire)
+    }
+
+    /// Reports that the consumer has handled commits up to `commit_index`, and no longer needs
+    /// them replayed after a restart.
+    pub fn set_highest_handled_commit(&self, commit_index: CommitIndex) {
+        self.highest_handled_commit
+            .fetch_max(commit_index, Ordering::AcqRel);
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -0,0 +1,454 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commit synchronization for authorities falling behind.
+//!
+//! Synchronizer fetches missing blocks one layer of ancestors at a time, which is fine for small
+//! gaps but too slow for an authority that fell far behind, e.g. after being offline. CommitSyncer
+//! instead tracks the commits voted by peers in their blocks. When a quorum of authorities voted
+//! for commits beyond the local last commit by more than a batch, it fetches the certified
+//! commits directly from a peer, together with their blocks, and sends the blocks to Core. Core
+//! then derives the same commits locally.
+//!
+//! Fetched commits are verified before their blocks are fetched. Commits must be consecutive and
+//! chained by their digests to the last synced commit, and the last commit must be voted by a
+//! quorum of authorities in the returned certifier blocks. This certifies all the commits.
+
+use std::{collections::BTreeMap, ops::Range, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use consensus_config::AuthorityIndex;
+use parking_lot::{Mutex, RwLock};
+#[cfg(not(test))]
+use rand::{rngs::ThreadRng, seq::SliceRandom};
+use tokio::{
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tracing::{debug, info, warn};
+
+use crate::{
+    block::{BlockAPI as _, BlockRef, SignedBlock, VerifiedBlock},
+    block_verifier::BlockVerifier,
+    commit::{Commit, CommitAPI as _, CommitIndex, CommitRef, TrustedCommit},
+    context::Context,
+    core_thread::CoreThreadDispatcher,
+    dag_state::DagState,
+    ensure,
+    error::{ConsensusError, ConsensusResult},
+    network::NetworkClient,
+    stake_aggregator::{QuorumThreshold, StakeAggregator},
+    storage::Store,
+    synchronizer::MAX_FETCH_BLOCKS_PER_REQUEST,
+};
+
+#[cfg(test)]
+#[path = "tests/commit_syncer_tests.rs"]
+mod commit_syncer_tests;
+
+// How often CommitSyncer checks whether the authority is falling behind.
+const COMMIT_SYNC_INTERVAL: Duration = Duration::from_secs(2);
+
+const FETCH_COMMITS_TIMEOUT: Duration = Duration::from_secs(10);
+
+const FETCH_BLOCKS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tracks the highest commit voted by each authority, from the blocks received from peers.
+pub(crate) struct CommitVoteMonitor {
+    context: Arc<Context>,
+    highest_voted_commits: Mutex<Vec<CommitIndex>>,
+}
+
+impl CommitVoteMonitor {
+    pub(crate) fn new(context: Arc<Context>) -> Self {
+        let highest_voted_commits = Mutex::new(vec![0; context.committee.size()]);
+        Self {
+            context,
+            highest_voted_commits,
+        }
+    }
+
+    /// Keeps track of the commit votes in a verified block.
+    pub(crate) fn observe(&self, block: &VerifiedBlock) {
+        let Some(highest_vote) = block.commit_votes().iter().map(|vote| vote.index).max() else {
+            return;
+        };
+        let mut highest_voted_commits = self.highest_voted_commits.lock();
+        let highest = &mut highest_voted_commits[block.author()];
+        *highest = (*highest).max(highest_vote);
+    }
+
+    /// Returns the highest commit index such that a quorum of authorities voted for this commit or
+    /// a later one. Commits up to this index exist on at least one honest authority.
+    pub(crate) fn quorum_commit_index(&self) -> CommitIndex {
+        let highest_voted_commits = self.highest_voted_commits.lock();
+        let mut votes: Vec<_> = highest_voted_commits
+            .iter()
+            .zip(self.context.committee.authorities())
+            .map(|(index, (authority, _))| (*index, authority))
+            .collect();
+        votes.sort_by(|a, b| b.cmp(a));
+        let mut aggregator = StakeAggregator::<QuorumThreshold>::new();
+        for (index, authority) in votes {
+            if aggregator.add(authority, &self.context.committee) {
+                return index;
+            }
+        }
+        0
+    }
+}
+
+/// Reads the commits in `commit_range` from `store`, up to `commit_sync_batch_size` of them, along
+/// with the blocks certifying the last returned commit. Trailing commits that do not have a quorum
+/// of votes in store yet are not returned, since the requester could not verify them.
+pub(crate) fn load_certified_commits(
+    context: &Context,
+    store: &dyn Store,
+    commit_range: Range<CommitIndex>,
+) -> ConsensusResult<(Vec<TrustedCommit>, Vec<VerifiedBlock>)> {
+    ensure!(
+        commit_range.start > 0 && commit_range.start < commit_range.end,
+        ConsensusError::InvalidCommitRange {
+            start: commit_range.start,
+            end: commit_range.end,
+        }
+    );
+    let end = commit_range
+        .end
+        .min(commit_range.start + context.parameters.commit_sync_batch_size);
+    let mut commits = store.scan_commits(commit_range.start..end)?;
+
+    while let Some(last_commit) = commits.last() {
+        let last_commit_ref = last_commit.reference();
+        let vote_refs = store.read_commit_votes(last_commit_ref.index)?;
+        let mut votes = StakeAggregator::<QuorumThreshold>::new();
+        let mut certifier_blocks = vec![];
+        for block in store.read_blocks(&vote_refs)?.into_iter().flatten() {
+            if !block.commit_votes().contains(&last_commit_ref) {
+                continue;
+            }
+            let reached_quorum = votes.add(block.author(), &context.committee);
+            certifier_blocks.push(block);
+            if reached_quorum {
+                return Ok((commits, certifier_blocks));
+            }
+        }
+        commits.pop();
+    }
+    Ok((vec![], vec![]))
+}
+
+pub(crate) struct CommitSyncerHandle {
+    schedule_task: JoinHandle<()>,
+}
+
+impl CommitSyncerHandle {
+    pub(crate) async fn stop(self) {
+        self.schedule_task.abort();
+        let _ = self.schedule_task.await;
+    }
+}
+
+pub(crate) struct CommitSyncer<C: NetworkClient, V: BlockVerifier, D: CoreThreadDispatcher> {
+    context: Arc<Context>,
+    network_client: Arc<C>,
+    block_verifier: Arc<V>,
+    core_dispatcher: Arc<D>,
+    dag_state: Arc<RwLock<DagState>>,
+    commit_vote_monitor: Arc<CommitVoteMonitor>,
+    // The last commit whose blocks have been sent to Core. It can be ahead of the last local
+    // commit, while Core is processing the blocks.
+    synced_commit: CommitRef,
+}
+
+impl<C: NetworkClient, V: BlockVerifier, D: CoreThreadDispatcher> CommitSyncer<C, V, D> {
+    pub(crate) fn new(
+        context: Arc<Context>,
+        network_client: Arc<C>,
+        block_verifier: Arc<V>,
+        core_dispatcher: Arc<D>,
+        dag_state: Arc<RwLock<DagState>>,
+        commit_vote_monitor: Arc<CommitVoteMonitor>,
+    ) -> Self {
+        Self {
+            context,
+            network_client,
+            block_verifier,
+            core_dispatcher,
+            dag_state,
+            commit_vote_monitor,
+            synced_commit: CommitRef::default(),
+        }
+    }
+
+    pub(crate) fn start(self) -> CommitSyncerHandle {
+        let schedule_task = tokio::spawn(self.run());
+        CommitSyncerHandle { schedule_task }
+    }
+
+    async fn run(mut self) {
+        let mut interval = interval(COMMIT_SYNC_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.try_sync_commits().await {
+                if matches!(e, ConsensusError::Shutdown) {
+                    info!("Core is shutting down, commit syncer is shutting down.");
+                    return;
+                }
+                warn!("Failed to sync commits: {e}");
+            }
+        }
+    }
+
+    /// Fetches the next batch of certified commits if the authority is behind the commits voted
+    /// by a quorum by more than a batch. Returns the number of synced commits.
+    pub(crate) async fn try_sync_commits(&mut self) -> ConsensusResult<usize> {
+        {
+            let dag_state = self.dag_state.read();
+            if dag_state.last_commit_index() >= self.synced_commit.index {
+                self.synced_commit = CommitRef {
+                    index: dag_state.last_commit_index(),
+                    digest: dag_state.last_commit_digest(),
+                };
+            }
+        }
+        let quorum_commit_index = self.commit_vote_monitor.quorum_commit_index();
+        self.context
+            .metrics
+            .node_metrics
+            .commit_sync_quorum_index
+            .set(quorum_commit_index as i64);
+
+        let batch_size = self.context.parameters.commit_sync_batch_size;
+        if quorum_commit_index <= self.synced_commit.index + batch_size {
+            return Ok(0);
+        }
+        let commit_range = self.synced_commit.index + 1..self.synced_commit.index + batch_size + 1;
+
+        #[allow(unused_mut)]
+        let mut peers: Vec<_> = self
+            .context
+            .committee
+            .authorities()
+            .filter_map(|(peer, _)| (peer != self.context.own_index).then_some(peer))
+            .collect();
+        cfg_if::cfg_if! {
+            if #[cfg(not(test))] {
+                peers.shuffle(&mut ThreadRng::default());
+            }
+        }
+
+        for peer in peers {
+            let (commits, blocks) = match self
+                .fetch_certified_commits(peer, commit_range.clone())
+                .await
+            {
+                Ok((commits, _)) if commits.is_empty() => continue,
+                Ok(result) => result,
+                Err(e) => {
+                    warn!("Failed to fetch commits {commit_range:?} from {peer}: {e}");
+                    continue;
+                }
+            };
+            let last_commit = commits.last().expect("Commits are not empty").reference();
+            debug!(
+                "Synced commits {}..={} with {} blocks from {peer}",
+                commit_range.start,
+                last_commit.index,
+                blocks.len()
+            );
+            // Missing ancestors of certifier blocks are left to Synchronizer.
+            let _missing_blocks = self
+                .core_dispatcher
+                .add_blocks(blocks)
+                .await
+                .map_err(|_| ConsensusError::Shutdown)?;
+            self.synced_commit = last_commit;
+            return Ok(commits.len());
+        }
+        Ok(0)
+    }
+
+    /// Fetches certified commits in `commit_range` from `peer`, following the last synced commit,
+    /// and fetches their blocks missing locally. Returns the verified commits, and blocks in commit
+    /// order followed by the certifier blocks.
+    pub(crate) async fn fetch_certified_commits(
+        &self,
+        peer: AuthorityIndex,
+        commit_range: Range<CommitIndex>,
+    ) -> ConsensusResult<(Vec<TrustedCommit>, Vec<VerifiedBlock>)> {
+        let (serialized_commits, serialized_certifier_blocks) = self
+            .network_client
+            .fetch_commits(peer, commit_range.clone(), FETCH_COMMITS_TIMEOUT)
+            .await?;
+        let (commits, certifier_blocks) = self.verify_commits(
+            peer,
+            commit_range,
+            serialized_commits,
+            serialized_certifier_blocks,
+        )?;
+        if commits.is_empty() {
+            return Ok((commits, certifier_blocks));
+        }
+        self.context
+            .metrics
+            .node_metrics
+            .fetched_commits
+            .with_label_values(&[&peer.to_string()])
+            .inc_by(commits.len() as u64);
+
+        let block_refs: Vec<BlockRef> = commits
+            .iter()
+            .flat_map(|commit| commit.blocks().iter().copied())
+            .collect();
+        let exist = self.dag_state.read().contains_blocks(block_refs.clone());
+        let missing_refs: Vec<BlockRef> = block_refs
+            .iter()
+            .zip(exist)
+            .filter_map(|(block_ref, exist)| (!exist).then_some(*block_ref))
+            .collect();
+        let mut fetched_blocks = BTreeMap::new();
+        for chunk in missing_refs.chunks(MAX_FETCH_BLOCKS_PER_REQUEST) {
+            let serialized_blocks = self
+                .network_client
+                .fetch_blocks(peer, chunk.to_vec(), FETCH_BLOCKS_TIMEOUT)
+                .await?;
+            for block in self.verify_blocks(peer, chunk, serialized_blocks)? {
+                fetched_blocks.insert(block.reference(), block);
+            }
+        }
+        self.context
+            .metrics
+            .node_metrics
+            .fetched_blocks
+            .with_label_values(&[&peer.to_string(), "commit_sync"])
+            .inc_by(fetched_blocks.len() as u64);
+
+        let mut blocks = Vec::with_capacity(fetched_blocks.len() + certifier_blocks.len());
+        for block_ref in &missing_refs {
+            let block =
+                fetched_blocks
+                    .remove(block_ref)
+                    .ok_or(ConsensusError::MissingFetchedBlock {
+                        peer,
+                        block_ref: *block_ref,
+                    })?;
+            blocks.push(block);
+        }
+        blocks.extend(certifier_blocks);
+        Ok((commits, blocks))
+    }
+
+    /// Verifies that the commits fetched from `peer` are consecutive from the start of
+    /// `commit_range`, follow the last synced commit, and that the last one is certified by a
+    /// quorum of votes in the certifier blocks.
+    fn verify_commits(
+        &self,
+        peer: AuthorityIndex,
+        commit_range: Range<CommitIndex>,
+        serialized_commits: Vec<Bytes>,
+        serialized_certifier_blocks: Vec<Bytes>,
+    ) -> ConsensusResult<(Vec<TrustedCommit>, Vec<VerifiedBlock>)> {
+        ensure!(
+            serialized_commits.len() <= commit_range.len(),
+            ConsensusError::TooManyFetchedCommitsReturned(peer)
+        );
+        let mut commits = Vec::with_capacity(serialized_commits.len());
+        let mut previous_digest = self.synced_commit.digest;
+        for (expected_index, serialized) in commit_range.zip(serialized_commits) {
+            let commit: Commit =
+                bcs::from_bytes(&serialized).map_err(ConsensusError::MalformedCommit)?;
+            ensure!(
+                commit.index() == expected_index,
+                ConsensusError::UnexpectedFetchedCommit {
+                    peer,
+                    expected: expected_index,
+                    actual: commit.index(),
+                }
+            );
+            ensure!(
+                commit.previous_digest() == previous_digest,
+                ConsensusError::UnexpectedPreviousCommitDigest {
+                    index: commit.index(),
+                    expected: previous_digest,
+                    actual: commit.previous_digest(),
+                }
+            );
+            // The commit is only trusted once the whole chain is certified below.
+            let commit = TrustedCommit::new_trusted(commit, serialized);
+            previous_digest = commit.digest();
+            commits.push(commit);
+        }
+        let Some(last_commit) = commits.last() else {
+            return Ok((vec![], vec![]));
+        };
+
+        let last_commit_ref = last_commit.reference();
+        let committee = &self.context.committee;
+        let mut votes = StakeAggregator::<QuorumThreshold>::new();
+        let mut certifier_blocks = Vec::with_capacity(serialized_certifier_blocks.len());
+        for serialized in serialized_certifier_blocks {
+            let block = self.verify_block(peer, serialized)?;
+            if block.commit_votes().contains(&last_commit_ref) {
+                votes.add(block.author(), committee);
+            }
+            certifier_blocks.push(block);
+        }
+        ensure!(
+            votes.reached_threshold(committee),
+            ConsensusError::InsufficientCommitVotes {
+                commit: last_commit_ref,
+                stake: votes.stake(),
+                quorum: committee.quorum_threshold(),
+            }
+        );
+        Ok((commits, certifier_blocks))
+    }
+
+    /// Verifies the blocks fetched from `peer` for `requested_refs`.
+    fn verify_blocks(
+        &self,
+        peer: AuthorityIndex,
+        requested_refs: &[BlockRef],
+        serialized_blocks: Vec<Bytes>,
+    ) -> ConsensusResult<Vec<VerifiedBlock>> {
+        ensure!(
+            serialized_blocks.len() <= requested_refs.len(),
+            ConsensusError::TooManyFetchedBlocksReturned(peer)
+        );
+        let mut blocks = Vec::with_capacity(serialized_blocks.len());
+        for serialized in serialized_blocks {
+            let block = self.verify_block(peer, serialized)?;
+            ensure!(
+                requested_refs.contains(&block.reference()),
+                ConsensusError::UnexpectedFetchedBlock {
+                    index: peer,
+                    block_ref: block.reference(),
+                }
+            );
+            blocks.push(block);
+        }
+        Ok(blocks)
+    }
+
+    fn verify_block(
+        &self,
+        peer: AuthorityIndex,
+        serialized: Bytes,
+    ) -> ConsensusResult<VerifiedBlock> {
+        let signed_block: SignedBlock =
+            bcs::from_bytes(&serialized).map_err(ConsensusError::MalformedBlock)?;
+        if let Err(e) = self.block_verifier.verify(&signed_block) {
+            self.context
+                .metrics
+                .node_metrics
+                .invalid_blocks
+                .with_label_values(&[&signed_block.author().to_string(), "commit_syncer"])
+                .inc();
+            warn!("Invalid block received from {}: {}", peer, e);
+            return Err(e);
+        }
+        Ok(VerifiedBlock::new_verified(signed_block, serialized))
+    }
+}
@@ -21,10 +21,12 @@ use crate::{
         Slot, VerifiedBlock, GENESIS_ROUND,
     },
     block_manager::BlockManager,
+    commit::LeaderStatus,
     commit_observer::CommitObserver,
     context::Context,
     dag_state::DagState,
     error::{ConsensusError, ConsensusResult},
+    leader_schedule::LeaderSchedule,
     threshold_clock::ThresholdClock,
     transaction::TransactionConsumer,
     universal_committer::{
@@ -51,6 +53,9 @@ pub(crate) struct Core {
     block_manager: BlockManager,
     /// Used to make commit decisions for leader blocks in the dag.
     committer: UniversalCommitter,
+    /// The leader schedule shared with the committer, updated from the reputation scores of
+    /// authorities in commits.
+    leader_schedule: LeaderSchedule,
     /// The last produced block
     last_proposed_block: VerifiedBlock,
     /// The blocks of the last included ancestors per authority. This vector is basically used as a
@@ -85,7 +90,9 @@ impl Core {
     ) -> Self {
         let last_decided_leader = dag_state.read().last_commit_leader();
 
+        let leader_schedule = LeaderSchedule::from_store(context.clone(), dag_state.clone());
         let committer = UniversalCommitterBuilder::new(context.clone(), dag_state.clone())
+            .with_leader_schedule(leader_schedule.clone())
             .with_number_of_leaders(NUM_LEADERS_PER_ROUND)
             .with_pipeline(true)
             .build();
@@ -116,6 +123,7 @@ impl Core {
             last_included_ancestors,
             block_manager,
             committer,
+            leader_schedule,
             last_decided_leader,
             commit_observer,
             signals,
@@ -347,24 +355,58 @@ impl Core {
             .with_label_values(&["Core::try_commit"])
             .start_timer();
 
-        // TODO: Add optimization to abort early without quorum for a round.
-        let sequenced_leaders = self.committer.try_commit(self.last_decided_leader);
+        let mut committed_sub_dags = Vec::new();
+        loop {
+            // Leaders after the next leader schedule update need to be elected with the updated
+            // schedule, so only commit up to the update, then update the schedule and try again.
+            let last_commit_index = self.dag_state.read().last_commit_index();
+            let commits_until_update = self
+                .leader_schedule
+                .commits_until_leader_schedule_update(last_commit_index);
+            if commits_until_update == 0 {
+                self.leader_schedule
+                    .update_leader_schedule(last_commit_index);
+                continue;
+            }
+
+            // TODO: Add optimization to abort early without quorum for a round.
+            let mut sequenced_leaders = self.committer.try_commit(self.last_decided_leader);
+
+            let mut num_commits = 0;
+            if let Some(pos) = sequenced_leaders.iter().position(|leader| {
+                if matches!(leader, LeaderStatus::Commit(_)) {
+                    num_commits += 1;
+                }
+                num_commits == commits_until_update
+            }) {
+                sequenced_leaders.truncate(pos + 1);
+            }
 
-        if let Some(last) = sequenced_leaders.last() {
+            let Some(last) = sequenced_leaders.last() else {
+                break;
+            };
             self.last_decided_leader = last.get_decided_slot();
             self.context
                 .metrics
                 .node_metrics
                 .last_decided_leader_round
                 .set(self.last_decided_leader.round as i64);
-        }
 
-        let committed_leaders = sequenced_leaders
-            .into_iter()
-            .filter_map(|leader| leader.into_committed_block())
-            .collect::<Vec<_>>();
+            let committed_leaders = sequenced_leaders
+                .into_iter()
+                .filter_map(|leader| leader.into_committed_block())
+                .collect::<Vec<_>>();
+
+            let sub_dags = self.commit_observer.handle_commit(committed_leaders)?;
+            self.leader_schedule.add_committed_sub_dags(&sub_dags);
+            committed_sub_dags.extend(sub_dags);
+
+            if num_commits < commits_until_update {
+                break;
+            }
+        }
 
-        self.commit_observer.handle_commit(committed_leaders)
+        Ok(committed_sub_dags)
     }
 
     pub(crate) fn get_missing_blocks(&self) -> BTreeSet<BlockRef> {
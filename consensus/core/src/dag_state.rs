@@ -4,7 +4,10 @@
 use std::{
     cmp::max,
     collections::{BTreeMap, BTreeSet, VecDeque},
-    ops::Bound::{Excluded, Included, Unbounded},
+    ops::{
+        Bound::{Excluded, Included, Unbounded},
+        Range,
+    },
     panic,
     sync::Arc,
 };
@@ -16,8 +19,13 @@ use crate::block::GENESIS_ROUND;
 use crate::stake_aggregator::{QuorumThreshold, StakeAggregator};
 use crate::{
     block::{genesis_blocks, BlockAPI, BlockDigest, BlockRef, Round, Slot, VerifiedBlock},
-    commit::{CommitAPI as _, CommitDigest, CommitIndex, CommitRef, TrustedCommit},
+    commit::{
+        load_committed_subdag_from_store, CommitAPI as _, CommitConsumerMonitor, CommitDigest,
+        CommitIndex, CommitRef, CommittedSubDag, TrustedCommit,
+    },
     context::Context,
+    error::{ConsensusError, ConsensusResult},
+    leader_schedule::LeaderSchedule,
     storage::{Store, WriteBatch},
 };
 
@@ -26,6 +34,15 @@ use crate::{
 /// The rest of blocks are stored on disk.
 /// Refs to cached blocks and additional refs are cached as well, to speed up existence checks.
 ///
+/// Uncommitted blocks at or below the GC round, `dag_state_cached_rounds` below the last commit
+/// round, are also dropped from memory, except the last block of each authority. The linearizer
+/// only reaches them for blocks of authorities lagging far behind, and reads them from the store.
+///
+/// When `store_retention_depth` is set, commits and blocks that fall out of the retention depth
+/// are pruned from the store after flushes. Blocks of an authority are only pruned up to its last
+/// committed round as of the last pruned commit, so the remaining commits can still be loaded.
+/// Commits are only pruned once the commit consumer has handled them.
+///
 /// Note: DagState should be wrapped with Arc<parking_lot::RwLock<_>>, to allow
 /// concurrent access from multiple components.
 pub(crate) struct DagState {
@@ -65,6 +82,14 @@ pub(crate) struct DagState {
     // Persistent storage for blocks, commits and other consensus data.
     store: Arc<dyn Store>,
 
+    // Flushed commits that can be pruned from store once they fall out of the retention depth,
+    // with their leader rounds and the last committed rounds after them.
+    // Does not persist across restarts.
+    pruning_candidates: VecDeque<(CommitIndex, Round, Vec<Round>)>,
+
+    // Progress of the commit consumer. Nothing is pruned from the store without it.
+    commit_consumer_monitor: Option<Arc<CommitConsumerMonitor>>,
+
     // The number of cached rounds
     cached_rounds: Round,
 }
@@ -107,6 +132,8 @@ impl DagState {
             blocks_to_write: vec![],
             commits_to_write: vec![],
             store,
+            pruning_candidates: VecDeque::new(),
+            commit_consumer_monitor: None,
             cached_rounds,
         };
 
@@ -120,10 +147,21 @@ impl DagState {
                 state.update_block_metadata(&block);
             }
         }
+        state.evict_blocks();
 
         state
     }
 
+    /// Limits pruning of the store to the commits handled by the commit consumer, which may
+    /// otherwise need them replayed after a restart.
+    pub(crate) fn with_commit_consumer_monitor(
+        mut self,
+        commit_consumer_monitor: Arc<CommitConsumerMonitor>,
+    ) -> Self {
+        self.commit_consumer_monitor = Some(commit_consumer_monitor);
+        self
+    }
+
     /// Accepts a block into DagState and keeps it in memory.
     pub(crate) fn accept_block(&mut self, block: VerifiedBlock) {
         assert_ne!(
@@ -563,6 +601,25 @@ impl DagState {
         }
     }
 
+    /// Reads the committed sub-dags in `commit_range` from storage. Fails if some commits in
+    /// the range are not in storage, e.g. because they were not flushed or were pruned.
+    pub(crate) fn read_committed_sub_dags(
+        &self,
+        commit_range: Range<CommitIndex>,
+    ) -> ConsensusResult<Vec<CommittedSubDag>> {
+        let commits = self.store.scan_commits(commit_range.clone())?;
+        if commits.len() != commit_range.len() {
+            return Err(ConsensusError::MissingCommits {
+                start: commit_range.start,
+                end: commit_range.end,
+            });
+        }
+        Ok(commits
+            .into_iter()
+            .map(|commit| load_committed_subdag_from_store(self.store.as_ref(), commit))
+            .collect())
+    }
+
     /// Last committed round per authority.
     pub(crate) fn last_committed_rounds(&self) -> Vec<Round> {
         self.last_committed_rounds.clone()
@@ -584,6 +641,9 @@ impl DagState {
         if blocks.is_empty() && commits.is_empty() {
             return;
         }
+        let last_flushed_commit = commits
+            .last()
+            .map(|commit| (commit.index(), commit.leader().round));
         self.store
             .write(WriteBatch::new(
                 blocks,
@@ -597,15 +657,35 @@ impl DagState {
             .node_metrics
             .dag_state_store_write_count
             .inc();
+        if let Some((commit_index, leader_round)) = last_flushed_commit {
+            self.pruning_candidates.push_back((
+                commit_index,
+                leader_round,
+                self.last_committed_rounds.clone(),
+            ));
+            self.prune_store();
+        }
 
         // Clean up old cached data. After flushing, all cached blocks are guaranteed to be persisted.
+        self.evict_blocks();
+    }
+
+    /// Drops blocks from memory that are committed and more than the cached rounds old, or
+    /// uncommitted and at or below the GC round. The last block of each authority is kept, so
+    /// it is still known which blocks of the authority exist. All blocks in memory must have
+    /// been persisted.
+    fn evict_blocks(&mut self) {
+        let gc_round = self.gc_round();
         for (authority_refs, last_committed_round) in self
             .recent_refs
             .iter_mut()
             .zip(self.last_committed_rounds.iter())
         {
+            let evict_round = Self::evict_round(*last_committed_round, self.cached_rounds);
             while let Some(block_ref) = authority_refs.first() {
-                if block_ref.round <= Self::evict_round(*last_committed_round, self.cached_rounds) {
+                if block_ref.round <= evict_round
+                    || (block_ref.round <= gc_round && authority_refs.len() > 1)
+                {
                     self.recent_blocks.remove(block_ref);
                     authority_refs.pop_first();
                 } else {
@@ -615,6 +695,60 @@ impl DagState {
         }
     }
 
+    /// Prunes the last flushed commit that fell out of the store retention depth, along with
+    /// earlier commits and blocks they committed. Commits that the commit consumer has not
+    /// handled yet, or that are needed to recover the leader schedule, are retained.
+    fn prune_store(&mut self) {
+        let Some(retention_depth) = self.context.parameters.store_retention_depth else {
+            return;
+        };
+        let Some(commit_consumer_monitor) = &self.commit_consumer_monitor else {
+            return;
+        };
+        let retention_round = self
+            .last_commit_round()
+            .saturating_sub(max(retention_depth, self.cached_rounds));
+        let leader_schedule_commit_index = self
+            .last_commit_index()
+            .saturating_sub(LeaderSchedule::update_interval(&self.context).map_or(0, |i| 2 * i));
+        let retention_commit_index = commit_consumer_monitor
+            .highest_handled_commit()
+            .min(leader_schedule_commit_index);
+
+        let mut pruned = None;
+        while let Some((commit_index, leader_round, _)) = self.pruning_candidates.front() {
+            if *commit_index > retention_commit_index || *leader_round > retention_round {
+                break;
+            }
+            pruned = self.pruning_candidates.pop_front();
+        }
+        let Some((last_pruned_commit, _, committed_rounds)) = pruned else {
+            return;
+        };
+
+        // Blocks at or below the committed rounds are not needed by later commits. Blocks within
+        // the cached rounds are still needed to recover DagState.
+        let last_pruned_rounds: Vec<_> = self
+            .context
+            .committee
+            .authorities()
+            .map(|(authority, _)| {
+                (
+                    authority,
+                    committed_rounds[authority].min(self.authority_evict_round(authority)),
+                )
+            })
+            .collect();
+        self.store
+            .prune(last_pruned_commit, &last_pruned_rounds)
+            .unwrap_or_else(|e| panic!("Failed to prune storage: {:?}", e));
+        self.context
+            .metrics
+            .node_metrics
+            .dag_state_store_pruned_commit_index
+            .set(last_pruned_commit as i64);
+    }
+
     /// Detects and returns the blocks of the round that forms the last quorum. The method will return
     /// the quorum even if that's genesis.
     pub(crate) fn last_quorum(&self) -> Vec<VerifiedBlock> {
@@ -652,6 +786,13 @@ impl DagState {
         }
     }
 
+    /// Round at or below which uncommitted blocks are dropped from memory. Leaders are only
+    /// decided above the last commit round, so these blocks are not needed by the committer, and
+    /// the linearizer reads them from the store in the rare case they get committed.
+    fn gc_round(&self) -> Round {
+        Self::evict_round(self.last_commit_round(), self.cached_rounds)
+    }
+
     /// The last round that got evicted after a cache clean up operation. After this round we are
     /// guaranteed to have all the produced blocks from that authority. For any round that is
     /// <= `last_evicted_round` we don't have such guarantees as out of order blocks might exist.
@@ -672,10 +813,15 @@ mod test {
     use parking_lot::RwLock;
     use std::vec;
 
+    use consensus_config::Parameters;
+
     use super::*;
-    use crate::test_dag::build_dag;
+    use crate::test_dag::{build_dag, get_all_leader_blocks};
     use crate::{
         block::{BlockDigest, BlockRef, BlockTimestampMs, TestBlock, VerifiedBlock},
+        commit::DEFAULT_WAVE_LENGTH,
+        leader_schedule::LeaderSchedule,
+        linearizer::Linearizer,
         storage::{mem_store::MemStore, WriteBatch},
     };
 
@@ -1504,4 +1650,171 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_prune_store_and_reproduce_commits_after_restart() {
+        telemetry_subscribers::init_for_testing();
+        let parameters = Parameters {
+            dag_state_cached_rounds: 3,
+            ..Default::default()
+        };
+        let (mut context, _) = Context::new_for_test(4);
+        context
+            .protocol_config
+            .set_mysticeti_leader_scoring_and_schedule_for_testing(true);
+        context
+            .protocol_config
+            .set_mysticeti_leader_schedule_update_interval_for_testing(1);
+        let reference_context = Arc::new(context.clone().with_parameters(parameters.clone()));
+        let context = Arc::new(context.with_parameters(Parameters {
+            store_retention_depth: Some(6),
+            ..parameters
+        }));
+
+        // Commit all leaders of a fully connected dag without pruning, as a reference.
+        let num_rounds = 30;
+        let reference_dag_state = Arc::new(RwLock::new(DagState::new(
+            reference_context.clone(),
+            Arc::new(MemStore::new()),
+        )));
+        build_dag(
+            reference_context.clone(),
+            reference_dag_state.clone(),
+            None,
+            num_rounds,
+        );
+        let leaders = get_all_leader_blocks(
+            reference_dag_state.clone(),
+            LeaderSchedule::new(reference_context.clone()),
+            num_rounds,
+            DEFAULT_WAVE_LENGTH,
+            false,
+            1,
+        );
+        assert_eq!(leaders.len(), 10);
+        let mut linearizer = Linearizer::new(reference_dag_state.clone());
+        let reference_sub_dags: Vec<_> = leaders
+            .iter()
+            .flat_map(|leader| linearizer.handle_commit(vec![leader.clone()]))
+            .collect();
+
+        // Commit the first 6 leaders with pruning, one at a time, while the commit consumer has
+        // only handled the first 2 commits.
+        let store = Arc::new(MemStore::new());
+        let commit_consumer_monitor = Arc::new(CommitConsumerMonitor::new(2));
+        let dag_state = Arc::new(RwLock::new(
+            DagState::new(context.clone(), store.clone())
+                .with_commit_consumer_monitor(commit_consumer_monitor.clone()),
+        ));
+        build_dag(context.clone(), dag_state.clone(), None, num_rounds);
+        let mut linearizer = Linearizer::new(dag_state.clone());
+        for leader in &leaders[..6] {
+            linearizer.handle_commit(vec![leader.clone()]);
+        }
+
+        // Commits not handled by the consumer are retained, even when they fall out of the
+        // retention depth.
+        assert!(store.scan_commits(1..3).unwrap().is_empty());
+        assert_eq!(store.scan_commits(3..7).unwrap().len(), 4);
+
+        // Commit the 7th leader after the consumer has handled all commits.
+        commit_consumer_monitor.set_highest_handled_commit(6);
+        linearizer.handle_commit(vec![leaders[6].clone()]);
+
+        // The last commit at leader round 21 retains commits with leader rounds above 15,
+        // except the last 2 commits needed to recover the leader schedule.
+        assert!(store.scan_commits(1..6).unwrap().is_empty());
+        assert_eq!(store.scan_commits(6..8).unwrap().len(), 2);
+        // Commit 5 committed blocks up to round 14, and its leader at round 15 from authority 3.
+        for (authority, _) in context.committee.authorities() {
+            let first_round = if authority.value() == 3 { 16 } else { 15 };
+            let blocks = store.scan_blocks_by_author(authority, 0).unwrap();
+            assert_eq!(blocks.first().unwrap().round(), first_round);
+        }
+
+        // Restart from the pruned store.
+        let last_committed_rounds = dag_state.read().last_committed_rounds();
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store.clone())));
+        assert_eq!(dag_state.read().last_commit_index(), 7);
+        assert_eq!(
+            dag_state.read().last_committed_rounds(),
+            last_committed_rounds
+        );
+
+        // Retained commits can be reproduced from the store.
+        for commit in store.scan_commits(6..8).unwrap() {
+            let sub_dag = load_committed_subdag_from_store(store.as_ref(), commit.clone());
+            assert_eq!(sub_dag, reference_sub_dags[commit.index() as usize - 1]);
+        }
+
+        // Later commits are the same as without pruning.
+        let mut linearizer = Linearizer::new(dag_state.clone());
+        let sub_dags = linearizer.handle_commit(leaders[7..].to_vec());
+        assert_eq!(sub_dags, reference_sub_dags[7..]);
+    }
+
+    #[test]
+    fn test_evict_uncommitted_blocks_below_gc_round() {
+        const CACHED_ROUNDS: Round = 2;
+        let (mut context, _) = Context::new_for_test(4);
+        context.parameters.dag_state_cached_rounds = CACHED_ROUNDS;
+        let context = Arc::new(context);
+        let store = Arc::new(MemStore::new());
+        let mut dag_state = DagState::new(context.clone(), store.clone());
+
+        // Authority 0 has blocks for rounds 1 ~ 10, which get committed. Authority 1 lags
+        // behind with blocks for rounds 1 ~ 3, which are not committed.
+        let authority_0_blocks: Vec<_> = (1..=10)
+            .map(|round| VerifiedBlock::new_for_test(TestBlock::new(round, 0).build()))
+            .collect();
+        let authority_1_blocks: Vec<_> = (1..=3)
+            .map(|round| VerifiedBlock::new_for_test(TestBlock::new(round, 1).build()))
+            .collect();
+        dag_state.accept_blocks(authority_0_blocks.clone());
+        dag_state.accept_blocks(authority_1_blocks.clone());
+        dag_state.add_commit(TrustedCommit::new_for_test(
+            1 as CommitIndex,
+            CommitDigest::MIN,
+            authority_0_blocks.last().unwrap().reference(),
+            authority_0_blocks
+                .iter()
+                .map(|block| block.reference())
+                .collect::<Vec<_>>(),
+        ));
+        dag_state.flush();
+
+        // The GC round is 10 - 2 = 8. Uncommitted blocks of authority 1 at or below it are
+        // evicted from memory, except its last block.
+        let authority_1 = AuthorityIndex::new_for_test(1);
+        assert_eq!(
+            dag_state.get_cached_blocks(authority_1, 1),
+            authority_1_blocks[2..]
+        );
+        assert_eq!(
+            dag_state.get_last_block_for_authority(authority_1),
+            authority_1_blocks[2]
+        );
+
+        // Evicted blocks can still be read from the store, e.g. by the linearizer.
+        let block_refs: Vec<_> = authority_1_blocks
+            .iter()
+            .map(|block| block.reference())
+            .collect();
+        assert_eq!(dag_state.contains_blocks(block_refs.clone()), vec![true; 3]);
+        assert_eq!(
+            dag_state
+                .get_blocks(&block_refs)
+                .into_iter()
+                .map(|block| block.unwrap())
+                .collect::<Vec<_>>(),
+            authority_1_blocks
+        );
+
+        // The same blocks are evicted after recovery.
+        let dag_state = DagState::new(context.clone(), store.clone());
+        assert_eq!(
+            dag_state.get_cached_blocks(authority_1, 1),
+            authority_1_blocks[2..]
+        );
+    }
 }
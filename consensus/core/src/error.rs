@@ -8,7 +8,10 @@ use fastcrypto::error::FastCryptoError;
 use thiserror::Error;
 use typed_store::TypedStoreError;
 
-use crate::block::{BlockRef, BlockTimestampMs, Round};
+use crate::{
+    block::{BlockRef, BlockTimestampMs, Round},
+    commit::{CommitDigest, CommitIndex, CommitRef},
+};
 
 /// Errors that can occur when processing blocks, reading from storage, or encountering shutdown.
 #[derive(Clone, Debug, Error)]
@@ -46,6 +49,53 @@ pub enum ConsensusError {
     #[error("Too many blocks have been requested from authority {0}")]
     TooManyFetchBlocksRequested(AuthorityIndex),
 
+    #[error("Invalid commit range {start}..{end} requested")]
+    InvalidCommitRange {
+        start: CommitIndex,
+        end: CommitIndex,
+    },
+
+    #[error("Block {actual} is stored under a different reference {key}")]
+    UnexpectedStoredBlock { key: BlockRef, actual: BlockRef },
+
+    #[error("Some commits in range {start}..{end} are not in the store")]
+    MissingCommits {
+        start: CommitIndex,
+        end: CommitIndex,
+    },
+
+    #[error(
+        "Too many commits have been returned from authority {0} when requesting to fetch commits"
+    )]
+    TooManyFetchedCommitsReturned(AuthorityIndex),
+
+    #[error("Unexpected commit {actual} returned by authority {peer}, expected commit {expected}")]
+    UnexpectedFetchedCommit {
+        peer: AuthorityIndex,
+        expected: CommitIndex,
+        actual: CommitIndex,
+    },
+
+    #[error("Commit {index} does not follow the previous commit: expected previous digest {expected:?}, actual {actual:?}")]
+    UnexpectedPreviousCommitDigest {
+        index: CommitIndex,
+        expected: CommitDigest,
+        actual: CommitDigest,
+    },
+
+    #[error("Insufficient stake from votes certifying commit {commit:?}: {stake} < {quorum}")]
+    InsufficientCommitVotes {
+        commit: CommitRef,
+        stake: Stake,
+        quorum: Stake,
+    },
+
+    #[error("Authority {peer} did not return block {block_ref} of the fetched commits")]
+    MissingFetchedBlock {
+        peer: AuthorityIndex,
+        block_ref: BlockRef,
+    },
+
     #[error("Invalid authority index: {index} > {max}")]
     InvalidAuthorityIndex { index: AuthorityIndex, max: usize },
 
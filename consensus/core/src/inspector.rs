@@ -0,0 +1,223 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    ops::Range,
+};
+
+use consensus_config::AuthorityIndex;
+use serde::Serialize;
+
+use crate::{
+    block::{BlockAPI as _, BlockRef, Round, VerifiedBlock},
+    commit::{CommitAPI as _, CommitIndex, TrustedCommit},
+    error::ConsensusResult,
+    storage::{rocksdb_store::RocksDBStore, Store as _},
+};
+
+/// Summary of a block in the consensus store, for printing and exporting.
+/// Block references are formatted as `<author><round>(<digest>)`, with the full digest.
+#[derive(Clone, Debug, Serialize)]
+pub struct BlockSummary {
+    pub reference: String,
+    pub author: AuthorityIndex,
+    pub round: Round,
+    pub timestamp_ms: u64,
+    pub ancestors: Vec<String>,
+    pub num_transactions: usize,
+    pub commit_votes: Vec<CommitIndex>,
+    #[serde(skip)]
+    block_ref: BlockRef,
+    #[serde(skip)]
+    ancestor_refs: Vec<BlockRef>,
+}
+
+impl BlockSummary {
+    fn new(block: &VerifiedBlock) -> Self {
+        Self {
+            reference: format!("{:?}", block.reference()),
+            author: block.author(),
+            round: block.round(),
+            timestamp_ms: block.timestamp_ms(),
+            ancestors: block.ancestors().iter().map(|a| format!("{a:?}")).collect(),
+            num_transactions: block.transactions().len(),
+            commit_votes: block.commit_votes().iter().map(|c| c.index).collect(),
+            block_ref: block.reference(),
+            ancestor_refs: block.ancestors().to_vec(),
+        }
+    }
+
+    /// Returns the block reference with the digest shortened, e.g. `A12(Qx3b)`.
+    pub fn short_reference(&self) -> String {
+        self.block_ref.to_string()
+    }
+}
+
+/// Summary of a commit in the consensus store, with blocks in the linearized order.
+#[derive(Clone, Debug, Serialize)]
+pub struct CommitSummary {
+    pub index: CommitIndex,
+    pub digest: String,
+    pub previous_digest: String,
+    pub leader: String,
+    pub blocks: Vec<String>,
+}
+
+impl CommitSummary {
+    fn new(commit: &TrustedCommit) -> Self {
+        Self {
+            index: commit.index(),
+            digest: format!("{:?}", commit.digest()),
+            previous_digest: format!("{:?}", commit.previous_digest()),
+            leader: format!("{:?}", commit.leader()),
+            blocks: commit.blocks().iter().map(|b| format!("{b:?}")).collect(),
+        }
+    }
+}
+
+/// Read-only access to the consensus store of an authority, for debugging.
+pub struct StoreInspector {
+    store: RocksDBStore,
+}
+
+impl StoreInspector {
+    /// Opens the consensus store at `path` as a secondary instance, so it can be inspected while
+    /// the authority is running.
+    pub fn open(path: &str) -> ConsensusResult<Self> {
+        Ok(Self {
+            store: RocksDBStore::new_read_only(path)?,
+        })
+    }
+
+    /// Returns blocks with rounds in the range, optionally only from `author`, ordered by round
+    /// and author.
+    pub fn blocks(
+        &self,
+        author: Option<u32>,
+        rounds: Range<Round>,
+    ) -> ConsensusResult<Vec<BlockSummary>> {
+        Ok(self
+            .store
+            .scan_blocks_by_rounds(rounds)?
+            .iter()
+            .filter(|b| author.map_or(true, |a| b.author().value() == a as usize))
+            .map(BlockSummary::new)
+            .collect())
+    }
+
+    /// Returns blocks at the slot of `author` and `round` whose full digest starts with
+    /// `digest_prefix`. There can be more than one block per slot when the author equivocates.
+    pub fn find_blocks(
+        &self,
+        author: u32,
+        round: Round,
+        digest_prefix: &str,
+    ) -> ConsensusResult<Vec<BlockSummary>> {
+        Ok(self
+            .blocks(Some(author), round..round + 1)?
+            .into_iter()
+            .filter(|b| format!("{:?}", b.block_ref.digest).starts_with(digest_prefix))
+            .collect())
+    }
+
+    /// Returns `block` and its ancestors in the store down to `depth` rounds below it, ordered
+    /// by descending round. Ancestors pruned from the store are skipped.
+    pub fn ancestry(
+        &self,
+        block: &BlockSummary,
+        depth: Round,
+    ) -> ConsensusResult<Vec<BlockSummary>> {
+        let lowest_round = block.round.saturating_sub(depth);
+        let mut ancestry = vec![block.clone()];
+        let mut visited = BTreeSet::from([block.block_ref]);
+        let mut frontier = block.ancestor_refs.clone();
+        while !frontier.is_empty() {
+            let refs: Vec<BlockRef> = frontier
+                .drain(..)
+                .filter(|r| r.round >= lowest_round && visited.insert(*r))
+                .collect();
+            for ancestor in self.store.read_blocks(&refs)?.into_iter().flatten() {
+                let summary = BlockSummary::new(&ancestor);
+                frontier.extend(summary.ancestor_refs.iter().copied());
+                ancestry.push(summary);
+            }
+        }
+        ancestry.sort_by(|a, b| b.block_ref.cmp(&a.block_ref));
+        Ok(ancestry)
+    }
+
+    /// Returns commits with indices in the range.
+    pub fn commits(&self, range: Range<CommitIndex>) -> ConsensusResult<Vec<CommitSummary>> {
+        Ok(self
+            .store
+            .scan_commits(range)?
+            .iter()
+            .map(CommitSummary::new)
+            .collect())
+    }
+}
+
+/// Renders blocks and their links to ancestors as a graph in the DOT language. Ancestors outside
+/// of `blocks` are omitted, and blocks of the same round are placed at the same rank.
+pub fn blocks_to_dot(blocks: &[BlockSummary]) -> String {
+    let references: BTreeSet<&String> = blocks.iter().map(|b| &b.reference).collect();
+    let mut rounds: BTreeMap<Round, Vec<&BlockSummary>> = BTreeMap::new();
+    for block in blocks {
+        rounds.entry(block.round).or_default().push(block);
+    }
+
+    let mut dot = String::from("digraph dag {\n  rankdir=BT;\n");
+    for (round, blocks) in rounds {
+        let _ = writeln!(dot, "  subgraph round_{round} {{\n    rank=same;");
+        for block in blocks {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\\n{} txns\"];",
+                block.reference,
+                block.short_reference(),
+                block.num_transactions
+            );
+        }
+        dot.push_str("  }\n");
+    }
+    for block in blocks {
+        for ancestor in block.ancestors.iter().filter(|a| references.contains(a)) {
+            let _ = writeln!(dot, "  \"{}\" -> \"{}\";", block.reference, ancestor);
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::TestBlock;
+
+    #[test]
+    fn test_blocks_to_dot() {
+        let parent = VerifiedBlock::new_for_test(TestBlock::new(1, 0).build());
+        let child = VerifiedBlock::new_for_test(
+            TestBlock::new(2, 1)
+                .set_ancestors(vec![
+                    parent.reference(),
+                    BlockRef::new(1, AuthorityIndex::new_for_test(2), Default::default()),
+                ])
+                .build(),
+        );
+        let blocks = vec![BlockSummary::new(&parent), BlockSummary::new(&child)];
+
+        let dot = blocks_to_dot(&blocks);
+
+        assert!(dot.starts_with("digraph dag {"));
+        assert!(dot.contains(&format!(
+            "\"{}\" -> \"{}\";",
+            blocks[1].reference, blocks[0].reference
+        )));
+        // Only one edge, since the other ancestor is not in the graph.
+        assert_eq!(dot.matches("->").count(), 1);
+        assert_eq!(dot.matches("rank=same").count(), 2);
+    }
+}
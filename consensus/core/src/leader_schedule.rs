@@ -1,29 +1,171 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::sync::Arc;
+use std::{
+    collections::{BTreeSet, HashSet},
+    sync::Arc,
+};
 
+use parking_lot::RwLock;
 use rand::{prelude::SliceRandom, rngs::StdRng, SeedableRng};
 
 use consensus_config::AuthorityIndex;
+use tracing::info;
 
-use crate::context::Context;
+use crate::{
+    block::{BlockAPI, BlockRef, Round},
+    commit::{CommitIndex, CommittedSubDag},
+    context::Context,
+    dag_state::DagState,
+};
 
 /// The LeaderSchedule is responsible for producing the leader schedule across
-/// an epoch. For now it is a simple wrapper around Context to provide a leader
-/// for a round deterministically.
-// TODO: complete full leader schedule changes
+/// an epoch. Leaders are elected deterministically per round, based on stake.
+/// When `mysticeti_leader_scoring_and_schedule` is enabled in the protocol config,
+/// every `mysticeti_leader_schedule_update_interval` commits, authorities with the
+/// lowest reputation scores over these commits are swapped out of the schedule for
+/// the authorities with the highest scores.
+///
+/// Clones share the same schedule, so updates are visible to all committers.
 #[derive(Clone)]
 pub(crate) struct LeaderSchedule {
     context: Arc<Context>,
+    /// Number of commits between updates of the schedule, or None if it is never updated.
+    update_interval: Option<CommitIndex>,
+    state: Arc<RwLock<LeaderScheduleState>>,
+}
+
+#[derive(Default)]
+struct LeaderScheduleState {
+    /// The swaps of leaders applied to rounds elected after the last update.
+    leader_swap_table: LeaderSwapTable,
+    /// The reputation scores accumulated since the last update.
+    reputation_scores: ReputationScores,
+    /// Index of the last commit before the last update of the leader schedule.
+    last_update_commit_index: CommitIndex,
 }
 
 impl LeaderSchedule {
     pub fn new(context: Arc<Context>) -> Self {
-        Self { context }
+        let update_interval = Self::update_interval(&context);
+        assert!(
+            update_interval != Some(0),
+            "The leader schedule update interval must be positive"
+        );
+        let reputation_scores = ReputationScores::new(context.committee.size());
+        Self {
+            context,
+            update_interval,
+            state: Arc::new(RwLock::new(LeaderScheduleState {
+                reputation_scores,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Number of commits between updates of the leader schedule, from the protocol config of
+    /// the epoch so that all authorities elect the same leaders. None if the leader schedule
+    /// is not updated from reputation scores.
+    pub(crate) fn update_interval(context: &Context) -> Option<CommitIndex> {
+        let protocol_config = &context.protocol_config;
+        protocol_config
+            .mysticeti_leader_scoring_and_schedule()
+            .then(|| protocol_config.mysticeti_leader_schedule_update_interval() as CommitIndex)
+    }
+
+    /// Recovers the leader schedule from the commits in store. The leader swap table is
+    /// recomputed from the commits before the last update, and the reputation scores from the
+    /// commits after it, so all authorities arrive at the same schedule. Panics if these
+    /// commits can't be read, as the node would otherwise elect different leaders than its peers.
+    pub fn from_store(context: Arc<Context>, dag_state: Arc<RwLock<DagState>>) -> Self {
+        let leader_schedule = Self::new(context);
+        let Some(interval) = leader_schedule.update_interval else {
+            return leader_schedule;
+        };
+        let dag_state = dag_state.read();
+        let last_commit_index = dag_state.last_commit_index();
+        let last_update_commit_index = last_commit_index - last_commit_index % interval;
+        let first_commit_index = last_update_commit_index.saturating_sub(interval) + 1;
+
+        let sub_dags = dag_state
+            .read_committed_sub_dags(first_commit_index..last_commit_index + 1)
+            .unwrap_or_else(|e| panic!("Failed to recover the leader schedule: {:?}", e));
+        let (before_update, after_update) =
+            sub_dags.split_at((last_update_commit_index + 1 - first_commit_index) as usize);
+        if last_update_commit_index > 0 {
+            leader_schedule.add_committed_sub_dags(before_update);
+            leader_schedule.update_leader_schedule(last_update_commit_index);
+        }
+        leader_schedule.add_committed_sub_dags(after_update);
+        leader_schedule
+    }
+
+    /// Returns the number of commits that can still be made before the leader schedule
+    /// needs to be updated. Leaders after them must be elected with the updated schedule.
+    /// Unbounded if the leader schedule is never updated.
+    pub(crate) fn commits_until_leader_schedule_update(
+        &self,
+        last_commit_index: CommitIndex,
+    ) -> usize {
+        let Some(interval) = self.update_interval else {
+            return usize::MAX;
+        };
+        let last_update_commit_index = self.state.read().last_update_commit_index;
+        (last_update_commit_index + interval).saturating_sub(last_commit_index) as usize
+    }
+
+    /// Accumulates the reputation scores from newly committed sub-dags.
+    pub(crate) fn add_committed_sub_dags(&self, sub_dags: &[CommittedSubDag]) {
+        let mut state = self.state.write();
+        for sub_dag in sub_dags {
+            state.reputation_scores.add_committed_sub_dag(sub_dag);
+        }
+    }
+
+    /// Updates the leader swap table from the reputation scores accumulated since the last
+    /// update, which must have covered exactly the commits up to `last_commit_index`.
+    pub(crate) fn update_leader_schedule(&self, last_commit_index: CommitIndex) {
+        let mut state = self.state.write();
+        let leader_swap_table = LeaderSwapTable::new(
+            &self.context,
+            &state.reputation_scores,
+            self.context
+                .protocol_config
+                .consensus_bad_nodes_stake_threshold(),
+        );
+        info!(
+            "Updating leader schedule at commit {last_commit_index} with scores {:?}: {leader_swap_table:?}",
+            state.reputation_scores.scores_per_authority
+        );
+        for (authority, _) in self.context.committee.authorities() {
+            self.context
+                .metrics
+                .node_metrics
+                .leader_reputation_scores
+                .with_label_values(&[&authority.to_string()])
+                .set(state.reputation_scores.scores_per_authority[authority] as i64);
+        }
+        self.context
+            .metrics
+            .node_metrics
+            .leader_swap_table_bad_nodes
+            .set(leader_swap_table.bad_nodes.len() as i64);
+
+        state.leader_swap_table = leader_swap_table;
+        state.reputation_scores = ReputationScores::new(self.context.committee.size());
+        state.last_update_commit_index = last_commit_index;
     }
 
     pub fn elect_leader(&self, round: u32, leader_offset: u32) -> AuthorityIndex {
+        let leader = self.elect_leader_without_swaps(round, leader_offset);
+        self.state
+            .read()
+            .leader_swap_table
+            .swap(leader, round, leader_offset)
+            .unwrap_or(leader)
+    }
+
+    fn elect_leader_without_swaps(&self, round: u32, leader_offset: u32) -> AuthorityIndex {
         cfg_if::cfg_if! {
             // TODO: we need to differentiate the leader strategy in tests, so for
             // some type of testing (ex sim tests) we can use the staked approach.
@@ -65,13 +207,201 @@ impl LeaderSchedule {
     }
 }
 
+/// Reputation scores of authorities over a window of commits. An authority scores a point
+/// for each of its committed blocks voting for a leader committed in the same window, i.e.
+/// linking to the leader from the round right after it.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub(crate) struct ReputationScores {
+    /// Score per authority, indexed by authority index.
+    pub(crate) scores_per_authority: Vec<u64>,
+    /// Leaders committed in the window.
+    committed_leaders: HashSet<BlockRef>,
+}
+
+impl ReputationScores {
+    pub(crate) fn new(committee_size: usize) -> Self {
+        Self {
+            scores_per_authority: vec![0; committee_size],
+            committed_leaders: HashSet::new(),
+        }
+    }
+
+    /// Scores the votes in a committed sub-dag. Sub-dags must be added in commit order. Votes
+    /// for a leader are always committed after the leader itself, since they are not part of
+    /// its causal history.
+    pub(crate) fn add_committed_sub_dag(&mut self, sub_dag: &CommittedSubDag) {
+        for block in &sub_dag.blocks {
+            let votes = block
+                .ancestors()
+                .iter()
+                .filter(|ancestor| {
+                    ancestor.round + 1 == block.round() && self.committed_leaders.contains(ancestor)
+                })
+                .count();
+            self.scores_per_authority[block.author()] += votes as u64;
+        }
+        self.committed_leaders.insert(sub_dag.leader);
+    }
+}
+
+/// Swaps leaders with low reputation scores for leaders with high scores.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct LeaderSwapTable {
+    /// Authorities with the highest scores, which are elected instead of bad nodes.
+    pub(crate) good_nodes: Vec<AuthorityIndex>,
+    /// Authorities with the lowest scores, which are not elected as leaders.
+    pub(crate) bad_nodes: BTreeSet<AuthorityIndex>,
+}
+
+impl LeaderSwapTable {
+    /// Picks good and bad nodes each up to `stake_threshold` percent of the total stake,
+    /// from the highest and lowest scores respectively. Ties are broken by authority index,
+    /// so the table is deterministic. Nodes are only considered bad when they scored strictly
+    /// lower than all the good nodes.
+    pub(crate) fn new(context: &Context, scores: &ReputationScores, stake_threshold: u64) -> Self {
+        assert!(
+            stake_threshold <= 33,
+            "The bad leaders stake threshold must be at most 33%, got {stake_threshold}"
+        );
+        let committee = &context.committee;
+        let max_stake = committee.total_stake() * stake_threshold / 100;
+
+        let mut authorities: Vec<(AuthorityIndex, u64)> = committee
+            .authorities()
+            .map(|(authority, _)| (authority, scores.scores_per_authority[authority]))
+            .collect();
+        authorities.sort_by(|(a1, s1), (a2, s2)| s2.cmp(s1).then(a1.cmp(a2)));
+
+        let mut good_nodes = vec![];
+        let mut stake = 0;
+        for (authority, _) in &authorities {
+            stake += committee.stake(*authority);
+            if stake > max_stake {
+                break;
+            }
+            good_nodes.push(*authority);
+        }
+        let Some(lowest_good_score) = good_nodes
+            .last()
+            .map(|authority| scores.scores_per_authority[*authority])
+        else {
+            return Self::default();
+        };
+
+        let mut bad_nodes = BTreeSet::new();
+        let mut stake = 0;
+        for (authority, score) in authorities.iter().rev() {
+            stake += committee.stake(*authority);
+            if stake > max_stake || *score >= lowest_good_score {
+                break;
+            }
+            bad_nodes.insert(*authority);
+        }
+        if bad_nodes.is_empty() {
+            return Self::default();
+        }
+
+        Self {
+            good_nodes,
+            bad_nodes,
+        }
+    }
+
+    /// Returns the good node elected instead of `leader`, if it is a bad node. The good node
+    /// is picked deterministically from the round and leader offset.
+    pub(crate) fn swap(
+        &self,
+        leader: AuthorityIndex,
+        round: Round,
+        leader_offset: u32,
+    ) -> Option<AuthorityIndex> {
+        if !self.bad_nodes.contains(&leader) {
+            return None;
+        }
+        let mut seed_bytes = [0u8; 32];
+        seed_bytes[32 - 8..32 - 4].copy_from_slice(&leader_offset.to_le_bytes());
+        seed_bytes[32 - 4..].copy_from_slice(&round.to_le_bytes());
+        let mut rng = StdRng::from_seed(seed_bytes);
+        self.good_nodes.choose(&mut rng).copied()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use consensus_config::{local_committee_and_keys, Parameters};
     use sui_protocol_config::ProtocolConfig;
 
     use super::*;
-    use crate::metrics::test_metrics;
+    use crate::{
+        block::{genesis_blocks, TestBlock, VerifiedBlock},
+        commit::{CommitDigest, TrustedCommit},
+        metrics::test_metrics,
+        storage::{mem_store::MemStore, Store, WriteBatch},
+    };
+
+    fn context_for_leader_schedule_test() -> Arc<Context> {
+        let (mut context, _) = Context::new_for_test(4);
+        context
+            .protocol_config
+            .set_mysticeti_leader_scoring_and_schedule_for_testing(true);
+        context
+            .protocol_config
+            .set_mysticeti_leader_schedule_update_interval_for_testing(2);
+        context
+            .protocol_config
+            .set_consensus_bad_nodes_stake_threshold(33);
+        Arc::new(context)
+    }
+
+    /// Builds 3 rounds of blocks, each committed by the leader of the round. Authority 3 does
+    /// not vote for the leader of round 1 in round 2.
+    fn build_committed_sub_dags(context: Arc<Context>) -> (Vec<VerifiedBlock>, Vec<TrustedCommit>) {
+        let mut ancestors: Vec<BlockRef> = genesis_blocks(context.clone())
+            .iter()
+            .map(|block| block.reference())
+            .collect();
+        let mut blocks = vec![];
+        let mut commits: Vec<TrustedCommit> = vec![];
+        for round in 1..=3 {
+            let leader = round % 4;
+            let round_blocks: Vec<VerifiedBlock> = (0..4)
+                .map(|author| {
+                    let block_ancestors = ancestors
+                        .iter()
+                        .filter(|ancestor| {
+                            !(round == 2 && author == 3 && ancestor.author.value() == 1)
+                        })
+                        .cloned()
+                        .collect();
+                    VerifiedBlock::new_for_test(
+                        TestBlock::new(round, author)
+                            .set_ancestors(block_ancestors)
+                            .build(),
+                    )
+                })
+                .collect();
+            ancestors = round_blocks.iter().map(|block| block.reference()).collect();
+            let previous_digest = commits
+                .last()
+                .map(|commit| commit.digest())
+                .unwrap_or(CommitDigest::MIN);
+            commits.push(TrustedCommit::new_for_test(
+                round,
+                previous_digest,
+                round_blocks[leader as usize].reference(),
+                ancestors.clone(),
+            ));
+            blocks.extend(round_blocks);
+        }
+        (blocks, commits)
+    }
+
+    fn to_sub_dags(store: &MemStore, commits: &[TrustedCommit]) -> Vec<CommittedSubDag> {
+        commits
+            .iter()
+            .map(|commit| crate::commit::load_committed_subdag_from_store(store, commit.clone()))
+            .collect()
+    }
 
     #[test]
     fn test_elect_leader() {
@@ -136,4 +466,189 @@ mod tests {
             leader_schedule.elect_leader_stake_based(1, 2)
         );
     }
+
+    #[test]
+    fn test_reputation_scores() {
+        let context = context_for_leader_schedule_test();
+        let (blocks, commits) = build_committed_sub_dags(context.clone());
+        let store = MemStore::new();
+        store
+            .write(
+                WriteBatch::default()
+                    .blocks(blocks)
+                    .commits(commits.clone()),
+            )
+            .unwrap();
+        let sub_dags = to_sub_dags(&store, &commits);
+
+        let mut scores = ReputationScores::new(context.committee.size());
+        scores.add_committed_sub_dag(&sub_dags[0]);
+        assert_eq!(scores.scores_per_authority, vec![0, 0, 0, 0]);
+        scores.add_committed_sub_dag(&sub_dags[1]);
+        assert_eq!(scores.scores_per_authority, vec![1, 1, 1, 0]);
+        scores.add_committed_sub_dag(&sub_dags[2]);
+        assert_eq!(scores.scores_per_authority, vec![2, 2, 2, 1]);
+    }
+
+    #[test]
+    fn test_leader_swap_table() {
+        let context = context_for_leader_schedule_test();
+        let scores = ReputationScores {
+            scores_per_authority: vec![5, 10, 1, 7],
+            ..Default::default()
+        };
+
+        let swap_table = LeaderSwapTable::new(&context, &scores, 33);
+        assert_eq!(swap_table.good_nodes, vec![AuthorityIndex::new_for_test(1)]);
+        assert_eq!(
+            swap_table.bad_nodes,
+            BTreeSet::from([AuthorityIndex::new_for_test(2)])
+        );
+        for round in 1..10 {
+            assert_eq!(
+                swap_table.swap(AuthorityIndex::new_for_test(2), round, 0),
+                Some(AuthorityIndex::new_for_test(1))
+            );
+            assert_eq!(
+                swap_table.swap(AuthorityIndex::new_for_test(0), round, 0),
+                None
+            );
+        }
+
+        // No swaps when disabled, or when all authorities have the same score.
+        assert_eq!(
+            LeaderSwapTable::new(&context, &scores, 0),
+            LeaderSwapTable::default()
+        );
+        let equal_scores = ReputationScores {
+            scores_per_authority: vec![3, 3, 3, 3],
+            ..Default::default()
+        };
+        assert_eq!(
+            LeaderSwapTable::new(&context, &equal_scores, 33),
+            LeaderSwapTable::default()
+        );
+    }
+
+    #[test]
+    fn test_update_leader_schedule() {
+        let context = context_for_leader_schedule_test();
+        let (blocks, commits) = build_committed_sub_dags(context.clone());
+        let store = MemStore::new();
+        store
+            .write(
+                WriteBatch::default()
+                    .blocks(blocks)
+                    .commits(commits.clone()),
+            )
+            .unwrap();
+        let sub_dags = to_sub_dags(&store, &commits);
+
+        let leader_schedule = LeaderSchedule::new(context.clone());
+        assert_eq!(leader_schedule.commits_until_leader_schedule_update(0), 2);
+        leader_schedule.add_committed_sub_dags(&sub_dags[0..2]);
+        assert_eq!(leader_schedule.commits_until_leader_schedule_update(2), 0);
+        assert_eq!(
+            leader_schedule.elect_leader(3, 0),
+            AuthorityIndex::new_for_test(3)
+        );
+
+        // Authority 3 has the lowest score, so it is swapped for the authority with the
+        // highest score and lowest index.
+        leader_schedule.update_leader_schedule(2);
+        assert_eq!(leader_schedule.commits_until_leader_schedule_update(2), 2);
+        assert_eq!(
+            leader_schedule.elect_leader(3, 0),
+            AuthorityIndex::new_for_test(0)
+        );
+        assert_eq!(
+            leader_schedule.elect_leader(5, 0),
+            AuthorityIndex::new_for_test(1)
+        );
+
+        // Clones share the updated schedule.
+        assert_eq!(
+            leader_schedule.clone().elect_leader(7, 0),
+            AuthorityIndex::new_for_test(0)
+        );
+    }
+
+    #[test]
+    fn test_leader_schedule_from_store() {
+        let context = context_for_leader_schedule_test();
+        let (blocks, commits) = build_committed_sub_dags(context.clone());
+        let store = Arc::new(MemStore::new());
+        store
+            .write(
+                WriteBatch::default()
+                    .blocks(blocks)
+                    .commits(commits.clone()),
+            )
+            .unwrap();
+        let sub_dags = to_sub_dags(&store, &commits);
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+
+        let recovered = LeaderSchedule::from_store(context.clone(), dag_state);
+
+        let expected = LeaderSchedule::new(context.clone());
+        expected.add_committed_sub_dags(&sub_dags[0..2]);
+        expected.update_leader_schedule(2);
+        expected.add_committed_sub_dags(&sub_dags[2..]);
+
+        let recovered_state = recovered.state.read();
+        let expected_state = expected.state.read();
+        assert_eq!(
+            recovered_state.leader_swap_table,
+            expected_state.leader_swap_table
+        );
+        assert_eq!(
+            recovered_state.reputation_scores,
+            expected_state.reputation_scores
+        );
+        assert_eq!(recovered_state.last_update_commit_index, 2);
+        assert_eq!(recovered.commits_until_leader_schedule_update(3), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to recover the leader schedule")]
+    fn test_leader_schedule_from_pruned_store() {
+        let context = context_for_leader_schedule_test();
+        let (blocks, commits) = build_committed_sub_dags(context.clone());
+        let store = Arc::new(MemStore::new());
+        store
+            .write(WriteBatch::default().blocks(blocks).commits(commits))
+            .unwrap();
+        store.prune(1, &[]).unwrap();
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+
+        // The commits before the last update are gone, so the leader swap table can't be
+        // recomputed.
+        LeaderSchedule::from_store(context, dag_state);
+    }
+
+    #[test]
+    fn test_leader_schedule_disabled() {
+        let (context, _) = Context::new_for_test(4);
+        let context = Arc::new(context);
+        assert!(!context
+            .protocol_config
+            .mysticeti_leader_scoring_and_schedule());
+        let (blocks, commits) = build_committed_sub_dags(context.clone());
+        let store = Arc::new(MemStore::new());
+        store
+            .write(WriteBatch::default().blocks(blocks).commits(commits))
+            .unwrap();
+        let dag_state = Arc::new(RwLock::new(DagState::new(context.clone(), store)));
+
+        // Commits are not replayed, and the schedule is never updated.
+        let recovered = LeaderSchedule::from_store(context.clone(), dag_state);
+        assert_eq!(
+            recovered.state.read().reputation_scores,
+            ReputationScores::new(context.committee.size())
+        );
+        assert_eq!(
+            recovered.commits_until_leader_schedule_update(3),
+            usize::MAX
+        );
+    }
 }
@@ -10,11 +10,13 @@ mod block_verifier;
 mod broadcaster;
 mod commit;
 mod commit_observer;
+mod commit_syncer;
 mod context;
 mod core;
 mod core_thread;
 mod dag_state;
 mod error;
+mod inspector;
 mod leader_schedule;
 mod leader_timeout;
 mod linearizer;
@@ -31,5 +33,6 @@ mod universal_committer;
 
 pub use authority_node::{ConsensusAuthority, NetworkType};
 pub use block::{BlockAPI, Round};
-pub use commit::{CommitConsumer, CommitIndex, CommittedSubDag};
+pub use commit::{CommitConsumer, CommitConsumerMonitor, CommitIndex, CommittedSubDag};
+pub use inspector::{blocks_to_dot, BlockSummary, CommitSummary, StoreInspector};
 pub use transaction::{TransactionClient, TransactionVerifier, ValidationError};
@@ -85,14 +85,19 @@ pub(crate) struct NodeMetrics {
     pub accepted_blocks: IntCounter,
     pub dag_state_store_read_count: IntCounterVec,
     pub dag_state_store_write_count: IntCounter,
+    pub dag_state_store_pruned_commit_index: IntGauge,
     pub fetch_blocks_scheduler_inflight: IntGauge,
     pub fetched_blocks: IntCounterVec,
+    pub fetched_commits: IntCounterVec,
+    pub commit_sync_quorum_index: IntGauge,
     pub invalid_blocks: IntCounterVec,
     pub committed_leaders_total: IntCounterVec,
     pub last_committed_leader_round: IntGauge,
     pub commit_round_advancement_interval: Histogram,
     pub last_decided_leader_round: IntGauge,
     pub leader_timeout_total: IntCounter,
+    pub leader_reputation_scores: IntGaugeVec,
+    pub leader_swap_table_bad_nodes: IntGauge,
     pub missing_blocks_total: IntGauge,
     pub quorum_receive_latency: Histogram,
     pub scope_processing_time: HistogramVec,
@@ -171,6 +176,11 @@ impl NodeMetrics {
                 "Number of times DagState needs to write to store",
                 registry,
             ).unwrap(),
+            dag_state_store_pruned_commit_index: register_int_gauge_with_registry!(
+                "dag_state_store_pruned_commit_index",
+                "Index of the last commit pruned from store, along with the blocks only needed by it and earlier commits",
+                registry,
+            ).unwrap(),
             fetch_blocks_scheduler_inflight: register_int_gauge_with_registry!(
                 "fetch_blocks_scheduler_inflight",
                 "Designates whether the synchronizer scheduler task to fetch blocks is currently running",
@@ -182,6 +192,17 @@ impl NodeMetrics {
                 &["authority", "type"],
                 registry,
             ).unwrap(),
+            fetched_commits: register_int_counter_vec_with_registry!(
+                "fetched_commits",
+                "Number of certified commits fetched per peer authority via commit sync.",
+                &["authority"],
+                registry,
+            ).unwrap(),
+            commit_sync_quorum_index: register_int_gauge_with_registry!(
+                "commit_sync_quorum_index",
+                "The highest commit index voted by a quorum of authorities, as observed by commit sync.",
+                registry,
+            ).unwrap(),
             // TODO: add a short status label.
             invalid_blocks: register_int_counter_vec_with_registry!(
                 "invalid_blocks",
@@ -216,6 +237,17 @@ impl NodeMetrics {
                 "Total number of leader timeouts",
                 registry,
             ).unwrap(),
+            leader_reputation_scores: register_int_gauge_vec_with_registry!(
+                "leader_reputation_scores",
+                "Reputation scores per authority, used for the last leader schedule update",
+                &["authority"],
+                registry,
+            ).unwrap(),
+            leader_swap_table_bad_nodes: register_int_gauge_with_registry!(
+                "leader_swap_table_bad_nodes",
+                "Number of authorities swapped out of the leader schedule",
+                registry,
+            ).unwrap(),
             missing_blocks_total: register_int_gauge_with_registry!(
                 "missing_blocks_total",
                 "Total number of missing blocks",
@@ -3,6 +3,7 @@
 
 use std::{
     collections::{BTreeMap, HashMap},
+    ops::Range,
     panic,
     sync::Arc,
     time::Duration,
@@ -37,6 +38,7 @@ use super::{
 };
 use crate::{
     block::{BlockRef, VerifiedBlock},
+    commit::CommitIndex,
     context::Context,
     error::{ConsensusError, ConsensusResult},
     Round,
@@ -176,6 +178,25 @@ impl NetworkClient for AnemoClient {
             .map_err(|e| ConsensusError::NetworkError(format!("fetch_blocks failed: {e:?}")))?;
         Ok(response.into_body().blocks)
     }
+
+    async fn fetch_commits(
+        &self,
+        peer: AuthorityIndex,
+        commit_range: Range<CommitIndex>,
+        timeout: Duration,
+    ) -> ConsensusResult<(Vec<Bytes>, Vec<Bytes>)> {
+        let mut client = self.get_client(peer, timeout).await?;
+        let request = FetchCommitsRequest {
+            start: commit_range.start,
+            end: commit_range.end,
+        };
+        let response = client
+            .fetch_commits(anemo::Request::new(request).with_timeout(timeout))
+            .await
+            .map_err(|e| ConsensusError::NetworkError(format!("fetch_commits failed: {e:?}")))?;
+        let response = response.into_body();
+        Ok((response.commits, response.certifier_blocks))
+    }
 }
 
 /// Proxies Anemo requests to NetworkService with actual handler implementation.
@@ -269,6 +290,47 @@ impl<S: NetworkService> ConsensusRpc for AnemoServiceProxy<S> {
             })?;
         Ok(Response::new(FetchBlocksResponse { blocks }))
     }
+
+    async fn fetch_commits(
+        &self,
+        request: anemo::Request<FetchCommitsRequest>,
+    ) -> Result<anemo::Response<FetchCommitsResponse>, anemo::rpc::Status> {
+        let Some(peer_id) = request.peer_id() else {
+            return Err(anemo::rpc::Status::new_with_message(
+                anemo::types::response::StatusCode::BadRequest,
+                "peer_id not found",
+            ));
+        };
+        let index = self.peer_map.get(peer_id).ok_or_else(|| {
+            anemo::rpc::Status::new_with_message(
+                anemo::types::response::StatusCode::BadRequest,
+                "peer not found",
+            )
+        })?;
+        let request = request.into_body();
+        let (commits, certifier_blocks) = self
+            .service
+            .handle_fetch_commits(*index, request.start..request.end)
+            .await
+            .map_err(|e| {
+                anemo::rpc::Status::new_with_message(
+                    anemo::types::response::StatusCode::BadRequest,
+                    format!("{e}"),
+                )
+            })?;
+        let commits = commits
+            .into_iter()
+            .map(|commit| commit.serialized().clone())
+            .collect();
+        let certifier_blocks = certifier_blocks
+            .into_iter()
+            .map(|block| block.serialized().clone())
+            .collect();
+        Ok(Response::new(FetchCommitsResponse {
+            commits,
+            certifier_blocks,
+        }))
+    }
 }
 
 /// Manages the lifecycle of Anemo network. Typical usage during initialization:
@@ -521,6 +583,20 @@ pub(crate) struct FetchBlocksResponse {
     blocks: Vec<Bytes>,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct FetchCommitsRequest {
+    start: CommitIndex,
+    end: CommitIndex,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct FetchCommitsResponse {
+    // Serialized consecutive Commit.
+    commits: Vec<Bytes>,
+    // Serialized SignedBlock that certify the last commit from above.
+    certifier_blocks: Vec<Bytes>,
+}
+
 #[derive(Clone)]
 pub(crate) struct MetricsMakeCallbackHandler {
     metrics: Arc<NetworkRouteMetrics>,
@@ -635,7 +711,7 @@ impl Drop for MetricsResponseHandler {
 
 #[cfg(test)]
 mod test {
-    use std::{sync::Arc, time::Duration};
+    use std::{ops::Range, sync::Arc, time::Duration};
 
     use async_trait::async_trait;
     use bytes::Bytes;
@@ -645,6 +721,7 @@ mod test {
 
     use crate::{
         block::{BlockRef, TestBlock, VerifiedBlock},
+        commit::{CommitIndex, TrustedCommit},
         context::Context,
         error::ConsensusResult,
         network::{
@@ -694,6 +771,14 @@ mod test {
             self.lock().handle_fetch_blocks.push((peer, block_refs));
             Ok(vec![])
         }
+
+        async fn handle_fetch_commits(
+            &self,
+            _peer: AuthorityIndex,
+            _commit_range: Range<CommitIndex>,
+        ) -> ConsensusResult<(Vec<TrustedCommit>, Vec<VerifiedBlock>)> {
+            unimplemented!()
+        }
     }
 
     #[tokio::test(flavor = "current_thread", start_paused = true)]
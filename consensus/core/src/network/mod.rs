@@ -16,7 +16,7 @@
 //! directly to the server. This keeps the logic agnostics to the underlying network outside of
 //! this module, so they can be reused easily across network implementations.
 
-use std::{pin::Pin, sync::Arc, time::Duration};
+use std::{ops::Range, pin::Pin, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -25,6 +25,7 @@ use futures::Stream;
 
 use crate::{
     block::{BlockRef, VerifiedBlock},
+    commit::{CommitIndex, TrustedCommit},
     context::Context,
     error::ConsensusResult,
     Round,
@@ -43,6 +44,8 @@ pub(crate) mod anemo_network;
 pub(crate) mod connection_monitor;
 pub(crate) mod epoch_filter;
 pub(crate) mod metrics;
+#[cfg(test)]
+pub(crate) mod sim_network;
 pub(crate) mod tonic_network;
 
 /// A stream of serialized blocks returned over the network.
@@ -82,6 +85,16 @@ pub(crate) trait NetworkClient: Send + Sync + 'static {
         block_refs: Vec<BlockRef>,
         timeout: Duration,
     ) -> ConsensusResult<Vec<Bytes>>;
+
+    /// Fetches serialized `Commit`s in the index range from a peer, starting from the start of the
+    /// range. Also returns serialized `SignedBlock`s voting for the last returned commit, which
+    /// certify it and, through the chain of commit digests, all the returned commits.
+    async fn fetch_commits(
+        &self,
+        peer: AuthorityIndex,
+        commit_range: Range<CommitIndex>,
+        timeout: Duration,
+    ) -> ConsensusResult<(Vec<Bytes>, Vec<Bytes>)>;
 }
 
 /// Network service for handling requests from peers.
@@ -100,6 +113,11 @@ pub(crate) trait NetworkService: Send + Sync + 'static {
         peer: AuthorityIndex,
         block_refs: Vec<BlockRef>,
     ) -> ConsensusResult<Vec<Bytes>>;
+    async fn handle_fetch_commits(
+        &self,
+        peer: AuthorityIndex,
+        commit_range: Range<CommitIndex>,
+    ) -> ConsensusResult<(Vec<TrustedCommit>, Vec<VerifiedBlock>)>;
 }
 
 /// An `AuthorityNode` holds a `NetworkManager` until shutdown.
@@ -0,0 +1,347 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! An in-process network for simulation tests of multiple authorities.
+//!
+//! All authorities started within `SimNetwork::scope()` exchange messages by calling each other's
+//! `NetworkService` directly. The fabric can inject faults into the messages: drops, random
+//! delays which reorder concurrent messages, partitions between groups of authorities, and
+//! equivocating blocks sent on behalf of byzantine authorities. Faults are drawn from a seeded
+//! RNG, so a failing schedule can be reproduced with the same seed.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use consensus_config::{AuthorityIndex, NetworkKeyPair, ProtocolKeyPair};
+use futures::Future;
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, Rng as _, SeedableRng as _};
+use tracing::debug;
+
+use super::{BlockStream, NetworkClient, NetworkManager, NetworkService};
+use crate::{
+    block::{Block, BlockAPI as _, BlockRef, BlockV1, Round, SignedBlock, VerifiedBlock},
+    commit::CommitIndex,
+    context::Context,
+    error::{ConsensusError, ConsensusResult},
+};
+
+tokio::task_local! {
+    // The network that `SimNetworkManager`s created in the current scope join.
+    static SIM_NETWORK: SimNetwork;
+}
+
+/// Faults injected into messages between authorities.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NetworkFaults {
+    /// Probability of each message to be dropped.
+    pub(crate) drop_probability: f64,
+    /// Each message is delayed by a random duration up to this value.
+    pub(crate) max_delay: Duration,
+    /// When not empty, authorities can only reach authorities in the same group.
+    pub(crate) partitions: Vec<BTreeSet<AuthorityIndex>>,
+}
+
+/// The shared fabric of the simulated network.
+#[derive(Clone)]
+pub(crate) struct SimNetwork {
+    inner: Arc<Mutex<SimNetworkInner>>,
+}
+
+struct SimNetworkInner {
+    services: BTreeMap<AuthorityIndex, Arc<dyn NetworkService>>,
+    faults: NetworkFaults,
+    rng: StdRng,
+    // Keys of byzantine authorities, used to sign equivocating blocks.
+    equivocators: BTreeMap<AuthorityIndex, ProtocolKeyPair>,
+    // Equivocating block sent in place of each block of byzantine authorities.
+    equivocating_blocks: BTreeMap<BlockRef, VerifiedBlock>,
+    dropped_messages: u64,
+}
+
+impl SimNetwork {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(SimNetworkInner {
+                services: BTreeMap::new(),
+                faults: NetworkFaults::default(),
+                rng: StdRng::seed_from_u64(seed),
+                equivocators: BTreeMap::new(),
+                equivocating_blocks: BTreeMap::new(),
+                dropped_messages: 0,
+            })),
+        }
+    }
+
+    /// Runs `f`, with `SimNetworkManager`s created by it joining this network.
+    pub(crate) async fn scope<F: Future>(&self, f: F) -> F::Output {
+        SIM_NETWORK.scope(self.clone(), f).await
+    }
+
+    /// Replaces the faults injected into subsequent messages.
+    pub(crate) fn set_faults(&self, faults: NetworkFaults) {
+        self.inner.lock().faults = faults;
+    }
+
+    /// Makes `authority` byzantine: peers with odd indices receive an equivocating version of
+    /// each of its blocks, signed with `protocol_keypair`.
+    pub(crate) fn add_equivocator(
+        &self,
+        authority: AuthorityIndex,
+        protocol_keypair: ProtocolKeyPair,
+    ) {
+        self.inner
+            .lock()
+            .equivocators
+            .insert(authority, protocol_keypair);
+    }
+
+    /// Returns the number of equivocating blocks created so far.
+    pub(crate) fn num_equivocating_blocks(&self) -> usize {
+        self.inner.lock().equivocating_blocks.len()
+    }
+
+    /// Returns the number of messages dropped so far.
+    pub(crate) fn num_dropped_messages(&self) -> u64 {
+        self.inner.lock().dropped_messages
+    }
+
+    fn install_service(&self, authority: AuthorityIndex, service: Arc<dyn NetworkService>) {
+        self.inner.lock().services.insert(authority, service);
+    }
+
+    fn remove_service(&self, authority: AuthorityIndex) {
+        self.inner.lock().services.remove(&authority);
+    }
+
+    /// Decides the fate of a message from `from` to `to`. Returns the service of `to` and the
+    /// delay of the message, if it is delivered.
+    fn route(
+        &self,
+        from: AuthorityIndex,
+        to: AuthorityIndex,
+    ) -> ConsensusResult<(Arc<dyn NetworkService>, Duration)> {
+        let mut inner = self.inner.lock();
+        let Some(service) = inner.services.get(&to).cloned() else {
+            return Err(ConsensusError::PeerDisconnected(to.to_string()));
+        };
+        let partitioned = !inner.faults.partitions.is_empty()
+            && !inner
+                .faults
+                .partitions
+                .iter()
+                .any(|group| group.contains(&from) && group.contains(&to));
+        let drop_probability = inner.faults.drop_probability;
+        if partitioned || inner.rng.gen_bool(drop_probability) {
+            inner.dropped_messages += 1;
+            debug!("Dropped message from {from} to {to}");
+            return Err(ConsensusError::NetworkError(format!(
+                "message from {from} to {to} dropped"
+            )));
+        }
+        let max_delay_ms = inner.faults.max_delay.as_millis() as u64;
+        let delay = Duration::from_millis(inner.rng.gen_range(0..=max_delay_ms));
+        Ok((service, delay))
+    }
+
+    /// Returns the block to send from `from` to `to`, which is an equivocating block when `from`
+    /// is byzantine and `to` has an odd index.
+    fn block_to_send(
+        &self,
+        from: AuthorityIndex,
+        to: AuthorityIndex,
+        block: &VerifiedBlock,
+    ) -> VerifiedBlock {
+        let mut inner = self.inner.lock();
+        let Some(protocol_keypair) = inner.equivocators.get(&from) else {
+            return block.clone();
+        };
+        if to.value() % 2 == 0 || block.author() != from {
+            return block.clone();
+        }
+        // Equivocates by changing the timestamp, which results in a different block digest.
+        let equivocating_block = Block::V1(BlockV1::new(
+            block.epoch(),
+            block.round(),
+            block.author(),
+            block.timestamp_ms() + 1,
+            block.ancestors().to_vec(),
+            block.transactions().to_vec(),
+            block.commit_votes().to_vec(),
+        ));
+        let signed_block = SignedBlock::new(equivocating_block, protocol_keypair)
+            .expect("Signing a block should not fail");
+        let serialized: Bytes = bcs::to_bytes(&signed_block)
+            .expect("Serialization should not fail")
+            .into();
+        inner
+            .equivocating_blocks
+            .entry(block.reference())
+            .or_insert_with(|| VerifiedBlock::new_verified(signed_block, serialized))
+            .clone()
+    }
+
+    /// Byzantine authorities do not accept their own equivocating blocks from peers, since
+    /// their own DAG can only contain one block per own slot.
+    fn filter_equivocating_blocks(
+        &self,
+        to: AuthorityIndex,
+        serialized_blocks: Vec<Bytes>,
+    ) -> Vec<Bytes> {
+        let inner = self.inner.lock();
+        if !inner.equivocators.contains_key(&to) {
+            return serialized_blocks;
+        }
+        let equivocating_blocks: BTreeSet<&Bytes> = inner
+            .equivocating_blocks
+            .values()
+            .map(|block| block.serialized())
+            .collect();
+        serialized_blocks
+            .into_iter()
+            .filter(|serialized| !equivocating_blocks.contains(serialized))
+            .collect()
+    }
+}
+
+/// Sends messages of an authority through the `SimNetwork`.
+pub(crate) struct SimNetworkClient {
+    context: Arc<Context>,
+    network: SimNetwork,
+}
+
+impl SimNetworkClient {
+    // Delivers a message after its delay, or fails after the timeout.
+    async fn deliver(
+        &self,
+        peer: AuthorityIndex,
+        timeout: Duration,
+    ) -> ConsensusResult<Arc<dyn NetworkService>> {
+        let (service, delay) = self.network.route(self.context.own_index, peer)?;
+        if delay > timeout {
+            tokio::time::sleep(timeout).await;
+            return Err(ConsensusError::NetworkError(format!(
+                "request to {peer} timed out"
+            )));
+        }
+        tokio::time::sleep(delay).await;
+        Ok(service)
+    }
+}
+
+#[async_trait]
+impl NetworkClient for SimNetworkClient {
+    const SUPPORT_STREAMING: bool = false;
+
+    async fn send_block(
+        &self,
+        peer: AuthorityIndex,
+        block: &VerifiedBlock,
+        timeout: Duration,
+    ) -> ConsensusResult<()> {
+        let block = self
+            .network
+            .block_to_send(self.context.own_index, peer, block);
+        let service = self.deliver(peer, timeout).await?;
+        service
+            .handle_send_block(self.context.own_index, block.serialized().clone())
+            .await
+    }
+
+    async fn subscribe_blocks(
+        &self,
+        peer: AuthorityIndex,
+        last_received: Round,
+        timeout: Duration,
+    ) -> ConsensusResult<BlockStream> {
+        let service = self.deliver(peer, timeout).await?;
+        service
+            .handle_subscribe_blocks(self.context.own_index, last_received)
+            .await
+    }
+
+    async fn fetch_blocks(
+        &self,
+        peer: AuthorityIndex,
+        block_refs: Vec<BlockRef>,
+        timeout: Duration,
+    ) -> ConsensusResult<Vec<Bytes>> {
+        let service = self.deliver(peer, timeout).await?;
+        let blocks = service
+            .handle_fetch_blocks(self.context.own_index, block_refs)
+            .await?;
+        Ok(self
+            .network
+            .filter_equivocating_blocks(self.context.own_index, blocks))
+    }
+
+    async fn fetch_commits(
+        &self,
+        peer: AuthorityIndex,
+        commit_range: Range<CommitIndex>,
+        timeout: Duration,
+    ) -> ConsensusResult<(Vec<Bytes>, Vec<Bytes>)> {
+        let service = self.deliver(peer, timeout).await?;
+        let (commits, certifier_blocks) = service
+            .handle_fetch_commits(self.context.own_index, commit_range)
+            .await?;
+        let commits = commits
+            .into_iter()
+            .map(|commit| commit.serialized().clone())
+            .collect();
+        let certifier_blocks = certifier_blocks
+            .into_iter()
+            .map(|block| block.serialized().clone())
+            .collect();
+        Ok((
+            commits,
+            self.network
+                .filter_equivocating_blocks(self.context.own_index, certifier_blocks),
+        ))
+    }
+}
+
+/// Joins an authority to the `SimNetwork` of the current `SimNetwork::scope()`.
+pub(crate) struct SimNetworkManager {
+    context: Arc<Context>,
+    network: SimNetwork,
+    client: Arc<SimNetworkClient>,
+}
+
+impl<S: NetworkService> NetworkManager<S> for SimNetworkManager {
+    type Client = SimNetworkClient;
+
+    fn new(context: Arc<Context>) -> Self {
+        let network = SIM_NETWORK
+            .try_with(|network| network.clone())
+            .expect("SimNetworkManager must be created within SimNetwork::scope()");
+        let client = Arc::new(SimNetworkClient {
+            context: context.clone(),
+            network: network.clone(),
+        });
+        Self {
+            context,
+            network,
+            client,
+        }
+    }
+
+    fn client(&self) -> Arc<Self::Client> {
+        self.client.clone()
+    }
+
+    async fn install_service(&mut self, _network_keypair: NetworkKeyPair, service: Arc<S>) {
+        self.network
+            .install_service(self.context.own_index, service);
+    }
+
+    async fn stop(&mut self) {
+        self.network.remove_service(self.context.own_index);
+    }
+}
@@ -4,6 +4,7 @@
 use std::{
     collections::BTreeMap,
     net::{SocketAddr, SocketAddrV4, SocketAddrV6},
+    ops::Range,
     pin::Pin,
     sync::Arc,
     time::Duration,
@@ -36,6 +37,7 @@ use super::{
 };
 use crate::{
     block::{BlockRef, VerifiedBlock},
+    commit::CommitIndex,
     context::Context,
     error::{ConsensusError, ConsensusResult},
     network::tonic_gen::consensus_service_server::ConsensusServiceServer,
@@ -203,6 +205,31 @@ impl NetworkClient for TonicClient {
         }
         Ok(blocks)
     }
+
+    async fn fetch_commits(
+        &self,
+        peer: AuthorityIndex,
+        commit_range: Range<CommitIndex>,
+        timeout: Duration,
+    ) -> ConsensusResult<(Vec<Bytes>, Vec<Bytes>)> {
+        let mut client = self.get_client(peer, timeout).await?;
+        let mut request = Request::new(FetchCommitsRequest {
+            start: commit_range.start,
+            end: commit_range.end,
+        });
+        request.set_timeout(timeout);
+        // TODO: remove below after adding authentication.
+        request.metadata_mut().insert(
+            AUTHORITY_INDEX_METADATA_KEY,
+            self.context.own_index.value().to_string().parse().unwrap(),
+        );
+        let response = client
+            .fetch_commits(request)
+            .await
+            .map_err(|e| ConsensusError::NetworkError(format!("fetch_commits failed: {e:?}")))?
+            .into_inner();
+        Ok((response.commits, response.certifier_blocks))
+    }
 }
 
 /// Manages a pool of connections to peers to avoid constantly reconnecting,
@@ -385,6 +412,40 @@ impl<S: NetworkService> ConsensusService for TonicServiceProxy<S> {
         let stream = iter(responses);
         Ok(Response::new(stream))
     }
+
+    async fn fetch_commits(
+        &self,
+        request: Request<FetchCommitsRequest>,
+    ) -> Result<Response<FetchCommitsResponse>, tonic::Status> {
+        // TODO: switch to using authenticated peer identity.
+        let Some(peer_index) = request
+            .metadata()
+            .get(AUTHORITY_INDEX_METADATA_KEY)
+            .and_then(|s| s.to_str().ok())
+            .and_then(|s| s.parse().ok())
+            .and_then(|index| self.context.committee.to_authority_index(index))
+        else {
+            return Err(tonic::Status::invalid_argument("Invalid authority index"));
+        };
+        let request = request.into_inner();
+        let (commits, certifier_blocks) = self
+            .service
+            .handle_fetch_commits(peer_index, request.start..request.end)
+            .await
+            .map_err(|e| tonic::Status::internal(format!("{e:?}")))?;
+        let commits = commits
+            .into_iter()
+            .map(|commit| commit.serialized().clone())
+            .collect();
+        let certifier_blocks = certifier_blocks
+            .into_iter()
+            .map(|block| block.serialized().clone())
+            .collect();
+        Ok(Response::new(FetchCommitsResponse {
+            commits,
+            certifier_blocks,
+        }))
+    }
 }
 
 /// Manages the lifecycle of Tonic network client and service. Typical usage during initialization:
@@ -569,6 +630,24 @@ pub(crate) struct FetchBlocksResponse {
     blocks: Vec<Bytes>,
 }
 
+#[derive(Clone, prost::Message)]
+pub(crate) struct FetchCommitsRequest {
+    #[prost(uint32, tag = "1")]
+    start: CommitIndex,
+    #[prost(uint32, tag = "2")]
+    end: CommitIndex,
+}
+
+#[derive(Clone, prost::Message)]
+pub(crate) struct FetchCommitsResponse {
+    // Serialized consecutive Commit.
+    #[prost(bytes = "bytes", repeated, tag = "1")]
+    commits: Vec<Bytes>,
+    // Serialized SignedBlock that certify the last commit from above.
+    #[prost(bytes = "bytes", repeated, tag = "2")]
+    certifier_blocks: Vec<Bytes>,
+}
+
 fn chunk_blocks(blocks: Vec<Bytes>, chunk_limit: usize) -> Vec<Vec<Bytes>> {
     let mut chunks = vec![];
     let mut chunk = vec![];
@@ -592,7 +671,7 @@ fn chunk_blocks(blocks: Vec<Bytes>, chunk_limit: usize) -> Vec<Vec<Bytes>> {
 // TODO: after supporting peer authentication, using rtest to share the test case with anemo_network.rs
 #[cfg(test)]
 mod test {
-    use std::{sync::Arc, time::Duration};
+    use std::{ops::Range, sync::Arc, time::Duration};
 
     use async_trait::async_trait;
     use bytes::Bytes;
@@ -602,6 +681,7 @@ mod test {
 
     use crate::{
         block::{BlockRef, TestBlock, VerifiedBlock},
+        commit::{CommitIndex, TrustedCommit},
         context::Context,
         error::ConsensusResult,
         network::{
@@ -672,6 +752,14 @@ mod test {
             self.lock().handle_fetch_blocks.push((peer, block_refs));
             Ok(vec![])
         }
+
+        async fn handle_fetch_commits(
+            &self,
+            _peer: AuthorityIndex,
+            _commit_range: Range<CommitIndex>,
+        ) -> ConsensusResult<(Vec<TrustedCommit>, Vec<VerifiedBlock>)> {
+            unimplemented!()
+        }
     }
 
     #[tokio::test]
@@ -192,8 +192,55 @@ impl Store for MemStore {
         Ok(commits)
     }
 
+    fn read_commit_votes(&self, commit_index: CommitIndex) -> ConsensusResult<Vec<BlockRef>> {
+        let inner = self.inner.read();
+        let votes = inner
+            .commit_votes
+            .range((
+                Included((commit_index, CommitDigest::MIN, BlockRef::default())),
+                Excluded((commit_index + 1, CommitDigest::MIN, BlockRef::default())),
+            ))
+            .map(|(_, _, block_ref)| *block_ref)
+            .collect();
+        Ok(votes)
+    }
+
     fn read_last_commit_info(&self) -> ConsensusResult<Option<CommitInfo>> {
         let inner = self.inner.read();
         Ok(inner.commit_info.last_key_value().map(|(_k, v)| v.clone()))
     }
+
+    fn prune(
+        &self,
+        last_pruned_commit: CommitIndex,
+        last_pruned_rounds: &[(AuthorityIndex, Round)],
+    ) -> ConsensusResult<()> {
+        let mut inner = self.inner.write();
+        for (author, last_pruned_round) in last_pruned_rounds {
+            let pruned: Vec<_> = inner
+                .digests_by_authorities
+                .range((
+                    Included((*author, Round::MIN, BlockDigest::MIN)),
+                    Included((*author, *last_pruned_round, BlockDigest::MAX)),
+                ))
+                .cloned()
+                .collect();
+            for (author, round, digest) in pruned {
+                inner
+                    .digests_by_authorities
+                    .remove(&(author, round, digest));
+                inner.blocks.remove(&(round, author, digest));
+            }
+        }
+        inner
+            .commits
+            .retain(|(index, _), _| *index > last_pruned_commit);
+        inner
+            .commit_votes
+            .retain(|(index, _, _)| *index > last_pruned_commit);
+        inner
+            .commit_info
+            .retain(|(index, _), _| *index > last_pruned_commit);
+        Ok(())
+    }
 }
@@ -56,8 +56,20 @@ pub(crate) trait Store: Send + Sync {
     /// Reads all commits from start (inclusive) until end (exclusive).
     fn scan_commits(&self, range: Range<CommitIndex>) -> ConsensusResult<Vec<TrustedCommit>>;
 
+    /// Reads the refs of blocks voting for a commit at the given index, regardless of the voted
+    /// commit digest.
+    fn read_commit_votes(&self, commit_index: CommitIndex) -> ConsensusResult<Vec<BlockRef>>;
+
     /// Reads the last commit info, including last committed round per authority.
     fn read_last_commit_info(&self) -> ConsensusResult<Option<CommitInfo>>;
+
+    /// Deletes commits with index up to and including `last_pruned_commit`, along with their
+    /// votes and commit info, and blocks of each authority up to and including its given round.
+    fn prune(
+        &self,
+        last_pruned_commit: CommitIndex,
+        last_pruned_rounds: &[(AuthorityIndex, Round)],
+    ) -> ConsensusResult<()>;
 }
 
 /// Represents data to be written to the store together atomically.
@@ -3,6 +3,7 @@
 
 use std::collections::VecDeque;
 use std::ops::Range;
+use std::sync::Arc;
 use std::{
     ops::Bound::{Excluded, Included},
     time::Duration,
@@ -13,7 +14,11 @@ use consensus_config::AuthorityIndex;
 use typed_store::{
     metrics::SamplingInterval,
     reopen,
-    rocks::{default_db_options, open_cf_opts, DBMap, MetricConf, ReadWriteOptions},
+    rocks::{
+        default_db_options, open_cf_opts, open_cf_opts_secondary, DBMap, MetricConf,
+        ReadWriteOptions, RocksDB,
+    },
+    sally::SallyColumn,
     Map as _,
 };
 
@@ -27,18 +32,19 @@ use crate::{
 };
 
 /// Persistent storage with RocksDB.
+/// Tables are `SallyColumn`s, so tests can run the same store on the in-memory backend.
 pub(crate) struct RocksDBStore {
     /// Stores SignedBlock by refs.
-    blocks: DBMap<(Round, AuthorityIndex, BlockDigest), Bytes>,
+    blocks: SallyColumn<(Round, AuthorityIndex, BlockDigest), Bytes>,
     /// A secondary index that orders refs first by authors.
-    digests_by_authorities: DBMap<(AuthorityIndex, Round, BlockDigest), ()>,
+    digests_by_authorities: SallyColumn<(AuthorityIndex, Round, BlockDigest), ()>,
     /// Maps commit index to content.
-    commits: DBMap<(CommitIndex, CommitDigest), Bytes>,
+    commits: SallyColumn<(CommitIndex, CommitDigest), Bytes>,
     /// Collects votes on commits.
     /// TODO: batch multiple votes into a single row.
-    commit_votes: DBMap<(CommitIndex, CommitDigest, BlockRef), ()>,
+    commit_votes: SallyColumn<(CommitIndex, CommitDigest, BlockRef), ()>,
     /// Stores the latest values of a few properties.
-    commit_info: DBMap<(CommitIndex, CommitDigest), CommitInfo>,
+    commit_info: SallyColumn<(CommitIndex, CommitDigest), CommitInfo>,
 }
 
 impl RocksDBStore {
@@ -53,10 +59,57 @@ impl RocksDBStore {
         // Consensus data has high write throughput (all transactions) and is rarely read
         // (only during recovery and when helping peers catch up).
         let db_options = default_db_options().optimize_db_for_write_throughput(2);
+        let rocksdb = open_cf_opts(
+            path,
+            Some(db_options.options),
+            Self::metrics_conf(),
+            &Self::column_family_options(),
+        )
+        .expect("Cannot open database");
+        Self::from_rocksdb(&rocksdb)
+    }
+
+    /// Creates a new instance of the storage on the in-memory test backend, for tests.
+    #[cfg(test)]
+    pub(crate) fn new_in_memory() -> Self {
+        use typed_store::test_db::{InMemoryDB, TestDB};
+
+        let db = InMemoryDB::new();
+        Self {
+            blocks: SallyColumn::new_testdb(TestDB::reopen(&db, Self::BLOCKS_CF)),
+            digests_by_authorities: SallyColumn::new_testdb(TestDB::reopen(
+                &db,
+                Self::DIGESTS_BY_AUTHORITIES_CF,
+            )),
+            commits: SallyColumn::new_testdb(TestDB::reopen(&db, Self::COMMITS_CF)),
+            commit_votes: SallyColumn::new_testdb(TestDB::reopen(&db, Self::COMMIT_VOTES_CF)),
+            commit_info: SallyColumn::new_testdb(TestDB::reopen(&db, Self::COMMIT_INFO_CF)),
+        }
+    }
+
+    /// Opens the storage at `path` as a RocksDB secondary instance, which only reads data and
+    /// can be used while the authority is running.
+    pub(crate) fn new_read_only(path: &str) -> ConsensusResult<Self> {
+        let rocksdb = open_cf_opts_secondary(
+            path,
+            None,
+            None,
+            Self::metrics_conf(),
+            &Self::column_family_options(),
+        )
+        .map_err(ConsensusError::RocksDBFailure)?;
+        Ok(Self::from_rocksdb(&rocksdb))
+    }
+
+    fn metrics_conf() -> MetricConf {
         let mut metrics_conf = MetricConf::new("consensus");
         metrics_conf.read_sample_interval = SamplingInterval::new(Duration::from_secs(60), 0);
+        metrics_conf
+    }
+
+    fn column_family_options() -> Vec<(&'static str, rocksdb::Options)> {
         let cf_options = default_db_options().optimize_for_write_throughput().options;
-        let column_family_options = vec![
+        vec![
             (
                 Self::BLOCKS_CF,
                 default_db_options()
@@ -70,16 +123,11 @@ impl RocksDBStore {
             (Self::COMMITS_CF, cf_options.clone()),
             (Self::COMMIT_VOTES_CF, cf_options.clone()),
             (Self::COMMIT_INFO_CF, cf_options.clone()),
-        ];
-        let rocksdb = open_cf_opts(
-            path,
-            Some(db_options.options),
-            metrics_conf,
-            &column_family_options,
-        )
-        .expect("Cannot open database");
+        ]
+    }
 
-        let (blocks, digests_by_authorities, commits, commit_votes, commit_info) = reopen!(&rocksdb,
+    fn from_rocksdb(rocksdb: &Arc<RocksDB>) -> Self {
+        let (blocks, digests_by_authorities, commits, commit_votes, commit_info) = reopen!(rocksdb,
             Self::BLOCKS_CF;<(Round, AuthorityIndex, BlockDigest), bytes::Bytes>,
             Self::DIGESTS_BY_AUTHORITIES_CF;<(AuthorityIndex, Round, BlockDigest), ()>,
             Self::COMMITS_CF;<(CommitIndex, CommitDigest), Bytes>,
@@ -88,12 +136,39 @@ impl RocksDBStore {
         );
 
         Self {
-            blocks,
-            digests_by_authorities,
-            commits,
-            commit_votes,
-            commit_info,
+            blocks: SallyColumn::new_single_rocksdb(blocks),
+            digests_by_authorities: SallyColumn::new_single_rocksdb(digests_by_authorities),
+            commits: SallyColumn::new_single_rocksdb(commits),
+            commit_votes: SallyColumn::new_single_rocksdb(commit_votes),
+            commit_info: SallyColumn::new_single_rocksdb(commit_info),
+        }
+    }
+
+    /// Reads blocks of all authorities with rounds in the range, ordered by round and author.
+    /// Only used for inspecting the store, since it scans the blocks column family.
+    pub(crate) fn scan_blocks_by_rounds(
+        &self,
+        rounds: Range<Round>,
+    ) -> ConsensusResult<Vec<VerifiedBlock>> {
+        let mut blocks = vec![];
+        for kv in self.blocks.safe_range_iter((
+            Included((rounds.start, AuthorityIndex::ZERO, BlockDigest::MIN)),
+            Excluded((rounds.end, AuthorityIndex::ZERO, BlockDigest::MIN)),
+        )) {
+            let ((round, author, digest), serialized) = kv?;
+            let signed_block: SignedBlock =
+                bcs::from_bytes(&serialized).map_err(ConsensusError::MalformedBlock)?;
+            let block = VerifiedBlock::new_verified(signed_block, serialized);
+            let key = BlockRef::new(round, author, digest);
+            if key != block.reference() {
+                return Err(ConsensusError::UnexpectedStoredBlock {
+                    key,
+                    actual: block.reference(),
+                });
+            }
+            blocks.push(block);
         }
+        Ok(blocks)
     }
 }
 
@@ -145,7 +220,7 @@ impl Store for RocksDBStore {
                 )
                 .map_err(ConsensusError::RocksDBFailure)?;
         }
-        batch.write()?;
+        batch.write_sync()?;
         Ok(())
     }
 
@@ -280,6 +355,18 @@ impl Store for RocksDBStore {
         Ok(commits)
     }
 
+    fn read_commit_votes(&self, commit_index: CommitIndex) -> ConsensusResult<Vec<BlockRef>> {
+        let mut votes = vec![];
+        for result in self.commit_votes.safe_range_iter((
+            Included((commit_index, CommitDigest::MIN, BlockRef::default())),
+            Excluded((commit_index + 1, CommitDigest::MIN, BlockRef::default())),
+        )) {
+            let ((_index, _digest, block_ref), _) = result?;
+            votes.push(block_ref);
+        }
+        Ok(votes)
+    }
+
     fn read_last_commit_info(&self) -> ConsensusResult<Option<CommitInfo>> {
         let Some(result) = self.commit_info.safe_iter().skip_to_last().next() else {
             return Ok(None);
@@ -287,4 +374,61 @@ impl Store for RocksDBStore {
         let (_, commit_info) = result.map_err(ConsensusError::RocksDBFailure)?;
         Ok(Some(commit_info))
     }
+
+    fn prune(
+        &self,
+        last_pruned_commit: CommitIndex,
+        last_pruned_rounds: &[(AuthorityIndex, Round)],
+    ) -> ConsensusResult<()> {
+        let mut batch = self.blocks.batch();
+        for (author, last_pruned_round) in last_pruned_rounds {
+            let mut pruned = vec![];
+            for kv in self.digests_by_authorities.safe_range_iter((
+                Included((*author, Round::MIN, BlockDigest::MIN)),
+                Included((*author, *last_pruned_round, BlockDigest::MAX)),
+            )) {
+                let (key, _) = kv?;
+                pruned.push(key);
+            }
+            batch
+                .delete_batch(
+                    &self.blocks,
+                    pruned
+                        .iter()
+                        .map(|(author, round, digest)| (*round, *author, *digest)),
+                )
+                .map_err(ConsensusError::RocksDBFailure)?;
+            batch
+                .delete_batch(&self.digests_by_authorities, pruned)
+                .map_err(ConsensusError::RocksDBFailure)?;
+        }
+        // Commits are keyed by index first, so they can be deleted by ranges.
+        batch
+            .delete_range(
+                &self.commits,
+                &(CommitIndex::MIN, CommitDigest::MIN),
+                &(last_pruned_commit + 1, CommitDigest::MIN),
+            )
+            .map_err(ConsensusError::RocksDBFailure)?;
+        batch
+            .delete_range(
+                &self.commit_votes,
+                &(CommitIndex::MIN, CommitDigest::MIN, BlockRef::default()),
+                &(
+                    last_pruned_commit + 1,
+                    CommitDigest::MIN,
+                    BlockRef::default(),
+                ),
+            )
+            .map_err(ConsensusError::RocksDBFailure)?;
+        batch
+            .delete_range(
+                &self.commit_info,
+                &(CommitIndex::MIN, CommitDigest::MIN),
+                &(last_pruned_commit + 1, CommitDigest::MIN),
+            )
+            .map_err(ConsensusError::RocksDBFailure)?;
+        batch.write_sync()?;
+        Ok(())
+    }
 }
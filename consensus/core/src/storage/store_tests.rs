@@ -8,12 +8,13 @@ use tempfile::TempDir;
 use super::{mem_store::MemStore, rocksdb_store::RocksDBStore, Store, WriteBatch};
 use crate::{
     block::{BlockAPI, BlockDigest, BlockRef, Slot, TestBlock, VerifiedBlock},
-    commit::{CommitDigest, TrustedCommit},
+    commit::{CommitAPI as _, CommitDigest, TrustedCommit},
 };
 
 /// Test fixture for store tests. Wraps around various store implementations.
 enum TestStore {
     RocksDB((RocksDBStore, TempDir)),
+    InMemoryDB(RocksDBStore),
     Mem(MemStore),
 }
 
@@ -21,6 +22,7 @@ impl TestStore {
     fn store(&self) -> &dyn Store {
         match self {
             TestStore::RocksDB((store, _)) => store,
+            TestStore::InMemoryDB(store) => store,
             TestStore::Mem(store) => store,
         }
     }
@@ -34,6 +36,10 @@ fn new_rocksdb_teststore() -> TestStore {
     ))
 }
 
+fn new_in_memory_db_teststore() -> TestStore {
+    TestStore::InMemoryDB(RocksDBStore::new_in_memory())
+}
+
 fn new_mem_teststore() -> TestStore {
     TestStore::Mem(MemStore::new())
 }
@@ -41,7 +47,12 @@ fn new_mem_teststore() -> TestStore {
 #[rstest]
 #[tokio::test]
 async fn read_and_contain_blocks(
-    #[values(new_rocksdb_teststore(), new_mem_teststore())] test_store: TestStore,
+    #[values(
+        new_rocksdb_teststore(),
+        new_in_memory_db_teststore(),
+        new_mem_teststore()
+    )]
+    test_store: TestStore,
 ) {
     let store = test_store.store();
 
@@ -120,7 +131,12 @@ async fn read_and_contain_blocks(
 #[rstest]
 #[tokio::test]
 async fn scan_blocks(
-    #[values(new_rocksdb_teststore(), new_mem_teststore())] test_store: TestStore,
+    #[values(
+        new_rocksdb_teststore(),
+        new_in_memory_db_teststore(),
+        new_mem_teststore()
+    )]
+    test_store: TestStore,
 ) {
     let store = test_store.store();
 
@@ -203,7 +219,12 @@ async fn scan_blocks(
 #[rstest]
 #[tokio::test]
 async fn read_and_scan_commits(
-    #[values(new_rocksdb_teststore(), new_mem_teststore())] test_store: TestStore,
+    #[values(
+        new_rocksdb_teststore(),
+        new_in_memory_db_teststore(),
+        new_mem_teststore()
+    )]
+    test_store: TestStore,
 ) {
     let store = test_store.store();
 
@@ -293,3 +314,174 @@ async fn read_and_scan_commits(
         assert_eq!(scanned_commits, written_commits,);
     }
 }
+
+#[rstest]
+#[tokio::test]
+async fn read_commit_votes(
+    #[values(
+        new_rocksdb_teststore(),
+        new_in_memory_db_teststore(),
+        new_mem_teststore()
+    )]
+    test_store: TestStore,
+) {
+    let store = test_store.store();
+
+    let commits: Vec<_> = (1..=3)
+        .map(|index| {
+            TrustedCommit::new_for_test(
+                index,
+                CommitDigest::MIN,
+                BlockRef::new(
+                    index,
+                    AuthorityIndex::new_for_test(0),
+                    BlockDigest::default(),
+                ),
+                vec![],
+            )
+            .reference()
+        })
+        .collect();
+    let written_blocks: Vec<VerifiedBlock> = vec![
+        VerifiedBlock::new_for_test(
+            TestBlock::new(5, 0)
+                .set_commit_votes(vec![commits[0], commits[1]])
+                .build(),
+        ),
+        VerifiedBlock::new_for_test(
+            TestBlock::new(5, 1)
+                .set_commit_votes(vec![commits[1]])
+                .build(),
+        ),
+        VerifiedBlock::new_for_test(
+            TestBlock::new(6, 2)
+                .set_commit_votes(vec![commits[1], commits[2]])
+                .build(),
+        ),
+    ];
+    store
+        .write(WriteBatch::default().blocks(written_blocks.clone()))
+        .unwrap();
+
+    let votes = store.read_commit_votes(1).unwrap();
+    assert_eq!(votes, vec![written_blocks[0].reference()]);
+
+    let mut votes = store.read_commit_votes(2).unwrap();
+    votes.sort();
+    let mut expected: Vec<_> = written_blocks.iter().map(|b| b.reference()).collect();
+    expected.sort();
+    assert_eq!(votes, expected);
+
+    let votes = store.read_commit_votes(3).unwrap();
+    assert_eq!(votes, vec![written_blocks[2].reference()]);
+
+    assert!(store.read_commit_votes(4).unwrap().is_empty());
+}
+
+#[rstest]
+#[tokio::test]
+async fn prune(
+    #[values(
+        new_rocksdb_teststore(),
+        new_in_memory_db_teststore(),
+        new_mem_teststore()
+    )]
+    test_store: TestStore,
+) {
+    let store = test_store.store();
+
+    let mut commits = vec![];
+    let mut written_blocks = vec![];
+    for index in 1..=4 {
+        let previous_digest = commits
+            .last()
+            .map(|commit: &TrustedCommit| commit.digest())
+            .unwrap_or(CommitDigest::MIN);
+        let blocks: Vec<VerifiedBlock> = (0..2)
+            .map(|author| VerifiedBlock::new_for_test(TestBlock::new(index, author).build()))
+            .collect();
+        let commit = TrustedCommit::new_for_test(
+            index,
+            previous_digest,
+            blocks[0].reference(),
+            blocks.iter().map(|block| block.reference()).collect(),
+        );
+        // Blocks of the next round vote for the commit.
+        let votes = VerifiedBlock::new_for_test(
+            TestBlock::new(index + 1, 2)
+                .set_commit_votes(vec![commit.reference()])
+                .build(),
+        );
+        store
+            .write(WriteBatch::new(
+                vec![blocks.clone(), vec![votes.clone()]].concat(),
+                vec![commit.clone()],
+                vec![index, index, index + 1],
+            ))
+            .unwrap();
+        commits.push(commit);
+        written_blocks.extend(blocks);
+        written_blocks.push(votes);
+    }
+
+    store
+        .prune(
+            2,
+            &[
+                (AuthorityIndex::new_for_test(0), 2),
+                (AuthorityIndex::new_for_test(1), 1),
+                (AuthorityIndex::new_for_test(2), 3),
+            ],
+        )
+        .unwrap();
+
+    // Commits up to the pruned index are deleted, with their votes.
+    assert_eq!(store.scan_commits(1..5).unwrap(), commits[2..].to_vec());
+    assert!(store.read_commit_votes(1).unwrap().is_empty());
+    assert!(store.read_commit_votes(2).unwrap().is_empty());
+    assert_eq!(store.read_commit_votes(3).unwrap().len(), 1);
+    assert_eq!(store.read_last_commit().unwrap(), Some(commits[3].clone()));
+    assert_eq!(
+        store
+            .read_last_commit_info()
+            .unwrap()
+            .unwrap()
+            .last_committed_rounds,
+        vec![4, 4, 5]
+    );
+
+    // Blocks are deleted up to the pruned round of each authority.
+    for (author, first_round) in [(0, 3), (1, 2), (2, 4)] {
+        let blocks = store
+            .scan_blocks_by_author(AuthorityIndex::new_for_test(author), 0)
+            .unwrap();
+        assert_eq!(blocks.first().unwrap().round(), first_round);
+    }
+    let exist = store
+        .contains_blocks(
+            &written_blocks
+                .iter()
+                .map(|block| block.reference())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+    for (block, exist) in written_blocks.iter().zip(exist) {
+        let pruned_round = match block.author().value() {
+            0 => 2,
+            1 => 1,
+            _ => 3,
+        };
+        assert_eq!(
+            exist,
+            block.round() > pruned_round,
+            "{:?}",
+            block.reference()
+        );
+    }
+
+    // Later commits can still be loaded from the remaining blocks.
+    for commit in store.scan_commits(3..5).unwrap() {
+        let blocks = store.read_blocks(commit.blocks()).unwrap();
+        assert!(blocks.iter().all(|block| block.is_some()));
+    }
+}
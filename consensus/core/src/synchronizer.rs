@@ -34,7 +34,7 @@ const FETCH_REQUEST_TIMEOUT: Duration = Duration::from_millis(2_000);
 
 const FETCH_FROM_PEERS_TIMEOUT: Duration = Duration::from_millis(4_000);
 
-const MAX_FETCH_BLOCKS_PER_REQUEST: usize = 200;
+pub(crate) const MAX_FETCH_BLOCKS_PER_REQUEST: usize = 200;
 
 enum Command {
     FetchBlocks {
@@ -484,6 +484,7 @@ impl<C: NetworkClient, V: BlockVerifier, D: CoreThreadDispatcher> Synchronizer<C
 mod tests {
     use crate::block::{BlockRef, Round, TestBlock, VerifiedBlock};
     use crate::block_verifier::NoopBlockVerifier;
+    use crate::commit::CommitIndex;
     use crate::context::Context;
     use crate::core_thread::{CoreError, CoreThreadDispatcher};
     use crate::error::{ConsensusError, ConsensusResult};
@@ -493,6 +494,7 @@ mod tests {
     use bytes::Bytes;
     use consensus_config::AuthorityIndex;
     use std::collections::{BTreeMap, BTreeSet};
+    use std::ops::Range;
     use std::sync::Arc;
     use std::time::Duration;
     use tokio::time::sleep;
@@ -610,6 +612,14 @@ mod tests {
 
             Ok(serialised)
         }
+        async fn fetch_commits(
+            &self,
+            _peer: AuthorityIndex,
+            _commit_range: Range<CommitIndex>,
+            _timeout: Duration,
+        ) -> ConsensusResult<(Vec<Bytes>, Vec<Bytes>)> {
+            unimplemented!("Unimplemented")
+        }
     }
 
     #[tokio::test]
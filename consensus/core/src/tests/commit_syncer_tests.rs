@@ -0,0 +1,428 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::BTreeSet, ops::Range, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use consensus_config::{AuthorityIndex, Parameters};
+use parking_lot::{Mutex, RwLock};
+
+use super::{load_certified_commits, CommitSyncer, CommitVoteMonitor};
+use crate::{
+    block::{genesis_blocks, BlockAPI as _, BlockRef, Round, TestBlock, VerifiedBlock},
+    block_verifier::NoopBlockVerifier,
+    commit::{CommitAPI as _, CommitDigest, CommitIndex, CommitRef, TrustedCommit},
+    context::Context,
+    core_thread::{CoreError, CoreThreadDispatcher},
+    dag_state::DagState,
+    error::{ConsensusError, ConsensusResult},
+    network::{BlockStream, NetworkClient},
+    storage::{mem_store::MemStore, Store, WriteBatch},
+};
+
+/// Builds a chain of `num_commits` commits, each committing the blocks of one round, with a
+/// leader from authority 0. Blocks of each round vote for the commit of the previous round, so
+/// when `certify_last` is set, blocks of an additional round certify the last commit.
+fn build_commit_chain(
+    context: Arc<Context>,
+    num_commits: u32,
+    certify_last: bool,
+) -> (Arc<MemStore>, Vec<TrustedCommit>, Vec<VerifiedBlock>) {
+    let store = Arc::new(MemStore::new());
+    let mut ancestors: Vec<BlockRef> = genesis_blocks(context.clone())
+        .iter()
+        .map(|block| block.reference())
+        .collect();
+    let mut commits: Vec<TrustedCommit> = vec![];
+    let mut all_blocks = vec![];
+    let last_round = if certify_last {
+        num_commits + 1
+    } else {
+        num_commits
+    };
+    for round in 1..=last_round {
+        let commit_votes: Vec<CommitRef> =
+            commits.last().map(|c| c.reference()).into_iter().collect();
+        let blocks: Vec<VerifiedBlock> = context
+            .committee
+            .authorities()
+            .map(|(authority, _)| {
+                VerifiedBlock::new_for_test(
+                    TestBlock::new(round, authority.value() as u32)
+                        .set_ancestors(ancestors.clone())
+                        .set_commit_votes(commit_votes.clone())
+                        .build(),
+                )
+            })
+            .collect();
+        ancestors = blocks.iter().map(|block| block.reference()).collect();
+        if round <= num_commits {
+            let previous_digest = commits
+                .last()
+                .map(|c| c.digest())
+                .unwrap_or(CommitDigest::MIN);
+            commits.push(TrustedCommit::new_for_test(
+                round,
+                previous_digest,
+                blocks[0].reference(),
+                ancestors.clone(),
+            ));
+        }
+        all_blocks.extend(blocks);
+    }
+    store
+        .write(
+            WriteBatch::default()
+                .blocks(all_blocks.clone())
+                .commits(commits.clone()),
+        )
+        .unwrap();
+    (store, commits, all_blocks)
+}
+
+fn context_with_batch_size(batch_size: u32) -> Arc<Context> {
+    let (context, _) = Context::new_for_test(4);
+    Arc::new(context.with_parameters(Parameters {
+        commit_sync_batch_size: batch_size,
+        ..Default::default()
+    }))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PeerBehavior {
+    Honest,
+    WithoutCertifierBlocks,
+    SkipFirstCommit,
+}
+
+/// Serves commits and blocks from a store, like AuthorityService of peers would.
+struct StoreNetworkClient {
+    context: Arc<Context>,
+    store: Arc<MemStore>,
+    behavior: PeerBehavior,
+    fetch_commits_requests: Mutex<Vec<(AuthorityIndex, Range<CommitIndex>)>>,
+}
+
+impl StoreNetworkClient {
+    fn new(context: Arc<Context>, store: Arc<MemStore>, behavior: PeerBehavior) -> Self {
+        Self {
+            context,
+            store,
+            behavior,
+            fetch_commits_requests: Mutex::new(vec![]),
+        }
+    }
+}
+
+#[async_trait]
+impl NetworkClient for StoreNetworkClient {
+    const SUPPORT_STREAMING: bool = false;
+
+    async fn send_block(
+        &self,
+        _peer: AuthorityIndex,
+        _block: &VerifiedBlock,
+        _timeout: Duration,
+    ) -> ConsensusResult<()> {
+        unimplemented!("Unimplemented")
+    }
+
+    async fn subscribe_blocks(
+        &self,
+        _peer: AuthorityIndex,
+        _last_received: Round,
+        _timeout: Duration,
+    ) -> ConsensusResult<BlockStream> {
+        unimplemented!("Unimplemented")
+    }
+
+    async fn fetch_blocks(
+        &self,
+        _peer: AuthorityIndex,
+        block_refs: Vec<BlockRef>,
+        _timeout: Duration,
+    ) -> ConsensusResult<Vec<Bytes>> {
+        Ok(self
+            .store
+            .read_blocks(&block_refs)?
+            .into_iter()
+            .flatten()
+            .map(|block| block.serialized().clone())
+            .collect())
+    }
+
+    async fn fetch_commits(
+        &self,
+        peer: AuthorityIndex,
+        commit_range: Range<CommitIndex>,
+        _timeout: Duration,
+    ) -> ConsensusResult<(Vec<Bytes>, Vec<Bytes>)> {
+        self.fetch_commits_requests
+            .lock()
+            .push((peer, commit_range.clone()));
+        let (commits, certifier_blocks) =
+            load_certified_commits(&self.context, self.store.as_ref(), commit_range)?;
+        let mut commits: Vec<Bytes> = commits
+            .iter()
+            .map(|commit| commit.serialized().clone())
+            .collect();
+        let mut certifier_blocks: Vec<Bytes> = certifier_blocks
+            .iter()
+            .map(|block| block.serialized().clone())
+            .collect();
+        match self.behavior {
+            PeerBehavior::Honest => {}
+            PeerBehavior::WithoutCertifierBlocks => certifier_blocks.truncate(1),
+            PeerBehavior::SkipFirstCommit => {
+                commits.remove(0);
+            }
+        }
+        Ok((commits, certifier_blocks))
+    }
+}
+
+#[derive(Default)]
+struct MockCoreThreadDispatcher {
+    blocks: Mutex<Vec<VerifiedBlock>>,
+}
+
+#[async_trait]
+impl CoreThreadDispatcher for MockCoreThreadDispatcher {
+    async fn add_blocks(
+        &self,
+        blocks: Vec<VerifiedBlock>,
+    ) -> Result<BTreeSet<BlockRef>, CoreError> {
+        self.blocks.lock().extend(blocks);
+        Ok(BTreeSet::new())
+    }
+
+    async fn force_new_block(&self, _round: Round) -> Result<(), CoreError> {
+        unimplemented!()
+    }
+
+    async fn get_missing_blocks(&self) -> Result<BTreeSet<BlockRef>, CoreError> {
+        unimplemented!()
+    }
+}
+
+fn new_commit_syncer(
+    context: Arc<Context>,
+    network_client: Arc<StoreNetworkClient>,
+    core_dispatcher: Arc<MockCoreThreadDispatcher>,
+    commit_vote_monitor: Arc<CommitVoteMonitor>,
+) -> CommitSyncer<StoreNetworkClient, NoopBlockVerifier, MockCoreThreadDispatcher> {
+    let dag_state = Arc::new(RwLock::new(DagState::new(
+        context.clone(),
+        Arc::new(MemStore::new()),
+    )));
+    CommitSyncer::new(
+        context,
+        network_client,
+        Arc::new(NoopBlockVerifier),
+        core_dispatcher,
+        dag_state,
+        commit_vote_monitor,
+    )
+}
+
+#[test]
+fn commit_vote_monitor_quorum_index() {
+    let context = context_with_batch_size(10);
+    let monitor = CommitVoteMonitor::new(context.clone());
+    assert_eq!(monitor.quorum_commit_index(), 0);
+
+    let vote = |index| CommitRef {
+        index,
+        digest: CommitDigest::MIN,
+    };
+    let block = |author, votes: Vec<CommitIndex>| {
+        VerifiedBlock::new_for_test(
+            TestBlock::new(1, author)
+                .set_commit_votes(votes.into_iter().map(vote).collect())
+                .build(),
+        )
+    };
+    monitor.observe(&block(0, vec![10, 12]));
+    monitor.observe(&block(1, vec![20]));
+    // A quorum needs 3 of 4 authorities.
+    assert_eq!(monitor.quorum_commit_index(), 0);
+
+    monitor.observe(&block(2, vec![15]));
+    assert_eq!(monitor.quorum_commit_index(), 12);
+
+    // Lower votes do not lower the highest vote of an authority.
+    monitor.observe(&block(0, vec![5]));
+    monitor.observe(&block(3, vec![30]));
+    assert_eq!(monitor.quorum_commit_index(), 15);
+}
+
+#[test]
+fn load_certified_commits_from_store() {
+    let context = context_with_batch_size(4);
+    let (store, commits, _) = build_commit_chain(context.clone(), 10, false);
+
+    // Responses are bounded by the batch size.
+    let (loaded, certifier_blocks) =
+        load_certified_commits(&context, store.as_ref(), 1..11).unwrap();
+    assert_eq!(loaded, commits[0..4].to_vec());
+    assert_eq!(certifier_blocks.len(), 3);
+    for block in &certifier_blocks {
+        assert!(block.commit_votes().contains(&commits[3].reference()));
+    }
+
+    // The last commit does not have any vote yet, so it is not returned.
+    let (loaded, certifier_blocks) =
+        load_certified_commits(&context, store.as_ref(), 8..11).unwrap();
+    assert_eq!(loaded, commits[7..9].to_vec());
+    assert_eq!(certifier_blocks.len(), 3);
+
+    let (loaded, certifier_blocks) =
+        load_certified_commits(&context, store.as_ref(), 10..11).unwrap();
+    assert!(loaded.is_empty());
+    assert!(certifier_blocks.is_empty());
+
+    assert!(matches!(
+        load_certified_commits(&context, store.as_ref(), 5..5),
+        Err(ConsensusError::InvalidCommitRange { .. })
+    ));
+    assert!(matches!(
+        load_certified_commits(&context, store.as_ref(), 0..5),
+        Err(ConsensusError::InvalidCommitRange { .. })
+    ));
+}
+
+#[tokio::test]
+async fn fetch_and_verify_certified_commits() {
+    let context = context_with_batch_size(4);
+    let (store, commits, _) = build_commit_chain(context.clone(), 10, true);
+    let network_client = Arc::new(StoreNetworkClient::new(
+        context.clone(),
+        store.clone(),
+        PeerBehavior::Honest,
+    ));
+    let commit_syncer = new_commit_syncer(
+        context.clone(),
+        network_client,
+        Arc::new(MockCoreThreadDispatcher::default()),
+        Arc::new(CommitVoteMonitor::new(context.clone())),
+    );
+
+    let peer = AuthorityIndex::new_for_test(1);
+    let (fetched_commits, blocks) = commit_syncer
+        .fetch_certified_commits(peer, 1..5)
+        .await
+        .unwrap();
+    assert_eq!(fetched_commits, commits[0..4].to_vec());
+
+    // All blocks of the commits are fetched in commit order, followed by the certifier blocks.
+    let committed_refs: Vec<BlockRef> = commits[0..4]
+        .iter()
+        .flat_map(|commit| commit.blocks().to_vec())
+        .collect();
+    let fetched_refs: Vec<BlockRef> = blocks.iter().map(|block| block.reference()).collect();
+    assert_eq!(fetched_refs[..committed_refs.len()], committed_refs[..]);
+    for block in &blocks[committed_refs.len()..] {
+        assert!(block.commit_votes().contains(&commits[3].reference()));
+    }
+}
+
+#[tokio::test]
+async fn reject_uncertified_or_unchained_commits() {
+    let context = context_with_batch_size(4);
+    let (store, _, _) = build_commit_chain(context.clone(), 10, true);
+    let peer = AuthorityIndex::new_for_test(1);
+
+    let commit_syncer = new_commit_syncer(
+        context.clone(),
+        Arc::new(StoreNetworkClient::new(
+            context.clone(),
+            store.clone(),
+            PeerBehavior::WithoutCertifierBlocks,
+        )),
+        Arc::new(MockCoreThreadDispatcher::default()),
+        Arc::new(CommitVoteMonitor::new(context.clone())),
+    );
+    assert!(matches!(
+        commit_syncer.fetch_certified_commits(peer, 1..5).await,
+        Err(ConsensusError::InsufficientCommitVotes { .. })
+    ));
+
+    let commit_syncer = new_commit_syncer(
+        context.clone(),
+        Arc::new(StoreNetworkClient::new(
+            context.clone(),
+            store.clone(),
+            PeerBehavior::SkipFirstCommit,
+        )),
+        Arc::new(MockCoreThreadDispatcher::default()),
+        Arc::new(CommitVoteMonitor::new(context.clone())),
+    );
+    assert!(matches!(
+        commit_syncer.fetch_certified_commits(peer, 1..5).await,
+        Err(ConsensusError::UnexpectedFetchedCommit {
+            expected: 1,
+            actual: 2,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn sync_commits_when_falling_behind() {
+    let context = context_with_batch_size(4);
+    let (store, commits, all_blocks) = build_commit_chain(context.clone(), 10, true);
+    let network_client = Arc::new(StoreNetworkClient::new(
+        context.clone(),
+        store.clone(),
+        PeerBehavior::Honest,
+    ));
+    let core_dispatcher = Arc::new(MockCoreThreadDispatcher::default());
+    let commit_vote_monitor = Arc::new(CommitVoteMonitor::new(context.clone()));
+    let mut commit_syncer = new_commit_syncer(
+        context.clone(),
+        network_client.clone(),
+        core_dispatcher.clone(),
+        commit_vote_monitor.clone(),
+    );
+
+    // Nothing is synced until a quorum voted for commits more than a batch ahead.
+    assert_eq!(commit_syncer.try_sync_commits().await.unwrap(), 0);
+    for block in all_blocks.iter().filter(|block| block.round() == 5) {
+        commit_vote_monitor.observe(block);
+    }
+    assert_eq!(commit_vote_monitor.quorum_commit_index(), 4);
+    assert_eq!(commit_syncer.try_sync_commits().await.unwrap(), 0);
+    assert!(network_client.fetch_commits_requests.lock().is_empty());
+
+    for block in all_blocks.iter().filter(|block| block.round() == 11) {
+        commit_vote_monitor.observe(block);
+    }
+    assert_eq!(commit_vote_monitor.quorum_commit_index(), 10);
+    assert_eq!(commit_syncer.try_sync_commits().await.unwrap(), 4);
+    assert_eq!(commit_syncer.synced_commit, commits[3].reference());
+    let added_refs: BTreeSet<BlockRef> = core_dispatcher
+        .blocks
+        .lock()
+        .iter()
+        .map(|block| block.reference())
+        .collect();
+    for commit in &commits[0..4] {
+        for block_ref in commit.blocks() {
+            assert!(added_refs.contains(block_ref));
+        }
+    }
+
+    // The next batch follows the synced commits, before Core commits them locally.
+    assert_eq!(commit_syncer.try_sync_commits().await.unwrap(), 4);
+    assert_eq!(commit_syncer.synced_commit, commits[7].reference());
+    // The remaining commits are within a batch of the quorum, left to regular synchronization.
+    assert_eq!(commit_syncer.try_sync_commits().await.unwrap(), 0);
+    assert_eq!(
+        *network_client.fetch_commits_requests.lock(),
+        vec![
+            (AuthorityIndex::new_for_test(1), 1..5),
+            (AuthorityIndex::new_for_test(1), 5..9),
+        ]
+    );
+}
@@ -0,0 +1,209 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
+
+use consensus_config::{local_committee_and_keys, AuthorityIndex, Parameters};
+use prometheus::Registry;
+use rstest::rstest;
+use sui_protocol_config::ProtocolConfig;
+use tempfile::TempDir;
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver},
+    time::sleep,
+};
+
+use super::AuthorityNode;
+use crate::{
+    block::{BlockAPI as _, BlockRef},
+    commit::{CommitIndex, CommittedSubDag},
+    network::sim_network::{NetworkFaults, SimNetwork, SimNetworkManager},
+    transaction::NoopTransactionVerifier,
+    CommitConsumer,
+};
+
+const NUM_AUTHORITIES: usize = 4;
+
+/// An authority running on the `SimNetwork`, with the commits it has output so far.
+struct SimAuthority {
+    node: AuthorityNode<SimNetworkManager>,
+    commit_receiver: UnboundedReceiver<CommittedSubDag>,
+    commits: Vec<(CommitIndex, BlockRef, Vec<BlockRef>)>,
+    transactions: BTreeSet<Vec<u8>>,
+}
+
+impl SimAuthority {
+    /// Collects commits output by `CommitObserver` since the last call.
+    fn receive_commits(&mut self) -> &[(CommitIndex, BlockRef, Vec<BlockRef>)] {
+        while let Ok(subdag) = self.commit_receiver.try_recv() {
+            let block_refs = subdag.blocks.iter().map(|b| b.reference()).collect();
+            self.transactions.extend(
+                subdag
+                    .blocks
+                    .iter()
+                    .flat_map(|b| b.transactions().iter().map(|t| t.data().to_vec())),
+            );
+            self.commits
+                .push((subdag.commit_index, subdag.leader, block_refs));
+        }
+        &self.commits
+    }
+}
+
+/// Starts all authorities of a committee with equal stake on `network`. Authorities in
+/// `equivocators` send equivocating blocks to peers.
+async fn start_authorities(
+    network: &SimNetwork,
+    equivocators: &BTreeSet<usize>,
+) -> Vec<SimAuthority> {
+    let (committee, keypairs) = local_committee_and_keys(0, vec![1; NUM_AUTHORITIES]);
+    let mut authorities = vec![];
+    for (index, _authority_info) in committee.authorities() {
+        let temp_dir = TempDir::new().unwrap();
+        let parameters = Parameters {
+            db_path: Some(temp_dir.into_path()),
+            ..Default::default()
+        };
+        let network_keypair = keypairs[index].0.clone();
+        let protocol_keypair = keypairs[index].1.clone();
+        if equivocators.contains(&index.value()) {
+            network.add_equivocator(index, protocol_keypair.clone());
+        }
+
+        let (sender, commit_receiver) = unbounded_channel();
+        let node = network
+            .scope(AuthorityNode::<SimNetworkManager>::start(
+                index,
+                committee.clone(),
+                parameters,
+                ProtocolConfig::get_for_max_version_UNSAFE(),
+                protocol_keypair,
+                network_keypair,
+                Arc::new(NoopTransactionVerifier {}),
+                CommitConsumer::new(sender, 0, 0),
+                Registry::new(),
+            ))
+            .await;
+        authorities.push(SimAuthority {
+            node,
+            commit_receiver,
+            commits: vec![],
+            transactions: BTreeSet::new(),
+        });
+    }
+    authorities
+}
+
+/// Asserts that commit sequences of honest authorities are identical up to the shortest one,
+/// which has at least `min_commits` commits.
+fn assert_consistent_commits(
+    authorities: &mut [SimAuthority],
+    equivocators: &BTreeSet<usize>,
+    min_commits: usize,
+) {
+    let sequences: Vec<_> = authorities
+        .iter_mut()
+        .enumerate()
+        .filter(|(index, _)| !equivocators.contains(index))
+        .map(|(_, authority)| authority.receive_commits().to_vec())
+        .collect();
+    let shortest = sequences.iter().map(|s| s.len()).min().unwrap();
+    assert!(
+        shortest >= min_commits,
+        "Expected at least {min_commits} commits, got {shortest}"
+    );
+    for sequence in &sequences[1..] {
+        assert_eq!(sequences[0][..shortest], sequence[..shortest]);
+    }
+}
+
+async fn stop_authorities(authorities: Vec<SimAuthority>) {
+    for authority in authorities {
+        authority.node.stop().await;
+    }
+}
+
+#[rstest]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_commits_with_drops_and_delays(#[values(1, 2, 3)] seed: u64) {
+    let network = SimNetwork::new(seed);
+    network.set_faults(NetworkFaults {
+        drop_probability: 0.1,
+        max_delay: Duration::from_millis(300),
+        partitions: vec![],
+    });
+    let mut authorities = start_authorities(&network, &BTreeSet::new()).await;
+
+    let mut submitted_transactions = BTreeSet::new();
+    for i in 0..20u8 {
+        let txn = vec![i; 16];
+        submitted_transactions.insert(txn.clone());
+        authorities[i as usize % NUM_AUTHORITIES]
+            .node
+            .transaction_client()
+            .submit(txn)
+            .await
+            .unwrap();
+    }
+
+    sleep(Duration::from_secs(30)).await;
+
+    assert!(network.num_dropped_messages() > 0);
+    assert_consistent_commits(&mut authorities, &BTreeSet::new(), 10);
+
+    // All submitted transactions are committed.
+    for authority in &authorities {
+        assert_eq!(authority.transactions, submitted_transactions);
+    }
+
+    stop_authorities(authorities).await;
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_commits_with_partition() {
+    let network = SimNetwork::new(0);
+    let mut authorities = start_authorities(&network, &BTreeSet::new()).await;
+
+    sleep(Duration::from_secs(5)).await;
+    assert_consistent_commits(&mut authorities, &BTreeSet::new(), 1);
+
+    // Isolate authority 3. The rest of the committee has a quorum and keeps committing.
+    network.set_faults(NetworkFaults {
+        partitions: vec![
+            BTreeSet::from([0, 1, 2].map(AuthorityIndex::new_for_test)),
+            BTreeSet::from([AuthorityIndex::new_for_test(3)]),
+        ],
+        ..Default::default()
+    });
+    let lagging_commits = authorities[3].receive_commits().len();
+    sleep(Duration::from_secs(20)).await;
+    let partitioned_commits = authorities[0].receive_commits().len();
+    assert!(partitioned_commits > lagging_commits + 10);
+    assert!(authorities[3].receive_commits().len() < partitioned_commits);
+
+    // After the partition heals, authority 3 catches up with the same commits.
+    network.set_faults(NetworkFaults::default());
+    sleep(Duration::from_secs(20)).await;
+    assert_consistent_commits(&mut authorities, &BTreeSet::new(), partitioned_commits);
+
+    stop_authorities(authorities).await;
+}
+
+#[rstest]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_commits_with_equivocator(#[values(1, 2)] seed: u64) {
+    let network = SimNetwork::new(seed);
+    network.set_faults(NetworkFaults {
+        max_delay: Duration::from_millis(100),
+        ..Default::default()
+    });
+    let equivocators = BTreeSet::from([0]);
+    let mut authorities = start_authorities(&network, &equivocators).await;
+
+    sleep(Duration::from_secs(30)).await;
+
+    assert!(network.num_equivocating_blocks() > 0);
+    assert_consistent_commits(&mut authorities, &equivocators, 10);
+
+    stop_authorities(authorities).await;
+}
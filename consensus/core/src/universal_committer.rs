@@ -157,6 +157,12 @@ pub(crate) mod universal_committer_builder {
             }
         }
 
+        /// Uses a leader schedule shared with the caller, e.g. to update it from commits.
+        pub(crate) fn with_leader_schedule(mut self, leader_schedule: LeaderSchedule) -> Self {
+            self.leader_schedule = leader_schedule;
+            self
+        }
+
         #[allow(unused)]
         pub(crate) fn with_wave_length(mut self, wave_length: Round) -> Self {
             self.wave_length = wave_length;
@@ -73,6 +73,9 @@ impl ArchiveReaderBalancer {
         }
         Ok(ArchiveReaderBalancer { readers })
     }
+    pub fn is_empty(&self) -> bool {
+        self.readers.is_empty()
+    }
     pub async fn get_archive_watermark(&self) -> Result<Option<u64>> {
         let mut checkpoints: Vec<Result<CheckpointSequenceNumber>> = vec![];
         for reader in self
@@ -473,6 +476,68 @@ impl ArchiveReader {
             .await
     }
 
+    /// Return the checkpoint range of the content file holding `sequence_number`, without
+    /// downloading it.
+    pub async fn contents_file_range(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Result<Range<CheckpointSequenceNumber>> {
+        Ok(self
+            .contents_file_metadata(sequence_number)
+            .await?
+            .checkpoint_seq_range)
+    }
+
+    /// Download the content file holding `sequence_number` and return its checkpoint range along
+    /// with the contents of every checkpoint in the range, in order. Contents are not verified,
+    /// callers must check them against the digests in verified checkpoint summaries.
+    pub async fn read_contents_file(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Result<(Range<CheckpointSequenceNumber>, Vec<CheckpointContents>)> {
+        let file_metadata = self.contents_file_metadata(sequence_number).await?;
+        let content_data = get(&self.remote_object_store, &file_metadata.file_path()).await?;
+        let contents: Vec<CheckpointContents> = make_iterator::<CheckpointContents, Reader<Bytes>>(
+            CHECKPOINT_FILE_MAGIC,
+            content_data.reader(),
+        )?
+        .collect();
+        let checkpoint_seq_range = file_metadata.checkpoint_seq_range;
+        if contents.len() as u64 != checkpoint_seq_range.end - checkpoint_seq_range.start {
+            return Err(anyhow!(
+                "Content file {:?} has {} checkpoints, expected range {:?}",
+                file_metadata.file_path(),
+                contents.len(),
+                checkpoint_seq_range
+            ));
+        }
+        let num_txns: usize = contents.iter().map(|c| c.size()).sum();
+        self.archive_reader_metrics
+            .archive_txns_read
+            .with_label_values(&[&self.bucket])
+            .inc_by(num_txns as u64);
+        self.archive_reader_metrics
+            .archive_checkpoints_read
+            .with_label_values(&[&self.bucket])
+            .inc_by(contents.len() as u64);
+        Ok((checkpoint_seq_range, contents))
+    }
+
+    async fn contents_file_metadata(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Result<FileMetadata> {
+        let manifest = self.manifest.lock().await;
+        manifest
+            .files()
+            .into_iter()
+            .find(|f| {
+                f.file_type == FileType::CheckpointContent
+                    && f.checkpoint_seq_range.contains(&sequence_number)
+            })
+            .ok_or_else(|| anyhow!("Checkpoint {sequence_number} is not in the archive"))
+    }
+
     /// Return latest available checkpoint in archive
     pub async fn latest_available_checkpoint(&self) -> Result<CheckpointSequenceNumber> {
         let manifest = self.manifest.lock().await.clone();
@@ -171,6 +171,17 @@ pub struct NodeConfig {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub run_with_range: Option<RunWithRange>,
+
+    /// Fraction of transactions, between 0 and 1, traced end to end through the validator. Spans
+    /// of a traced transaction share a trace derived from its digest, and are exported through
+    /// OTLP tracing when it is enabled. No transaction is traced if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_tracing_sample_rate: Option<f64>,
+
+    /// Limits of event subscriptions filtering on the fields of events. Defaults are used if
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_subscription_config: Option<EventSubscriptionConfig>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
@@ -430,6 +441,40 @@ pub struct CheckpointExecutorConfig {
     /// When specified, each executed checkpoint will be saved in a local directory for post processing
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub data_ingestion_dir: Option<PathBuf>,
+
+    /// If set, the executor switches to catch-up mode when it falls far enough behind the
+    /// synced checkpoints. In catch-up mode, batches of checkpoints are executed together, and
+    /// transactions that don't conflict with each other are executed in parallel with their
+    /// inputs loaded in bulk.
+    ///
+    /// If unspecified, catch-up mode is disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub catch_up: Option<CheckpointCatchUpConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CheckpointCatchUpConfig {
+    /// Minimum number of synced but not yet executed checkpoints to enter catch-up mode.
+    ///
+    /// If unspecified, this will default to `100`.
+    #[serde(default = "default_catch_up_min_lag")]
+    pub min_lag: u64,
+
+    /// Max number of checkpoints executed together in catch-up mode.
+    ///
+    /// If unspecified, this will default to `50`.
+    #[serde(default = "default_catch_up_batch_size")]
+    pub batch_size: usize,
+}
+
+impl Default for CheckpointCatchUpConfig {
+    fn default() -> Self {
+        Self {
+            min_lag: default_catch_up_min_lag(),
+            batch_size: default_catch_up_batch_size(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -524,12 +569,21 @@ fn default_local_execution_timeout_sec() -> u64 {
     30
 }
 
+fn default_catch_up_min_lag() -> u64 {
+    100
+}
+
+fn default_catch_up_batch_size() -> usize {
+    50
+}
+
 impl Default for CheckpointExecutorConfig {
     fn default() -> Self {
         Self {
             checkpoint_execution_max_concurrency: default_checkpoint_execution_max_concurrency(),
             local_execution_timeout_sec: default_local_execution_timeout_sec(),
             data_ingestion_dir: None,
+            catch_up: None,
         }
     }
 }
@@ -648,6 +702,13 @@ pub struct DBCheckpointConfig {
     pub perform_index_db_checkpoints_at_epoch_end: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prune_and_compact_before_upload: Option<bool>,
+    /// If set, db checkpoints are also backed up incrementally to this object store, uploading
+    /// only the SST files that changed since the backup of the previous db checkpoint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_object_store_config: Option<ObjectStoreConfig>,
+    /// Number of incremental backups to keep in the backup object store. All are kept if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_backups_to_keep: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -730,10 +791,41 @@ pub struct AuthorityOverloadConfig {
     // is overloaded.
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub check_system_overload_at_execution: bool,
+
+    // When set to true, load shedding is applied per sender: transactions are
+    // shed from the senders submitting the most transactions first, so that a
+    // single spammy sender can't starve everyone else.
+    #[serde(default = "default_per_sender_load_shedding")]
+    pub per_sender_load_shedding: bool,
+
+    // Which address of a transaction is accounted for in per sender load shedding.
+    #[serde(default)]
+    pub load_shedding_accounting_key: LoadSheddingAccountingKey,
+
+    // The maximum number of senders tracked in an overload monitor interval for
+    // per sender load shedding. Transactions from senders beyond that are shed
+    // at the global load shedding percentage.
+    #[serde(default = "default_max_tracked_load_shedding_senders")]
+    pub max_tracked_load_shedding_senders: usize,
+
+    // The number of senders with the most shed transactions reported in metrics.
+    #[serde(default = "default_top_shed_senders_to_report")]
+    pub top_shed_senders_to_report: usize,
     // TODO: Move other thresholds here as well, including `MAX_TM_QUEUE_LENGTH`
     // and `MAX_PER_OBJECT_QUEUE_LENGTH`.
 }
 
+/// The address a transaction is attributed to for per sender load shedding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LoadSheddingAccountingKey {
+    /// The sender of the transaction.
+    Sender,
+    /// The owner of the gas objects, which differs from the sender for sponsored transactions.
+    #[default]
+    GasOwner,
+}
+
 fn default_max_txn_age_in_queue() -> Duration {
     Duration::from_secs(1)
 }
@@ -766,6 +858,18 @@ fn default_check_system_overload_at_signing() -> bool {
     true
 }
 
+fn default_per_sender_load_shedding() -> bool {
+    true
+}
+
+fn default_max_tracked_load_shedding_senders() -> usize {
+    10_000
+}
+
+fn default_top_shed_senders_to_report() -> usize {
+    10
+}
+
 impl Default for AuthorityOverloadConfig {
     fn default() -> Self {
         Self {
@@ -779,6 +883,10 @@ impl Default for AuthorityOverloadConfig {
             safe_transaction_ready_rate: default_safe_transaction_ready_rate(),
             check_system_overload_at_signing: true,
             check_system_overload_at_execution: false,
+            per_sender_load_shedding: default_per_sender_load_shedding(),
+            load_shedding_accounting_key: LoadSheddingAccountingKey::default(),
+            max_tracked_load_shedding_senders: default_max_tracked_load_shedding_senders(),
+            top_shed_senders_to_report: default_top_shed_senders_to_report(),
         }
     }
 }
@@ -787,6 +895,42 @@ fn default_authority_overload_config() -> AuthorityOverloadConfig {
     AuthorityOverloadConfig::default()
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct EventSubscriptionConfig {
+    /// Max cost of the filter of an event subscription that compares event fields with
+    /// `MoveEventFieldPredicate`, counting each filter and each segment of the paths into event
+    /// fields. Such subscriptions with costlier filters are rejected.
+    ///
+    /// If unspecified, this will default to `32`.
+    #[serde(default = "default_max_event_filter_cost")]
+    pub max_filter_cost: usize,
+
+    /// Max number of concurrent event subscriptions whose filter compares event fields with
+    /// `MoveEventFieldPredicate`, which is evaluated for every event.
+    ///
+    /// If unspecified, this will default to `50`.
+    #[serde(default = "default_max_field_predicate_subscriptions")]
+    pub max_field_predicate_subscriptions: usize,
+}
+
+fn default_max_event_filter_cost() -> usize {
+    32
+}
+
+fn default_max_field_predicate_subscriptions() -> usize {
+    50
+}
+
+impl Default for EventSubscriptionConfig {
+    fn default() -> Self {
+        Self {
+            max_filter_cost: default_max_event_filter_cost(),
+            max_field_predicate_subscriptions: default_max_field_predicate_subscriptions(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Eq)]
 pub struct Genesis {
     #[serde(flatten)]
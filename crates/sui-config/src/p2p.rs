@@ -9,6 +9,8 @@ use sui_types::{
     multiaddr::Multiaddr,
 };
 
+use crate::object_storage_config::ObjectStoreConfig;
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct P2pConfig {
@@ -164,13 +166,15 @@ pub struct StateSyncConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub get_checkpoint_summary_rate_limit: Option<NonZeroU32>,
 
-    /// Per-peer rate-limit (in requests/sec) for the GetCheckpointContents RPC.
+    /// Per-peer rate-limit (in requests/sec) for the GetCheckpointContents RPC. Requests over the
+    /// limit are rejected, and count against the score of the requesting peer.
     ///
     /// If unspecified, this will default to no limit.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub get_checkpoint_contents_rate_limit: Option<NonZeroU32>,
 
-    /// Per-peer inflight limit for the GetCheckpointContents RPC.
+    /// Inflight limit for the GetCheckpointContents RPC. This is enforced globally across all
+    /// peers, so requests over the limit don't count against the score of the requesting peer.
     ///
     /// If unspecified, this will default to no limit.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -182,6 +186,14 @@ pub struct StateSyncConfig {
     /// If unspecified, this will default to no limit.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub get_checkpoint_contents_per_checkpoint_limit: Option<usize>,
+
+    /// Object store holding `{sequence_number}.chk` checkpoint blobs, used as an additional
+    /// source of checkpoint contents after peers. Contents are verified against the synced
+    /// checkpoint summaries like contents from any other source.
+    ///
+    /// If unspecified, checkpoint contents are not downloaded from an object store.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint_content_object_store: Option<ObjectStoreConfig>,
 }
 
 impl StateSyncConfig {
@@ -299,6 +311,21 @@ pub struct DiscoveryConfig {
     /// to this peer, nor advertise this peer's info to other peers in the network.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub allowlisted_peers: Vec<AllowlistedPeer>,
+
+    /// Peers are scored between `100` and below `0` based on the success rate and latency of our
+    /// requests to them, and on how often they violate our rate limits. Peers whose score drops
+    /// below this threshold are disconnected and banned for `peer_ban_duration_ms`. Allowlisted
+    /// and seed peers are never banned.
+    ///
+    /// If unspecified, this will default to `0`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_ban_score_threshold: Option<f64>,
+
+    /// How long a peer stays banned after its score drops below `peer_ban_score_threshold`.
+    ///
+    /// If unspecified, this will default to `600,000` milliseconds, or 10 minutes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_ban_duration_ms: Option<u64>,
 }
 
 impl DiscoveryConfig {
@@ -325,6 +352,19 @@ impl DiscoveryConfig {
         // defaults None to Public
         self.access_type.unwrap_or(AccessType::Public)
     }
+
+    pub fn peer_ban_score_threshold(&self) -> f64 {
+        const PEER_BAN_SCORE_THRESHOLD: f64 = 0.0;
+
+        self.peer_ban_score_threshold
+            .unwrap_or(PEER_BAN_SCORE_THRESHOLD)
+    }
+
+    pub fn peer_ban_duration(&self) -> Duration {
+        const PEER_BAN_DURATION_MS: u64 = 10 * 60 * 1_000; // 10 minutes
+
+        Duration::from_millis(self.peer_ban_duration_ms.unwrap_or(PEER_BAN_DURATION_MS))
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -2,11 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
 
+use move_core_types::language_storage::StructTag;
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
 use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::{parse_sui_fq_name, Identifier};
 
+#[serde_as]
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct TransactionDenyConfig {
@@ -22,13 +28,34 @@ pub struct TransactionDenyConfig {
     /// Note that this does not apply to type arguments.
     /// Also since we only compare the deny list against the upgraded package ID of each dependency
     /// in the used package, when a package ID is denied, newer versions of that package are
-    /// still allowed. If we want to deny the entire upgrade family of a package, we need to
-    /// explicitly specify all the package IDs in the deny list.
-    /// TODO: We could consider making this more flexible, e.g. whether to check in type args,
-    /// whether to block entire upgrade family, whether to allow upgrade and etc.
+    /// still allowed. To deny the entire upgrade family of a package, use
+    /// `package_family_deny_list` instead.
+    /// TODO: We could consider making this more flexible, e.g. whether to allow upgrade and etc.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     package_deny_list: Vec<ObjectID>,
 
+    /// A list of original package IDs whose entire upgrade family is not allowed to be called
+    /// into, upgraded, or depended on in transactions. Every version of a package upgraded
+    /// through its `UpgradeCap` keeps the ID of the first version as its original ID, so this
+    /// covers both existing and future versions of the package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    package_family_deny_list: Vec<ObjectID>,
+
+    /// A list of Move struct types that are not allowed to be used as the type of an input
+    /// object, or as a type argument in transactions, including nested in the type parameters
+    /// of other types. A type listed without type parameters denies all of its instantiations.
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    type_deny_list: Vec<StructTag>,
+
+    /// A list of Move functions, as `package::module::function`, that are not allowed to be
+    /// called from transactions. Only functions called directly by the transaction are checked.
+    /// When the package is the original ID of a package, the function is denied in every version
+    /// of the package.
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    function_deny_list: Vec<DeniedFunction>,
+
     /// A list of sui addresses that are not allowed to be used as the sender or sponsor.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     address_deny_list: Vec<SuiAddress>,
@@ -57,6 +84,12 @@ pub struct TransactionDenyConfig {
     #[serde(skip)]
     package_deny_set: OnceCell<HashSet<ObjectID>>,
 
+    #[serde(skip)]
+    package_family_deny_set: OnceCell<HashSet<ObjectID>>,
+
+    #[serde(skip)]
+    function_deny_set: OnceCell<HashSet<DeniedFunction>>,
+
     #[serde(skip)]
     address_deny_set: OnceCell<HashSet<SuiAddress>>,
 
@@ -71,7 +104,6 @@ pub struct TransactionDenyConfig {
     /// A list of disabled OAuth providers for zkLogin
     #[serde(default)]
     zklogin_disabled_providers: HashSet<String>,
-    // TODO: We could consider add a deny list for types that we want to disable public transfer.
     // TODO: We could also consider disable more types of commands, such as transfer, split and etc.
 }
 
@@ -86,6 +118,20 @@ impl TransactionDenyConfig {
             .get_or_init(|| self.package_deny_list.iter().cloned().collect())
     }
 
+    pub fn get_package_family_deny_set(&self) -> &HashSet<ObjectID> {
+        self.package_family_deny_set
+            .get_or_init(|| self.package_family_deny_list.iter().cloned().collect())
+    }
+
+    pub fn type_deny_list(&self) -> &[StructTag] {
+        &self.type_deny_list
+    }
+
+    pub fn get_function_deny_set(&self) -> &HashSet<DeniedFunction> {
+        self.function_deny_set
+            .get_or_init(|| self.function_deny_list.iter().cloned().collect())
+    }
+
     pub fn get_address_deny_set(&self) -> &HashSet<SuiAddress> {
         self.address_deny_set
             .get_or_init(|| self.address_deny_list.iter().cloned().collect())
@@ -118,6 +164,176 @@ impl TransactionDenyConfig {
     pub fn zklogin_disabled_providers(&self) -> &HashSet<String> {
         &self.zklogin_disabled_providers
     }
+
+    /// Returns a copy of this config with `patch` applied. Denies are applied before allows, so
+    /// an ID present in both ends up allowed.
+    pub fn patched(&self, patch: &TransactionDenyConfigPatch) -> Self {
+        fn patch_list<T: Clone + Eq + std::hash::Hash>(
+            list: &[T],
+            deny: &[T],
+            allow: &[T],
+        ) -> Vec<T> {
+            let mut seen = HashSet::new();
+            list.iter()
+                .chain(deny)
+                .filter(|id| !allow.contains(id) && seen.insert((*id).clone()))
+                .cloned()
+                .collect()
+        }
+
+        let mut zklogin_disabled_providers = self.zklogin_disabled_providers.clone();
+        zklogin_disabled_providers.extend(patch.disable_zklogin_providers.iter().cloned());
+        for provider in &patch.enable_zklogin_providers {
+            zklogin_disabled_providers.remove(provider);
+        }
+
+        // The lookup sets are left empty, to be rebuilt from the patched lists.
+        Self {
+            object_deny_list: patch_list(
+                &self.object_deny_list,
+                &patch.deny_objects,
+                &patch.allow_objects,
+            ),
+            package_deny_list: patch_list(
+                &self.package_deny_list,
+                &patch.deny_packages,
+                &patch.allow_packages,
+            ),
+            package_family_deny_list: patch_list(
+                &self.package_family_deny_list,
+                &patch.deny_package_families,
+                &patch.allow_package_families,
+            ),
+            type_deny_list: patch_list(&self.type_deny_list, &patch.deny_types, &patch.allow_types),
+            function_deny_list: patch_list(
+                &self.function_deny_list,
+                &patch.deny_functions,
+                &patch.allow_functions,
+            ),
+            address_deny_list: patch_list(
+                &self.address_deny_list,
+                &patch.deny_addresses,
+                &patch.allow_addresses,
+            ),
+            package_publish_disabled: patch
+                .package_publish_disabled
+                .unwrap_or(self.package_publish_disabled),
+            package_upgrade_disabled: patch
+                .package_upgrade_disabled
+                .unwrap_or(self.package_upgrade_disabled),
+            shared_object_disabled: patch
+                .shared_object_disabled
+                .unwrap_or(self.shared_object_disabled),
+            user_transaction_disabled: patch
+                .user_transaction_disabled
+                .unwrap_or(self.user_transaction_disabled),
+            object_deny_set: OnceCell::new(),
+            package_deny_set: OnceCell::new(),
+            package_family_deny_set: OnceCell::new(),
+            function_deny_set: OnceCell::new(),
+            address_deny_set: OnceCell::new(),
+            receiving_objects_disabled: patch
+                .receiving_objects_disabled
+                .unwrap_or(self.receiving_objects_disabled),
+            zklogin_sig_disabled: patch
+                .zklogin_sig_disabled
+                .unwrap_or(self.zklogin_sig_disabled),
+            zklogin_disabled_providers,
+        }
+    }
+}
+
+/// Changes to apply to a `TransactionDenyConfig` at runtime, e.g. through the admin API.
+/// Fields that are not set leave the config unchanged.
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TransactionDenyConfigPatch {
+    /// Object IDs to add to, and remove from, the object deny list.
+    #[serde(default)]
+    pub deny_objects: Vec<ObjectID>,
+    #[serde(default)]
+    pub allow_objects: Vec<ObjectID>,
+
+    /// Package IDs to add to, and remove from, the package deny list.
+    #[serde(default)]
+    pub deny_packages: Vec<ObjectID>,
+    #[serde(default)]
+    pub allow_packages: Vec<ObjectID>,
+
+    /// Original package IDs to add to, and remove from, the package family deny list.
+    #[serde(default)]
+    pub deny_package_families: Vec<ObjectID>,
+    #[serde(default)]
+    pub allow_package_families: Vec<ObjectID>,
+
+    /// Move struct types to add to, and remove from, the type deny list.
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[serde(default)]
+    pub deny_types: Vec<StructTag>,
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[serde(default)]
+    pub allow_types: Vec<StructTag>,
+
+    /// Move functions to add to, and remove from, the function deny list.
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[serde(default)]
+    pub deny_functions: Vec<DeniedFunction>,
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[serde(default)]
+    pub allow_functions: Vec<DeniedFunction>,
+
+    /// Addresses to add to, and remove from, the address deny list.
+    #[serde(default)]
+    pub deny_addresses: Vec<SuiAddress>,
+    #[serde(default)]
+    pub allow_addresses: Vec<SuiAddress>,
+
+    #[serde(default)]
+    pub package_publish_disabled: Option<bool>,
+    #[serde(default)]
+    pub package_upgrade_disabled: Option<bool>,
+    #[serde(default)]
+    pub shared_object_disabled: Option<bool>,
+    #[serde(default)]
+    pub user_transaction_disabled: Option<bool>,
+    #[serde(default)]
+    pub receiving_objects_disabled: Option<bool>,
+    #[serde(default)]
+    pub zklogin_sig_disabled: Option<bool>,
+
+    /// OAuth providers to disable, and re-enable, for zkLogin.
+    #[serde(default)]
+    pub disable_zklogin_providers: Vec<String>,
+    #[serde(default)]
+    pub enable_zklogin_providers: Vec<String>,
+}
+
+/// A Move function in the function deny list, written as `package::module::function`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DeniedFunction {
+    pub package: ObjectID,
+    pub module: Identifier,
+    pub function: Identifier,
+}
+
+impl FromStr for DeniedFunction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (module, function) = parse_sui_fq_name(s)?;
+        Ok(Self {
+            package: ObjectID::from(*module.address()),
+            module: module.name().to_owned(),
+            function: Identifier::new(function)?,
+        })
+    }
+}
+
+impl fmt::Display for DeniedFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}::{}", self.package, self.module, self.function)
+    }
 }
 
 #[derive(Default)]
@@ -174,6 +390,21 @@ impl TransactionDenyConfigBuilder {
         self
     }
 
+    pub fn add_denied_package_family(mut self, original_id: ObjectID) -> Self {
+        self.config.package_family_deny_list.push(original_id);
+        self
+    }
+
+    pub fn add_denied_type(mut self, tag: StructTag) -> Self {
+        self.config.type_deny_list.push(tag);
+        self
+    }
+
+    pub fn add_denied_function(mut self, function: DeniedFunction) -> Self {
+        self.config.function_deny_list.push(function);
+        self
+    }
+
     pub fn disable_zklogin_sig(mut self) -> Self {
         self.config.zklogin_sig_disabled = true;
         self
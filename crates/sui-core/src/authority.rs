@@ -40,7 +40,10 @@ use std::{
     sync::Arc,
     vec,
 };
-use sui_config::node::{AuthorityOverloadConfig, StateDebugDumpConfig};
+use sui_config::node::{
+    AuthorityOverloadConfig, EventSubscriptionConfig, LoadSheddingAccountingKey,
+    StateDebugDumpConfig,
+};
 use sui_config::NodeConfig;
 use sui_types::crypto::RandomnessRound;
 use sui_types::execution_status::ExecutionStatus;
@@ -136,6 +139,7 @@ use crate::authority::authority_store::{ExecutionLockReadGuard, ObjectLockStatus
 use crate::authority::authority_store_pruner::AuthorityStorePruner;
 use crate::authority::epoch_start_configuration::EpochStartConfigTrait;
 use crate::authority::epoch_start_configuration::EpochStartConfiguration;
+use crate::authority::execution_time_estimator::ExecutionTimeObserver;
 use crate::checkpoints::checkpoint_executor::CheckpointExecutor;
 use crate::checkpoints::CheckpointStore;
 use crate::consensus_adapter::ConsensusAdapter;
@@ -154,6 +158,7 @@ use crate::state_accumulator::{AccumulatorStore, StateAccumulator, WrappedObject
 use crate::subscription_handler::SubscriptionHandler;
 use crate::transaction_input_loader::TransactionInputLoader;
 use crate::transaction_manager::TransactionManager;
+use crate::transaction_tracing::TransactionTracer;
 
 #[cfg(msim)]
 use sui_types::committee::CommitteeTrait;
@@ -193,6 +198,7 @@ pub mod authority_store_pruner;
 pub mod authority_store_tables;
 pub mod authority_store_types;
 pub mod epoch_start_configuration;
+pub mod execution_time_estimator;
 pub mod shared_object_congestion_tracker;
 pub mod shared_object_version_manager;
 pub mod test_authority_builder;
@@ -253,6 +259,7 @@ pub struct AuthorityMetrics {
 
     pub(crate) authority_overload_status: IntGauge,
     pub(crate) authority_load_shedding_percentage: IntGauge,
+    pub(crate) authority_load_shedding_top_shed_senders: IntGaugeVec,
 
     /// Post processing metrics
     post_processing_total_events_emitted: IntCounter,
@@ -510,6 +517,12 @@ impl AuthorityMetrics {
                 "The percentage of transactions is shed when the authority is in load shedding mode.",
                 registry)
             .unwrap(),
+            authority_load_shedding_top_shed_senders: register_int_gauge_vec_with_registry!(
+                "authority_load_shedding_top_shed_senders",
+                "Number of transactions shed from the senders with the most shed transactions, in the last overload monitor interval.",
+                &["sender"],
+                registry)
+            .unwrap(),
             transaction_manager_object_cache_misses: register_int_counter_with_registry!(
                 "transaction_manager_object_cache_misses",
                 "Number of object-availability cache misses in TransactionManager",
@@ -781,7 +794,9 @@ pub struct AuthorityState {
     /// Config controlling what kind of expensive safety checks to perform.
     expensive_safety_check_config: ExpensiveSafetyCheckConfig,
 
-    transaction_deny_config: TransactionDenyConfig,
+    /// Swapped as a whole when the config is changed at runtime, see
+    /// `set_transaction_deny_config`.
+    transaction_deny_config: ArcSwap<TransactionDenyConfig>,
 
     certificate_deny_config: CertificateDenyConfig,
 
@@ -793,6 +808,13 @@ pub struct AuthorityState {
 
     /// Current overload status in this authority. Updated periodically.
     pub overload_info: AuthorityOverloadInfo,
+
+    /// Execution times of transactions on shared objects measured by this authority, shared with
+    /// other validators for per-object congestion control.
+    execution_time_observer: ExecutionTimeObserver,
+
+    /// Decides which transactions are traced end to end by this authority.
+    transaction_tracer: TransactionTracer,
 }
 
 /// The authority state encapsulates all state, drives execution, and ensures safety.
@@ -848,14 +870,15 @@ impl AuthorityState {
         let receiving_objects_refs = tx_data.receiving_objects();
 
         // Note: the deny checks may do redundant package loads but:
-        // - they only load packages when there is an active package deny map
+        // - they only load packages when there is an active package, family or function deny rule
         // - the loads are cached anyway
+        let transaction_deny_config = self.transaction_deny_config.load();
         sui_transaction_checks::deny::check_transaction_for_signing(
             tx_data,
             transaction.tx_signatures(),
             &input_object_kinds,
             &receiving_objects_refs,
-            &self.transaction_deny_config,
+            &transaction_deny_config,
             self.get_backing_package_store().as_ref(),
         )?;
 
@@ -868,6 +891,12 @@ impl AuthorityState {
                 epoch_store.epoch(),
             )
             .await?;
+        sui_transaction_checks::deny::check_input_object_types(
+            &transaction_deny_config,
+            input_objects
+                .iter_objects()
+                .chain(receiving_objects.iter_objects()),
+        )?;
 
         let (_gas_status, checked_input_objects) = sui_transaction_checks::check_transaction_input(
             epoch_store.protocol_config(),
@@ -991,15 +1020,37 @@ impl AuthorityState {
     }
 
     fn check_authority_overload(&self, tx_data: &SenderSignedData) -> SuiResult {
+        let config = &self.authority_overload_config;
+        let sender = match config.load_shedding_accounting_key {
+            LoadSheddingAccountingKey::Sender => tx_data.transaction_data().sender(),
+            LoadSheddingAccountingKey::GasOwner => tx_data.transaction_data().gas_owner(),
+        };
         if !self.overload_info.is_overload.load(Ordering::Relaxed) {
             return Ok(());
         }
 
-        let load_shedding_percentage = self
+        // Senders are only tracked while overloaded, so that transactions don't contend on the
+        // sender accounting otherwise. Senders are shed at the global percentage during the
+        // first interval of an overload, until their share of the load is known.
+        if config.per_sender_load_shedding {
+            self.overload_info
+                .record_submission(sender, config.max_tracked_load_shedding_senders);
+        }
+
+        let mut load_shedding_percentage = self
             .overload_info
             .load_shedding_percentage
             .load(Ordering::Relaxed);
-        overload_monitor_accept_tx(load_shedding_percentage, tx_data.digest())
+        if config.per_sender_load_shedding {
+            load_shedding_percentage = self
+                .overload_info
+                .sender_load_shedding_percentage(&sender, load_shedding_percentage);
+        }
+        let result = overload_monitor_accept_tx(load_shedding_percentage, tx_data.digest());
+        if result.is_err() && config.per_sender_load_shedding {
+            self.overload_info.record_shed(sender);
+        }
+        result
     }
 
     /// Executes a transaction that's known to have correct effects.
@@ -1180,6 +1231,35 @@ impl AuthorityState {
         self.read_objects(certificate, epoch_store).await
     }
 
+    /// Reads the input objects of a batch of certificates that are ready to be executed, with a
+    /// single bulk read. End of epoch transactions are not supported.
+    pub(crate) async fn read_objects_for_batch_execution(
+        &self,
+        certificates: &[VerifiedExecutableTransaction],
+        epoch_store: &Arc<AuthorityPerEpochStore>,
+    ) -> SuiResult<Vec<InputObjects>> {
+        let _scope = monitored_scope("Execution::load_input_objects_for_batch");
+        let input_objects = certificates
+            .iter()
+            .map(|certificate| {
+                let transaction_data = certificate.data().transaction_data();
+                assert!(!transaction_data.is_end_of_epoch_tx());
+                Ok((certificate.key(), transaction_data.input_objects()?))
+            })
+            .collect::<SuiResult<Vec<_>>>()?;
+        let transactions: Vec<_> = input_objects
+            .iter()
+            .map(|(key, input_object_kinds)| (*key, input_object_kinds.as_slice()))
+            .collect();
+        self.input_loader
+            .read_objects_for_batch_execution(
+                epoch_store.as_ref(),
+                &transactions,
+                epoch_store.epoch(),
+            )
+            .await
+    }
+
     /// Test only wrapper for `try_execute_immediately()` above, useful for checking errors if the
     /// pre-conditions are not satisfied, and executing change epoch transactions.
     pub async fn try_execute_for_test(
@@ -1659,12 +1739,13 @@ impl AuthorityState {
         let input_object_kinds = transaction.input_objects()?;
         let receiving_object_refs = transaction.receiving_objects();
 
+        let transaction_deny_config = self.transaction_deny_config.load();
         sui_transaction_checks::deny::check_transaction_for_signing(
             &transaction,
             &[],
             &input_object_kinds,
             &receiving_object_refs,
-            &self.transaction_deny_config,
+            &transaction_deny_config,
             self.get_backing_package_store().as_ref(),
         )?;
 
@@ -1677,6 +1758,12 @@ impl AuthorityState {
                 epoch_store.protocol_config(),
             )
             .await?;
+        sui_transaction_checks::deny::check_input_object_types(
+            &transaction_deny_config,
+            input_objects
+                .iter_objects()
+                .chain(receiving_objects.iter_objects()),
+        )?;
 
         // make a gas object if one was not provided
         let mut gas_object_refs = transaction.gas().to_vec();
@@ -1878,12 +1965,13 @@ impl AuthorityState {
         let input_object_kinds = transaction.input_objects()?;
         let receiving_object_refs = transaction.receiving_objects();
 
+        let transaction_deny_config = self.transaction_deny_config.load();
         sui_transaction_checks::deny::check_transaction_for_signing(
             &transaction,
             &[],
             &input_object_kinds,
             &receiving_object_refs,
-            &self.transaction_deny_config,
+            &transaction_deny_config,
             self.get_backing_package_store().as_ref(),
         )?;
 
@@ -1895,6 +1983,12 @@ impl AuthorityState {
                 protocol_config,
             )
             .await?;
+        sui_transaction_checks::deny::check_input_object_types(
+            &transaction_deny_config,
+            input_objects
+                .iter_objects()
+                .chain(receiving_objects.iter_objects()),
+        )?;
 
         // Create and use a dummy gas object if there is no gas object provided.
         let dummy_gas_object = Object::new_gas_with_balance_and_owner_for_testing(
@@ -2585,7 +2679,9 @@ impl AuthorityState {
         indirect_objects_threshold: usize,
         debug_dump_config: StateDebugDumpConfig,
         authority_overload_config: AuthorityOverloadConfig,
+        event_subscription_config: EventSubscriptionConfig,
         archive_readers: ArchiveReaderBalancer,
+        transaction_tracer: TransactionTracer,
     ) -> Arc<Self> {
         Self::check_protocol_version(supported_protocol_versions, epoch_store.protocol_version());
 
@@ -2596,6 +2692,7 @@ impl AuthorityState {
             &epoch_store,
             tx_ready_certificates,
             metrics.clone(),
+            transaction_tracer,
         ));
         let (tx_execution_shutdown, rx_execution_shutdown) = oneshot::channel();
 
@@ -2624,7 +2721,10 @@ impl AuthorityState {
             execution_cache,
             execution_cache_trait_pointers: cache_pointers,
             indexes,
-            subscription_handler: Arc::new(SubscriptionHandler::new(prometheus_registry)),
+            subscription_handler: Arc::new(SubscriptionHandler::new(
+                prometheus_registry,
+                event_subscription_config,
+            )),
             checkpoint_store,
             committee_store,
             transaction_manager,
@@ -2634,11 +2734,13 @@ impl AuthorityState {
             _authority_per_epoch_pruner,
             db_checkpoint_config: db_checkpoint_config.clone(),
             expensive_safety_check_config,
-            transaction_deny_config,
+            transaction_deny_config: ArcSwap::new(Arc::new(transaction_deny_config)),
             certificate_deny_config,
             debug_dump_config,
             authority_overload_config: authority_overload_config.clone(),
             overload_info: AuthorityOverloadInfo::default(),
+            execution_time_observer: ExecutionTimeObserver::default(),
+            transaction_tracer,
         });
 
         // Start a task to execute ready certificates.
@@ -2664,6 +2766,10 @@ impl AuthorityState {
         self.execution_cache.clone()
     }
 
+    pub fn transaction_tracer(&self) -> &TransactionTracer {
+        &self.transaction_tracer
+    }
+
     // TODO: Consolidate our traits to reduce the number of methods here.
     pub fn get_cache_reader(&self) -> &Arc<dyn ExecutionCacheRead> {
         &self.execution_cache_trait_pointers.cache_reader
@@ -3724,6 +3830,7 @@ impl AuthorityState {
             // not using "_ =>" because we want to make sure we remember to add new variants here
             EventFilter::Package(_)
             | EventFilter::MoveEventField { .. }
+            | EventFilter::MoveEventFieldPredicate { .. }
             | EventFilter::Any(_)
             | EventFilter::And(_, _)
             | EventFilter::Or(_, _) => {
@@ -4048,6 +4155,48 @@ impl AuthorityState {
         epoch_store.clear_override_protocol_upgrade_buffer_stake()
     }
 
+    pub fn execution_time_observer(&self) -> &ExecutionTimeObserver {
+        &self.execution_time_observer
+    }
+
+    pub fn transaction_deny_config(&self) -> Arc<TransactionDenyConfig> {
+        self.transaction_deny_config.load_full()
+    }
+
+    /// Atomically replaces the transaction deny config, e.g. to respond to an incident without
+    /// restarting the node. Transactions being checked concurrently see either the previous or
+    /// the new config as a whole. Returns the previous config.
+    pub fn set_transaction_deny_config(
+        &self,
+        config: TransactionDenyConfig,
+    ) -> Arc<TransactionDenyConfig> {
+        Self::init_deny_sets(&config);
+        self.transaction_deny_config.swap(Arc::new(config))
+    }
+
+    /// Atomically replaces the transaction deny config with `update` applied to it. `update` may
+    /// be called more than once if the config is changed concurrently. Returns the previous config.
+    pub fn update_transaction_deny_config(
+        &self,
+        update: impl Fn(&TransactionDenyConfig) -> TransactionDenyConfig,
+    ) -> Arc<TransactionDenyConfig> {
+        self.transaction_deny_config.rcu(|current| {
+            let config = update(current);
+            Self::init_deny_sets(&config);
+            Arc::new(config)
+        })
+    }
+
+    /// Builds the lookup sets of a deny config before it is installed, rather than lazily while
+    /// signing transactions.
+    fn init_deny_sets(config: &TransactionDenyConfig) {
+        config.get_object_deny_set();
+        config.get_package_deny_set();
+        config.get_package_family_deny_set();
+        config.get_function_deny_set();
+        config.get_address_deny_set();
+    }
+
     /// Get the set of system packages that are compiled in to this build, if those packages are
     /// compatible with the current versions of those packages on-chain.
     pub async fn get_available_system_packages(
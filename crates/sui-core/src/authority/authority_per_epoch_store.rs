@@ -48,7 +48,10 @@ use typed_store::{
 
 use super::authority_store_tables::ENV_VAR_LOCKS_BLOCK_CACHE_SIZE;
 use super::epoch_start_configuration::EpochStartConfigTrait;
-use super::shared_object_congestion_tracker::SharedObjectCongestionTracker;
+use super::execution_time_estimator::{ExecutionTimeEstimator, MAX_ESTIMATES_PER_OBSERVATION};
+use super::shared_object_congestion_tracker::{
+    CommitCongestionSnapshot, SharedObjectCongestionTracker,
+};
 use crate::authority::epoch_start_configuration::{EpochFlag, EpochStartConfiguration};
 use crate::authority::ResolverWrapper;
 use crate::checkpoints::{
@@ -92,7 +95,7 @@ use sui_types::messages_checkpoint::{
 };
 use sui_types::messages_consensus::{
     check_total_jwk_size, AuthorityCapabilities, ConsensusTransaction, ConsensusTransactionKey,
-    ConsensusTransactionKind,
+    ConsensusTransactionKind, ExecutionTimeObservation,
 };
 use sui_types::storage::GetSharedLocks;
 use sui_types::sui_system_state::epoch_start_sui_system_state::{
@@ -304,6 +307,18 @@ pub struct AuthorityPerEpochStore {
     /// State machine managing randomness DKG and generation.
     randomness_manager: OnceCell<tokio::sync::Mutex<RandomnessManager>>,
     randomness_reporter: OnceCell<RandomnessReporter>,
+
+    /// Round, and congestion trackers for regular and randomness transactions, of the last
+    /// consensus commit processed. Kept for the admin API, which builds its view on demand.
+    last_commit_congestion: Mutex<(
+        Round,
+        SharedObjectCongestionTracker,
+        SharedObjectCongestionTracker,
+    )>,
+
+    /// Per-object execution time estimates agreed on through consensus. Only present when
+    /// per-object congestion control uses execution time estimates.
+    execution_time_estimator: Option<Mutex<ExecutionTimeEstimator>>,
 }
 
 /// AuthorityEpochTables contains tables that contain data that is only valid within an epoch.
@@ -442,6 +457,10 @@ pub struct AuthorityEpochTables {
     /// Record of the capabilities advertised by each authority.
     authority_capabilities: DBMap<AuthorityName, AuthorityCapabilities>,
 
+    /// Latest execution time observation of each shared object by each authority, as
+    /// (generation, estimate in microseconds).
+    execution_time_observations: DBMap<(ObjectID, AuthorityName), (u64, u64)>,
+
     /// Contains a single key, which overrides the value of
     /// ProtocolConfig::buffer_stake_for_protocol_upgrade_bps
     override_protocol_upgrade_buffer_stake: DBMap<u64, u64>,
@@ -849,6 +868,7 @@ impl AuthorityPerEpochStore {
         );
         let epoch_start_configuration = Arc::new(epoch_start_configuration);
         metrics.current_epoch.set(epoch_id as i64);
+        metrics.epoch_shared_object_congestion_deferral_count.set(0);
         metrics
             .current_voting_right
             .set(committee.weight(&name) as i64);
@@ -923,6 +943,22 @@ impl AuthorityPerEpochStore {
 
         let jwk_aggregator = Mutex::new(jwk_aggregator);
 
+        let execution_time_estimator =
+            if let PerObjectCongestionControlMode::ExecutionTimeEstimate(params) =
+                protocol_config.per_object_congestion_control_mode()
+            {
+                Some(Mutex::new(ExecutionTimeEstimator::new(
+                    committee.clone(),
+                    params,
+                    tables
+                        .execution_time_observations
+                        .unbounded_iter()
+                        .seek_to_first(),
+                )))
+            } else {
+                None
+            };
+
         let s = Arc::new(Self {
             name,
             committee,
@@ -950,6 +986,8 @@ impl AuthorityPerEpochStore {
             jwk_aggregator,
             randomness_manager: OnceCell::new(),
             randomness_reporter: OnceCell::new(),
+            last_commit_congestion: Mutex::new(Default::default()),
+            execution_time_estimator,
         });
         s.update_buffer_stake_metric();
         s
@@ -1678,25 +1716,48 @@ impl AuthorityPerEpochStore {
         }
 
         // Defer transaction if it uses shared objects that are congested.
+        let tx_cost = self.get_transaction_execution_cost(cert)?;
+        if let Some((deferral_key, congested_objects)) = shared_object_congestion_tracker
+            .should_defer_due_to_object_congestion(
+                cert,
+                tx_cost,
+                self.protocol_config()
+                    .max_accumulated_txn_cost_per_object_in_checkpoint(),
+                previously_deferred_tx_digests,
+                commit_round,
+            )
+        {
+            Some((
+                deferral_key,
+                DeferralReason::SharedObjectCongestion(congested_objects),
+            ))
+        } else {
+            None
+        }
+    }
+
+    // Returns the execution cost of `cert` used by per-object congestion control, or None if
+    // congestion control is disabled.
+    fn get_transaction_execution_cost(&self, cert: &VerifiedExecutableTransaction) -> Option<u64> {
         match self.protocol_config().per_object_congestion_control_mode() {
             PerObjectCongestionControlMode::None => None,
-            PerObjectCongestionControlMode::TotalGasBudget => {
-                if let Some((deferral_key, congested_objects)) = shared_object_congestion_tracker
-                    .should_defer_due_to_object_congestion(
-                        cert,
-                        self.protocol_config()
-                            .max_accumulated_txn_cost_per_object_in_checkpoint(),
-                        previously_deferred_tx_digests,
-                        commit_round,
-                    )
-                {
-                    Some((
-                        deferral_key,
-                        DeferralReason::SharedObjectCongestion(congested_objects),
-                    ))
-                } else {
-                    None
-                }
+            PerObjectCongestionControlMode::TotalGasBudget => Some(cert.gas_budget()),
+            // Costs are microseconds of estimated execution time in this mode, which makes
+            // `max_accumulated_txn_cost_per_object_in_checkpoint` a budget of execution time
+            // per object, in microseconds.
+            PerObjectCongestionControlMode::ExecutionTimeEstimate(_) => {
+                let estimator = self
+                    .execution_time_estimator
+                    .as_ref()
+                    .expect("execution time estimator must exist in ExecutionTimeEstimate mode")
+                    .lock();
+                // A transaction is as slow as its most expensive shared object.
+                Some(
+                    cert.shared_input_objects()
+                        .map(|obj| estimator.get_estimate(&obj.id))
+                        .max()
+                        .unwrap_or(0),
+                )
             }
         }
     }
@@ -1708,14 +1769,11 @@ impl AuthorityPerEpochStore {
         cert: &VerifiedExecutableTransaction,
         shared_object_congestion_tracker: &mut SharedObjectCongestionTracker,
     ) {
-        match self.protocol_config().per_object_congestion_control_mode() {
-            PerObjectCongestionControlMode::None => {}
-            PerObjectCongestionControlMode::TotalGasBudget => {
-                shared_object_congestion_tracker.bump_object_execution_cost(
-                    &cert.shared_input_objects().collect::<Vec<_>>(),
-                    cert.gas_budget(),
-                );
-            }
+        if let Some(tx_cost) = self.get_transaction_execution_cost(cert) {
+            shared_object_congestion_tracker.bump_object_execution_cost(
+                &cert.shared_input_objects().collect::<Vec<_>>(),
+                tx_cost,
+            );
         }
     }
 
@@ -2030,6 +2088,58 @@ impl AuthorityPerEpochStore {
         Ok(())
     }
 
+    /// Record execution time observations of shared objects, sequenced in a commit with
+    /// `commit_timestamp`, and update the estimates used for per-object congestion control.
+    pub fn record_execution_time_observation(
+        &self,
+        batch: &mut DBBatch,
+        observation: &ExecutionTimeObservation,
+        commit_timestamp: TimestampMs,
+    ) -> SuiResult {
+        let Some(estimator) = self.execution_time_estimator.as_ref() else {
+            debug!(
+                "ignoring execution time observation from {:?} because execution time estimates are not enabled",
+                observation.authority.concise()
+            );
+            return Ok(());
+        };
+        let mut estimator = estimator.lock();
+        if !estimator.is_generation_acceptable(observation.generation, commit_timestamp) {
+            warn!(
+                "Ignoring execution time observation from {:?} with generation {}, too far ahead of commit timestamp {}",
+                observation.authority.concise(),
+                observation.generation,
+                commit_timestamp
+            );
+            return Ok(());
+        }
+        let mut updated = Vec::with_capacity(observation.estimates.len());
+        for (object_id, micros) in &observation.estimates {
+            if estimator.process_observation(
+                observation.authority,
+                observation.generation,
+                *object_id,
+                *micros,
+            ) {
+                updated.push((
+                    (*object_id, observation.authority),
+                    (observation.generation, *micros),
+                ));
+            }
+        }
+        batch.insert_batch(&self.tables()?.execution_time_observations, updated)?;
+        Ok(())
+    }
+
+    /// Returns the execution time estimate of `object_id`, in microseconds, that validators have
+    /// agreed on, if execution time estimates are enabled.
+    pub fn get_agreed_execution_time_estimate(&self, object_id: &ObjectID) -> Option<u64> {
+        self.execution_time_estimator
+            .as_ref()?
+            .lock()
+            .get_agreed_estimate(object_id)
+    }
+
     pub fn get_capabilities(&self) -> SuiResult<Vec<AuthorityCapabilities>> {
         let result: Result<Vec<AuthorityCapabilities>, TypedStoreError> = self
             .tables()?
@@ -2040,6 +2150,20 @@ impl AuthorityPerEpochStore {
         Ok(result?)
     }
 
+    pub fn metrics(&self) -> &Arc<EpochMetrics> {
+        &self.metrics
+    }
+
+    /// Returns the shared object costs accumulated in the last consensus commit processed.
+    pub fn last_commit_congestion(&self) -> CommitCongestionSnapshot {
+        let (commit_round, tracker, randomness_tracker) = &*self.last_commit_congestion.lock();
+        CommitCongestionSnapshot {
+            commit_round: *commit_round,
+            object_execution_costs: tracker.object_execution_costs(),
+            randomness_object_execution_costs: randomness_tracker.object_execution_costs(),
+        }
+    }
+
     pub fn record_jwk_vote(
         &self,
         batch: &mut DBBatch,
@@ -2345,6 +2469,28 @@ impl AuthorityPerEpochStore {
                     return None;
                 }
             }
+            SequencedConsensusTransactionKind::External(ConsensusTransaction {
+                kind: ConsensusTransactionKind::ExecutionTimeObservation(observation),
+                ..
+            }) => {
+                if transaction.sender_authority() != observation.authority {
+                    warn!(
+                        "ExecutionTimeObservation authority {} does not match narwhal certificate source {}",
+                        observation.authority,
+                        transaction.certificate_author_index
+                    );
+                    return None;
+                }
+                if observation.estimates.len() > MAX_ESTIMATES_PER_OBSERVATION {
+                    warn!(
+                        "{:?} sent an execution time observation with {} estimates, more than the maximum of {}",
+                        observation.authority.concise(),
+                        observation.estimates.len(),
+                        MAX_ESTIMATES_PER_OBSERVATION
+                    );
+                    return None;
+                }
+            }
             SequencedConsensusTransactionKind::External(ConsensusTransaction {
                 kind: ConsensusTransactionKind::NewJWKFetched(authority, id, jwk),
                 ..
@@ -2604,6 +2750,7 @@ impl AuthorityPerEpochStore {
                 checkpoint_service,
                 cache_reader,
                 commit_round,
+                commit_timestamp,
                 previously_deferred_tx_digests,
                 randomness_manager.as_deref_mut(),
                 dkg_closed,
@@ -2808,6 +2955,7 @@ impl AuthorityPerEpochStore {
         checkpoint_service: &Arc<C>,
         cache_reader: &dyn ExecutionCacheRead,
         commit_round: Round,
+        commit_timestamp: TimestampMs,
         previously_deferred_tx_digests: HashMap<TransactionDigest, DeferralKey>,
         mut randomness_manager: Option<&mut RandomnessManager>,
         dkg_closed: bool,
@@ -2864,6 +3012,7 @@ impl AuthorityPerEpochStore {
                     tx,
                     checkpoint_service,
                     commit_round,
+                    commit_timestamp,
                     &previously_deferred_tx_digests,
                     randomness_manager.as_deref_mut(),
                     dkg_closed,
@@ -2909,6 +3058,12 @@ impl AuthorityPerEpochStore {
             }
         }
 
+        *self.last_commit_congestion.lock() = (
+            commit_round,
+            shared_object_congestion_tracker,
+            shared_object_using_randomness_congestion_tracker,
+        );
+
         let commit_has_deferred_txns = !deferred_txns.is_empty();
         for (key, txns) in deferred_txns.into_iter() {
             self.defer_transactions(batch, key, txns)?;
@@ -3051,6 +3206,7 @@ impl AuthorityPerEpochStore {
         transaction: &VerifiedSequencedConsensusTransaction,
         checkpoint_service: &Arc<C>,
         commit_round: Round,
+        commit_timestamp: TimestampMs,
         previously_deferred_tx_digests: &HashMap<TransactionDigest, DeferralKey>,
         mut randomness_manager: Option<&mut RandomnessManager>,
         dkg_closed: bool,
@@ -3118,11 +3274,16 @@ impl AuthorityPerEpochStore {
                     shared_object_congestion_tracker,
                 );
 
-                if let Some((deferral_key, _)) = deferral_info {
+                if let Some((deferral_key, deferral_reason)) = deferral_info {
                     debug!(
                         "Deferring consensus certificate for transaction {:?} until {deferral_key:?}",
                         certificate.digest(),
                     );
+                    if matches!(deferral_reason, DeferralReason::SharedObjectCongestion(_)) {
+                        self.metrics
+                            .epoch_shared_object_congestion_deferral_count
+                            .inc();
+                    }
                     return Ok(ConsensusCertificateResult::Deferred(deferral_key));
                 }
 
@@ -3188,6 +3349,27 @@ impl AuthorityPerEpochStore {
                 }
                 Ok(ConsensusCertificateResult::ConsensusMessage)
             }
+            SequencedConsensusTransactionKind::External(ConsensusTransaction {
+                kind: ConsensusTransactionKind::ExecutionTimeObservation(observation),
+                ..
+            }) => {
+                if self
+                    .get_reconfig_state_read_lock_guard()
+                    .should_accept_consensus_certs()
+                {
+                    debug!(
+                        "Received ExecutionTimeObservation from {:?}",
+                        observation.authority.concise()
+                    );
+                    self.record_execution_time_observation(batch, observation, commit_timestamp)?;
+                } else {
+                    debug!(
+                        "Ignoring ExecutionTimeObservation from {:?} because of end of epoch",
+                        observation.authority.concise()
+                    );
+                }
+                Ok(ConsensusCertificateResult::ConsensusMessage)
+            }
             SequencedConsensusTransactionKind::External(ConsensusTransaction {
                 kind: ConsensusTransactionKind::NewJWKFetched(authority, jwk_id, jwk),
                 ..
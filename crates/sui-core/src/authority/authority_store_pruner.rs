@@ -805,7 +805,7 @@ mod tests {
         let registry = Registry::default();
         let metrics = AuthorityStorePruningMetrics::new(&registry);
         let to_keep = {
-            let db = Arc::new(AuthorityPerpetualTables::open(path, None));
+            let db = Arc::new(AuthorityPerpetualTables::open(path, None).unwrap());
             let (to_keep, to_delete, tombstones) = generate_test_data(
                 db.clone(),
                 num_versions_per_object,
@@ -877,7 +877,7 @@ mod tests {
         let path = tempfile::tempdir().unwrap().into_path();
         run_pruner(&path, 3, 2, 1000, 1).await;
         {
-            let perpetual_db = AuthorityPerpetualTables::open(&path, None);
+            let perpetual_db = AuthorityPerpetualTables::open(&path, None).unwrap();
             let count = perpetual_db.indirect_move_objects.keys().count();
             // references are not reset, expected to have 1000 unique objects
             assert_eq!(count, 1000);
@@ -886,7 +886,7 @@ mod tests {
         let path = tempfile::tempdir().unwrap().into_path();
         run_pruner(&path, 3, 0, 1000, 1).await;
         {
-            let perpetual_db = AuthorityPerpetualTables::open(&path, None);
+            let perpetual_db = AuthorityPerpetualTables::open(&path, None).unwrap();
             perpetual_db.indirect_move_objects.flush().unwrap();
             perpetual_db
                 .indirect_move_objects
@@ -905,7 +905,7 @@ mod tests {
     #[tokio::test]
     async fn test_db_size_after_compaction() -> Result<(), anyhow::Error> {
         let primary_path = tempfile::tempdir()?.into_path();
-        let perpetual_db = Arc::new(AuthorityPerpetualTables::open(&primary_path, None));
+        let perpetual_db = Arc::new(AuthorityPerpetualTables::open(&primary_path, None)?);
         let total_unique_object_ids = 10_000;
         let num_versions_per_object = 10;
         let ids = ObjectID::in_range(ObjectID::ZERO, total_unique_object_ids)?;
@@ -1073,7 +1073,7 @@ mod pprof_tests {
         let registry = Registry::default();
         let metrics = AuthorityStorePruningMetrics::new(&registry);
         let primary_path = tempfile::tempdir()?.into_path();
-        let perpetual_db = Arc::new(AuthorityPerpetualTables::open(&primary_path, None));
+        let perpetual_db = Arc::new(AuthorityPerpetualTables::open(&primary_path, None)?);
         let effects = insert_keys(&perpetual_db.objects)?;
         AuthorityStorePruner::prune_objects(
             vec![effects],
@@ -1107,7 +1107,7 @@ mod pprof_tests {
         // We then record a cpu profile of the `get()` calls and do not find any range fragmentation stack frame
         // in it.
         let primary_path = tempfile::tempdir()?.into_path();
-        let perpetual_db = Arc::new(AuthorityPerpetualTables::open(&primary_path, None));
+        let perpetual_db = Arc::new(AuthorityPerpetualTables::open(&primary_path, None)?);
         let effects = insert_keys(&perpetual_db.objects)?;
         let registry = Registry::default();
         let metrics = AuthorityStorePruningMetrics::new(&registry);
@@ -12,9 +12,11 @@ use sui_types::digests::TransactionEventsDigest;
 use sui_types::effects::TransactionEffects;
 use sui_types::storage::MarkerValue;
 use typed_store::metrics::SamplingInterval;
+use typed_store::rocks::migration::MigrationRegistry;
 use typed_store::rocks::util::{empty_compaction_filter, reference_count_merge_operator};
 use typed_store::rocks::{
-    default_db_options, read_size_from_env, DBBatch, DBMap, DBOptions, MetricConf, ReadWriteOptions,
+    default_db_options, in_memory_db_options, in_memory_db_path, read_size_from_env, DBBatch,
+    DBMap, DBOptions, MetricConf, ReadWriteOptions,
 };
 use typed_store::traits::{Map, TableSummary, TypedStoreDebug};
 
@@ -132,16 +134,35 @@ impl AuthorityPerpetualTables {
         parent_path.join("perpetual")
     }
 
-    pub fn open(parent_path: &Path, db_options: Option<Options>) -> Self {
-        Self::open_tables_read_write(
+    /// Opens the tables and runs their pending schema migrations.
+    pub fn open(parent_path: &Path, db_options: Option<Options>) -> Result<Self, TypedStoreError> {
+        Self::open_tables_read_write_with_migrations(
             Self::path(parent_path),
             MetricConf::new("perpetual")
                 .with_sampling(SamplingInterval::new(Duration::from_secs(60), 0)),
             db_options,
             None,
+            &Self::migrations(),
         )
     }
 
+    /// Opens the tables with their data kept in memory instead of on disk, for tests. The data is
+    /// lost when the tables are dropped.
+    ///
+    /// Unlike the consensus store, these tables stay on RocksDB with an in-memory env rather than
+    /// the `InMemoryDB` test backend: the authority store writes them through `DBBatch`, and reads
+    /// them with RocksDB-only APIs such as checkpoints and live file listings.
+    pub fn open_in_memory() -> Self {
+        Self::open(&in_memory_db_path(), Some(in_memory_db_options()))
+            .expect("Failed to migrate empty tables")
+    }
+
+    /// Schema migrations of the perpetual tables. When the stored type of a table changes, register
+    /// a migration from its current schema version here instead of adding a versioned wrapper.
+    pub fn migrations() -> MigrationRegistry {
+        MigrationRegistry::default()
+    }
+
     pub fn open_readonly(parent_path: &Path) -> AuthorityPerpetualTablesReadOnly {
         Self::get_read_only_handle(
             Self::path(parent_path),
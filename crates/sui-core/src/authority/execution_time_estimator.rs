@@ -0,0 +1,375 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Execution time based transaction cost for per-object congestion control.
+//!
+//! Each validator measures how long transactions touching shared objects take to execute, and
+//! keeps a moving average per object in `ExecutionTimeObserver`. Averages that changed
+//! significantly are periodically shared through consensus as `ExecutionTimeObservation`s.
+//!
+//! Local measurements differ between validators, so they can't be used for deferral decisions
+//! directly. Instead, every validator feeds the sequenced observations into an
+//! `ExecutionTimeEstimator`, which deterministically derives the same per-object estimate on all
+//! validators: the stake-weighted median of the latest observation of each validator, once
+//! validators with a quorum of stake have reported on the object.
+
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use sui_protocol_config::ExecutionTimeEstimateParams;
+use sui_types::base_types::{AuthorityName, ObjectID};
+use sui_types::committee::{Committee, CommitteeTrait, StakeUnit};
+
+/// Maximum number of per-object estimates carried by a single `ExecutionTimeObservation`.
+/// Observations with more estimates are rejected by consensus handler.
+pub const MAX_ESTIMATES_PER_OBSERVATION: usize = 256;
+
+/// Maximum number of objects the local observer keeps moving averages for.
+const MAX_OBSERVED_OBJECTS: usize = 10_000;
+
+/// Weight of a new measurement in the local exponential moving average.
+const MOVING_AVERAGE_WEIGHT: f64 = 0.1;
+
+/// Relative change of the local moving average, compared to the last shared value, above which
+/// the estimate of an object is shared again.
+const SHARE_CHANGE_THRESHOLD: f64 = 0.2;
+
+struct ObservedObject {
+    average_micros: f64,
+    // The last estimate shared through consensus, if any.
+    last_shared_micros: Option<u64>,
+}
+
+impl ObservedObject {
+    fn needs_sharing(&self) -> bool {
+        match self.last_shared_micros {
+            None => true,
+            Some(last_shared) => {
+                let last_shared = last_shared.max(1) as f64;
+                (self.average_micros - last_shared).abs() / last_shared >= SHARE_CHANGE_THRESHOLD
+            }
+        }
+    }
+}
+
+/// Tracks the execution times of transactions on shared objects measured by this validator.
+pub struct ExecutionTimeObserver {
+    objects: Mutex<LruCache<ObjectID, ObservedObject>>,
+}
+
+impl Default for ExecutionTimeObserver {
+    fn default() -> Self {
+        Self {
+            objects: Mutex::new(LruCache::new(
+                NonZeroUsize::new(MAX_OBSERVED_OBJECTS).unwrap(),
+            )),
+        }
+    }
+}
+
+impl ExecutionTimeObserver {
+    /// Records the execution time of a transaction. Transactions on the same shared object are
+    /// executed sequentially, so the whole duration is attributed to every shared input object.
+    pub fn record_local_observation(
+        &self,
+        shared_object_ids: impl IntoIterator<Item = ObjectID>,
+        duration: Duration,
+    ) {
+        let micros = duration.as_micros() as f64;
+        let mut objects = self.objects.lock();
+        for object_id in shared_object_ids {
+            if let Some(object) = objects.get_mut(&object_id) {
+                object.average_micros = object.average_micros * (1.0 - MOVING_AVERAGE_WEIGHT)
+                    + micros * MOVING_AVERAGE_WEIGHT;
+            } else {
+                objects.put(
+                    object_id,
+                    ObservedObject {
+                        average_micros: micros,
+                        last_shared_micros: None,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Returns up to `max` estimates that changed significantly since they were last shared,
+    /// largest change first, and marks them as shared.
+    pub fn take_estimates_to_share(&self, max: usize) -> Vec<(ObjectID, u64)> {
+        let mut objects = self.objects.lock();
+        let mut candidates: Vec<_> = objects
+            .iter()
+            .filter(|(_, object)| object.needs_sharing())
+            .map(|(id, object)| {
+                let change =
+                    (object.average_micros - object.last_shared_micros.unwrap_or(0) as f64).abs();
+                (*id, change)
+            })
+            .collect();
+        candidates.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        candidates.truncate(max);
+
+        candidates
+            .into_iter()
+            .map(|(id, _)| {
+                let object = objects.peek_mut(&id).expect("object was just listed");
+                let micros = object.average_micros.round() as u64;
+                object.last_shared_micros = Some(micros);
+                (id, micros)
+            })
+            .collect()
+    }
+
+    /// Forgets what has been shared, so that all estimates are shared again. Observations are
+    /// per epoch, so this is called at the start of each epoch.
+    pub fn reset_shared(&self) {
+        for (_, object) in self.objects.lock().iter_mut() {
+            object.last_shared_micros = None;
+        }
+    }
+}
+
+/// Aggregates the execution time observations sequenced by consensus into per-object estimates.
+/// All inputs come from consensus, so all validators compute the same estimates.
+///
+/// Each authority can report on at most `max_observed_objects_per_authority` objects per epoch,
+/// which bounds the observations kept here and in the epoch tables.
+pub struct ExecutionTimeEstimator {
+    committee: Arc<Committee>,
+    params: ExecutionTimeEstimateParams,
+    // Latest (generation, estimate in microseconds) reported by each authority, per object.
+    observations: HashMap<ObjectID, HashMap<AuthorityName, (u64, u64)>>,
+    // Number of objects each authority has reported on.
+    observed_objects: HashMap<AuthorityName, u64>,
+    estimates: HashMap<ObjectID, u64>,
+}
+
+impl ExecutionTimeEstimator {
+    pub fn new(
+        committee: Arc<Committee>,
+        params: ExecutionTimeEstimateParams,
+        initial_observations: impl IntoIterator<Item = ((ObjectID, AuthorityName), (u64, u64))>,
+    ) -> Self {
+        let mut estimator = Self {
+            committee,
+            params,
+            observations: HashMap::new(),
+            observed_objects: HashMap::new(),
+            estimates: HashMap::new(),
+        };
+        for ((object_id, authority), (generation, micros)) in initial_observations {
+            estimator
+                .observations
+                .entry(object_id)
+                .or_default()
+                .insert(authority, (generation, micros));
+            *estimator.observed_objects.entry(authority).or_default() += 1;
+        }
+        let object_ids: Vec<_> = estimator.observations.keys().copied().collect();
+        for object_id in object_ids {
+            estimator.update_estimate(object_id);
+        }
+        estimator
+    }
+
+    /// Whether observations with `generation` can be accepted in a consensus commit with
+    /// `commit_timestamp_ms`. Generations are timestamps set by the sending authority, so one far
+    /// ahead of consensus time would keep the authority's later observations from replacing it.
+    pub fn is_generation_acceptable(&self, generation: u64, commit_timestamp_ms: u64) -> bool {
+        generation <= commit_timestamp_ms.saturating_add(self.params.max_generation_lead_ms)
+    }
+
+    /// Records an observation of `object_id` by `authority`. Returns false if the authority has
+    /// already reported a newer observation for the object, or has reported on as many objects
+    /// as allowed, in which case it is ignored.
+    pub fn process_observation(
+        &mut self,
+        authority: AuthorityName,
+        generation: u64,
+        object_id: ObjectID,
+        micros: u64,
+    ) -> bool {
+        let previous = self
+            .observations
+            .get(&object_id)
+            .and_then(|object_observations| object_observations.get(&authority));
+        match previous {
+            Some((previous_generation, _)) if *previous_generation >= generation => return false,
+            Some(_) => {}
+            None => {
+                let observed_objects = self.observed_objects.entry(authority).or_default();
+                if *observed_objects >= self.params.max_observed_objects_per_authority {
+                    return false;
+                }
+                *observed_objects += 1;
+            }
+        }
+        self.observations
+            .entry(object_id)
+            .or_default()
+            .insert(authority, (generation, micros));
+        self.update_estimate(object_id);
+        true
+    }
+
+    /// Returns the estimated execution time of a transaction on `object_id`, in microseconds.
+    pub fn get_estimate(&self, object_id: &ObjectID) -> u64 {
+        self.get_agreed_estimate(object_id)
+            .unwrap_or(self.params.default_estimate_micros)
+    }
+
+    /// Returns the estimate of `object_id` agreed on by a quorum of stake, if any.
+    pub fn get_agreed_estimate(&self, object_id: &ObjectID) -> Option<u64> {
+        self.estimates.get(object_id).copied()
+    }
+
+    fn update_estimate(&mut self, object_id: ObjectID) {
+        let Some(object_observations) = self.observations.get(&object_id) else {
+            return;
+        };
+        let mut weighted: Vec<(u64, StakeUnit)> = object_observations
+            .iter()
+            .map(|(authority, (_, micros))| (*micros, self.committee.weight(authority)))
+            .collect();
+        let total_stake: StakeUnit = weighted.iter().map(|(_, stake)| stake).sum();
+        if total_stake < self.committee.quorum_threshold() {
+            self.estimates.remove(&object_id);
+            return;
+        }
+
+        weighted.sort_unstable();
+        let mut cumulative_stake = 0;
+        for (micros, stake) in weighted {
+            cumulative_stake += stake;
+            if cumulative_stake * 2 >= total_stake {
+                self.estimates.insert(object_id, micros);
+                return;
+            }
+        }
+        unreachable!("total stake is reached in the loop");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_PARAMS: ExecutionTimeEstimateParams = ExecutionTimeEstimateParams {
+        default_estimate_micros: 100,
+        max_observed_objects_per_authority: 2,
+        max_generation_lead_ms: 1000,
+    };
+
+    #[test]
+    fn test_observer_shares_changed_estimates() {
+        let observer = ExecutionTimeObserver::default();
+        let hot = ObjectID::random();
+        let cold = ObjectID::random();
+
+        observer.record_local_observation([hot], Duration::from_micros(1000));
+        observer.record_local_observation([hot, cold], Duration::from_micros(1000));
+        let mut shared = observer.take_estimates_to_share(10);
+        shared.sort();
+        let mut expected = vec![(hot, 1000), (cold, 1000)];
+        expected.sort();
+        assert_eq!(shared, expected);
+
+        // Small changes are not shared again.
+        observer.record_local_observation([hot], Duration::from_micros(1100));
+        assert!(observer.take_estimates_to_share(10).is_empty());
+
+        // Large changes are, up to the limit.
+        for _ in 0..10 {
+            observer.record_local_observation([hot, cold], Duration::from_micros(5000));
+        }
+        let shared = observer.take_estimates_to_share(1);
+        assert_eq!(shared.len(), 1);
+        assert!(shared[0].1 > 2000);
+        assert_eq!(observer.take_estimates_to_share(10).len(), 1);
+
+        // Everything is shared again after a reset.
+        observer.reset_shared();
+        assert_eq!(observer.take_estimates_to_share(10).len(), 2);
+    }
+
+    #[test]
+    fn test_estimator_stake_weighted_median() {
+        // Four authorities with equal stake.
+        let (committee, _) = Committee::new_simple_test_committee();
+        let authorities: Vec<_> = committee.names().copied().collect();
+        let object_id = ObjectID::random();
+        let mut estimator = ExecutionTimeEstimator::new(Arc::new(committee), TEST_PARAMS, []);
+
+        // No estimate until a quorum of stake has reported.
+        assert!(estimator.process_observation(authorities[0], 1, object_id, 1000));
+        assert!(estimator.process_observation(authorities[1], 1, object_id, 3000));
+        assert_eq!(estimator.get_estimate(&object_id), 100);
+
+        assert!(estimator.process_observation(authorities[2], 1, object_id, 2000));
+        assert_eq!(estimator.get_estimate(&object_id), 2000);
+
+        // An outlier can't move the estimate past honest observations.
+        assert!(estimator.process_observation(authorities[3], 1, object_id, u64::MAX));
+        assert_eq!(estimator.get_estimate(&object_id), 2000);
+
+        // Older observations are ignored.
+        assert!(!estimator.process_observation(authorities[0], 1, object_id, 10));
+        assert!(estimator.process_observation(authorities[0], 2, object_id, 5000));
+        assert_eq!(estimator.get_estimate(&object_id), 3000);
+
+        // Estimates are rebuilt from persisted observations.
+        let initial: Vec<_> = estimator
+            .observations
+            .iter()
+            .flat_map(|(object_id, observations)| {
+                observations
+                    .iter()
+                    .map(move |(authority, value)| ((*object_id, *authority), *value))
+            })
+            .collect();
+        let rebuilt =
+            ExecutionTimeEstimator::new(estimator.committee.clone(), TEST_PARAMS, initial);
+        assert_eq!(rebuilt.get_estimate(&object_id), 3000);
+    }
+
+    #[test]
+    fn test_estimator_bounds_observations() {
+        let (committee, _) = Committee::new_simple_test_committee();
+        let authorities: Vec<_> = committee.names().copied().collect();
+        let objects: Vec<_> = (0..3).map(|_| ObjectID::random()).collect();
+        let mut estimator = ExecutionTimeEstimator::new(Arc::new(committee), TEST_PARAMS, []);
+
+        // Each authority reports on at most 2 objects, but can keep updating them.
+        assert!(estimator.process_observation(authorities[0], 1, objects[0], 1000));
+        assert!(estimator.process_observation(authorities[0], 1, objects[1], 1000));
+        assert!(!estimator.process_observation(authorities[0], 1, objects[2], 1000));
+        assert!(estimator.process_observation(authorities[0], 2, objects[1], 2000));
+        assert!(!estimator.observations.contains_key(&objects[2]));
+        assert!(estimator.process_observation(authorities[1], 1, objects[2], 1000));
+
+        // The count is rebuilt from persisted observations.
+        let initial: Vec<_> = estimator
+            .observations
+            .iter()
+            .flat_map(|(object_id, observations)| {
+                observations
+                    .iter()
+                    .map(move |(authority, value)| ((*object_id, *authority), *value))
+            })
+            .collect();
+        let mut rebuilt =
+            ExecutionTimeEstimator::new(estimator.committee.clone(), TEST_PARAMS, initial);
+        assert!(!rebuilt.process_observation(authorities[0], 3, objects[2], 1000));
+        assert!(rebuilt.process_observation(authorities[1], 3, objects[0], 1000));
+
+        // Generations can only be ahead of consensus time by the allowed lead.
+        assert!(estimator.is_generation_acceptable(5000, 5000));
+        assert!(estimator.is_generation_acceptable(6000, 5000));
+        assert!(!estimator.is_generation_acceptable(6001, 5000));
+        assert!(estimator.is_generation_acceptable(u64::MAX, u64::MAX));
+    }
+}
@@ -3,6 +3,7 @@
 
 use crate::authority::authority_per_epoch_store::DeferralKey;
 use narwhal_types::Round;
+use serde::Serialize;
 use std::collections::HashMap;
 use sui_types::base_types::{ObjectID, TransactionDigest};
 use sui_types::executable_transaction::VerifiedExecutableTransaction;
@@ -22,6 +23,16 @@ pub struct SharedObjectCongestionTracker {
     object_execution_cost: HashMap<ObjectID, u64>,
 }
 
+/// The per-object execution costs accumulated over a consensus commit, kept for introspection.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CommitCongestionSnapshot {
+    pub commit_round: Round,
+    /// Cost of each shared object written in the commit, most expensive first.
+    pub object_execution_costs: Vec<(ObjectID, u64)>,
+    /// Same as `object_execution_costs`, for transactions using randomness.
+    pub randomness_object_execution_costs: Vec<(ObjectID, u64)>,
+}
+
 impl SharedObjectCongestionTracker {
     pub fn new_with_initial_value_for_test(init_values: &[(ObjectID, u64)]) -> Self {
         let mut object_execution_cost = HashMap::new();
@@ -46,17 +57,19 @@ impl SharedObjectCongestionTracker {
             .expect("There must be at least one object in shared_input_objects.")
     }
 
-    // Given a transaction, returns the deferral key and the congested objects if the transaction should be deferred.
+    // Given a transaction and its execution cost, returns the deferral key and the congested objects if the
+    // transaction should be deferred.
     pub fn should_defer_due_to_object_congestion(
         &self,
         cert: &VerifiedExecutableTransaction,
+        tx_cost: u64,
         max_accumulated_txn_cost_per_object_in_checkpoint: u64,
         previously_deferred_tx_digests: &HashMap<TransactionDigest, DeferralKey>,
         commit_round: Round,
     ) -> Option<(DeferralKey, Vec<ObjectID>)> {
         let shared_input_objects: Vec<_> = cert.shared_input_objects().collect();
         let start_cost = self.compute_tx_start_at_cost(&shared_input_objects);
-        if start_cost + cert.gas_budget() <= max_accumulated_txn_cost_per_object_in_checkpoint {
+        if start_cost + tx_cost <= max_accumulated_txn_cost_per_object_in_checkpoint {
             return None;
         }
 
@@ -98,6 +111,17 @@ impl SharedObjectCongestionTracker {
         Some((deferral_key, congested_objects))
     }
 
+    // Returns the accumulated cost of each object, most expensive first.
+    pub fn object_execution_costs(&self) -> Vec<(ObjectID, u64)> {
+        let mut costs: Vec<_> = self
+            .object_execution_cost
+            .iter()
+            .map(|(id, cost)| (*id, *cost))
+            .collect();
+        costs.sort_by(|(id_a, cost_a), (id_b, cost_b)| cost_b.cmp(cost_a).then(id_a.cmp(id_b)));
+        costs
+    }
+
     pub fn bump_object_execution_cost(
         &mut self,
         shared_input_objects: &[SharedInputObject],
@@ -240,6 +264,7 @@ mod object_cost_tests {
             if let Some((_, congested_objects)) = shared_object_congestion_tracker
                 .should_defer_due_to_object_congestion(
                     &tx,
+                    tx.gas_budget(),
                     max_accumulated_txn_cost_per_object_in_checkpoint,
                     &HashMap::new(),
                     0,
@@ -258,6 +283,7 @@ mod object_cost_tests {
             assert!(shared_object_congestion_tracker
                 .should_defer_due_to_object_congestion(
                     &tx,
+                    tx.gas_budget(),
                     max_accumulated_txn_cost_per_object_in_checkpoint,
                     &HashMap::new(),
                     0,
@@ -273,6 +299,7 @@ mod object_cost_tests {
                 if let Some((_, congested_objects)) = shared_object_congestion_tracker
                     .should_defer_due_to_object_congestion(
                         &tx,
+                        tx.gas_budget(),
                         max_accumulated_txn_cost_per_object_in_checkpoint,
                         &HashMap::new(),
                         0,
@@ -314,6 +341,7 @@ mod object_cost_tests {
             _,
         )) = shared_object_congestion_tracker.should_defer_due_to_object_congestion(
             &tx,
+            tx.gas_budget(),
             max_accumulated_txn_cost_per_object_in_checkpoint,
             &previously_deferred_tx_digests,
             10,
@@ -341,6 +369,7 @@ mod object_cost_tests {
             _,
         )) = shared_object_congestion_tracker.should_defer_due_to_object_congestion(
             &tx,
+            tx.gas_budget(),
             max_accumulated_txn_cost_per_object_in_checkpoint,
             &previously_deferred_tx_digests,
             10,
@@ -369,6 +398,7 @@ mod object_cost_tests {
             _,
         )) = shared_object_congestion_tracker.should_defer_due_to_object_congestion(
             &tx,
+            tx.gas_budget(),
             max_accumulated_txn_cost_per_object_in_checkpoint,
             &previously_deferred_tx_digests,
             10,
@@ -432,4 +462,27 @@ mod object_cost_tests {
             ])
         );
     }
+
+    #[test]
+    fn test_object_execution_costs() {
+        let object_id_0 = ObjectID::from_single_byte(0);
+        let object_id_1 = ObjectID::from_single_byte(1);
+        let object_id_2 = ObjectID::from_single_byte(2);
+
+        let shared_object_congestion_tracker =
+            SharedObjectCongestionTracker::new_with_initial_value_for_test(&[
+                (object_id_2, 5),
+                (object_id_1, 10),
+                (object_id_0, 5),
+            ]);
+
+        // Most expensive objects come first, ties are ordered by object ID.
+        assert_eq!(
+            shared_object_congestion_tracker.object_execution_costs(),
+            vec![(object_id_1, 10), (object_id_0, 5), (object_id_2, 5)]
+        );
+        assert!(SharedObjectCongestionTracker::default()
+            .object_execution_costs()
+            .is_empty());
+    }
 }
@@ -11,6 +11,7 @@ use crate::epoch::epoch_metrics::EpochMetrics;
 use crate::execution_cache::ExecutionCache;
 use crate::module_cache_metrics::ResolverMetrics;
 use crate::signature_verifier::SignatureVerifierMetrics;
+use crate::transaction_tracing::TransactionTracer;
 use fastcrypto::traits::KeyPair;
 use prometheus::Registry;
 use std::path::PathBuf;
@@ -18,7 +19,7 @@ use std::sync::Arc;
 use sui_archival::reader::ArchiveReaderBalancer;
 use sui_config::certificate_deny_config::CertificateDenyConfig;
 use sui_config::genesis::Genesis;
-use sui_config::node::{AuthorityOverloadConfig, StateDebugDumpConfig};
+use sui_config::node::{AuthorityOverloadConfig, EventSubscriptionConfig, StateDebugDumpConfig};
 use sui_config::node::{
     AuthorityStorePruningConfig, DBCheckpointConfig, ExpensiveSafetyCheckConfig,
 };
@@ -54,6 +55,7 @@ pub struct TestAuthorityBuilder<'a> {
     /// By default, we don't insert the genesis checkpoint, which isn't needed by most tests.
     insert_genesis_checkpoint: bool,
     authority_overload_config: Option<AuthorityOverloadConfig>,
+    transaction_tracing_sample_rate: Option<f64>,
 }
 
 impl<'a> TestAuthorityBuilder<'a> {
@@ -150,6 +152,14 @@ impl<'a> TestAuthorityBuilder<'a> {
         self
     }
 
+    pub fn with_transaction_tracing_sample_rate(mut self, sample_rate: f64) -> Self {
+        assert!(self
+            .transaction_tracing_sample_rate
+            .replace(sample_rate)
+            .is_none());
+        self
+    }
+
     pub async fn build(self) -> Arc<AuthorityState> {
         let mut local_network_config_builder =
             sui_swarm_config::network_config_builder::ConfigBuilder::new_with_temp_dir()
@@ -173,7 +183,7 @@ impl<'a> TestAuthorityBuilder<'a> {
             Some(store) => store,
             None => {
                 let perpetual_tables =
-                    Arc::new(AuthorityPerpetualTables::open(&path.join("store"), None));
+                    Arc::new(AuthorityPerpetualTables::open(&path.join("store"), None).unwrap());
                 // unwrap ok - for testing only.
                 AuthorityStore::open_with_committee_for_testing(
                     perpetual_tables,
@@ -292,7 +302,9 @@ impl<'a> TestAuthorityBuilder<'a> {
                 dump_file_directory: Some(tempdir().unwrap().into_path()),
             },
             authority_overload_config,
+            EventSubscriptionConfig::default(),
             ArchiveReaderBalancer::default(),
+            TransactionTracer::new(self.transaction_tracing_sample_rate.unwrap_or(0.0)),
         )
         .await;
         // For any type of local testing that does not actually spawn a node, the checkpoint executor
@@ -89,17 +89,20 @@ impl AuthorityServer {
         state: Arc<AuthorityState>,
         consensus_address: Multiaddr,
     ) -> Self {
-        let consensus_adapter = Arc::new(ConsensusAdapter::new(
-            Arc::new(LazyNarwhalClient::new(consensus_address)),
-            state.name,
-            Arc::new(ConnectionMonitorStatusForTests {}),
-            100_000,
-            100_000,
-            None,
-            None,
-            ConsensusAdapterMetrics::new_test(),
-            state.epoch_store_for_testing().protocol_config().clone(),
-        ));
+        let consensus_adapter = Arc::new(
+            ConsensusAdapter::new(
+                Arc::new(LazyNarwhalClient::new(consensus_address)),
+                state.name,
+                Arc::new(ConnectionMonitorStatusForTests {}),
+                100_000,
+                100_000,
+                None,
+                None,
+                ConsensusAdapterMetrics::new_test(),
+                state.epoch_store_for_testing().protocol_config().clone(),
+            )
+            .with_transaction_tracer(*state.transaction_tracer()),
+        );
 
         let metrics = Arc::new(ValidatorServiceMetrics::new_for_tests());
 
@@ -352,6 +355,9 @@ impl ValidatorService {
 
         // Enable Trace Propagation across spans/processes using tx_digest
         let span = error_span!("validator_state_process_tx", ?tx_digest);
+        state
+            .transaction_tracer()
+            .trace_transaction(&span, tx_digest);
 
         let info = state
             .handle_transaction(&epoch_store, transaction)
@@ -542,7 +548,11 @@ impl Validator for ValidatorService {
         // The call to digest() assumes the transaction is valid, so we need to verify it first.
         request.get_ref().verify_user_input()?;
 
-        let span = error_span!("submit_certificate", tx_digest = ?request.get_ref().digest());
+        let tx_digest = *request.get_ref().digest();
+        let span = error_span!("submit_certificate", ?tx_digest);
+        self.state
+            .transaction_tracer()
+            .trace_transaction(&span, &tx_digest);
         self.handle_certificate(request, false)
             .instrument(span)
             .await
@@ -556,7 +566,11 @@ impl Validator for ValidatorService {
         // The call to digest() assumes the transaction is valid, so we need to verify it first.
         request.get_ref().verify_user_input()?;
 
-        let span = error_span!("handle_certificate", tx_digest = ?request.get_ref().digest());
+        let tx_digest = *request.get_ref().digest();
+        let span = error_span!("handle_certificate", ?tx_digest);
+        self.state
+            .transaction_tracer()
+            .trace_transaction(&span, &tx_digest);
         self.handle_certificate(request, true)
             .instrument(span)
             .await
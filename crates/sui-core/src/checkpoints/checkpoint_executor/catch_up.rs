@@ -0,0 +1,319 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Catch-up execution of checkpoints.
+//!
+//! When a node is far behind the synced checkpoints, scheduling every transaction through the
+//! TransactionManager and awaiting effects one checkpoint at a time is dominated by
+//! per-transaction overheads. In catch-up mode, CheckpointExecutor instead takes a batch of
+//! checkpoints, derives the objects each transaction reads and writes from the transaction and its
+//! certified effects, and splits the batch into waves of transactions that don't conflict with each
+//! other. Waves are executed one after the other. The inputs of all transactions of a wave are
+//! loaded with a single bulk read, and the transactions are executed in parallel, without going
+//! through the TransactionManager or notify-read of their effects.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures::future::join_all;
+use mysten_metrics::spawn_monitored_task;
+use sui_types::base_types::{ObjectID, TransactionDigest};
+use sui_types::crypto::RandomnessRound;
+use sui_types::effects::{TransactionEffects, TransactionEffectsAPI};
+use sui_types::error::SuiResult;
+use sui_types::executable_transaction::VerifiedExecutableTransaction;
+use sui_types::messages_checkpoint::VerifiedCheckpoint;
+use sui_types::transaction::TransactionDataAPI;
+use tracing::{debug, instrument};
+
+use super::metrics::CheckpointExecutorMetrics;
+use super::{finalize_checkpoint, get_unexecuted_transactions, notify_randomness_in_checkpoint};
+use crate::authority::authority_per_epoch_store::AuthorityPerEpochStore;
+use crate::authority::AuthorityState;
+use crate::checkpoints::CheckpointStore;
+use crate::execution_cache::ExecutionCacheRead;
+use crate::state_accumulator::StateAccumulator;
+
+/// Objects read and written by a transaction.
+struct AccessedObjects {
+    reads: Vec<ObjectID>,
+    writes: Vec<ObjectID>,
+}
+
+impl AccessedObjects {
+    /// Reads come from the inputs of the transaction, and writes from its effects. Child objects
+    /// accessed at runtime are not listed, but they can only be reached through an input object,
+    /// which already orders conflicting transactions.
+    fn new(
+        transaction: &VerifiedExecutableTransaction,
+        effects: &TransactionEffects,
+    ) -> SuiResult<Self> {
+        let transaction_data = transaction.data().transaction_data();
+        let reads = transaction_data
+            .input_objects()?
+            .iter()
+            .map(|kind| kind.object_id())
+            .chain(
+                transaction_data
+                    .receiving_objects()
+                    .into_iter()
+                    .map(|(id, _, _)| id),
+            )
+            .collect();
+        let writes = effects
+            .object_changes()
+            .into_iter()
+            .map(|change| change.id)
+            .collect();
+        Ok(Self { reads, writes })
+    }
+}
+
+/// Splits a batch of transactions into waves of transaction indices. Two transactions conflict if
+/// one of them writes an object the other one reads or writes. Transactions of the same wave don't
+/// conflict with each other, and each transaction is in a later wave than all the earlier
+/// transactions of the batch it conflicts with.
+fn schedule_waves(accessed_objects: &[AccessedObjects]) -> Vec<Vec<usize>> {
+    // Wave of the last transaction writing each object, and latest wave reading it.
+    let mut last_write: HashMap<ObjectID, usize> = HashMap::new();
+    let mut last_read: HashMap<ObjectID, usize> = HashMap::new();
+    let mut waves: Vec<Vec<usize>> = Vec::new();
+
+    for (index, AccessedObjects { reads, writes }) in accessed_objects.iter().enumerate() {
+        let after_writes = reads
+            .iter()
+            .chain(writes)
+            .filter_map(|id| last_write.get(id))
+            .map(|wave| wave + 1);
+        let after_reads = writes
+            .iter()
+            .filter_map(|id| last_read.get(id))
+            .map(|wave| wave + 1);
+        let wave = after_writes.chain(after_reads).max().unwrap_or(0);
+
+        for id in reads {
+            let last = last_read.entry(*id).or_default();
+            *last = (*last).max(wave);
+        }
+        for id in writes {
+            last_write.insert(*id, wave);
+        }
+        if wave == waves.len() {
+            waves.push(Vec::new());
+        }
+        waves[wave].push(index);
+    }
+    waves
+}
+
+/// A checkpoint of a catch-up batch whose transactions are all executed.
+pub(super) struct CatchUpCheckpoint {
+    checkpoint: VerifiedCheckpoint,
+    all_tx_digests: Vec<TransactionDigest>,
+    randomness_round: Option<RandomnessRound>,
+}
+
+/// Executes the transactions of a batch of consecutive checkpoints of the current epoch, none of
+/// which can be the last checkpoint of the epoch. Returns the checkpoints, ready to be finalized
+/// in order with [`finalize_catch_up_checkpoint`].
+#[instrument(level = "debug", skip_all, fields(first = ?checkpoints.first().map(|c| c.sequence_number), last = ?checkpoints.last().map(|c| c.sequence_number)))]
+pub(super) async fn execute_checkpoints_for_catch_up(
+    checkpoints: Vec<VerifiedCheckpoint>,
+    state: &Arc<AuthorityState>,
+    cache_reader: &dyn ExecutionCacheRead,
+    checkpoint_store: Arc<CheckpointStore>,
+    epoch_store: Arc<AuthorityPerEpochStore>,
+    metrics: &Arc<CheckpointExecutorMetrics>,
+) -> SuiResult<Vec<CatchUpCheckpoint>> {
+    let mut prepared = Vec::with_capacity(checkpoints.len());
+    let mut executable_txns = Vec::new();
+    for checkpoint in checkpoints {
+        assert!(checkpoint.end_of_epoch_data.is_none());
+        let (_, all_tx_digests, txns, randomness_round) = get_unexecuted_transactions(
+            checkpoint.clone(),
+            cache_reader,
+            checkpoint_store.clone(),
+            epoch_store.clone(),
+        );
+        metrics
+            .checkpoint_transaction_count
+            .report(all_tx_digests.len() as u64);
+        executable_txns.extend(txns);
+        prepared.push(CatchUpCheckpoint {
+            checkpoint,
+            all_tx_digests,
+            randomness_round,
+        });
+    }
+
+    // Effects of synced checkpoints are available before execution, and tell which objects each
+    // transaction writes.
+    let effects_digests: Vec<_> = executable_txns
+        .iter()
+        .map(|(_, effects_digest)| *effects_digest)
+        .collect();
+    let effects: Vec<TransactionEffects> = cache_reader
+        .multi_get_effects(&effects_digests)?
+        .into_iter()
+        .zip(&effects_digests)
+        .map(|(fx, fx_digest)| {
+            fx.unwrap_or_else(|| {
+                panic!(
+                    "Transaction effects for effects digest {:?} do not exist in effects table",
+                    fx_digest
+                )
+            })
+        })
+        .collect();
+
+    for ((tx, _), fx) in executable_txns.iter().zip(&effects) {
+        if tx.contains_shared_object() {
+            epoch_store
+                .acquire_shared_locks_from_effects(tx, fx, cache_reader)
+                .await?;
+        }
+    }
+
+    let accessed_objects = executable_txns
+        .iter()
+        .zip(&effects)
+        .map(|((tx, _), fx)| AccessedObjects::new(tx, fx))
+        .collect::<SuiResult<Vec<_>>>()?;
+    let waves = schedule_waves(&accessed_objects);
+    debug!(
+        num_transactions = executable_txns.len(),
+        num_waves = waves.len(),
+        "Executing checkpoint batch in catch-up mode"
+    );
+
+    for wave in waves {
+        metrics
+            .checkpoint_exec_catch_up_wave_size
+            .report(wave.len() as u64);
+        let certificates: Vec<_> = wave
+            .iter()
+            .map(|index| executable_txns[*index].0.clone())
+            .collect();
+        let input_objects = state
+            .read_objects_for_batch_execution(&certificates, &epoch_store)
+            .await?;
+
+        let tasks = wave.into_iter().zip(certificates).zip(input_objects).map(
+            |((index, certificate), input_objects)| {
+                let state = state.clone();
+                let epoch_store = epoch_store.clone();
+                let expected_effects_digest = executable_txns[index].1;
+                spawn_monitored_task!(async move {
+                    let tx_guard = epoch_store.acquire_tx_guard(&certificate).await?;
+                    state
+                        .process_certificate(
+                            tx_guard,
+                            &certificate,
+                            input_objects,
+                            Some(expected_effects_digest),
+                            &epoch_store,
+                        )
+                        .await
+                        .map(|_| ())
+                })
+            },
+        );
+        for result in join_all(tasks).await {
+            result.expect("Catch-up execution task cannot panic")?;
+        }
+    }
+
+    Ok(prepared)
+}
+
+/// Finalizes a checkpoint of a catch-up batch. The checkpoints of a batch must be finalized in
+/// order, and each of them must be committed before the next one is finalized, so that a failure
+/// leaves no finalized checkpoint uncommitted. Returns the checkpoint with the digests of its
+/// transactions, ready to be committed.
+pub(super) async fn finalize_catch_up_checkpoint(
+    CatchUpCheckpoint {
+        checkpoint,
+        all_tx_digests,
+        randomness_round,
+    }: CatchUpCheckpoint,
+    state: &AuthorityState,
+    cache_reader: &dyn ExecutionCacheRead,
+    checkpoint_store: Arc<CheckpointStore>,
+    epoch_store: Arc<AuthorityPerEpochStore>,
+    accumulator: Arc<StateAccumulator>,
+    data_ingestion_dir: Option<PathBuf>,
+) -> SuiResult<(VerifiedCheckpoint, Vec<TransactionDigest>)> {
+    // All transactions are executed, and their effects are checked against the certified ones, so
+    // they can be read directly.
+    let effects = cache_reader
+        .multi_get_executed_effects(&all_tx_digests)?
+        .into_iter()
+        .zip(&all_tx_digests)
+        .map(|(fx, tx_digest)| {
+            fx.unwrap_or_else(|| panic!("Transaction {tx_digest:?} must have been executed"))
+        })
+        .collect();
+    finalize_checkpoint(
+        state,
+        cache_reader,
+        checkpoint_store,
+        &all_tx_digests,
+        epoch_store.clone(),
+        checkpoint.clone(),
+        accumulator,
+        effects,
+        data_ingestion_dir,
+    )
+    .await?;
+    if let Some(round) = randomness_round {
+        notify_randomness_in_checkpoint(&epoch_store, round)?;
+    }
+    Ok((checkpoint, all_tx_digests))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accessed(reads: &[ObjectID], writes: &[ObjectID]) -> AccessedObjects {
+        AccessedObjects {
+            reads: reads.to_vec(),
+            writes: writes.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_schedule_waves() {
+        let (a, b, c, clock) = (
+            ObjectID::random(),
+            ObjectID::random(),
+            ObjectID::random(),
+            ObjectID::random(),
+        );
+
+        // Transactions on different owned objects don't conflict.
+        let waves = schedule_waves(&[accessed(&[a], &[a]), accessed(&[b], &[b])]);
+        assert_eq!(waves, vec![vec![0, 1]]);
+
+        // Transactions on the same object are executed in order.
+        let waves = schedule_waves(&[
+            accessed(&[a], &[a]),
+            accessed(&[b], &[b]),
+            accessed(&[a], &[a, c]),
+            accessed(&[c], &[c]),
+        ]);
+        assert_eq!(waves, vec![vec![0, 1], vec![2], vec![3]]);
+
+        // Readers of an object run in parallel, and after its last writer. The next writer
+        // waits for all the readers.
+        let waves = schedule_waves(&[
+            accessed(&[clock], &[clock]),
+            accessed(&[a, clock], &[a]),
+            accessed(&[b, clock], &[b]),
+            accessed(&[clock], &[clock]),
+            accessed(&[c, clock], &[c]),
+        ]);
+        assert_eq!(waves, vec![vec![0], vec![1, 2], vec![3], vec![4]]);
+    }
+}
@@ -21,6 +21,8 @@ pub struct CheckpointExecutorMetrics {
     pub checkpoint_contents_age_ms: Histogram,
     pub last_executed_checkpoint_age_ms: Histogram,
     pub accumulator_inconsistent_state: IntGauge,
+    pub checkpoint_exec_catch_up_batches: IntCounter,
+    pub checkpoint_exec_catch_up_wave_size: Histogram,
 }
 
 impl CheckpointExecutorMetrics {
@@ -93,6 +95,17 @@ impl CheckpointExecutorMetrics {
                 registry,
             )
             .unwrap(),
+            checkpoint_exec_catch_up_batches: register_int_counter_with_registry!(
+                "checkpoint_exec_catch_up_batches",
+                "Number of checkpoint batches executed in catch-up mode",
+                registry
+            )
+            .unwrap(),
+            checkpoint_exec_catch_up_wave_size: Histogram::new_in_registry(
+                "checkpoint_exec_catch_up_wave_size",
+                "Number of transactions executed in parallel in a catch-up wave",
+                registry,
+            ),
         };
         Arc::new(this)
     }
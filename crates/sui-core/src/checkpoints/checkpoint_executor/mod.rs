@@ -59,6 +59,7 @@ use crate::state_accumulator::StateAccumulator;
 use crate::transaction_manager::TransactionManager;
 use crate::{checkpoints::CheckpointStore, execution_cache::ExecutionCacheRead};
 
+mod catch_up;
 mod data_ingestion_handler;
 mod metrics;
 #[cfg(test)]
@@ -218,6 +219,84 @@ impl CheckpointExecutor {
                 return StopReason::EpochComplete;
             }
 
+            if pending.is_empty() {
+                let batch = self.get_catch_up_batch(next_to_schedule, &epoch_store, run_with_range);
+                if !batch.is_empty() {
+                    self.metrics.checkpoint_exec_catch_up_batches.inc();
+                    match catch_up::execute_checkpoints_for_catch_up(
+                        batch,
+                        &self.state,
+                        self.cache_reader.as_ref(),
+                        self.checkpoint_store.clone(),
+                        epoch_store.clone(),
+                        &self.metrics,
+                    )
+                    .await
+                    {
+                        Ok(executed) => {
+                            // Each checkpoint is committed as soon as it is finalized, so that if
+                            // finalizing a checkpoint fails, regular execution takes over right
+                            // after the last committed one.
+                            let mut finalized_all = true;
+                            for executed in executed {
+                                let (checkpoint, tx_digests) =
+                                    match catch_up::finalize_catch_up_checkpoint(
+                                        executed,
+                                        &self.state,
+                                        self.cache_reader.as_ref(),
+                                        self.checkpoint_store.clone(),
+                                        epoch_store.clone(),
+                                        self.accumulator.clone(),
+                                        self.config.data_ingestion_dir.clone(),
+                                    )
+                                    .await
+                                    {
+                                        Ok(finalized) => finalized,
+                                        Err(err) => {
+                                            error!(
+                                                "Error while finalizing checkpoint in catch-up mode: {:?}",
+                                                err
+                                            );
+                                            self.metrics.checkpoint_exec_errors.inc();
+                                            finalized_all = false;
+                                            break;
+                                        }
+                                    };
+                                self.process_executed_checkpoint(
+                                    &epoch_store,
+                                    &checkpoint,
+                                    &tx_digests,
+                                )
+                                .await;
+                                next_to_schedule = checkpoint.sequence_number() + 1;
+                                highest_executed = Some(checkpoint.clone());
+                                if run_with_range.map_or(false, |rwr| {
+                                    rwr.matches_checkpoint(checkpoint.sequence_number)
+                                }) {
+                                    info!(
+                                        "RunWithRange condition satisfied after checkpoint sequence number {:?}",
+                                        checkpoint.sequence_number
+                                    );
+                                    return StopReason::RunWithRangeCondition;
+                                }
+                            }
+                            if finalized_all {
+                                continue;
+                            }
+                        }
+                        Err(err) => {
+                            // Fall back to regular execution, which skips the transactions that
+                            // were already executed.
+                            error!(
+                                "Error while executing checkpoints in catch-up mode: {:?}",
+                                err
+                            );
+                            self.metrics.checkpoint_exec_errors.inc();
+                        }
+                    }
+                }
+            }
+
             self.schedule_synced_checkpoints(
                 &mut pending,
                 // next_to_schedule will be updated to the next checkpoint to schedule.
@@ -367,6 +446,56 @@ impl CheckpointExecutor {
         }
     }
 
+    /// Returns the checkpoints to execute together in catch-up mode, if it is enabled and
+    /// execution is far enough behind the synced checkpoints. The last checkpoint of the epoch
+    /// is never part of a batch, because of the special handling of the change epoch transaction.
+    fn get_catch_up_batch(
+        &self,
+        next_to_schedule: CheckpointSequenceNumber,
+        epoch_store: &AuthorityPerEpochStore,
+        run_with_range: Option<RunWithRange>,
+    ) -> Vec<VerifiedCheckpoint> {
+        let Some(catch_up) = &self.config.catch_up else {
+            return vec![];
+        };
+        let Some(latest_synced_checkpoint) = self
+            .checkpoint_store
+            .get_highest_synced_checkpoint()
+            .expect("Failed to read highest synced checkpoint")
+        else {
+            return vec![];
+        };
+        let highest_synced = *latest_synced_checkpoint.sequence_number();
+        if highest_synced < next_to_schedule
+            || highest_synced - next_to_schedule + 1 < catch_up.min_lag
+        {
+            return vec![];
+        }
+
+        let mut last = highest_synced.min(next_to_schedule + catch_up.batch_size.max(1) as u64 - 1);
+        if let Some(RunWithRange::Checkpoint(seq)) = run_with_range {
+            last = last.min(seq);
+        }
+        let mut batch = Vec::new();
+        for seq in next_to_schedule..=last {
+            let checkpoint = self
+                .checkpoint_store
+                .get_checkpoint_by_sequence_number(seq)
+                .unwrap()
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Checkpoint sequence number {:?} does not exist in checkpoint store",
+                        seq
+                    )
+                });
+            if checkpoint.epoch() != epoch_store.epoch() || checkpoint.end_of_epoch_data.is_some() {
+                break;
+            }
+            batch.push(checkpoint);
+        }
+        batch
+    }
+
     #[instrument(level = "debug", skip_all)]
     fn schedule_synced_checkpoints(
         &self,
@@ -680,22 +809,30 @@ async fn execute_checkpoint(
     )
     .await?;
 
-    // Once execution is complete, we know that any randomness contained in this checkpoint has
-    // been successfully included in a checkpoint certified by quorum of validators.
     if let Some(round) = randomness_round {
-        // RandomnessManager is only present on validators.
-        if let Some(randomness_reporter) = epoch_store.randomness_reporter() {
-            debug!(
-                ?round,
-                "notifying RandomnessReporter that randomness update was executed in checkpoint"
-            );
-            randomness_reporter.notify_randomness_in_checkpoint(round)?;
-        }
+        notify_randomness_in_checkpoint(&epoch_store, round)?;
     }
 
     Ok(all_tx_digests)
 }
 
+// Once execution is complete, we know that any randomness contained in this checkpoint has
+// been successfully included in a checkpoint certified by quorum of validators.
+fn notify_randomness_in_checkpoint(
+    epoch_store: &AuthorityPerEpochStore,
+    round: RandomnessRound,
+) -> SuiResult {
+    // RandomnessManager is only present on validators.
+    if let Some(randomness_reporter) = epoch_store.randomness_reporter() {
+        debug!(
+            ?round,
+            "notifying RandomnessReporter that randomness update was executed in checkpoint"
+        );
+        randomness_reporter.notify_randomness_in_checkpoint(round)?;
+    }
+    Ok(())
+}
+
 #[instrument(level = "error", skip_all, fields(seq = ?checkpoint.sequence_number(), epoch = ?epoch_store.epoch()))]
 async fn handle_execution_effects(
     state: &AuthorityState,
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use sui_config::node::ExpensiveSafetyCheckConfig;
+use sui_config::node::{CheckpointCatchUpConfig, ExpensiveSafetyCheckConfig};
 use sui_types::gas::GasCostSummary;
 use tempfile::tempdir;
 
@@ -17,10 +17,18 @@ use sui_types::messages_checkpoint::{ECMHLiveObjectSetDigest, EndOfEpochData, Ve
 use tokio::{sync::broadcast, time::timeout};
 
 use crate::authority::test_authority_builder::TestAuthorityBuilder;
+use crate::execution_cache::StateSyncAPI;
+use crate::test_utils::make_transfer_sui_transaction;
 use crate::{
     authority::AuthorityState, checkpoints::CheckpointStore, state_accumulator::StateAccumulator,
 };
+use sui_swarm_config::genesis_config::{AccountConfig, DEFAULT_GAS_AMOUNT};
+use sui_swarm_config::network_config::NetworkConfig;
+use sui_swarm_config::network_config_builder::ConfigBuilder;
 use sui_swarm_config::test_utils::{empty_contents, CommitteeFixture};
+use sui_types::base_types::{ObjectRef, SuiAddress, VerifiedExecutionData};
+use sui_types::crypto::KeypairTraits;
+use sui_types::messages_checkpoint::{FullCheckpointContents, VerifiedCheckpointContents};
 use sui_types::sui_system_state::epoch_start_sui_system_state::EpochStartSystemState;
 use typed_store::Map;
 
@@ -99,6 +107,95 @@ pub async fn test_checkpoint_executor_crash_recovery() {
     executor_handle.abort();
 }
 
+/// Test that checkpoint executor executes batches of checkpoints in catch-up mode while it is far
+/// behind the synced checkpoints, and switches back to regular execution once it has caught up.
+/// Checkpoints contain transfers from different accounts, which don't conflict with each other,
+/// and several transfers from the same account, which do.
+#[tokio::test]
+pub async fn test_checkpoint_executor_catch_up() {
+    let buffer_size = num_cpus::get() * 2;
+    let tempdir = tempdir().unwrap();
+    let checkpoint_store = CheckpointStore::new(tempdir.path());
+
+    let network_config = ConfigBuilder::new_with_temp_dir()
+        .with_accounts(vec![
+            AccountConfig {
+                address: None,
+                gas_amounts: vec![DEFAULT_GAS_AMOUNT],
+            };
+            4
+        ])
+        .build();
+    let (state, _, accumulator, checkpoint_sender, committee) =
+        init_executor_test_with_network_config(
+            buffer_size,
+            checkpoint_store.clone(),
+            &network_config,
+        )
+        .await;
+    let mut executor = CheckpointExecutor::new(
+        checkpoint_sender.subscribe(),
+        checkpoint_store.clone(),
+        state.clone(),
+        accumulator.clone(),
+        CheckpointExecutorConfig {
+            catch_up: Some(CheckpointCatchUpConfig {
+                min_lag: 10,
+                batch_size: 4,
+            }),
+            ..Default::default()
+        },
+        &Registry::new(),
+    );
+
+    let contents = make_transfer_checkpoint_contents(&network_config, 24).await;
+    let (checkpoints, _, _, _) = committee.make_checkpoints_with_contents(contents.clone(), None);
+    let (root, checkpoints) = checkpoints.split_first().unwrap();
+    sync_checkpoint(root, &checkpoint_store, &checkpoint_sender);
+    for (checkpoint, contents) in checkpoints.iter().zip(contents.clone()) {
+        sync_checkpoint_with_contents(
+            &state,
+            checkpoint,
+            contents,
+            &checkpoint_store,
+            &checkpoint_sender,
+        );
+    }
+
+    let epoch_store = state.epoch_store_for_testing().clone();
+    let stop_reason = timeout(
+        Duration::from_secs(30),
+        executor.run_epoch(epoch_store, Some(RunWithRange::Checkpoint(24))),
+    )
+    .await
+    .expect("Checkpoint execution timed out");
+    assert_eq!(stop_reason, StopReason::RunWithRangeCondition);
+
+    assert_eq!(
+        checkpoint_store
+            .get_highest_executed_checkpoint_seq_number()
+            .unwrap(),
+        Some(24)
+    );
+    // Checkpoints 0 to 15 are executed in batches of 4, until less than 10 checkpoints are left.
+    assert_eq!(executor.metrics.checkpoint_exec_catch_up_batches.get(), 4);
+
+    // All transactions are executed with the certified effects.
+    let (tx_digests, effects_digests): (Vec<_>, Vec<_>) = contents
+        .iter()
+        .flat_map(|contents| contents.iter())
+        .map(|data| (*data.transaction.digest(), data.effects.digest()))
+        .unzip();
+    let executed_effects_digests = state
+        .get_execution_cache()
+        .multi_get_executed_effects_digests(&tx_digests)
+        .unwrap();
+    assert_eq!(
+        executed_effects_digests,
+        effects_digests.into_iter().map(Some).collect::<Vec<_>>()
+    );
+}
+
 /// Test that checkpoint execution correctly signals end of epoch after
 /// receiving last checkpoint of epoch, then resumes executing cehckpoints
 /// from the next epoch if called after reconfig
@@ -382,10 +479,23 @@ async fn init_executor_test(
     Sender<VerifiedCheckpoint>,
     CommitteeFixture,
 ) {
-    let network_config =
-        sui_swarm_config::network_config_builder::ConfigBuilder::new_with_temp_dir().build();
+    let network_config = ConfigBuilder::new_with_temp_dir().build();
+    init_executor_test_with_network_config(buffer_size, store, &network_config).await
+}
+
+async fn init_executor_test_with_network_config(
+    buffer_size: usize,
+    store: Arc<CheckpointStore>,
+    network_config: &NetworkConfig,
+) -> (
+    Arc<AuthorityState>,
+    CheckpointExecutor,
+    Arc<StateAccumulator>,
+    Sender<VerifiedCheckpoint>,
+    CommitteeFixture,
+) {
     let state = TestAuthorityBuilder::new()
-        .with_network_config(&network_config)
+        .with_network_config(network_config)
         .build()
         .await;
 
@@ -406,10 +516,76 @@ async fn init_executor_test(
         executor,
         accumulator,
         checkpoint_sender,
-        CommitteeFixture::from_network_config(&network_config),
+        CommitteeFixture::from_network_config(network_config),
     )
 }
 
+/// Executes transfers on a separate authority started from the same genesis, and returns the
+/// contents of the given number of checkpoints containing them. In each checkpoint, every account
+/// transfers to the next one, and the first account transfers a second time with the same gas coin.
+async fn make_transfer_checkpoint_contents(
+    network_config: &NetworkConfig,
+    number_of_checkpoints: usize,
+) -> Vec<VerifiedCheckpointContents> {
+    let state = TestAuthorityBuilder::new()
+        .with_network_config(network_config)
+        .build()
+        .await;
+    let epoch_store = state.epoch_store_for_testing().clone();
+    let rgp = state.reference_gas_price_for_testing().unwrap();
+    let mut accounts: Vec<_> = network_config
+        .account_keys
+        .iter()
+        .map(|key| {
+            let address: SuiAddress = key.public().into();
+            let gas: ObjectRef = state
+                .get_owner_objects(address, None, 1, None)
+                .unwrap()
+                .remove(0)
+                .into();
+            (address, key, gas)
+        })
+        .collect();
+
+    let mut contents = Vec::new();
+    for seq in 1..=number_of_checkpoints {
+        let mut transactions = Vec::new();
+        for (sender, recipient) in (0..accounts.len())
+            .map(|i| (i, (i + 1) % accounts.len()))
+            .chain([(0, 1)])
+        {
+            let recipient = accounts[recipient].0;
+            let (address, key, gas) = &mut accounts[sender];
+            let transaction = VerifiedTransaction::new_unchecked(make_transfer_sui_transaction(
+                *gas,
+                recipient,
+                Some(1),
+                *address,
+                *key,
+                rgp,
+            ));
+            let (effects, _) = state
+                .try_execute_immediately(
+                    &VerifiedExecutableTransaction::new_from_checkpoint(
+                        transaction.clone(),
+                        epoch_store.epoch(),
+                        seq as CheckpointSequenceNumber,
+                    ),
+                    None,
+                    &epoch_store,
+                )
+                .await
+                .unwrap();
+            *gas = effects.gas_object().0;
+            transactions.push(VerifiedExecutionData::new(transaction, effects).into_inner());
+        }
+        contents.push(VerifiedCheckpointContents::new_unchecked(
+            FullCheckpointContents::new_with_causally_ordered_transactions(transactions),
+        ));
+    }
+    contents
+}
+
 /// Creates and simulates syncing of a new checkpoint by StateSync, i.e. new
 /// checkpoint is persisted, along with its contents, highest synced checkpoint
 /// watermark is updated, and message is broadcasted notifying of the newly synced
@@ -477,3 +653,30 @@ fn sync_checkpoint(
         .unwrap();
     sender.send(checkpoint.clone()).unwrap();
 }
+
+/// Simulates syncing of a checkpoint with transactions by StateSync, i.e. the transactions and
+/// their effects are persisted along with the checkpoint and its full contents.
+fn sync_checkpoint_with_contents(
+    state: &AuthorityState,
+    checkpoint: &VerifiedCheckpoint,
+    contents: VerifiedCheckpointContents,
+    checkpoint_store: &CheckpointStore,
+    sender: &Sender<VerifiedCheckpoint>,
+) {
+    for data in contents.iter() {
+        state
+            .get_state_sync_store()
+            .insert_transaction_and_effects(&data.transaction, &data.effects)
+            .unwrap();
+    }
+    checkpoint_store
+        .insert_verified_checkpoint(checkpoint)
+        .unwrap();
+    checkpoint_store
+        .insert_verified_checkpoint_contents(checkpoint, contents)
+        .unwrap();
+    checkpoint_store
+        .update_highest_synced_checkpoint(checkpoint)
+        .unwrap();
+    sender.send(checkpoint.clone()).unwrap();
+}
@@ -0,0 +1,361 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Evidence of checkpoint forks.
+//!
+//! When the checkpoint computed locally disagrees with the certified checkpoint of the same
+//! sequence number, the validator cannot make progress and halts. Before halting, it writes an
+//! evidence bundle with what it knows about both checkpoints, so that bundles of validators on
+//! different sides of the fork can be compared with `sui-tool diff-fork-evidence`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use diffy::create_patch;
+use fastcrypto::hash::MultisetHash;
+use serde::{Deserialize, Serialize};
+use sui_types::accumulator::Accumulator;
+use sui_types::base_types::TransactionDigest;
+use sui_types::digests::{CheckpointContentsDigest, TransactionEffectsDigest};
+use sui_types::effects::{TransactionEffects, TransactionEffectsAPI};
+use sui_types::message_envelope::Message;
+use sui_types::messages_checkpoint::{
+    CertifiedCheckpointSummary, CheckpointContents, CheckpointSequenceNumber, CheckpointSummary,
+    ECMHLiveObjectSetDigest, VerifiedCheckpoint,
+};
+use tracing::error;
+
+use super::CheckpointStore;
+use crate::authority::authority_per_epoch_store::AuthorityPerEpochStore;
+use crate::execution_cache::ExecutionCacheRead;
+use crate::state_accumulator::StateAccumulator;
+
+/// What a validator knows about a checkpoint it computed differently from the certified one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CheckpointForkEvidence {
+    pub local_summary: CheckpointSummary,
+    pub local_contents: Option<CheckpointContents>,
+    pub certified_summary: CertifiedCheckpointSummary,
+    pub certified_contents: Option<CheckpointContents>,
+    /// Effects executed locally for the transactions of the local checkpoint, in checkpoint order.
+    /// Not collected when the fork is detected while certifying a locally built checkpoint.
+    pub local_effects: Vec<TransactionEffects>,
+    /// Accumulator of the local effects of this checkpoint alone, i.e. the delta it applies to
+    /// the state rather than the root state accumulator. Only computed when the fork is detected
+    /// by CheckpointBuilder, and all the local effects were found.
+    pub local_checkpoint_accumulator: Option<Accumulator>,
+}
+
+impl CheckpointForkEvidence {
+    /// Collects the summaries and contents of both checkpoints from the checkpoint store.
+    pub(crate) fn new(
+        store: &CheckpointStore,
+        local_summary: &CheckpointSummary,
+        certified_checkpoint: &VerifiedCheckpoint,
+    ) -> Self {
+        Self {
+            local_summary: local_summary.clone(),
+            local_contents: Self::read_contents(store, &local_summary.content_digest),
+            certified_summary: certified_checkpoint.clone().into_inner(),
+            certified_contents: Self::read_contents(store, &certified_checkpoint.content_digest),
+            local_effects: vec![],
+            local_checkpoint_accumulator: None,
+        }
+    }
+
+    /// Adds the locally executed effects of the transactions of the local checkpoint.
+    pub(crate) fn with_local_effects(mut self, cache_reader: &dyn ExecutionCacheRead) -> Self {
+        let Some(contents) = &self.local_contents else {
+            return self;
+        };
+        let digests: Vec<_> = contents.iter().map(|digests| digests.transaction).collect();
+        match cache_reader.multi_get_executed_effects(&digests) {
+            Ok(effects) => self.local_effects = effects.into_iter().flatten().collect(),
+            Err(e) => error!("Failed to read local effects for fork evidence: {:?}", e),
+        }
+        self
+    }
+
+    /// Adds the locally executed effects of the transactions of the local checkpoint, and their
+    /// accumulator.
+    pub(crate) fn with_local_execution(
+        self,
+        cache_reader: &dyn ExecutionCacheRead,
+        accumulator: &StateAccumulator,
+        epoch_store: &AuthorityPerEpochStore,
+    ) -> Self {
+        let mut evidence = self.with_local_effects(cache_reader);
+        let all_found = evidence
+            .local_contents
+            .as_ref()
+            .is_some_and(|contents| contents.size() == evidence.local_effects.len());
+        if all_found {
+            evidence.local_checkpoint_accumulator = Some(accumulator.accumulate_effects(
+                evidence.local_effects.clone(),
+                epoch_store.protocol_config(),
+            ));
+        }
+        evidence
+    }
+
+    fn read_contents(
+        store: &CheckpointStore,
+        digest: &CheckpointContentsDigest,
+    ) -> Option<CheckpointContents> {
+        store.get_checkpoint_contents(digest).unwrap_or_else(|e| {
+            error!(
+                "Failed to read checkpoint contents {:?} for fork evidence: {:?}",
+                digest, e
+            );
+            None
+        })
+    }
+
+    pub fn sequence_number(&self) -> CheckpointSequenceNumber {
+        self.local_summary.sequence_number
+    }
+
+    /// Writes the bundle to `dir`, and returns the path of the written file.
+    pub fn write(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("checkpoint_fork_{}.bcs", self.sequence_number()));
+        fs::write(&path, bcs::to_bytes(self)?)?;
+        Ok(path)
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        Ok(bcs::from_bytes(&fs::read(path)?)?)
+    }
+
+    /// Effects digest of each transaction of the local checkpoint.
+    pub fn local_effects_digests(&self) -> BTreeMap<TransactionDigest, TransactionEffectsDigest> {
+        self.local_contents
+            .iter()
+            .flat_map(|contents| contents.iter())
+            .map(|digests| (digests.transaction, digests.effects))
+            .collect()
+    }
+
+    fn checkpoint_accumulator_digest(&self) -> Option<ECMHLiveObjectSetDigest> {
+        self.local_checkpoint_accumulator
+            .as_ref()
+            .map(|acc| acc.digest().into())
+    }
+
+    /// Describes the differences between this bundle and the bundle of another validator, for
+    /// the same checkpoint.
+    pub fn diff(&self, other: &Self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "Checkpoint {} (other bundle: checkpoint {})",
+            self.sequence_number(),
+            other.sequence_number()
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "Local checkpoint digests: {:?} / {:?}",
+            self.local_summary.digest(),
+            other.local_summary.digest()
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "Certified checkpoint digests: {:?} / {:?}",
+            self.certified_summary.digest(),
+            other.certified_summary.digest()
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "Local checkpoint accumulator digests: {:?} / {:?}",
+            self.checkpoint_accumulator_digest(),
+            other.checkpoint_accumulator_digest()
+        )
+        .unwrap();
+
+        let local_summary_text = format!("{:#?}", self.local_summary);
+        let other_summary_text = format!("{:#?}", other.local_summary);
+        if local_summary_text != other_summary_text {
+            writeln!(
+                out,
+                "\nLocal summary diff:\n{}",
+                create_patch(&local_summary_text, &other_summary_text)
+            )
+            .unwrap();
+        }
+
+        let effects_digests = self.local_effects_digests();
+        let other_effects_digests = other.local_effects_digests();
+        let mut mismatches: Vec<_> = effects_digests
+            .iter()
+            .filter(|(tx, fx)| other_effects_digests.get(tx) != Some(fx))
+            .map(|(tx, fx)| (*tx, Some(*fx), other_effects_digests.get(tx).copied()))
+            .collect();
+        mismatches.extend(
+            other_effects_digests
+                .iter()
+                .filter(|(tx, _)| !effects_digests.contains_key(tx))
+                .map(|(tx, fx)| (*tx, None, Some(*fx))),
+        );
+        writeln!(
+            out,
+            "\n{} transactions with different effects digests",
+            mismatches.len()
+        )
+        .unwrap();
+        for (tx, fx, other_fx) in mismatches {
+            writeln!(out, "  {:?}: {:?} / {:?}", tx, fx, other_fx).unwrap();
+            let effects = self.local_effects_of(&tx);
+            let other_effects = other.local_effects_of(&tx);
+            if let (Some(effects), Some(other_effects)) = (effects, other_effects) {
+                let effects_text = format!("{:#?}", effects);
+                let other_effects_text = format!("{:#?}", other_effects);
+                writeln!(out, "{}", create_patch(&effects_text, &other_effects_text)).unwrap();
+            }
+        }
+        out
+    }
+
+    fn local_effects_of(&self, digest: &TransactionDigest) -> Option<&TransactionEffects> {
+        self.local_effects
+            .iter()
+            .find(|effects| effects.transaction_digest() == digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::test_authority_builder::TestAuthorityBuilder;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use sui_macros::sim_test;
+    use sui_swarm_config::test_utils::CommitteeFixture;
+    use sui_types::base_types::ExecutionDigests;
+    use sui_types::gas::GasCostSummary;
+
+    fn evidence(
+        certified: &VerifiedCheckpoint,
+        digests: Vec<ExecutionDigests>,
+    ) -> CheckpointForkEvidence {
+        let contents = CheckpointContents::new_with_digests_only_for_tests(digests);
+        let mut local_summary = certified.data().clone();
+        local_summary.content_digest = *contents.digest();
+        CheckpointForkEvidence {
+            local_summary,
+            local_contents: Some(contents),
+            certified_summary: certified.clone().into_inner(),
+            certified_contents: None,
+            local_effects: vec![],
+            local_checkpoint_accumulator: None,
+        }
+    }
+
+    #[test]
+    fn test_fork_evidence_diff() {
+        let committee = CommitteeFixture::generate(rand::rngs::OsRng, 0, 4);
+        let (checkpoints, _, _, _) = committee.make_empty_checkpoints(2, None);
+        let certified = &checkpoints[1];
+
+        let agreed = ExecutionDigests::random();
+        let forked = ExecutionDigests::random();
+        let forked_elsewhere =
+            ExecutionDigests::new(forked.transaction, TransactionEffectsDigest::random());
+        let local = evidence(certified, vec![agreed, forked]);
+        let other = evidence(certified, vec![agreed, forked_elsewhere]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = local.write(dir.path()).unwrap();
+        let local = CheckpointForkEvidence::read(&path).unwrap();
+        assert_eq!(local.sequence_number(), certified.sequence_number);
+
+        let diff = local.diff(&other);
+        assert!(diff.contains("1 transactions with different effects digests"));
+        assert!(diff.contains(&format!("{:?}", forked.transaction)));
+        assert!(!diff.contains(&format!("{:?}", agreed.transaction)));
+        assert!(local
+            .diff(&local)
+            .contains("0 transactions with different effects digests"));
+    }
+
+    #[sim_test]
+    async fn test_fork_evidence_includes_local_effects() {
+        let state = TestAuthorityBuilder::new().build().await;
+        let epoch_store = state.epoch_store_for_testing();
+        let perpetual_tables = &state.database_for_testing().perpetual_tables;
+
+        // A transaction executed locally, which the local checkpoint includes.
+        let mut effects = TransactionEffects::default();
+        *effects.transaction_digest_mut_for_testing() = TransactionDigest::random();
+        *effects.gas_cost_summary_mut_for_testing() = GasCostSummary::new(1, 2, 1, 1);
+        let tx_digest = *effects.transaction_digest();
+        perpetual_tables
+            .effects
+            .insert(&effects.digest(), &effects)
+            .unwrap();
+        perpetual_tables
+            .executed_effects
+            .insert(&tx_digest, &effects.digest())
+            .unwrap();
+
+        let committee = CommitteeFixture::generate(rand::rngs::OsRng, 0, 4);
+        let (checkpoints, _, _, _) = committee.make_empty_checkpoints(1, None);
+        let certified = &checkpoints[0];
+        let local_contents =
+            CheckpointContents::new_with_digests_only_for_tests([ExecutionDigests::new(
+                tx_digest,
+                effects.digest(),
+            )]);
+        let mut local_summary = certified.data().clone();
+        local_summary.content_digest = *local_contents.digest();
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = CheckpointStore::new(dir.path());
+        store
+            .checkpoint_content
+            .insert(local_contents.digest(), &local_contents)
+            .unwrap();
+        store
+            .locally_computed_checkpoints
+            .insert(&local_summary.sequence_number, &local_summary)
+            .unwrap();
+
+        // Fork detected by CheckpointBuilder.
+        let accumulator = StateAccumulator::new(state.get_accumulator_store().clone());
+        let evidence = CheckpointForkEvidence::new(&store, &local_summary, certified)
+            .with_local_execution(
+                state.get_cache_reader().as_ref(),
+                &accumulator,
+                &epoch_store,
+            );
+        assert_eq!(evidence.local_effects, vec![effects.clone()]);
+        assert_eq!(
+            evidence.local_checkpoint_accumulator,
+            Some(
+                accumulator
+                    .accumulate_effects(vec![effects.clone()], epoch_store.protocol_config())
+            )
+        );
+
+        // Fork detected when state sync inserts the certified checkpoint, which halts.
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            store.insert_verified_checkpoint_with_local_effects(
+                certified,
+                state.get_cache_reader().as_ref(),
+            )
+        }));
+        assert!(result.is_err());
+        let evidence = CheckpointForkEvidence::read(
+            &store
+                .fork_evidence_dir()
+                .join(format!("checkpoint_fork_{}.bcs", certified.sequence_number)),
+        )
+        .unwrap();
+        assert_eq!(evidence.local_contents, Some(local_contents));
+        assert_eq!(evidence.local_effects, vec![effects]);
+        assert_eq!(evidence.local_checkpoint_accumulator, None);
+    }
+}
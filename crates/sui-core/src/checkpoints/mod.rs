@@ -4,6 +4,7 @@
 mod causal_order;
 pub mod checkpoint_executor;
 mod checkpoint_output;
+mod fork_evidence;
 mod metrics;
 
 use crate::authority::{AuthorityState, EffectsNotifyRead};
@@ -13,6 +14,7 @@ use crate::checkpoints::checkpoint_output::{CertifiedCheckpointOutput, Checkpoin
 pub use crate::checkpoints::checkpoint_output::{
     LogCheckpointOutput, SendCheckpointToStateSync, SubmitCheckpointToConsensus,
 };
+pub use crate::checkpoints::fork_evidence::CheckpointForkEvidence;
 pub use crate::checkpoints::metrics::CheckpointMetrics;
 use crate::stake_aggregator::{InsertResult, MultiStakeAggregator};
 use crate::state_accumulator::StateAccumulator;
@@ -31,13 +33,14 @@ use sui_types::sui_system_state::epoch_start_sui_system_state::EpochStartSystemS
 
 use crate::authority::authority_per_epoch_store::AuthorityPerEpochStore;
 use crate::consensus_handler::SequencedConsensusTransactionKey;
+use crate::execution_cache::ExecutionCacheRead;
 use chrono::Utc;
 use rand::rngs::OsRng;
 use rand::seq::SliceRandom;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use sui_protocol_config::ProtocolVersion;
@@ -418,63 +421,47 @@ impl CheckpointStore {
         &self,
         local_checkpoint: &CheckpointSummary,
         verified_checkpoint: &VerifiedCheckpoint,
+        cache_reader: Option<&dyn ExecutionCacheRead>,
     ) {
         if local_checkpoint != verified_checkpoint.data() {
-            let verified_contents = self
-                .get_checkpoint_contents(&verified_checkpoint.content_digest)
-                .map(|opt_contents| {
-                    opt_contents
-                        .map(|contents| format!("{:?}", contents))
-                        .unwrap_or_else(|| {
-                            format!(
-                                "Verified checkpoint contents not found, digest: {:?}",
-                                verified_checkpoint.content_digest,
-                            )
-                        })
-                })
-                .map_err(|e| {
-                    format!(
-                        "Failed to get verified checkpoint contents, digest: {:?} error: {:?}",
-                        verified_checkpoint.content_digest, e
-                    )
-                })
-                .unwrap_or_else(|err_msg| err_msg);
-
-            let local_contents = self
-                .get_checkpoint_contents(&local_checkpoint.content_digest)
-                .map(|opt_contents| {
-                    opt_contents
-                        .map(|contents| format!("{:?}", contents))
-                        .unwrap_or_else(|| {
-                            format!(
-                                "Local checkpoint contents not found, digest: {:?}",
-                                local_checkpoint.content_digest
-                            )
-                        })
-                })
-                .map_err(|e| {
-                    format!(
-                        "Failed to get local checkpoint contents, digest: {:?} error: {:?}",
-                        local_checkpoint.content_digest, e
-                    )
-                })
-                .unwrap_or_else(|err_msg| err_msg);
-
-            // checkpoint contents may be too large for panic message.
-            error!(
-                verified_checkpoint = ?verified_checkpoint.data(),
-                ?verified_contents,
-                ?local_checkpoint,
-                ?local_contents,
-                "Local checkpoint fork detected!",
-            );
-            panic!(
-                "Local checkpoint fork detected for sequence number: {}",
-                local_checkpoint.sequence_number()
-            );
+            let mut evidence =
+                CheckpointForkEvidence::new(self, local_checkpoint, verified_checkpoint);
+            if let Some(cache_reader) = cache_reader {
+                evidence = evidence.with_local_effects(cache_reader);
+            }
+            self.handle_checkpoint_fork(evidence);
         }
     }
 
+    /// Directory where evidence of checkpoint forks is written, next to the checkpoint store.
+    pub fn fork_evidence_dir(&self) -> PathBuf {
+        let path = self.checkpoint_content.rocksdb.path();
+        path.parent().unwrap_or(path).join("fork_evidence")
+    }
+
+    /// Writes the evidence of a checkpoint fork to disk, then halts.
+    fn handle_checkpoint_fork(&self, evidence: CheckpointForkEvidence) -> ! {
+        let evidence_path = evidence
+            .write(&self.fork_evidence_dir())
+            .map_err(|e| error!("Failed to write checkpoint fork evidence: {:?}", e))
+            .ok();
+
+        // checkpoint contents may be too large for panic message.
+        error!(
+            verified_checkpoint = ?evidence.certified_summary.data(),
+            verified_contents = ?evidence.certified_contents,
+            local_checkpoint = ?evidence.local_summary,
+            local_contents = ?evidence.local_contents,
+            ?evidence_path,
+            "Local checkpoint fork detected!",
+        );
+        panic!(
+            "Local checkpoint fork detected for sequence number: {}, evidence written to {:?}",
+            evidence.sequence_number(),
+            evidence_path,
+        );
+    }
+
     // Called by consensus (ConsensusAggregator).
     // Different from `insert_verified_checkpoint`, it does not touch
     // the highest_verified_checkpoint watermark such that state sync
@@ -483,6 +470,16 @@ impl CheckpointStore {
     pub fn insert_certified_checkpoint(
         &self,
         checkpoint: &VerifiedCheckpoint,
+    ) -> Result<(), TypedStoreError> {
+        // Checkpoints certified by consensus are built locally, so they can't fork from the
+        // local checkpoint, and there are no local effects to add to the evidence.
+        self.insert_certified_checkpoint_impl(checkpoint, None)
+    }
+
+    fn insert_certified_checkpoint_impl(
+        &self,
+        checkpoint: &VerifiedCheckpoint,
+        cache_reader: Option<&dyn ExecutionCacheRead>,
     ) -> Result<(), TypedStoreError> {
         debug!(
             checkpoint_seq = checkpoint.sequence_number(),
@@ -510,7 +507,7 @@ impl CheckpointStore {
             .locally_computed_checkpoints
             .get(checkpoint.sequence_number())?
         {
-            self.check_for_checkpoint_fork(&local_checkpoint, checkpoint);
+            self.check_for_checkpoint_fork(&local_checkpoint, checkpoint, cache_reader);
         }
 
         Ok(())
@@ -527,6 +524,17 @@ impl CheckpointStore {
         self.update_highest_verified_checkpoint(checkpoint)
     }
 
+    /// Same as `insert_verified_checkpoint`, but if the checkpoint forks from the local one, the
+    /// fork evidence includes the local effects of its transactions read from `cache_reader`.
+    pub fn insert_verified_checkpoint_with_local_effects(
+        &self,
+        checkpoint: &VerifiedCheckpoint,
+        cache_reader: &dyn ExecutionCacheRead,
+    ) -> Result<(), TypedStoreError> {
+        self.insert_certified_checkpoint_impl(checkpoint, Some(cache_reader))?;
+        self.update_highest_verified_checkpoint(checkpoint)
+    }
+
     pub fn update_highest_verified_checkpoint(
         &self,
         checkpoint: &VerifiedCheckpoint,
@@ -888,6 +896,17 @@ impl CheckpointBuilder {
             let _scope = monitored_scope("CheckpointBuilder::causal_sort");
             CausalOrder::causal_sort(unsorted)
         };
+        // Spans of the traced transactions, covering the creation and writing of the checkpoints
+        // that include them.
+        let _transaction_spans: Vec<_> = sorted
+            .iter()
+            .map(|effects| {
+                self.state
+                    .transaction_tracer()
+                    .transaction_span("checkpoint_builder", effects.transaction_digest())
+            })
+            .filter(|span| !span.is_none())
+            .collect();
         let new_checkpoint = self.create_checkpoints(sorted, pending.details).await?;
         self.write_checkpoints(height, new_checkpoint).await?;
         Ok(())
@@ -938,8 +957,22 @@ impl CheckpointBuilder {
                 .certified_checkpoints
                 .get(local_checkpoint.sequence_number())?
             {
-                self.tables
-                    .check_for_checkpoint_fork(local_checkpoint, &certified_checkpoint.into());
+                let certified_checkpoint: VerifiedCheckpoint = certified_checkpoint.into();
+                if local_checkpoint != certified_checkpoint.data() {
+                    // Unlike forks detected when a certified checkpoint is inserted, the local
+                    // effects are at hand, so include them in the evidence.
+                    let evidence = CheckpointForkEvidence::new(
+                        &self.tables,
+                        local_checkpoint,
+                        &certified_checkpoint,
+                    )
+                    .with_local_execution(
+                        self.state.get_cache_reader().as_ref(),
+                        &self.accumulator,
+                        &self.epoch_store,
+                    );
+                    self.tables.handle_checkpoint_fork(evidence);
+                }
             }
         }
 
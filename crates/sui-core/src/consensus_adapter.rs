@@ -25,6 +25,7 @@ use prometheus::{
 };
 use rand::rngs::StdRng;
 use rand::SeedableRng;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::future::Future;
 use std::ops::Deref;
@@ -46,6 +47,7 @@ use crate::consensus_handler::{classify, SequencedConsensusTransactionKey};
 use crate::consensus_throughput_calculator::{ConsensusThroughputProfiler, Level};
 use crate::epoch::reconfiguration::{ReconfigState, ReconfigurationInitiator};
 use crate::metrics::LatencyObserver;
+use crate::transaction_tracing::TransactionTracer;
 use mysten_metrics::{spawn_monitored_task, GaugeGuard, GaugeGuardFutureExt};
 use sui_protocol_config::ProtocolConfig;
 use sui_simulator::anemo::PeerId;
@@ -53,9 +55,10 @@ use sui_simulator::narwhal_network::connectivity::ConnectionStatus;
 use sui_types::base_types::AuthorityName;
 use sui_types::fp_ensure;
 use sui_types::messages_consensus::ConsensusTransaction;
+use sui_types::messages_consensus::ConsensusTransactionKey;
 use sui_types::messages_consensus::ConsensusTransactionKind;
 use tokio::time::Duration;
-use tracing::{debug, info, warn};
+use tracing::{debug, info, warn, Instrument, Span};
 
 #[cfg(test)]
 #[path = "unit_tests/consensus_tests.rs"]
@@ -255,6 +258,10 @@ pub struct ConsensusAdapter {
     max_pending_transactions: usize,
     /// Number of submitted transactions still inflight at this node.
     num_inflight_transactions: AtomicU64,
+    /// Submitted transactions still inflight at this node, by submission ID.
+    inflight_submissions: DashMap<u64, InflightSubmission>,
+    /// ID of the next submission, only used as a key of `inflight_submissions`.
+    next_submission_id: AtomicU64,
     /// Dictates the maximum position  from which will submit to consensus. Even if the is elected to
     /// submit from a higher position than this, it will "reset" to the max_submit_position.
     max_submit_position: Option<usize>,
@@ -273,6 +280,26 @@ pub struct ConsensusAdapter {
     submit_semaphore: Semaphore,
     latency_observer: LatencyObserver,
     protocol_config: ProtocolConfig,
+    /// Decides which submitted user transactions are traced end to end.
+    transaction_tracer: TransactionTracer,
+}
+
+struct InflightSubmission {
+    key: ConsensusTransactionKey,
+    tx_type: String,
+    start: Instant,
+    position: Option<usize>,
+}
+
+/// A transaction submitted to consensus that has not been processed yet, for debugging.
+#[derive(Clone, Debug, Serialize)]
+pub struct InflightSubmissionSnapshot {
+    pub key: String,
+    pub tx_type: String,
+    pub age_ms: u64,
+    /// Position of this authority among the submitters of the transaction. Not set while
+    /// waiting for the authorities ahead of it to submit.
+    pub position: Option<usize>,
 }
 
 pub trait CheckConnection: Send + Sync {
@@ -316,6 +343,8 @@ impl ConsensusAdapter {
             max_submit_position,
             submit_delay_step_override,
             num_inflight_transactions,
+            inflight_submissions: DashMap::new(),
+            next_submission_id: AtomicU64::new(0),
             connection_monitor_status,
             low_scoring_authorities,
             metrics,
@@ -323,9 +352,17 @@ impl ConsensusAdapter {
             latency_observer: LatencyObserver::new(),
             consensus_throughput_profiler: ArcSwapOption::empty(),
             protocol_config,
+            transaction_tracer: TransactionTracer::default(),
         }
     }
 
+    /// Traces submitted user transactions with the given tracer. No transaction is traced by
+    /// default.
+    pub fn with_transaction_tracer(mut self, transaction_tracer: TransactionTracer) -> Self {
+        self.transaction_tracer = transaction_tracer;
+        self
+    }
+
     pub fn swap_low_scoring_authorities(
         &self,
         new_low_scoring: Arc<ArcSwap<HashMap<AuthorityName, u64>>>,
@@ -593,6 +630,22 @@ impl ConsensusAdapter {
         self.submit_semaphore.available_permits() > 0
     }
 
+    /// Returns the submissions that have not been processed by consensus yet, oldest first.
+    pub fn inflight_submissions(&self) -> Vec<InflightSubmissionSnapshot> {
+        let mut submissions: Vec<_> = self
+            .inflight_submissions
+            .iter()
+            .map(|submission| InflightSubmissionSnapshot {
+                key: format!("{:?}", submission.key),
+                tx_type: submission.tx_type.clone(),
+                age_ms: submission.start.elapsed().as_millis() as u64,
+                position: submission.position,
+            })
+            .collect();
+        submissions.sort_by_key(|submission| std::cmp::Reverse(submission.age_ms));
+        submissions
+    }
+
     pub(crate) fn check_consensus_overload(&self) -> SuiResult {
         fp_ensure!(
             self.check_limits(),
@@ -606,10 +659,18 @@ impl ConsensusAdapter {
         transaction: ConsensusTransaction,
         epoch_store: &Arc<AuthorityPerEpochStore>,
     ) -> JoinHandle<()> {
+        // Covers the time until the certificate is sequenced and processed by consensus handler.
+        let span = match &transaction.kind {
+            ConsensusTransactionKind::UserTransaction(certificate) => self
+                .transaction_tracer
+                .transaction_span("consensus_adapter", certificate.digest()),
+            _ => Span::none(),
+        };
         // Reconfiguration lock is dropped when pending_consensus_transactions is persisted, before it is handled by consensus
         let async_stage = self
             .clone()
-            .submit_and_wait(transaction, epoch_store.clone());
+            .submit_and_wait(transaction, epoch_store.clone())
+            .instrument(span);
         // Number of these tasks is weakly limited based on `num_inflight_transactions`.
         // (Limit is not applied atomically, and only to user transactions.)
         let join_handle = spawn_monitored_task!(async_stage);
@@ -659,7 +720,7 @@ impl ConsensusAdapter {
 
         let (await_submit, position, positions_moved, preceding_disconnected) =
             self.await_submit_delay(epoch_store.committee(), &transaction);
-        let mut guard = InflightDropGuard::acquire(&self, tx_type.to_string());
+        let mut guard = InflightDropGuard::acquire(&self, tx_type.to_string(), transaction.key());
 
         let processed_waiter = tokio::select! {
             // We need to wait for some delay until we submit transaction to the consensus
@@ -686,6 +747,7 @@ impl ConsensusAdapter {
                 | ConsensusTransactionKind::CapabilityNotification(_)
                 | ConsensusTransactionKind::RandomnessDkgMessage(_, _)
                 | ConsensusTransactionKind::RandomnessDkgConfirmation(_, _)
+                | ConsensusTransactionKind::ExecutionTimeObservation(_)
         ) {
             let transaction_key = transaction_key.clone();
             Some(CancelOnDrop(spawn_monitored_task!(async {
@@ -709,9 +771,7 @@ impl ConsensusAdapter {
 
             // populate the position only when this authority submits the transaction
             // to consensus
-            guard.position = Some(position);
-            guard.positions_moved = Some(positions_moved);
-            guard.preceding_disconnected = Some(preceding_disconnected);
+            guard.set_position(position, positions_moved, preceding_disconnected);
 
             let _permit: SemaphorePermit = self
                 .submit_semaphore
@@ -952,6 +1012,7 @@ impl<T> Drop for CancelOnDrop<T> {
 /// Tracks number of inflight consensus requests and relevant metrics
 struct InflightDropGuard<'a> {
     adapter: &'a ConsensusAdapter,
+    submission_id: u64,
     start: Instant,
     position: Option<usize>,
     positions_moved: Option<usize>,
@@ -960,10 +1021,25 @@ struct InflightDropGuard<'a> {
 }
 
 impl<'a> InflightDropGuard<'a> {
-    pub fn acquire(adapter: &'a ConsensusAdapter, tx_type: String) -> Self {
+    pub fn acquire(
+        adapter: &'a ConsensusAdapter,
+        tx_type: String,
+        key: ConsensusTransactionKey,
+    ) -> Self {
         let inflight = adapter
             .num_inflight_transactions
             .fetch_add(1, Ordering::SeqCst);
+        let submission_id = adapter.next_submission_id.fetch_add(1, Ordering::Relaxed);
+        let start = Instant::now();
+        adapter.inflight_submissions.insert(
+            submission_id,
+            InflightSubmission {
+                key,
+                tx_type: tx_type.clone(),
+                start,
+                position: None,
+            },
+        );
         adapter
             .metrics
             .sequencing_certificate_attempt
@@ -976,13 +1052,32 @@ impl<'a> InflightDropGuard<'a> {
             .set(inflight as i64);
         Self {
             adapter,
-            start: Instant::now(),
+            submission_id,
+            start,
             position: None,
             positions_moved: None,
             preceding_disconnected: None,
             tx_type,
         }
     }
+
+    fn set_position(
+        &mut self,
+        position: usize,
+        positions_moved: usize,
+        preceding_disconnected: usize,
+    ) {
+        self.position = Some(position);
+        self.positions_moved = Some(positions_moved);
+        self.preceding_disconnected = Some(preceding_disconnected);
+        if let Some(mut submission) = self
+            .adapter
+            .inflight_submissions
+            .get_mut(&self.submission_id)
+        {
+            submission.position = Some(position);
+        }
+    }
 }
 
 impl<'a> Drop for InflightDropGuard<'a> {
@@ -991,6 +1086,9 @@ impl<'a> Drop for InflightDropGuard<'a> {
             .adapter
             .num_inflight_transactions
             .fetch_sub(1, Ordering::SeqCst);
+        self.adapter
+            .inflight_submissions
+            .remove(&self.submission_id);
         // Store the latest latency
         self.adapter
             .metrics
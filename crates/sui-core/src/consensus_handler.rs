@@ -44,6 +44,7 @@ use crate::{
     execution_cache::ExecutionCacheRead,
     scoring_decision::update_low_scoring_authorities,
     transaction_manager::TransactionManager,
+    transaction_tracing::TransactionTracer,
 };
 
 pub struct ConsensusHandlerInitializer {
@@ -99,6 +100,7 @@ impl ConsensusHandlerInitializer {
             committee,
             self.state.metrics.clone(),
             self.throughput_calculator.clone(),
+            *self.state.transaction_tracer(),
         )
     }
 }
@@ -126,6 +128,7 @@ pub struct ConsensusHandler<C> {
     transaction_scheduler: AsyncTransactionScheduler,
     /// Using the throughput calculator to record the current consensus throughput
     throughput_calculator: Arc<ConsensusThroughputCalculator>,
+    transaction_tracer: TransactionTracer,
 }
 
 const PROCESSED_CACHE_CAP: usize = 1024 * 1024;
@@ -140,6 +143,7 @@ impl<C> ConsensusHandler<C> {
         committee: Committee,
         metrics: Arc<AuthorityMetrics>,
         throughput_calculator: Arc<ConsensusThroughputCalculator>,
+        transaction_tracer: TransactionTracer,
     ) -> Self {
         // Recover last_consensus_stats so it is consistent across validators.
         let mut last_consensus_stats = epoch_store
@@ -162,6 +166,7 @@ impl<C> ConsensusHandler<C> {
             processed_cache: LruCache::new(NonZeroUsize::new(PROCESSED_CACHE_CAP).unwrap()),
             transaction_scheduler,
             throughput_calculator,
+            transaction_tracer,
         }
     }
 
@@ -337,6 +342,9 @@ impl<C: CheckpointServiceNotify + Send + Sync> ConsensusHandler<C> {
             .inc();
 
         let mut bytes = 0usize;
+        // Spans of the traced user transactions of the commit, closed once the transactions are
+        // handed over for execution.
+        let mut transaction_spans = vec![];
         {
             let span = trace_span!("process_consensus_certs");
             let _guard = span.enter();
@@ -351,13 +359,18 @@ impl<C: CheckpointServiceNotify + Send + Sync> ConsensusHandler<C> {
                         .consensus_handler_processed
                         .with_label_values(&[classify(&transaction)])
                         .inc();
-                    if matches!(
-                        &transaction.kind,
-                        ConsensusTransactionKind::UserTransaction(_)
-                    ) {
+                    if let ConsensusTransactionKind::UserTransaction(certificate) =
+                        &transaction.kind
+                    {
                         self.last_consensus_stats
                             .stats
                             .inc_num_user_transactions(authority_index as usize);
+                        let span = self
+                            .transaction_tracer
+                            .transaction_span("consensus_handler", certificate.digest());
+                        if !span.is_none() {
+                            transaction_spans.push(span);
+                        }
                     }
                     if let ConsensusTransactionKind::RandomnessStateUpdate(randomness_round, _) =
                         &transaction.kind
@@ -473,6 +486,7 @@ impl<C: CheckpointServiceNotify + Send + Sync> ConsensusHandler<C> {
         self.transaction_scheduler
             .schedule(transactions_to_schedule)
             .await;
+        drop(transaction_spans);
     }
 }
 
@@ -518,12 +532,16 @@ impl MysticetiConsensusHandler {
     pub fn new(
         mut consensus_handler: ConsensusHandler<CheckpointService>,
         mut receiver: tokio::sync::mpsc::UnboundedReceiver<consensus_core::CommittedSubDag>,
+        commit_consumer_monitor: Arc<consensus_core::CommitConsumerMonitor>,
     ) -> Self {
         let handle = spawn_monitored_task!(async move {
             while let Some(committed_subdag) = receiver.recv().await {
+                let commit_index = committed_subdag.commit_index;
                 consensus_handler
                     .handle_consensus_output_internal(committed_subdag)
                     .await;
+                // The commit is persisted, so consensus no longer needs to replay it.
+                commit_consumer_monitor.set_highest_handled_commit(commit_index);
             }
         });
         Self { handle }
@@ -607,6 +625,7 @@ pub(crate) fn classify(transaction: &ConsensusTransaction) -> &'static str {
         ConsensusTransactionKind::RandomnessStateUpdate(_, _) => "randomness_state_update",
         ConsensusTransactionKind::RandomnessDkgMessage(_, _) => "randomness_dkg_message",
         ConsensusTransactionKind::RandomnessDkgConfirmation(_, _) => "randomness_dkg_confirmation",
+        ConsensusTransactionKind::ExecutionTimeObservation(_) => "execution_time_observation",
     }
 }
 
@@ -873,6 +892,7 @@ mod tests {
             committee.clone(),
             metrics,
             Arc::new(throughput_calculator),
+            TransactionTracer::default(),
         );
 
         // AND
@@ -74,7 +74,8 @@ impl SuiTxValidator {
                 | ConsensusTransactionKind::NewJWKFetched(_, _, _)
                 | ConsensusTransactionKind::RandomnessStateUpdate(_, _)
                 | ConsensusTransactionKind::RandomnessDkgMessage(_, _)
-                | ConsensusTransactionKind::RandomnessDkgConfirmation(_, _) => {}
+                | ConsensusTransactionKind::RandomnessDkgConfirmation(_, _)
+                | ConsensusTransactionKind::ExecutionTimeObservation(_) => {}
             }
         }
 
@@ -6,7 +6,7 @@ use crate::authority::authority_store_pruner::{
 };
 use crate::authority::authority_store_tables::AuthorityPerpetualTables;
 use crate::checkpoints::CheckpointStore;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use bytes::Bytes;
 use futures::future::try_join_all;
 use object_store::path::Path;
@@ -25,12 +25,17 @@ use sui_storage::object_store::util::{
     path_to_filesystem, put, run_manifest_update_loop, write_snapshot_manifest,
 };
 use tracing::{debug, error, info};
+use typed_store::rocks::backup::BackupEngine;
 use typed_store::rocks::MetricConf;
 
 pub const SUCCESS_MARKER: &str = "_SUCCESS";
 pub const TEST_MARKER: &str = "_TEST";
 pub const UPLOAD_COMPLETED_MARKER: &str = "_UPLOAD_COMPLETED";
 pub const STATE_SNAPSHOT_COMPLETED_MARKER: &str = "_STATE_SNAPSHOT_COMPLETED";
+pub const BACKUP_COMPLETED_MARKER: &str = "_BACKUP_COMPLETED";
+/// Databases of a db checkpoint which are backed up, relative to the db checkpoint directory.
+/// Each is backed up under the same prefix in the backup object store.
+const BACKED_UP_DBS: &[&str] = &["checkpoints", "store/perpetual", "epochs", "indexes"];
 
 pub struct DBCheckpointMetrics {
     pub first_missing_db_checkpoint_epoch: IntGauge,
@@ -57,6 +62,10 @@ pub struct DBCheckpointHandler {
     input_root_path: PathBuf,
     /// Bucket on cloud object store where db checkpoints will be copied
     output_object_store: Option<Arc<DynObjectStore>>,
+    /// Bucket on cloud object store where db checkpoints will be incrementally backed up
+    backup_object_store: Option<Arc<DynObjectStore>>,
+    /// Number of incremental backups to keep, all are kept if None
+    num_backups_to_keep: Option<usize>,
     /// Time interval to check for presence of new db checkpoint
     interval: Duration,
     /// File markers which signal that local db checkpoint can be garbage collected
@@ -76,6 +85,8 @@ impl DBCheckpointHandler {
     pub fn new(
         input_path: &std::path::Path,
         output_object_store_config: Option<&ObjectStoreConfig>,
+        backup_object_store_config: Option<&ObjectStoreConfig>,
+        num_backups_to_keep: Option<usize>,
         interval_s: u64,
         prune_and_compact_before_upload: bool,
         indirect_objects_threshold: usize,
@@ -92,11 +103,17 @@ impl DBCheckpointHandler {
         if state_snapshot_enabled {
             gc_markers.push(STATE_SNAPSHOT_COMPLETED_MARKER.to_string());
         }
+        if backup_object_store_config.is_some() {
+            gc_markers.push(BACKUP_COMPLETED_MARKER.to_string());
+        }
         Ok(Arc::new(DBCheckpointHandler {
             input_object_store: input_store_config.make()?,
             input_root_path: input_path.to_path_buf(),
             output_object_store: output_object_store_config
                 .map(|config| config.make().expect("Failed to make object store")),
+            backup_object_store: backup_object_store_config
+                .map(|config| config.make().expect("Failed to make object store")),
+            num_backups_to_keep,
             interval: Duration::from_secs(interval_s),
             gc_markers,
             prune_and_compact_before_upload,
@@ -109,10 +126,15 @@ impl DBCheckpointHandler {
     pub fn new_for_test(
         input_object_store_config: &ObjectStoreConfig,
         output_object_store_config: Option<&ObjectStoreConfig>,
+        backup_object_store_config: Option<&ObjectStoreConfig>,
         interval_s: u64,
         prune_and_compact_before_upload: bool,
         state_snapshot_enabled: bool,
     ) -> Result<Arc<Self>> {
+        let mut gc_markers = vec![UPLOAD_COMPLETED_MARKER.to_string(), TEST_MARKER.to_string()];
+        if backup_object_store_config.is_some() {
+            gc_markers.push(BACKUP_COMPLETED_MARKER.to_string());
+        }
         Ok(Arc::new(DBCheckpointHandler {
             input_object_store: input_object_store_config.make()?,
             input_root_path: input_object_store_config
@@ -122,8 +144,11 @@ impl DBCheckpointHandler {
                 .clone(),
             output_object_store: output_object_store_config
                 .map(|config| config.make().expect("Failed to make object store")),
+            backup_object_store: backup_object_store_config
+                .map(|config| config.make().expect("Failed to make object store")),
+            num_backups_to_keep: None,
             interval: Duration::from_secs(interval_s),
-            gc_markers: vec![UPLOAD_COMPLETED_MARKER.to_string(), TEST_MARKER.to_string()],
+            gc_markers,
             prune_and_compact_before_upload,
             indirect_objects_threshold: 0,
             state_snapshot_enabled,
@@ -151,6 +176,12 @@ impl DBCheckpointHandler {
                 kill_sender.subscribe(),
             ));
         }
+        if self.backup_object_store.is_some() {
+            tokio::task::spawn(Self::run_db_backup_loop(
+                self.clone(),
+                kill_sender.subscribe(),
+            ));
+        }
         tokio::task::spawn(Self::run_db_checkpoint_gc_loop(
             self,
             kill_sender.subscribe(),
@@ -217,6 +248,24 @@ impl DBCheckpointHandler {
         }
         Ok(())
     }
+    async fn run_db_backup_loop(
+        self: Arc<Self>,
+        mut recv: tokio::sync::broadcast::Receiver<()>,
+    ) -> Result<()> {
+        let mut interval = tokio::time::interval(self.interval);
+        info!("DB backup loop started");
+        loop {
+            tokio::select! {
+                _now = interval.tick() => {
+                    if let Err(err) = self.backup_db_checkpoints().await {
+                        error!("Failed to back up db checkpoint with err: {:?}", err);
+                    }
+                },
+                 _ = recv.recv() => break,
+            }
+        }
+        Ok(())
+    }
     async fn run_db_checkpoint_gc_loop(
         self: Arc<Self>,
         mut recv: tokio::sync::broadcast::Receiver<()>,
@@ -239,7 +288,10 @@ impl DBCheckpointHandler {
     }
 
     async fn prune_and_compact(&self, db_path: PathBuf, epoch: u64) -> Result<()> {
-        let perpetual_db = Arc::new(AuthorityPerpetualTables::open(&db_path.join("store"), None));
+        let perpetual_db = Arc::new(AuthorityPerpetualTables::open(
+            &db_path.join("store"),
+            None,
+        )?);
         let checkpoint_store = Arc::new(CheckpointStore::open_tables_read_write(
             db_path.join("checkpoints"),
             MetricConf::new("db_checkpoint"),
@@ -329,6 +381,55 @@ impl DBCheckpointHandler {
         Ok(())
     }
 
+    /// Incrementally backs up the databases of the db checkpoints that were uploaded (or marked
+    /// as such) and not backed up yet, in epoch order. Waiting for the upload means that backups
+    /// see db checkpoints after they were pruned and compacted, and never while that happens.
+    async fn backup_db_checkpoints(&self) -> Result<()> {
+        let backup_object_store = self
+            .backup_object_store
+            .as_ref()
+            .expect("Expected backup object store to exist");
+        let local_checkpoints_by_epoch =
+            find_all_dirs_with_epoch_prefix(&self.input_object_store, None).await?;
+        let mut dirs: Vec<_> = local_checkpoints_by_epoch.iter().collect();
+        dirs.sort_by_key(|(epoch_num, _path)| *epoch_num);
+        for (epoch, db_path) in dirs {
+            let local_db_path = path_to_filesystem(self.input_root_path.clone(), db_path)?;
+            if local_db_path.join(BACKUP_COMPLETED_MARKER).exists() {
+                continue;
+            }
+            if !local_db_path.join(UPLOAD_COMPLETED_MARKER).exists() {
+                // Later db checkpoints must not be backed up before this one.
+                break;
+            }
+            for db in BACKED_UP_DBS {
+                let db_dir = local_db_path.join(db);
+                if !db_dir.exists() {
+                    continue;
+                }
+                let engine = BackupEngine::new(backup_object_store.clone(), db);
+                let stats = engine
+                    .backup_dir(&db_dir)
+                    .await
+                    .map_err(|err| anyhow!(err.to_string()))?;
+                info!(
+                    "Backed up {db} of db checkpoint for epoch {epoch} as backup {}: uploaded {} of {} files",
+                    stats.backup_id, stats.num_uploaded_files, stats.num_files
+                );
+                if let Some(num_backups_to_keep) = self.num_backups_to_keep {
+                    engine
+                        .purge_old_backups(num_backups_to_keep)
+                        .await
+                        .map_err(|err| anyhow!(err.to_string()))?;
+                }
+            }
+            let bytes = Bytes::from_static(b"success");
+            let backup_completed_marker = db_path.child(BACKUP_COMPLETED_MARKER);
+            put(&self.input_object_store, &backup_completed_marker, bytes).await?;
+        }
+        Ok(())
+    }
+
     async fn garbage_collect_old_db_checkpoints(&self) -> Result<Vec<u64>> {
         let local_checkpoints_by_epoch =
             find_all_dirs_with_epoch_prefix(&self.input_object_store, None).await?;
@@ -366,7 +467,8 @@ impl DBCheckpointHandler {
 #[cfg(test)]
 mod tests {
     use crate::db_checkpoint_handler::{
-        DBCheckpointHandler, SUCCESS_MARKER, TEST_MARKER, UPLOAD_COMPLETED_MARKER,
+        DBCheckpointHandler, BACKUP_COMPLETED_MARKER, SUCCESS_MARKER, TEST_MARKER,
+        UPLOAD_COMPLETED_MARKER,
     };
     use itertools::Itertools;
     use std::fs;
@@ -375,6 +477,9 @@ mod tests {
         find_all_dirs_with_epoch_prefix, find_missing_epochs_dirs, path_to_filesystem,
     };
     use tempfile::TempDir;
+    use typed_store::rocks::backup::BackupEngine;
+    use typed_store::rocks::{open_cf, DBMap, MetricConf, ReadWriteOptions};
+    use typed_store::Map;
 
     #[tokio::test]
     async fn test_basic() -> anyhow::Result<()> {
@@ -408,6 +513,7 @@ mod tests {
         let db_checkpoint_handler = DBCheckpointHandler::new_for_test(
             &input_store_config,
             Some(&output_store_config),
+            None,
             10,
             false,
             false,
@@ -479,6 +585,7 @@ mod tests {
         let db_checkpoint_handler = DBCheckpointHandler::new_for_test(
             &input_store_config,
             Some(&output_store_config),
+            None,
             10,
             false,
             false,
@@ -601,6 +708,7 @@ mod tests {
         let db_checkpoint_handler = DBCheckpointHandler::new_for_test(
             &input_store_config,
             Some(&output_store_config),
+            None,
             10,
             false,
             false,
@@ -666,6 +774,7 @@ mod tests {
         let db_checkpoint_handler = DBCheckpointHandler::new_for_test(
             &input_store_config,
             Some(&output_store_config),
+            None,
             10,
             false,
             false,
@@ -692,4 +801,90 @@ mod tests {
         assert_eq!(missing_epochs, expected_missing_epochs);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_backup() -> anyhow::Result<()> {
+        let checkpoint_dir = TempDir::new()?;
+        let checkpoint_dir_path = checkpoint_dir.path();
+        let backup_dir = TempDir::new()?;
+        let db_dir = TempDir::new()?;
+
+        // Take db checkpoints of a database for two epochs, adding data in between.
+        let db = open_cf(db_dir.path(), None, MetricConf::default(), &["table"])?;
+        let table = DBMap::<u64, u64>::reopen(&db, Some("table"), &ReadWriteOptions::default())?;
+        let local_checkpoints: Vec<_> = (0..2)
+            .map(|epoch| checkpoint_dir_path.join(format!("epoch_{epoch}")))
+            .collect();
+        for (epoch, local_checkpoint) in local_checkpoints.iter().enumerate() {
+            let epoch = epoch as u64;
+            table.multi_insert((epoch * 100..(epoch + 1) * 100).map(|i| (i, i)))?;
+            fs::create_dir(local_checkpoint)?;
+            db.checkpoint(&local_checkpoint.join("checkpoints"))?;
+        }
+
+        let input_store_config = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(checkpoint_dir_path.to_path_buf()),
+            ..Default::default()
+        };
+        let backup_store_config = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(backup_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let db_checkpoint_handler = DBCheckpointHandler::new_for_test(
+            &input_store_config,
+            None,
+            Some(&backup_store_config),
+            10,
+            false,
+            false,
+        )?;
+
+        // Db checkpoints are only backed up once uploaded, in epoch order.
+        fs::write(
+            local_checkpoints[1].join(UPLOAD_COMPLETED_MARKER),
+            b"success",
+        )?;
+        db_checkpoint_handler.backup_db_checkpoints().await?;
+        assert!(!local_checkpoints[0].join(BACKUP_COMPLETED_MARKER).exists());
+        assert!(!local_checkpoints[1].join(BACKUP_COMPLETED_MARKER).exists());
+
+        fs::write(
+            local_checkpoints[0].join(UPLOAD_COMPLETED_MARKER),
+            b"success",
+        )?;
+        db_checkpoint_handler.backup_db_checkpoints().await?;
+        assert!(local_checkpoints[0].join(BACKUP_COMPLETED_MARKER).exists());
+        assert!(local_checkpoints[1].join(BACKUP_COMPLETED_MARKER).exists());
+
+        // Db checkpoints are not garbage collected before they are backed up.
+        fs::write(local_checkpoints[0].join(TEST_MARKER), b"success")?;
+        fs::remove_file(local_checkpoints[1].join(BACKUP_COMPLETED_MARKER))?;
+        fs::write(local_checkpoints[1].join(TEST_MARKER), b"success")?;
+        assert_eq!(
+            db_checkpoint_handler
+                .garbage_collect_old_db_checkpoints()
+                .await?,
+            vec![0]
+        );
+
+        let engine = BackupEngine::new(backup_store_config.make()?, "checkpoints");
+        let backup_ids = engine
+            .list_backups()
+            .await
+            .map_err(|err| anyhow::anyhow!(err.to_string()))?;
+        assert_eq!(backup_ids, vec![0, 1]);
+        let restored_path = db_dir.path().join("restored");
+        engine
+            .restore(None, &restored_path)
+            .await
+            .map_err(|err| anyhow::anyhow!(err.to_string()))?;
+        let restored_db = open_cf(&restored_path, None, MetricConf::default(), &["table"])?;
+        let restored_table =
+            DBMap::<u64, u64>::reopen(&restored_db, Some("table"), &ReadWriteOptions::default())?;
+        assert_eq!(restored_table.get(&0)?, Some(0));
+        assert_eq!(restored_table.get(&199)?, Some(199));
+        Ok(())
+    }
 }
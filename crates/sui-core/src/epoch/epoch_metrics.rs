@@ -98,6 +98,10 @@ pub struct EpochMetrics {
     /// The amount of time taken to complete first phase of the random beacon DKG protocol,
     /// at which point the node has submitted a DKG Confirmation, for the most recent epoch.
     pub epoch_random_beacon_dkg_confirmation_time_ms: IntGauge,
+
+    /// Number of times a transaction was deferred in the epoch because a shared object it uses
+    /// was congested. A transaction deferred several times is counted each time.
+    pub epoch_shared_object_congestion_deferral_count: IntGauge,
 }
 
 impl EpochMetrics {
@@ -216,6 +220,12 @@ impl EpochMetrics {
                 registry
             )
             .unwrap(),
+            epoch_shared_object_congestion_deferral_count: register_int_gauge_with_registry!(
+                "epoch_shared_object_congestion_deferral_count",
+                "Number of times a transaction was deferred in the epoch due to shared object congestion",
+                registry
+            )
+            .unwrap(),
         };
         Arc::new(this)
     }
@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use fastcrypto::hash::MultisetHash;
 use prometheus::default_registry;
 use rand::{rngs::StdRng, SeedableRng};
 use std::{
@@ -9,9 +10,10 @@ use std::{
     path::PathBuf,
     sync::atomic::Ordering,
     sync::{atomic::AtomicU32, Arc},
+    time::Duration,
 };
 use sui_framework::BuiltInFramework;
-use sui_macros::{register_fail_point_async, sim_test};
+use sui_macros::{clear_fail_point, register_fail_point_async, sim_test};
 use sui_test_transaction_builder::TestTransactionBuilder;
 use sui_types::{
     base_types::{random_object_ref, SuiAddress},
@@ -23,25 +25,28 @@ use sui_types::{
     effects::{TestEffectsBuilder, TransactionEffectsAPI},
     event::Event,
 };
-use tempfile::tempdir;
+use tempfile::TempDir;
+use typed_store::rocks::DBMap;
 
 use super::*;
 use crate::{
     authority::{authority_store_tables::AuthorityPerpetualTables, AuthorityStore},
     execution_cache::ExecutionCacheAPI,
+    state_accumulator::StateAccumulator,
     test_utils::init_state_parameters_from_rng,
 };
 
 async fn init_authority_store() -> Arc<AuthorityStore> {
+    open_authority_store(Arc::new(AuthorityPerpetualTables::open_in_memory())).await
+}
+
+async fn open_authority_store(
+    perpetual_tables: Arc<AuthorityPerpetualTables>,
+) -> Arc<AuthorityStore> {
     let seed = [1u8; 32];
     let (genesis, _) = init_state_parameters_from_rng(&mut StdRng::from_seed(seed));
     let committee = genesis.committee().unwrap();
 
-    // Create a random directory to store the DB
-    let dir = tempdir().unwrap();
-    let db_path = dir.path();
-
-    let perpetual_tables = Arc::new(AuthorityPerpetualTables::open(db_path, None));
     AuthorityStore::open_with_committee_for_testing(perpetual_tables, &committee, &genesis, 0)
         .await
         .unwrap()
@@ -962,3 +967,220 @@ async fn test_concurrent_readers() {
     t1.await.unwrap();
     t2.await.unwrap();
 }
+
+// Stages of commit_transaction_outputs at which crashes are injected, with the number of times
+// each fail point is hit per commit. The "crash" fail point is hit right before and right after
+// AuthorityStore writes the outputs to the db.
+const COMMIT_CRASH_POINTS: &[(&str, u32)] = &[
+    ("writeback-cache-commit", 1),
+    ("crash", 2),
+    ("writeback-cache-commit-after-db-write", 1),
+    ("writeback-cache-commit-after-pending-remove", 1),
+    ("writeback-cache-commit-before-objects-move", 1),
+    ("writeback-cache-commit-after-cache-update", 1),
+];
+
+// Builds the transaction outputs of a few checkpoints that create, mutate, delete, wrap
+// and receive objects, and emit events.
+async fn build_crash_test_workload() -> Vec<Vec<Arc<TransactionOutputs>>> {
+    let mut s = Scenario::new(None, Arc::new(AtomicU32::new(0))).await;
+    let mut checkpoints = Vec::new();
+
+    s.with_created(&[1, 2, 3]);
+    s.with_events();
+    let tx1 = s.take_outputs();
+    s.with_child(4, 1);
+    let tx2 = s.take_outputs();
+    s.with_packages(&[5]);
+    let tx3 = s.take_outputs();
+    checkpoints.push(vec![tx1, tx2, tx3]);
+
+    s.with_mutated(&[1, 2]);
+    s.with_events();
+    let tx1 = s.take_outputs();
+    s.with_deleted(&[3]);
+    let tx2 = s.take_outputs();
+    checkpoints.push(vec![tx1, tx2]);
+
+    s.with_mutated(&[1]);
+    s.with_received(&[1]);
+    let tx1 = s.take_outputs();
+    s.with_wrapped(&[2]);
+    let tx2 = s.take_outputs();
+    s.with_created(&[6, 7]);
+    s.with_events();
+    let tx3 = s.take_outputs();
+    s.with_mutated(&[6]);
+    s.with_deleted(&[7]);
+    let tx4 = s.take_outputs();
+    checkpoints.push(vec![tx1, tx2, tx3, tx4]);
+
+    checkpoints
+}
+
+// Executes and commits a checkpoint the way CheckpointExecutor does: transactions that are
+// already executed are skipped, and then all transactions of the checkpoint are committed.
+async fn execute_checkpoint(cache: Arc<WritebackCache>, checkpoint: Vec<Arc<TransactionOutputs>>) {
+    let cache: &dyn ExecutionCacheAPI = &*cache;
+    let digests: Vec<_> = checkpoint
+        .iter()
+        .map(|outputs| *outputs.transaction.digest())
+        .collect();
+    let executed = cache.multi_get_executed_effects_digests(&digests).unwrap();
+    for (outputs, executed) in checkpoint.into_iter().zip(executed) {
+        if executed.is_none() {
+            cache.write_transaction_outputs(1, outputs).await.unwrap();
+        }
+    }
+    for digest in &digests {
+        cache.commit_transaction_outputs(1, digest).await.unwrap();
+    }
+}
+
+// Runs the workload to completion against a store on disk. If `crash` is set, the node
+// crashes on the given hit of the given fail point: the commit never makes progress past it,
+// all in-memory state is lost, the store is reopened from disk, and execution restarts from
+// the last fully committed checkpoint.
+async fn run_crash_test_workload(
+    workload: &[Vec<Arc<TransactionOutputs>>],
+    crash: Option<(&'static str, u32)>,
+) -> (TempDir, Arc<AuthorityStore>) {
+    let crashed = Arc::new(tokio::sync::Notify::new());
+    if let Some((fail_point, crash_at)) = crash {
+        let hits = AtomicU32::new(0);
+        let crashed = crashed.clone();
+        register_fail_point_async(fail_point, move || {
+            let crash = hits.fetch_add(1, Ordering::Relaxed) + 1 == crash_at;
+            if crash {
+                crashed.notify_one();
+            }
+            async move {
+                if crash {
+                    std::future::pending::<()>().await;
+                }
+            }
+        });
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = open_authority_store(Arc::new(
+        AuthorityPerpetualTables::open(dir.path(), None).unwrap(),
+    ))
+    .await;
+    let mut cache = Arc::new(WritebackCache::new_for_tests(
+        store.clone(),
+        &prometheus::Registry::new(),
+    ));
+    let mut next_checkpoint = 0;
+    let mut restarts = 0;
+    while next_checkpoint < workload.len() {
+        let executed = tokio::select! {
+            _ = execute_checkpoint(cache.clone(), workload[next_checkpoint].clone()) => true,
+            _ = crashed.notified() => false,
+        };
+        if executed {
+            next_checkpoint += 1;
+            continue;
+        }
+
+        restarts += 1;
+        let db = Arc::downgrade(&store.perpetual_tables.objects.rocksdb);
+        drop(cache);
+        drop(store);
+        // The db is closed once the metrics tasks of its tables observe that they were dropped.
+        while db.strong_count() > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        store = open_authority_store(Arc::new(
+            AuthorityPerpetualTables::open(dir.path(), None).unwrap(),
+        ))
+        .await;
+        cache = Arc::new(WritebackCache::new_for_tests(
+            store.clone(),
+            &prometheus::Registry::new(),
+        ));
+    }
+
+    if let Some((fail_point, _)) = crash {
+        clear_fail_point(fail_point);
+        assert_eq!(restarts, 1, "node must crash exactly once");
+    }
+    (dir, store)
+}
+
+// Returns the raw contents of a table.
+fn table_bytes<K, V>(table: &DBMap<K, V>) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut iter = table
+        .rocksdb
+        .raw_iterator_cf(&table.cf(), table.opts.readopts());
+    iter.seek_to_first();
+    let mut contents = Vec::new();
+    while iter.valid() {
+        contents.push((iter.key().unwrap().to_vec(), iter.value().unwrap().to_vec()));
+        iter.next();
+    }
+    iter.status().unwrap();
+    contents
+}
+
+fn assert_same_perpetual_tables(expected: &AuthorityStore, actual: &AuthorityStore) {
+    let (expected, actual) = (&expected.perpetual_tables, &actual.perpetual_tables);
+    assert_eq!(table_bytes(&expected.objects), table_bytes(&actual.objects));
+    assert_eq!(
+        table_bytes(&expected.indirect_move_objects),
+        table_bytes(&actual.indirect_move_objects)
+    );
+    assert_eq!(
+        table_bytes(&expected.live_owned_object_markers),
+        table_bytes(&actual.live_owned_object_markers)
+    );
+    assert_eq!(
+        table_bytes(&expected.transactions),
+        table_bytes(&actual.transactions)
+    );
+    assert_eq!(table_bytes(&expected.effects), table_bytes(&actual.effects));
+    assert_eq!(
+        table_bytes(&expected.executed_effects),
+        table_bytes(&actual.executed_effects)
+    );
+    assert_eq!(table_bytes(&expected.events), table_bytes(&actual.events));
+    assert_eq!(
+        table_bytes(&expected.object_per_epoch_marker_table),
+        table_bytes(&actual.object_per_epoch_marker_table)
+    );
+}
+
+fn live_object_set_accumulator(store: &AuthorityStore) -> Accumulator {
+    let mut acc = Accumulator::default();
+    store
+        .iter_live_object_set(true)
+        .for_each(|object| StateAccumulator::accumulate_live_object(&mut acc, &object));
+    acc
+}
+
+#[sim_test]
+async fn test_crash_recovery_during_commit() {
+    telemetry_subscribers::init_for_testing();
+
+    let workload = build_crash_test_workload().await;
+    let num_commits = workload
+        .iter()
+        .map(|checkpoint| checkpoint.len())
+        .sum::<usize>() as u32;
+
+    let (_expected_dir, expected) = run_crash_test_workload(&workload, None).await;
+    let expected_accumulator = live_object_set_accumulator(&expected);
+
+    for (fail_point, hits_per_commit) in COMMIT_CRASH_POINTS {
+        for crash_at in 1..=num_commits * hits_per_commit {
+            println!("crashing at {} on hit {}", fail_point, crash_at);
+            let (_recovered_dir, recovered) =
+                run_crash_test_workload(&workload, Some((*fail_point, crash_at))).await;
+            assert_same_perpetual_tables(&expected, &recovered);
+            assert_eq!(
+                live_object_set_accumulator(&recovered).digest(),
+                expected_accumulator.digest()
+            );
+        }
+    }
+}
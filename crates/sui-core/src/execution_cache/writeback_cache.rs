@@ -617,6 +617,15 @@ impl WritebackCache {
 
         let DashMapEntry::Occupied(occupied) = self.dirty.pending_transaction_writes.entry(digest)
         else {
+            // If we crashed after the outputs of this transaction were written to the db, but
+            // before the checkpoint containing it was marked as executed, the checkpoint is
+            // re-executed after restart. The transaction is then found to be executed already
+            // and never re-enters the dirty set, but it is committed again with the rest of the
+            // checkpoint.
+            if self.store.is_tx_already_executed(&digest)? {
+                debug!(?digest, "transaction outputs were already committed");
+                return Ok(());
+            }
             panic!("Attempt to commit unknown transaction {:?}", digest);
         };
 
@@ -629,6 +638,8 @@ impl WritebackCache {
             .write_transaction_outputs(epoch, outputs.clone())
             .await?;
 
+        fail_point_async!("writeback-cache-commit-after-db-write");
+
         // Cache transaction before removing entry from self.dirty to avoid
         // unnecessary cache misses
         self.cached
@@ -638,6 +649,8 @@ impl WritebackCache {
         // releases lock on pending_transaction_writes
         occupied.remove();
 
+        fail_point_async!("writeback-cache-commit-after-pending-remove");
+
         // Now, remove each piece of committed data from the dirty state and insert it into the cache.
         // TODO: outputs should have a strong count of 1 so we should be able to move out of it
         let TransactionOutputs {
@@ -688,6 +701,8 @@ impl WritebackCache {
             .remove(&tx_digest)
             .expect("executed effects must exist");
 
+        fail_point_async!("writeback-cache-commit-before-objects-move");
+
         // Move dirty markers to cache
         for (object_key, marker_value) in markers.iter() {
             Self::move_version_from_dirty_to_cache(
@@ -729,6 +744,8 @@ impl WritebackCache {
             );
         }
 
+        fail_point_async!("writeback-cache-commit-after-cache-update");
+
         Ok(())
     }
 
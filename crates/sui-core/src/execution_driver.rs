@@ -3,7 +3,7 @@
 
 use std::{
     sync::{Arc, Weak},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use mysten_metrics::{monitored_scope, spawn_monitored_task};
@@ -12,6 +12,7 @@ use rand::{
     Rng, SeedableRng,
 };
 use sui_macros::fail_point_async;
+use sui_protocol_config::PerObjectCongestionControlMode;
 use tokio::{
     sync::{mpsc::UnboundedReceiver, oneshot, Semaphore},
     time::sleep,
@@ -106,6 +107,11 @@ pub async fn execution_process(
 
         authority.metrics.execution_rate_tracker.lock().record();
 
+        let span = error_span!("execution_driver", tx_digest = ?digest);
+        authority
+            .transaction_tracer()
+            .trace_transaction(&span, &digest);
+
         // Certificate execution can take significant time, so run it in a separate task.
         spawn_monitored_task!(async move {
             let _scope = monitored_scope("ExecutionDriver::task");
@@ -113,13 +119,29 @@ pub async fn execution_process(
             if let Ok(true) = authority.is_tx_already_executed(&digest) {
                 return;
             }
+            // Execution times of transactions on shared objects feed the per-object estimates used
+            // by congestion control.
+            let observe_execution_time = certificate.contains_shared_object()
+                && matches!(
+                    epoch_store
+                        .protocol_config()
+                        .per_object_congestion_control_mode(),
+                    PerObjectCongestionControlMode::ExecutionTimeEstimate(_)
+                );
             let mut attempts = 0;
             loop {
                 fail_point_async!("transaction_execution_delay");
                 attempts += 1;
+                let execution_start_time = Instant::now();
                 let res = authority
                     .try_execute_immediately(&certificate, expected_effects_digest, &epoch_store)
                     .await;
+                if observe_execution_time && res.is_ok() {
+                    authority.execution_time_observer().record_local_observation(
+                        certificate.shared_input_objects().map(|obj| obj.id),
+                        execution_start_time.elapsed(),
+                    );
+                }
                 if let Err(e) = res {
                     if attempts == EXECUTION_MAX_ATTEMPTS {
                         panic!("Failed to execute certified transaction {digest:?} after {attempts} attempts! error={e} certificate={certificate:?}");
@@ -136,6 +158,6 @@ pub async fn execution_process(
                 .metrics
                 .execution_driver_executed_transactions
                 .inc();
-        }.instrument(error_span!("execution_driver", tx_digest = ?digest)));
+        }.instrument(span));
     }
 }
@@ -38,6 +38,7 @@ mod transaction_input_loader;
 mod transaction_manager;
 pub mod transaction_orchestrator;
 mod transaction_outputs;
+pub mod transaction_tracing;
 pub mod verify_indexes;
 
 #[cfg(test)]
@@ -2,13 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::authority::AuthorityState;
+use parking_lot::Mutex;
+use serde::Serialize;
 use std::cmp::{max, min};
+use std::collections::HashMap;
 use std::hash::Hasher;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Weak;
 use std::time::Duration;
 use std::time::{SystemTime, UNIX_EPOCH};
 use sui_config::node::AuthorityOverloadConfig;
+use sui_types::base_types::SuiAddress;
 use sui_types::digests::TransactionDigest;
 use sui_types::error::SuiError;
 use sui_types::error::SuiResult;
@@ -28,6 +32,28 @@ pub struct AuthorityOverloadInfo {
 
     /// The calculated percentage of transactions to drop.
     pub load_shedding_percentage: AtomicU32,
+
+    /// Per sender accounting, used to shed load from the heaviest submitters first.
+    sender_load: Mutex<SenderLoad>,
+}
+
+#[derive(Default)]
+struct SenderLoad {
+    /// Transactions submitted by each sender in the current overload monitor interval, while the
+    /// authority is overloaded.
+    submitted: HashMap<SuiAddress, u64>,
+    /// Transactions shed from each sender in the current overload monitor interval.
+    shed: HashMap<SuiAddress, u64>,
+    /// The percentage of transactions to drop from each sender, calculated from the submissions
+    /// in the previous overload monitor interval.
+    shedding_percentages: HashMap<SuiAddress, u32>,
+}
+
+/// A point-in-time copy of `AuthorityOverloadInfo`.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct AuthorityOverloadSnapshot {
+    pub is_overload: bool,
+    pub load_shedding_percentage: u32,
 }
 
 impl AuthorityOverloadInfo {
@@ -41,6 +67,69 @@ impl AuthorityOverloadInfo {
         self.is_overload.store(false, Ordering::Relaxed);
         self.load_shedding_percentage.store(0, Ordering::Relaxed);
     }
+
+    /// Records a transaction submitted by `sender`, for per sender load shedding.
+    pub fn record_submission(&self, sender: SuiAddress, max_tracked_senders: usize) {
+        let mut sender_load = self.sender_load.lock();
+        let tracked_senders = sender_load.submitted.len();
+        if let Some(count) = sender_load.submitted.get_mut(&sender) {
+            *count += 1;
+        } else if tracked_senders < max_tracked_senders {
+            sender_load.submitted.insert(sender, 1);
+        }
+    }
+
+    /// Records a transaction from `sender` rejected by load shedding.
+    pub fn record_shed(&self, sender: SuiAddress) {
+        *self.sender_load.lock().shed.entry(sender).or_default() += 1;
+    }
+
+    /// Returns the percentage of transactions to drop from `sender`. Senders without a
+    /// percentage, because they did not submit transactions in the previous interval or were not
+    /// tracked, are shed at the global `load_shedding_percentage`.
+    pub fn sender_load_shedding_percentage(
+        &self,
+        sender: &SuiAddress,
+        load_shedding_percentage: u32,
+    ) -> u32 {
+        self.sender_load
+            .lock()
+            .shedding_percentages
+            .get(sender)
+            .copied()
+            .unwrap_or(load_shedding_percentage)
+    }
+
+    /// Starts a new overload monitor interval: calculates the percentage of transactions to drop
+    /// from each sender so that `load_shedding_percentage` of all transactions submitted in the
+    /// interval that just ended would have been shed, heaviest senders first. Returns the number
+    /// of transactions shed from the top `top_shed_senders` senders in the interval that ended.
+    pub fn update_sender_load_shedding(
+        &self,
+        load_shedding_percentage: u32,
+        top_shed_senders: usize,
+    ) -> Vec<(SuiAddress, u64)> {
+        let mut sender_load = self.sender_load.lock();
+        let submitted = std::mem::take(&mut sender_load.submitted);
+        let shed = std::mem::take(&mut sender_load.shed);
+
+        sender_load.shedding_percentages =
+            calculate_sender_load_shedding_percentages(&submitted, load_shedding_percentage);
+
+        let mut shed: Vec<_> = shed.into_iter().collect();
+        shed.sort_by(|(sender_a, count_a), (sender_b, count_b)| {
+            count_b.cmp(count_a).then(sender_a.cmp(sender_b))
+        });
+        shed.truncate(top_shed_senders);
+        shed
+    }
+
+    pub fn snapshot(&self) -> AuthorityOverloadSnapshot {
+        AuthorityOverloadSnapshot {
+            is_overload: self.is_overload.load(Ordering::Relaxed),
+            load_shedding_percentage: self.load_shedding_percentage.load(Ordering::Relaxed),
+        }
+    }
 }
 
 const STEADY_OVERLOAD_REDUCTION_PERCENTAGE: u32 = 10;
@@ -115,6 +204,20 @@ fn check_authority_overload(
         authority.overload_info.clear_overload();
     }
 
+    if config.per_sender_load_shedding {
+        let top_shed_senders = authority.overload_info.update_sender_load_shedding(
+            load_shedding_percentage,
+            config.top_shed_senders_to_report,
+        );
+        let metric = &authority.metrics.authority_load_shedding_top_shed_senders;
+        metric.reset();
+        for (sender, shed) in top_shed_senders {
+            metric
+                .with_label_values(&[&sender.to_string()])
+                .set(shed as i64);
+        }
+    }
+
     authority
         .metrics
         .authority_overload_status
@@ -151,6 +254,58 @@ fn calculate_load_shedding_percentage(txn_ready_rate: f64, execution_rate: f64)
         .round() as u32
 }
 
+// Calculates the percentage of transactions to drop from each sender, given the number of
+// transactions each sender submitted, so that `load_shedding_percentage` of all transactions are
+// dropped. Transactions are dropped from the heaviest senders first: every sender is allowed up
+// to the same number of transactions (the largest that fits in the budget of accepted
+// transactions), and only senders above that number are shed. Returns an empty map when there is
+// no load to shed.
+fn calculate_sender_load_shedding_percentages(
+    submitted: &HashMap<SuiAddress, u64>,
+    load_shedding_percentage: u32,
+) -> HashMap<SuiAddress, u32> {
+    let load_shedding_percentage = min(load_shedding_percentage, 100) as u64;
+    if load_shedding_percentage == 0 || submitted.is_empty() {
+        return HashMap::new();
+    }
+
+    let total: u64 = submitted.values().sum();
+    let mut remaining_budget = (total * (100 - load_shedding_percentage)) as f64 / 100.0;
+    let mut counts: Vec<u64> = submitted.values().copied().collect();
+    counts.sort_unstable();
+
+    // Senders submitting less than their share of the remaining budget are not shed, and leave
+    // the rest of their share to heavier senders.
+    let mut allowed_per_sender = f64::INFINITY;
+    let mut remaining_senders = counts.len();
+    for count in counts {
+        let share = remaining_budget / remaining_senders as f64;
+        if (count as f64) <= share {
+            remaining_budget -= count as f64;
+            remaining_senders -= 1;
+        } else {
+            allowed_per_sender = share;
+            break;
+        }
+    }
+
+    submitted
+        .iter()
+        .map(|(sender, count)| {
+            let count = *count as f64;
+            let percentage = if count > allowed_per_sender {
+                min(
+                    ((1.0 - allowed_per_sender / count) * 100.0).round() as u32,
+                    100,
+                )
+            } else {
+                0
+            };
+            (*sender, percentage)
+        })
+        .collect()
+}
+
 // Given overload signals (`queueing_latency`, `txn_ready_rate`, `execution_rate`), return whether
 // the authority server should enter load shedding mode, and how much percentage of transactions to drop.
 // Note that the final load shedding percentage should also take the current load shedding percentage
@@ -323,6 +478,86 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_calculate_sender_load_shedding_percentages() {
+        let heavy = SuiAddress::random_for_testing_only();
+        let light_1 = SuiAddress::random_for_testing_only();
+        let light_2 = SuiAddress::random_for_testing_only();
+
+        // No shedding.
+        let submitted = HashMap::from([(heavy, 80), (light_1, 10), (light_2, 10)]);
+        assert!(calculate_sender_load_shedding_percentages(&submitted, 0).is_empty());
+        assert!(calculate_sender_load_shedding_percentages(&HashMap::new(), 50).is_empty());
+
+        // Only the heavy sender is shed, enough to drop 50% of all transactions.
+        assert_eq!(
+            calculate_sender_load_shedding_percentages(&submitted, 50),
+            HashMap::from([(heavy, 63), (light_1, 0), (light_2, 0)])
+        );
+
+        // When all senders are shed, the heavier senders are shed more.
+        let submitted = HashMap::from([(heavy, 80), (light_1, 15), (light_2, 5)]);
+        assert_eq!(
+            calculate_sender_load_shedding_percentages(&submitted, 85),
+            HashMap::from([(heavy, 94), (light_1, 67), (light_2, 0)])
+        );
+
+        // Senders submitting the same number of transactions are shed equally.
+        let submitted = HashMap::from([(light_1, 10), (light_2, 10)]);
+        assert_eq!(
+            calculate_sender_load_shedding_percentages(&submitted, 30),
+            HashMap::from([(light_1, 30), (light_2, 30)])
+        );
+        assert_eq!(
+            calculate_sender_load_shedding_percentages(&submitted, 100),
+            HashMap::from([(light_1, 100), (light_2, 100)])
+        );
+    }
+
+    #[test]
+    pub fn test_per_sender_load_shedding() {
+        let overload_info = AuthorityOverloadInfo::default();
+        let heavy = SuiAddress::random_for_testing_only();
+        let light = SuiAddress::random_for_testing_only();
+        let untracked = SuiAddress::random_for_testing_only();
+
+        for _ in 0..90 {
+            overload_info.record_submission(heavy, 2);
+        }
+        for _ in 0..10 {
+            overload_info.record_submission(light, 2);
+        }
+        // Only 2 senders are tracked.
+        overload_info.record_submission(untracked, 2);
+        overload_info.record_shed(heavy);
+
+        let top_shed_senders = overload_info.update_sender_load_shedding(50, 10);
+        assert_eq!(top_shed_senders, vec![(heavy, 1)]);
+
+        // Percentages are calculated from the submissions in the previous interval.
+        assert_eq!(
+            overload_info.sender_load_shedding_percentage(&heavy, 50),
+            56
+        );
+        assert_eq!(overload_info.sender_load_shedding_percentage(&light, 50), 0);
+
+        // Senders that were not tracked use the global percentage, including once they are
+        // tracked in the current interval.
+        assert_eq!(
+            overload_info.sender_load_shedding_percentage(&untracked, 50),
+            50
+        );
+        overload_info.record_submission(untracked, 2);
+        assert_eq!(
+            overload_info.sender_load_shedding_percentage(&untracked, 50),
+            50
+        );
+
+        // No shedding once the authority is no longer overloaded.
+        assert!(overload_info.update_sender_load_shedding(0, 10).is_empty());
+        assert_eq!(overload_info.sender_load_shedding_percentage(&heavy, 0), 0);
+    }
+
     #[test]
     pub fn test_calculate_load_shedding_ratio() {
         assert_eq!(calculate_load_shedding_percentage(95.0, 100.1), 0);
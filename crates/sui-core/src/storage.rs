@@ -279,7 +279,10 @@ impl WriteStore for RocksDbStore {
         }
 
         self.checkpoint_store
-            .insert_verified_checkpoint(checkpoint)
+            .insert_verified_checkpoint_with_local_effects(
+                checkpoint,
+                self.execution_cache.as_ref(),
+            )
             .map_err(Into::into)
     }
 
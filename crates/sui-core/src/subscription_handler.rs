@@ -7,7 +7,9 @@ use prometheus::{
     register_int_counter_vec_with_registry, register_int_gauge_vec_with_registry, IntCounterVec,
     IntGaugeVec, Registry,
 };
-use tokio_stream::Stream;
+use sui_config::node::EventSubscriptionConfig;
+use tokio::sync::Semaphore;
+use tokio_stream::{Stream, StreamExt};
 use tracing::{error, instrument, trace};
 
 use crate::streamer::Streamer;
@@ -16,7 +18,8 @@ use sui_json_rpc_types::{
     TransactionFilter,
 };
 use sui_json_rpc_types::{SuiEvent, SuiTransactionBlockEffectsAPI};
-use sui_types::error::SuiResult;
+use sui_types::error::{SuiError, SuiResult, UserInputError};
+use sui_types::fp_ensure;
 use sui_types::transaction::TransactionData;
 
 #[cfg(test)]
@@ -62,14 +65,21 @@ impl SubscriptionMetrics {
 pub struct SubscriptionHandler {
     event_streamer: Streamer<SuiEvent, SuiEvent, EventFilter>,
     transaction_streamer: Streamer<EffectsWithInput, SuiTransactionBlockEffects, TransactionFilter>,
+    event_subscription_config: EventSubscriptionConfig,
+    // Event subscriptions with field predicates hold a permit for as long as they are active.
+    field_predicate_subscriptions: Arc<Semaphore>,
 }
 
 impl SubscriptionHandler {
-    pub fn new(registry: &Registry) -> Self {
+    pub fn new(registry: &Registry, event_subscription_config: EventSubscriptionConfig) -> Self {
         let metrics = Arc::new(SubscriptionMetrics::new(registry));
         Self {
             event_streamer: Streamer::spawn(EVENT_DISPATCH_BUFFER_SIZE, metrics.clone(), "event"),
             transaction_streamer: Streamer::spawn(EVENT_DISPATCH_BUFFER_SIZE, metrics, "tx"),
+            field_predicate_subscriptions: Arc::new(Semaphore::new(
+                event_subscription_config.max_field_predicate_subscriptions,
+            )),
+            event_subscription_config,
         }
     }
 }
@@ -108,8 +118,43 @@ impl SubscriptionHandler {
         Ok(())
     }
 
-    pub fn subscribe_events(&self, filter: EventFilter) -> impl Stream<Item = SuiEvent> {
-        self.event_streamer.subscribe(filter)
+    /// Subscribes to the events matching `filter`. Filters with field predicates are rejected if
+    /// they are too costly, or if too many such subscriptions are active. Other filters are always
+    /// accepted, as they were before field predicates were introduced.
+    pub fn subscribe_events(&self, filter: EventFilter) -> SuiResult<impl Stream<Item = SuiEvent>> {
+        let permit = if filter.has_field_predicates() {
+            let max_filter_cost = self.event_subscription_config.max_filter_cost;
+            fp_ensure!(
+                filter.cost() <= max_filter_cost,
+                SuiError::UserInputError {
+                    error: UserInputError::SizeLimitExceeded {
+                        limit: "maximum event filter cost".to_string(),
+                        value: max_filter_cost.to_string(),
+                    },
+                }
+            );
+            let permit = self
+                .field_predicate_subscriptions
+                .clone()
+                .try_acquire_owned()
+                .map_err(|_| SuiError::UserInputError {
+                    error: UserInputError::SizeLimitExceeded {
+                        limit: "maximum event subscriptions with field predicates".to_string(),
+                        value: self
+                            .event_subscription_config
+                            .max_field_predicate_subscriptions
+                            .to_string(),
+                    },
+                })?;
+            Some(permit)
+        } else {
+            None
+        };
+        // The permit is released when the subscriber drops the stream.
+        Ok(self.event_streamer.subscribe(filter).map(move |event| {
+            let _permit = &permit;
+            event
+        }))
     }
 
     pub fn subscribe_transactions(
@@ -184,46 +184,77 @@ impl TransactionInputLoader {
         input_object_kinds: &[InputObjectKind],
         epoch_id: EpochId,
     ) -> SuiResult<InputObjects> {
-        let shared_locks_cell: OnceCell<HashMap<_, _>> = OnceCell::new();
+        let mut results = self
+            .read_objects_for_batch_execution(
+                shared_lock_store,
+                &[(*tx_key, input_object_kinds)],
+                epoch_id,
+            )
+            .await?;
+        Ok(results
+            .pop()
+            .expect("must return one result per transaction"))
+    }
 
-        let mut results = vec![None; input_object_kinds.len()];
-        let mut object_keys = Vec::with_capacity(input_object_kinds.len());
-        let mut fetches = Vec::with_capacity(input_object_kinds.len());
+    /// Read the inputs for a batch of transactions that are ready to be executed, with a single
+    /// bulk read of all owned and shared input objects.
+    ///
+    /// The same requirements as for `read_objects_for_execution` apply to every transaction of the
+    /// batch. Used to execute checkpoints while catching up, where transactions bypass the
+    /// TransactionManager.
+    #[instrument(level = "trace", skip_all)]
+    pub async fn read_objects_for_batch_execution(
+        &self,
+        shared_lock_store: &impl GetSharedLocks,
+        transactions: &[(TransactionKey, &[InputObjectKind])],
+        epoch_id: EpochId,
+    ) -> SuiResult<Vec<InputObjects>> {
+        let mut results: Vec<Vec<Option<ObjectReadResult>>> = transactions
+            .iter()
+            .map(|(_, input_object_kinds)| vec![None; input_object_kinds.len()])
+            .collect();
+        let mut object_keys = Vec::new();
+        // (transaction index, input index) of each fetched object key.
+        let mut fetches = Vec::new();
 
-        for (i, input) in input_object_kinds.iter().enumerate() {
-            match input {
-                InputObjectKind::MovePackage(id) => {
-                    let package = self.cache.get_package_object(id)?.unwrap_or_else(|| {
-                        panic!("Executable transaction {tx_key:?} depends on non-existent package {id:?}")
-                    });
+        for (tx_index, (tx_key, input_object_kinds)) in transactions.iter().enumerate() {
+            let shared_locks_cell: OnceCell<HashMap<_, _>> = OnceCell::new();
 
-                    results[i] = Some(ObjectReadResult {
-                        input_object_kind: *input,
-                        object: ObjectReadResultKind::Object(package.into()),
-                    });
-                    continue;
-                }
-                InputObjectKind::ImmOrOwnedMoveObject(objref) => {
-                    object_keys.push(objref.into());
-                    fetches.push((i, input));
-                }
-                InputObjectKind::SharedMoveObject { id, .. } => {
-                    let shared_locks = shared_locks_cell.get_or_try_init(|| {
-                        Ok::<HashMap<ObjectID, SequenceNumber>, SuiError>(
-                            shared_lock_store
-                                .get_shared_locks(tx_key)?
-                                .into_iter()
-                                .collect(),
-                        )
-                    })?;
-                    // If we can't find the locked version, it means
-                    // 1. either we have a bug that skips shared object version assignment
-                    // 2. or we have some DB corruption
-                    let version = shared_locks.get(id).unwrap_or_else(|| {
-                        panic!("Shared object locks should have been set. key: {tx_key:?}, obj id: {id:?}")
-                    });
-                    object_keys.push(ObjectKey(*id, *version));
-                    fetches.push((i, input));
+            for (i, input) in input_object_kinds.iter().enumerate() {
+                match input {
+                    InputObjectKind::MovePackage(id) => {
+                        let package = self.cache.get_package_object(id)?.unwrap_or_else(|| {
+                            panic!("Executable transaction {tx_key:?} depends on non-existent package {id:?}")
+                        });
+
+                        results[tx_index][i] = Some(ObjectReadResult {
+                            input_object_kind: *input,
+                            object: ObjectReadResultKind::Object(package.into()),
+                        });
+                        continue;
+                    }
+                    InputObjectKind::ImmOrOwnedMoveObject(objref) => {
+                        object_keys.push(objref.into());
+                        fetches.push((tx_index, i));
+                    }
+                    InputObjectKind::SharedMoveObject { id, .. } => {
+                        let shared_locks = shared_locks_cell.get_or_try_init(|| {
+                            Ok::<HashMap<ObjectID, SequenceNumber>, SuiError>(
+                                shared_lock_store
+                                    .get_shared_locks(tx_key)?
+                                    .into_iter()
+                                    .collect(),
+                            )
+                        })?;
+                        // If we can't find the locked version, it means
+                        // 1. either we have a bug that skips shared object version assignment
+                        // 2. or we have some DB corruption
+                        let version = shared_locks.get(id).unwrap_or_else(|| {
+                            panic!("Shared object locks should have been set. key: {tx_key:?}, obj id: {id:?}")
+                        });
+                        object_keys.push(ObjectKey(*id, *version));
+                        fetches.push((tx_index, i));
+                    }
                 }
             }
         }
@@ -232,12 +263,14 @@ impl TransactionInputLoader {
 
         assert!(objects.len() == object_keys.len() && objects.len() == fetches.len());
 
-        for (object, key, (index, input)) in izip!(
+        for (object, key, (tx_index, index)) in izip!(
             objects.into_iter(),
             object_keys.into_iter(),
             fetches.into_iter()
         ) {
-            results[index] = Some(match (object, input) {
+            let (tx_key, input_object_kinds) = &transactions[tx_index];
+            let input = &input_object_kinds[index];
+            results[tx_index][index] = Some(match (object, input) {
                 (Some(obj), input_object_kind) => ObjectReadResult {
                     input_object_kind: *input_object_kind,
                     object: obj.into(),
@@ -260,9 +293,14 @@ impl TransactionInputLoader {
 
         Ok(results
             .into_iter()
-            .map(Option::unwrap)
-            .collect::<Vec<_>>()
-            .into())
+            .map(|results| {
+                results
+                    .into_iter()
+                    .map(Option::unwrap)
+                    .collect::<Vec<_>>()
+                    .into()
+            })
+            .collect())
     }
 }
 
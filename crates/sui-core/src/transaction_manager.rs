@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-    cmp::max,
-    collections::{BTreeSet, HashMap, HashSet},
+    cmp::{max, Reverse},
+    collections::{BTreeSet, BinaryHeap, HashMap, HashSet},
     sync::Arc,
     time::Duration,
 };
@@ -12,6 +12,7 @@ use indexmap::IndexMap;
 use lru::LruCache;
 use mysten_metrics::monitored_scope;
 use parking_lot::RwLock;
+use serde::Serialize;
 use sui_types::{
     base_types::{ObjectID, SequenceNumber, TransactionDigest},
     committee::EpochId,
@@ -25,12 +26,12 @@ use sui_types::{
 use sui_types::{executable_transaction::VerifiedExecutableTransaction, fp_bail};
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::time::Instant;
-use tracing::{error, info, instrument, trace, warn};
+use tracing::{error, info, instrument, trace, warn, Span};
 
 use crate::authority::AuthorityMetrics;
 use crate::{
     authority::authority_per_epoch_store::AuthorityPerEpochStore,
-    execution_cache::ExecutionCacheRead,
+    execution_cache::ExecutionCacheRead, transaction_tracing::TransactionTracer,
 };
 use sui_types::transaction::SenderSignedData;
 use tap::TapOptional;
@@ -61,6 +62,7 @@ pub struct TransactionManager {
     cache_read: Arc<dyn ExecutionCacheRead>,
     tx_ready_certificates: UnboundedSender<PendingCertificate>,
     metrics: Arc<AuthorityMetrics>,
+    transaction_tracer: TransactionTracer,
     inner: RwLock<Inner>,
 }
 
@@ -70,6 +72,8 @@ pub struct PendingCertificateStats {
     pub enqueue_time: Instant,
     // The time this certificate becomes ready for execution.
     pub ready_time: Option<Instant>,
+    // Span covering the time this certificate waits for its inputs, if the transaction is traced.
+    pub waiting_span: Span,
 }
 
 #[derive(Clone, Debug)]
@@ -85,6 +89,36 @@ pub struct PendingCertificate {
     pub stats: PendingCertificateStats,
 }
 
+/// A point-in-time view of the queues of TransactionManager, for debugging stuck transactions.
+#[derive(Clone, Debug, Serialize)]
+pub struct TransactionManagerSnapshot {
+    pub epoch: EpochId,
+    pub num_pending: usize,
+    pub num_executing: usize,
+    /// The oldest pending transactions, oldest first.
+    pub pending: Vec<PendingTransactionSnapshot>,
+    /// Transactions with all their inputs available, that have not finished execution.
+    pub executing: Vec<TransactionDigest>,
+    /// The objects with the most transactions waiting on them, with the number of transactions
+    /// and the age of the oldest one.
+    pub hot_objects: Vec<ObjectQueueSnapshot>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PendingTransactionSnapshot {
+    pub digest: TransactionDigest,
+    pub age_ms: u64,
+    /// Input objects, and their versions, that are not available yet. Packages have no version.
+    pub waiting_input_objects: Vec<(ObjectID, Option<SequenceNumber>)>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ObjectQueueSnapshot {
+    pub object_id: ObjectID,
+    pub queue_len: usize,
+    pub oldest_age_ms: Option<u64>,
+}
+
 struct CacheInner {
     versioned_cache: LruCache<ObjectID, SequenceNumber>,
     // we cache packages separately, because they are more expensive to look up in the db, so we
@@ -346,10 +380,12 @@ impl TransactionManager {
         epoch_store: &AuthorityPerEpochStore,
         tx_ready_certificates: UnboundedSender<PendingCertificate>,
         metrics: Arc<AuthorityMetrics>,
+        transaction_tracer: TransactionTracer,
     ) -> TransactionManager {
         let transaction_manager = TransactionManager {
             cache_read,
             metrics: metrics.clone(),
+            transaction_tracer,
             inner: RwLock::new(Inner::new(epoch_store.epoch(), metrics)),
             tx_ready_certificates,
         };
@@ -542,6 +578,9 @@ impl TransactionManager {
         let pending_cert_enqueue_time = Instant::now();
 
         for (cert, expected_effects_digest, input_object_keys) in certs {
+            let waiting_span = self
+                .transaction_tracer
+                .transaction_span("transaction_manager", cert.digest());
             pending.push(PendingCertificate {
                 certificate: cert,
                 expected_effects_digest,
@@ -549,6 +588,7 @@ impl TransactionManager {
                 stats: PendingCertificateStats {
                     enqueue_time: pending_cert_enqueue_time,
                     ready_time: None,
+                    waiting_span,
                 },
             });
         }
@@ -761,8 +801,9 @@ impl TransactionManager {
     }
 
     /// Sends the ready certificate for execution.
-    fn certificate_ready(&self, inner: &mut Inner, pending_certificate: PendingCertificate) {
+    fn certificate_ready(&self, inner: &mut Inner, mut pending_certificate: PendingCertificate) {
         trace!(tx_digest = ?pending_certificate.certificate.digest(), "certificate ready");
+        pending_certificate.stats.waiting_span = Span::none();
         assert_eq!(pending_certificate.waiting_input_objects.len(), 0);
         // Record as an executing certificate.
         assert!(inner
@@ -808,6 +849,66 @@ impl TransactionManager {
         inner.pending_certificates.len() + inner.executing_certificates.len()
     }
 
+    /// Returns the state of the pending and executing queues, with at most `limit` entries in
+    /// each list.
+    pub fn snapshot(&self, limit: usize) -> TransactionManagerSnapshot {
+        let inner = self.inner.read();
+        // Only `limit` entries are kept while iterating, so that large queues don't need to be
+        // copied under the lock.
+        let oldest = largest(
+            inner
+                .pending_certificates
+                .iter()
+                .map(|(digest, cert)| (Reverse(cert.stats.enqueue_time), *digest)),
+            limit,
+        );
+        let pending = oldest
+            .into_iter()
+            .map(
+                |(Reverse(enqueue_time), digest)| PendingTransactionSnapshot {
+                    digest,
+                    age_ms: enqueue_time.elapsed().as_millis() as u64,
+                    waiting_input_objects: inner.pending_certificates[&digest]
+                        .waiting_input_objects
+                        .iter()
+                        .map(|key| (key.id(), key.version()))
+                        .collect(),
+                },
+            )
+            .collect();
+        let executing = inner
+            .executing_certificates
+            .iter()
+            .take(limit)
+            .copied()
+            .collect();
+        let longest = largest(
+            inner
+                .input_objects
+                .iter()
+                .map(|(object_id, txns)| (txns.len(), *object_id)),
+            limit,
+        );
+        let hot_objects = longest
+            .into_iter()
+            .map(|(queue_len, object_id)| ObjectQueueSnapshot {
+                object_id,
+                queue_len,
+                oldest_age_ms: inner.input_objects[&object_id]
+                    .first()
+                    .map(|(_, time)| time.elapsed().as_millis() as u64),
+            })
+            .collect();
+        TransactionManagerSnapshot {
+            epoch: inner.epoch,
+            num_pending: inner.pending_certificates.len(),
+            num_executing: inner.executing_certificates.len(),
+            pending,
+            executing,
+            hot_objects,
+        }
+    }
+
     // Reconfigures the TransactionManager for a new epoch. Existing transactions will be dropped
     // because they are no longer relevant and may be incorrect in the new epoch.
     pub(crate) fn reconfigure(&self, new_epoch: EpochId) {
@@ -893,6 +994,22 @@ impl TransactionManager {
     }
 }
 
+/// Returns the `limit` largest items, largest first, holding at most `limit + 1` of them at a time.
+fn largest<T: Ord>(items: impl IntoIterator<Item = T>, limit: usize) -> Vec<T> {
+    let mut heap = BinaryHeap::with_capacity(limit + 1);
+    for item in items {
+        heap.push(Reverse(item));
+        if heap.len() > limit {
+            heap.pop();
+        }
+    }
+    // Sorting the reversed items ascending leaves the items descending.
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(item)| item)
+        .collect()
+}
+
 trait ResizableHashMap<K, V> {
     fn maybe_reserve_capacity(&mut self);
     fn maybe_shrink_capacity(&mut self);
@@ -946,6 +1063,13 @@ mod test {
     use super::*;
     use prometheus::Registry;
 
+    #[test]
+    fn test_largest() {
+        assert_eq!(largest([3, 1, 4, 1, 5, 9, 2, 6], 3), vec![9, 6, 5]);
+        assert_eq!(largest([3, 1, 4], 5), vec![4, 3, 1]);
+        assert_eq!(largest([3, 1, 4], 0), Vec::<i32>::new());
+    }
+
     #[test]
     #[cfg_attr(msim, ignore)]
     fn test_available_objects_cache() {
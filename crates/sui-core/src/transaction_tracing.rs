@@ -0,0 +1,100 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! End to end tracing of transactions.
+//!
+//! A transaction is handled by AuthorityServer, ConsensusAdapter, ConsensusHandler,
+//! TransactionManager, the execution driver and CheckpointBuilder, each on its own tasks. For a
+//! sampled fraction of transactions, every one of these stages records a span in a trace whose id
+//! is derived from the transaction digest, so the whole lifecycle of the transaction and the time
+//! spent in each stage can be followed as a single trace in the OTLP backend.
+//!
+//! Sampling is decided from the digest as well, so all stages, and all nodes configured with the
+//! same sample rate, trace the same transactions. Since the trace id only depends on the digest, a
+//! client tracing a transaction and the validators processing it record their spans in the same
+//! trace.
+
+use sui_types::digests::TransactionDigest;
+use tracing::{info_span, Span};
+
+/// Decides which transactions a node traces, from the sample rate configured for the node.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TransactionTracer {
+    /// Transactions whose digest prefix is at most this value are traced. Zero disables tracing.
+    sample_threshold: u64,
+}
+
+impl TransactionTracer {
+    /// Traces the given fraction of transactions, between 0 and 1.
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_threshold: sample_threshold(sample_rate),
+        }
+    }
+
+    /// Returns a span for `stage` of the processing of a transaction, in the trace of the
+    /// transaction. The span is disabled if the transaction is not traced.
+    pub fn transaction_span(&self, stage: &'static str, digest: &TransactionDigest) -> Span {
+        if !is_sampled(digest, self.sample_threshold) {
+            return Span::none();
+        }
+        let span = info_span!("transaction_stage", otel.name = stage, tx_digest = ?digest);
+        telemetry_subscribers::set_span_trace_id(&span, trace_id(digest));
+        span
+    }
+
+    /// Adds an existing span of the processing of a transaction to the trace of the transaction,
+    /// if the transaction is traced.
+    pub fn trace_transaction(&self, span: &Span, digest: &TransactionDigest) {
+        if is_sampled(digest, self.sample_threshold) {
+            telemetry_subscribers::set_span_trace_id(span, trace_id(digest));
+        }
+    }
+}
+
+fn sample_threshold(sample_rate: f64) -> u64 {
+    // The float to int conversion saturates, so a rate of 1 traces every transaction.
+    (sample_rate.clamp(0.0, 1.0) * u64::MAX as f64) as u64
+}
+
+fn is_sampled(digest: &TransactionDigest, threshold: u64) -> bool {
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest.inner()[..8]);
+    threshold != 0 && u64::from_be_bytes(prefix) <= threshold
+}
+
+fn trace_id(digest: &TransactionDigest) -> [u8; 16] {
+    let mut trace_id = [0u8; 16];
+    trace_id.copy_from_slice(&digest.inner()[..16]);
+    trace_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_sampling() {
+        let digests: Vec<_> = (0..1000).map(|_| TransactionDigest::random()).collect();
+        let num_sampled = |sample_rate| {
+            let threshold = sample_threshold(sample_rate);
+            digests
+                .iter()
+                .filter(|digest| is_sampled(digest, threshold))
+                .count()
+        };
+
+        assert_eq!(num_sampled(0.0), 0);
+        assert_eq!(num_sampled(1.0), digests.len());
+        assert_eq!(num_sampled(2.0), digests.len());
+        let half = num_sampled(0.5);
+        assert!((350..650).contains(&half), "{half} transactions sampled");
+
+        // A transaction sampled at some rate is sampled at every higher rate.
+        let threshold = sample_threshold(0.1);
+        let higher_threshold = sample_threshold(0.2);
+        for digest in &digests {
+            assert!(!is_sampled(digest, threshold) || is_sampled(digest, higher_threshold));
+        }
+    }
+}
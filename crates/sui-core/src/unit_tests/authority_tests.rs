@@ -32,8 +32,8 @@ use sui_json_rpc_types::{
 };
 use sui_macros::sim_test;
 use sui_protocol_config::{
-    Chain, PerObjectCongestionControlMode, ProtocolConfig, ProtocolVersion,
-    SupportedProtocolVersions,
+    Chain, ExecutionTimeEstimateParams, PerObjectCongestionControlMode, ProtocolConfig,
+    ProtocolVersion, SupportedProtocolVersions,
 };
 use sui_types::digests::ConsensusCommitDigest;
 use sui_types::dynamic_field::DynamicFieldType;
@@ -42,7 +42,10 @@ use sui_types::epoch_data::EpochData;
 use sui_types::error::UserInputError;
 use sui_types::execution_status::{ExecutionFailureStatus, ExecutionStatus};
 use sui_types::gas_coin::GasCoin;
-use sui_types::messages_consensus::{ConsensusCommitPrologue, ConsensusCommitPrologueV2};
+use sui_types::messages_consensus::{
+    ConsensusCommitPrologue, ConsensusCommitPrologueV2, ConsensusTransaction,
+    ExecutionTimeObservation,
+};
 use sui_types::object::Data;
 use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::randomness_state::get_randomness_state_obj_initial_shared_version;
@@ -62,11 +65,14 @@ use sui_types::{
 
 use crate::authority::authority_per_epoch_store::DeferralKey;
 use crate::authority::authority_store_tables::AuthorityPerpetualTables;
+use crate::authority::execution_time_estimator::MAX_ESTIMATES_PER_OBSERVATION;
 use crate::authority::move_integration_tests::build_and_publish_test_package_with_upgrade_cap;
 use crate::authority::test_authority_builder::TestAuthorityBuilder;
 use crate::{
     authority_client::{AuthorityAPI, NetworkAuthorityClient},
     authority_server::AuthorityServer,
+    checkpoints::CheckpointServiceNoop,
+    consensus_handler::SequencedConsensusTransaction,
     test_utils::init_state_parameters_from_rng,
 };
 
@@ -2895,7 +2901,7 @@ async fn test_authority_persist() {
     let path = dir.join(format!("DB_{:?}", ObjectID::random()));
     fs::create_dir(&path).unwrap();
 
-    let perpetual_tables = Arc::new(AuthorityPerpetualTables::open(&path, None));
+    let perpetual_tables = Arc::new(AuthorityPerpetualTables::open(&path, None).unwrap());
     // Create an authority
     let store =
         AuthorityStore::open_with_committee_for_testing(perpetual_tables, &committee, &genesis, 0)
@@ -2922,7 +2928,7 @@ async fn test_authority_persist() {
     let seed = [1u8; 32];
     let (genesis, authority_key) = init_state_parameters_from_rng(&mut StdRng::from_seed(seed));
     let committee = genesis.committee().unwrap();
-    let perpetual_tables = Arc::new(AuthorityPerpetualTables::open(&path, None));
+    let perpetual_tables = Arc::new(AuthorityPerpetualTables::open(&path, None).unwrap());
     let store =
         AuthorityStore::open_with_committee_for_testing(perpetual_tables, &committee, &genesis, 0)
             .await
@@ -5893,3 +5899,209 @@ async fn test_per_object_congestion_control() {
         .unwrap()
         .is_empty());
 }
+
+// Sends an execution time observation from `authority` through consensus, without executing anything.
+// Test commits have a timestamp of 0, which `generation` needs to be close enough to.
+async fn send_execution_time_observation(
+    authority: &AuthorityState,
+    generation: u64,
+    estimates: Vec<(ObjectID, u64)>,
+) {
+    let transaction = SequencedConsensusTransaction {
+        certificate_author: authority.name,
+        ..SequencedConsensusTransaction::new_test(
+            ConsensusTransaction::new_execution_time_observation(ExecutionTimeObservation {
+                authority: authority.name,
+                generation,
+                estimates,
+            }),
+        )
+    };
+    authority
+        .epoch_store_for_testing()
+        .process_consensus_transactions_for_tests(
+            vec![transaction],
+            &Arc::new(CheckpointServiceNoop {}),
+            authority.get_cache_reader().as_ref(),
+            &authority.metrics.skipped_consensus_txns,
+        )
+        .await
+        .unwrap();
+}
+
+const TEST_EXECUTION_TIME_ESTIMATE_PARAMS: ExecutionTimeEstimateParams =
+    ExecutionTimeEstimateParams {
+        default_estimate_micros: 1_000,
+        max_observed_objects_per_authority: 1_000,
+        max_generation_lead_ms: 1_000,
+    };
+
+#[sim_test]
+async fn test_per_object_congestion_control_with_execution_time_estimate() {
+    let (sender, keypair): (_, AccountKeyPair) = get_key_pair();
+
+    // In this test, transactions operate on 2 shared objects with the same gas budget. Once
+    // validators observe that transactions on the first object take much longer to execute,
+    // the number of transactions scheduled on it per commit is limited, while transactions on
+    // the second object keep going through.
+    let shared_objects = create_shared_objects(2);
+    let gas_objects_commit_1 = create_gas_objects(10, sender);
+    let gas_objects_commit_2 = create_gas_objects(10, sender);
+
+    // Each object can take 10ms of execution time per commit. Until estimates are agreed on,
+    // each transaction is assumed to take 1ms.
+    let mut protocol_config =
+        ProtocolConfig::get_for_version(ProtocolVersion::max(), Chain::Unknown);
+    protocol_config.set_per_object_congestion_control_mode(
+        PerObjectCongestionControlMode::ExecutionTimeEstimate(TEST_EXECUTION_TIME_ESTIMATE_PARAMS),
+    );
+    protocol_config.set_max_accumulated_txn_cost_per_object_in_checkpoint(10_000);
+    let authority = TestAuthorityBuilder::new()
+        .with_reference_gas_price(1000)
+        .with_protocol_config(protocol_config)
+        .build()
+        .await;
+    let mut genesis_objects = gas_objects_commit_1.clone();
+    genesis_objects.extend(gas_objects_commit_2.clone());
+    genesis_objects.extend(shared_objects.clone());
+    authority.insert_genesis_objects(&genesis_objects).await;
+
+    let make_certificates = |gas_objects: Vec<Object>| {
+        let authority = &authority;
+        let sender = &sender;
+        let keypair = &keypair;
+        let shared_objects = &shared_objects;
+        async move {
+            let mut certificates: Vec<VerifiedCertificate> = vec![];
+            for (index, gas_object) in gas_objects.iter().enumerate() {
+                let certificate = make_test_transaction(
+                    sender,
+                    keypair,
+                    shared_objects[index % 2].id(),
+                    OBJECT_START_VERSION,
+                    &gas_object.compute_object_reference(),
+                    &[authority],
+                    12345,
+                    Some(1000),
+                    Some(10_000_000),
+                )
+                .await;
+                certificates.push(certificate);
+            }
+            certificates
+        }
+    };
+    let operates_on = |cert: &VerifiedExecutableTransaction, object_id: ObjectID| {
+        cert.shared_input_objects().any(|obj| obj.id == object_id)
+    };
+
+    // Without observations, all 5 transactions on each object fit in the default estimate.
+    let certificates = make_certificates(gas_objects_commit_1).await;
+    let scheduled_txns = send_batch_consensus_no_execution(&authority, &certificates).await;
+    assert_eq!(scheduled_txns.len(), 10);
+
+    // The validator observes that transactions on the first object take 4ms, and 0.5ms on the
+    // second one.
+    send_execution_time_observation(
+        &authority,
+        1,
+        vec![
+            (shared_objects[0].id(), 4_000),
+            (shared_objects[1].id(), 500),
+        ],
+    )
+    .await;
+
+    // Observations with a generation too far ahead of the commit timestamp are ignored.
+    send_execution_time_observation(&authority, 1_000_000, vec![(shared_objects[0].id(), 100)])
+        .await;
+    assert_eq!(
+        authority
+            .epoch_store_for_testing()
+            .get_agreed_execution_time_estimate(&shared_objects[0].id()),
+        Some(4_000)
+    );
+
+    // Only 2 transactions on the hot object are scheduled in the next commit, while all
+    // transactions on the cold object are.
+    let certificates = make_certificates(gas_objects_commit_2).await;
+    let scheduled_txns = send_batch_consensus_no_execution(&authority, &certificates).await;
+    assert_eq!(scheduled_txns.len(), 7);
+    assert_eq!(
+        scheduled_txns
+            .iter()
+            .filter(|cert| operates_on(*cert, shared_objects[0].id()))
+            .count(),
+        2
+    );
+    let deferred_txns = authority
+        .epoch_store_for_testing()
+        .get_all_deferred_transactions_for_test()
+        .unwrap();
+    assert_eq!(deferred_txns.len(), 1);
+    assert_eq!(deferred_txns[0].1.len(), 3);
+
+    // Deferred transactions on the hot object drain at the same rate.
+    let scheduled_txns = send_batch_consensus_no_execution(&authority, &[]).await;
+    assert_eq!(scheduled_txns.len(), 2);
+    let scheduled_txns = send_batch_consensus_no_execution(&authority, &[]).await;
+    assert_eq!(scheduled_txns.len(), 1);
+    assert!(scheduled_txns
+        .iter()
+        .all(|cert| operates_on(cert, shared_objects[0].id())));
+    assert!(authority
+        .epoch_store_for_testing()
+        .get_all_deferred_transactions_for_test()
+        .unwrap()
+        .is_empty());
+}
+
+#[sim_test]
+async fn test_execution_time_observed_for_shared_object_transactions() {
+    let (sender, keypair): (_, AccountKeyPair) = get_key_pair();
+    let shared_objects = create_shared_objects(1);
+    let gas_objects = create_gas_objects(1, sender);
+
+    let mut protocol_config =
+        ProtocolConfig::get_for_version(ProtocolVersion::max(), Chain::Unknown);
+    protocol_config.set_per_object_congestion_control_mode(
+        PerObjectCongestionControlMode::ExecutionTimeEstimate(TEST_EXECUTION_TIME_ESTIMATE_PARAMS),
+    );
+    protocol_config.set_max_accumulated_txn_cost_per_object_in_checkpoint(10_000);
+    let authority = TestAuthorityBuilder::new()
+        .with_reference_gas_price(1000)
+        .with_protocol_config(protocol_config)
+        .build()
+        .await;
+    let mut genesis_objects = gas_objects.clone();
+    genesis_objects.extend(shared_objects.clone());
+    authority.insert_genesis_objects(&genesis_objects).await;
+
+    let certificate = make_test_transaction(
+        &sender,
+        &keypair,
+        shared_objects[0].id(),
+        OBJECT_START_VERSION,
+        &gas_objects[0].compute_object_reference(),
+        &[&authority],
+        12345,
+        None,
+        None,
+    )
+    .await;
+
+    // Sequence the certificate and let the execution driver execute it.
+    send_consensus(&authority, &certificate).await;
+    authority.notify_read_effects(&certificate).await.unwrap();
+
+    // The execution time of the transaction is attributed to its shared object.
+    let estimates = authority
+        .execution_time_observer()
+        .take_estimates_to_share(MAX_ESTIMATES_PER_OBSERVATION);
+    assert_eq!(estimates.len(), 1);
+    assert_eq!(estimates[0].0, shared_objects[0].id());
+    assert!(authority
+        .execution_time_observer()
+        .take_estimates_to_share(MAX_ESTIMATES_PER_OBSERVATION)
+        .is_empty());
+}
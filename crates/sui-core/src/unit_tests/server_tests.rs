@@ -3,12 +3,18 @@
 
 use super::*;
 use crate::{
-    authority::authority_tests::init_state_with_object_id,
+    authority::{
+        authority_tests::init_state_with_object_id, test_authority_builder::TestAuthorityBuilder,
+    },
     authority_client::{AuthorityAPI, NetworkAuthorityClient},
+    test_utils::make_transfer_object_transaction,
+    transaction_tracing::TransactionTracer,
 };
 use sui_types::{
     base_types::{dbg_addr, dbg_object_id},
+    crypto::{get_key_pair, AccountKeyPair},
     messages_grpc::LayoutGenerationOption,
+    object::Object,
 };
 
 //This is the most basic example of how to test the server logic
@@ -38,3 +44,70 @@ async fn test_simple_request() {
 
     client.handle_object_info_request(req).await.unwrap();
 }
+
+#[tokio::test]
+async fn test_transaction_trace_id_carried_to_validator() {
+    // Runs on the single thread of the test runtime, so the server sees this subscriber too.
+    let (subscriber, exported_spans) = telemetry_subscribers::otel_subscriber_for_testing();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let (sender, key): (_, AccountKeyPair) = get_key_pair();
+    let object_id = dbg_object_id(1);
+    let gas_object_id = dbg_object_id(2);
+    let authority_state = TestAuthorityBuilder::new()
+        .with_transaction_tracing_sample_rate(1.0)
+        .build()
+        .await;
+    for id in [object_id, gas_object_id] {
+        authority_state
+            .insert_genesis_object(Object::with_id_owner_for_testing(id, sender))
+            .await;
+    }
+    let object_ref = authority_state
+        .get_object(&object_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .compute_object_reference();
+    let gas_ref = authority_state
+        .get_object(&gas_object_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .compute_object_reference();
+    let transaction = make_transfer_object_transaction(
+        object_ref,
+        gas_ref,
+        sender,
+        &key,
+        dbg_addr(2),
+        authority_state.reference_gas_price_for_testing().unwrap(),
+    );
+    let digest = *transaction.digest();
+
+    let consensus_address = "/ip4/127.0.0.1/tcp/0/http".parse().unwrap();
+    let server = AuthorityServer::new_for_test(
+        "/ip4/127.0.0.1/tcp/0/http".parse().unwrap(),
+        authority_state,
+        consensus_address,
+    );
+    let server_handle = server.spawn_for_test().await.unwrap();
+    let client = NetworkAuthorityClient::connect(server_handle.address())
+        .await
+        .unwrap();
+
+    // The client traces the transaction on its own, with nothing but the digest in common.
+    let client_span = TransactionTracer::new(1.0).transaction_span("client", &digest);
+    client
+        .handle_transaction(transaction)
+        .instrument(client_span)
+        .await
+        .unwrap();
+
+    let client_trace_ids = exported_spans.trace_ids("client");
+    assert_eq!(client_trace_ids.len(), 1);
+    assert_eq!(
+        exported_spans.trace_ids("validator_state_process_tx"),
+        client_trace_ids
+    );
+}
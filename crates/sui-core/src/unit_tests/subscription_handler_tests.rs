@@ -10,12 +10,16 @@ use move_core_types::{
     language_storage::StructTag,
 };
 
+use crate::subscription_handler::SubscriptionHandler;
+use prometheus::Registry;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::json;
-use sui_json_rpc_types::SuiMoveStruct;
+use sui_config::node::EventSubscriptionConfig;
+use sui_json_rpc_types::{EventFieldComparison, EventFilter, SuiMoveStruct};
 
-use sui_types::base_types::ObjectID;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::error::{SuiError, SuiResult, UserInputError};
 use sui_types::gas_coin::GasCoin;
 use sui_types::object::bounded_visitor::BoundedVisitor;
 use sui_types::{MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS};
@@ -64,6 +68,104 @@ fn test_to_json_value() {
     assert_eq!(Some(&json!("test_event")), json_value.pointer("/name"));
 }
 
+fn field_predicate(path: &str) -> EventFilter {
+    EventFilter::MoveEventFieldPredicate {
+        path: path.to_string(),
+        op: EventFieldComparison::Gt,
+        value: json!(0),
+    }
+}
+
+fn is_size_limit_exceeded<T>(result: SuiResult<T>) -> bool {
+    matches!(
+        result,
+        Err(SuiError::UserInputError {
+            error: UserInputError::SizeLimitExceeded { .. }
+        })
+    )
+}
+
+#[tokio::test]
+async fn test_subscribe_events_rejects_costly_filters() {
+    let handler = SubscriptionHandler::new(
+        &Registry::new(),
+        EventSubscriptionConfig {
+            max_filter_cost: 4,
+            ..Default::default()
+        },
+    );
+
+    // 1 for the filter and 3 for the segments of the path.
+    handler.subscribe_events(field_predicate("/a/b/c")).unwrap();
+    assert!(is_size_limit_exceeded(
+        handler.subscribe_events(field_predicate("/a/b/c/d"))
+    ));
+    assert!(is_size_limit_exceeded(handler.subscribe_events(
+        EventFilter::Any(vec![
+            EventFilter::Sender(SuiAddress::ZERO),
+            EventFilter::Sender(SuiAddress::ZERO),
+            field_predicate("/a"),
+        ])
+    )));
+
+    // Filters without field predicates are accepted whatever their cost.
+    handler
+        .subscribe_events(EventFilter::Any(vec![
+            EventFilter::Sender(SuiAddress::ZERO),
+            EventFilter::Sender(SuiAddress::ZERO),
+            EventFilter::Sender(SuiAddress::ZERO),
+            EventFilter::Sender(SuiAddress::ZERO),
+        ]))
+        .unwrap();
+    handler
+        .subscribe_events(EventFilter::MoveEventField {
+            path: "/a/b/c/d".to_string(),
+            value: json!("1"),
+        })
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_subscribe_events_caps_field_predicate_subscriptions() {
+    let handler = SubscriptionHandler::new(
+        &Registry::new(),
+        EventSubscriptionConfig {
+            max_field_predicate_subscriptions: 2,
+            ..Default::default()
+        },
+    );
+
+    let first = handler
+        .subscribe_events(field_predicate("/amount"))
+        .unwrap();
+    let _second = handler
+        .subscribe_events(EventFilter::All(vec![
+            EventFilter::Sender(SuiAddress::ZERO),
+            field_predicate("/amount"),
+        ]))
+        .unwrap();
+    assert!(is_size_limit_exceeded(
+        handler.subscribe_events(field_predicate("/amount"))
+    ));
+
+    // Subscriptions without field predicates are not capped.
+    let _plain = handler
+        .subscribe_events(EventFilter::MoveEventField {
+            path: "/amount".to_string(),
+            value: json!("1"),
+        })
+        .unwrap();
+    let _sender = handler
+        .subscribe_events(EventFilter::Sender(SuiAddress::ZERO))
+        .unwrap();
+
+    // Dropping a subscription frees its slot.
+    drop(first);
+    handler
+        .subscribe_events(field_predicate("/amount"))
+        .unwrap();
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TestEvent {
     creator: AccountAddress,
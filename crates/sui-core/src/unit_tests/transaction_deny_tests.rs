@@ -13,7 +13,9 @@ use move_core_types::ident_str;
 use std::path::PathBuf;
 use std::sync::Arc;
 use sui_config::certificate_deny_config::CertificateDenyConfigBuilder;
-use sui_config::transaction_deny_config::{TransactionDenyConfig, TransactionDenyConfigBuilder};
+use sui_config::transaction_deny_config::{
+    DeniedFunction, TransactionDenyConfig, TransactionDenyConfigBuilder, TransactionDenyConfigPatch,
+};
 use sui_swarm_config::genesis_config::{AccountConfig, DEFAULT_GAS_AMOUNT};
 use sui_swarm_config::network_config::NetworkConfig;
 use sui_test_transaction_builder::TestTransactionBuilder;
@@ -22,14 +24,16 @@ use sui_types::effects::TransactionEffectsAPI;
 use sui_types::error::{SuiError, SuiResult, UserInputError};
 use sui_types::execution_status::{ExecutionFailureStatus, ExecutionStatus};
 use sui_types::messages_grpc::HandleTransactionResponse;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::transaction::{
-    CallArg, CertifiedTransaction, Transaction, TransactionData, VerifiedCertificate,
+    CallArg, CertifiedTransaction, ObjectArg, Transaction, TransactionData, VerifiedCertificate,
     VerifiedTransaction, TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
 };
 use sui_types::utils::get_zklogin_user_address;
 use sui_types::utils::{
     make_zklogin_tx, to_sender_signed_transaction, to_sender_signed_transaction_with_multi_signers,
 };
+use sui_types::{parse_sui_struct_tag, parse_sui_type_tag, SUI_FRAMEWORK_PACKAGE_ID};
 
 const ACCOUNT_NUM: usize = 5;
 const GAS_OBJECT_COUNT: usize = 15;
@@ -158,6 +162,31 @@ async fn handle_move_call_transaction(
     state.handle_transaction(&epoch_store, tx).await
 }
 
+async fn handle_coin_zero_transaction(
+    state: &Arc<AuthorityState>,
+    coin_type: &str,
+    account: &Account,
+    gas_payment_index: usize,
+) -> SuiResult<HandleTransactionResponse> {
+    let rgp = state.reference_gas_price_for_testing().unwrap();
+    let data = TransactionData::new_move_call(
+        account.0,
+        SUI_FRAMEWORK_PACKAGE_ID,
+        ident_str!("coin").to_owned(),
+        ident_str!("zero").to_owned(),
+        vec![parse_sui_type_tag(coin_type).unwrap()],
+        account.2[gas_payment_index],
+        vec![],
+        TEST_ONLY_GAS_UNIT_FOR_TRANSFER * rgp,
+        rgp,
+    )
+    .unwrap();
+    let epoch_store = state.epoch_store_for_testing();
+    let tx = to_sender_signed_transaction(data, &account.1);
+    let tx = epoch_store.verify_transaction(tx).unwrap();
+    state.handle_transaction(&epoch_store, tx).await
+}
+
 fn assert_denied<T: std::fmt::Debug>(result: &SuiResult<T>) {
     assert!(matches!(
         result.as_ref().unwrap_err(),
@@ -240,6 +269,43 @@ async fn test_signer_denied() {
     assert_denied(&transfer_with_account(&accounts[2], &accounts[1], &state).await);
 }
 
+#[tokio::test]
+async fn test_deny_config_changed_at_runtime() {
+    let (network_config, state) = setup_test(TransactionDenyConfigBuilder::new().build()).await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+
+    // Replacing the config takes effect without re-creating the state.
+    state.set_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_address(accounts[0].0)
+            .build(),
+    );
+    assert_denied(&transfer_with_account(&accounts[0], &accounts[0], &state).await);
+
+    // So does patching it.
+    state.update_transaction_deny_config(|config| {
+        config.patched(&TransactionDenyConfigPatch {
+            allow_addresses: vec![accounts[0].0],
+            ..Default::default()
+        })
+    });
+    assert!(state
+        .transaction_deny_config()
+        .get_address_deny_set()
+        .is_empty());
+    assert!(transfer_with_account(&accounts[0], &accounts[0], &state)
+        .await
+        .is_ok());
+
+    state.update_transaction_deny_config(|config| {
+        config.patched(&TransactionDenyConfigPatch {
+            user_transaction_disabled: Some(true),
+            ..Default::default()
+        })
+    });
+    assert_denied(&transfer_with_account(&accounts[1], &accounts[1], &state).await);
+}
+
 #[tokio::test]
 async fn test_shared_object_transaction_disabled() {
     let (network_config, state) = setup_test(
@@ -430,6 +496,284 @@ async fn test_package_denied() {
     assert!(result.is_ok());
 }
 
+/// Publishes package c, b depending on c, and upgrades c to c'.
+async fn publish_package_family(
+    state: &Arc<AuthorityState>,
+    account: &Account,
+) -> (ObjectID, ObjectRef, ObjectID, ObjectID) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let (package_c, cap_c) = publish_package_on_single_authority(
+        path.join("src/unit_tests/data/package_deny/c"),
+        account.0,
+        &account.1,
+        account.2[0],
+        [("c", ObjectID::ZERO)],
+        vec![],
+        state,
+    )
+    .await
+    .unwrap();
+    let (package_b, _) = publish_package_on_single_authority(
+        path.join("src/unit_tests/data/package_deny/b"),
+        account.0,
+        &account.1,
+        account.2[1],
+        [("b", ObjectID::ZERO), ("c", package_c)],
+        vec![package_c],
+        state,
+    )
+    .await
+    .unwrap();
+    let package_c_prime = upgrade_package_on_single_authority(
+        path.join("src/unit_tests/data/package_deny/c"),
+        account.0,
+        &account.1,
+        account.2[2],
+        package_c,
+        cap_c,
+        [("c", ObjectID::ZERO)],
+        vec![],
+        state,
+    )
+    .await
+    .unwrap();
+    let cap_c = state
+        .get_object(&cap_c.0)
+        .await
+        .unwrap()
+        .unwrap()
+        .compute_object_reference();
+    (package_c, cap_c, package_b, package_c_prime)
+}
+
+#[tokio::test]
+async fn test_package_family_denied() {
+    let (network_config, state) = setup_test(TransactionDenyConfigBuilder::new().build()).await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let (package_c, cap_c, package_b, package_c_prime) =
+        publish_package_family(&state, &accounts[0]).await;
+
+    // Denying the family of c, by its original ID, denies every version of c.
+    state.set_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_package_family(package_c)
+            .build(),
+    );
+    let result =
+        handle_move_call_transaction(&state, package_c, "c", "c", vec![], &accounts[0], 5).await;
+    assert_denied(&result);
+    let result =
+        handle_move_call_transaction(&state, package_c_prime, "c", "c", vec![], &accounts[0], 6)
+            .await;
+    assert_denied(&result);
+
+    // Packages depending on any version of c are denied too.
+    let result =
+        handle_move_call_transaction(&state, package_b, "b", "b", vec![], &accounts[0], 7).await;
+    assert_denied(&result);
+    let result = publish_package_on_single_authority(
+        path.join("src/unit_tests/data/package_deny/b"),
+        accounts[0].0,
+        &accounts[0].1,
+        accounts[0].2[8],
+        [("b", ObjectID::ZERO), ("c", package_c)],
+        vec![package_c_prime],
+        &state,
+    )
+    .await;
+    assert_denied(&result);
+
+    // The family can't be extended with new versions either.
+    let result = upgrade_package_on_single_authority(
+        path.join("src/unit_tests/data/package_deny/c"),
+        accounts[0].0,
+        &accounts[0].1,
+        accounts[0].2[9],
+        package_c_prime,
+        cap_c,
+        [("c", ObjectID::ZERO)],
+        vec![],
+        &state,
+    )
+    .await;
+    assert_denied(&result);
+
+    // Denying the ID of c' instead only denies that version, as it is not an original ID.
+    state.set_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_package_family(package_c_prime)
+            .build(),
+    );
+    let result =
+        handle_move_call_transaction(&state, package_c_prime, "c", "c", vec![], &accounts[0], 10)
+            .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_type_denied() {
+    let (network_config, state) = setup_test(TransactionDenyConfigBuilder::new().build()).await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+
+    // A type without type parameters is denied in type arguments, including nested ones.
+    state.set_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_type(parse_sui_struct_tag("0x1::string::String").unwrap())
+            .build(),
+    );
+    let result = handle_coin_zero_transaction(&state, "0x1::string::String", &accounts[0], 0).await;
+    assert_denied(&result);
+    let result = handle_coin_zero_transaction(
+        &state,
+        "0x1::option::Option<0x1::string::String>",
+        &accounts[0],
+        1,
+    )
+    .await;
+    assert_denied(&result);
+    let result = handle_coin_zero_transaction(&state, "0x1::ascii::String", &accounts[0], 2).await;
+    assert!(result.is_ok());
+
+    // A type with type parameters only denies that instantiation, here in input objects.
+    state.set_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_type(parse_sui_struct_tag("0x2::coin::Coin<0x1::string::String>").unwrap())
+            .build(),
+    );
+    assert!(transfer_with_account(&accounts[1], &accounts[1], &state)
+        .await
+        .is_ok());
+    state.set_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_type(parse_sui_struct_tag("0x2::coin::Coin<0x2::sui::SUI>").unwrap())
+            .build(),
+    );
+    assert_denied(&transfer_with_account(&accounts[2], &accounts[2], &state).await);
+}
+
+#[tokio::test]
+async fn test_type_denied_in_upgraded_package() {
+    let (network_config, state) = setup_test(TransactionDenyConfigBuilder::new().build()).await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+    let (package_c, _, _, package_c_prime) = publish_package_family(&state, &accounts[0]).await;
+    let type_in_c = format!("{}::c::C", package_c);
+    let type_in_c_prime = format!("{}::c::C", package_c_prime);
+
+    // Denying a type by its original package ID also denies it when it is named through the ID
+    // of an upgraded version of the package.
+    state.set_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_type(parse_sui_struct_tag(&type_in_c).unwrap())
+            .build(),
+    );
+    let result = handle_coin_zero_transaction(&state, &type_in_c, &accounts[0], 5).await;
+    assert_denied(&result);
+    let result = handle_coin_zero_transaction(&state, &type_in_c_prime, &accounts[0], 6).await;
+    assert_denied(&result);
+    let result = handle_coin_zero_transaction(
+        &state,
+        &format!("0x1::option::Option<{}>", type_in_c_prime),
+        &accounts[0],
+        7,
+    )
+    .await;
+    assert_denied(&result);
+
+    // Types from any version of a denied package family are denied too.
+    state.set_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_package_family(package_c)
+            .build(),
+    );
+    let result = handle_coin_zero_transaction(&state, &type_in_c_prime, &accounts[0], 8).await;
+    assert_denied(&result);
+
+    state.set_transaction_deny_config(TransactionDenyConfigBuilder::new().build());
+    let result = handle_coin_zero_transaction(&state, &type_in_c_prime, &accounts[0], 9).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_receiving_object_type_denied() {
+    let (network_config, state) = setup_test(TransactionDenyConfigBuilder::new().build()).await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+    let (_, cap_c, _, _) = publish_package_family(&state, &accounts[0]).await;
+    let (sender, key, gas_objects) = &accounts[0];
+    let rgp = state.reference_gas_price_for_testing().unwrap();
+
+    state.set_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_type(parse_sui_struct_tag("0x2::package::UpgradeCap").unwrap())
+            .build(),
+    );
+    let handle_transfer = |gas: ObjectRef, receiving: Option<ObjectRef>| {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        if let Some(receiving) = receiving {
+            builder.obj(ObjectArg::Receiving(receiving)).unwrap();
+        }
+        builder.transfer_sui(*sender, Some(1));
+        let tx = TestTransactionBuilder::new(*sender, gas, rgp)
+            .programmable(builder.finish())
+            .build_and_sign(key);
+        let state = state.clone();
+        async move {
+            let epoch_store = state.epoch_store_for_testing();
+            let tx = epoch_store.verify_transaction(tx).unwrap();
+            state.handle_transaction(&epoch_store, tx).await
+        }
+    };
+
+    // The cap is only loaded as an object to receive, and its type is checked all the same.
+    assert_denied(&handle_transfer(gas_objects[3], Some(cap_c)).await);
+    assert!(handle_transfer(gas_objects[4], None).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_function_denied() {
+    let (network_config, state) = setup_test(TransactionDenyConfigBuilder::new().build()).await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+    let (package_c, _, package_b, package_c_prime) =
+        publish_package_family(&state, &accounts[0]).await;
+
+    // Denying a function by the original ID of its package denies it in every version.
+    state.set_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_function(format!("{}::c::c", package_c).parse().unwrap())
+            .build(),
+    );
+    let result =
+        handle_move_call_transaction(&state, package_c, "c", "c", vec![], &accounts[0], 5).await;
+    assert_denied(&result);
+    let result =
+        handle_move_call_transaction(&state, package_c_prime, "c", "c", vec![], &accounts[0], 6)
+            .await;
+    assert_denied(&result);
+
+    // Only functions called by the transaction are checked, b can still call c internally.
+    let result =
+        handle_move_call_transaction(&state, package_b, "b", "b", vec![], &accounts[0], 7).await;
+    assert!(result.is_ok());
+
+    // Denying it by the ID of a later version only denies it in that version.
+    state.set_transaction_deny_config(
+        TransactionDenyConfigBuilder::new()
+            .add_denied_function(DeniedFunction {
+                package: package_c_prime,
+                module: ident_str!("c").to_owned(),
+                function: ident_str!("c").to_owned(),
+            })
+            .build(),
+    );
+    let result =
+        handle_move_call_transaction(&state, package_c_prime, "c", "c", vec![], &accounts[0], 8)
+            .await;
+    assert_denied(&result);
+    let result =
+        handle_move_call_transaction(&state, package_c, "c", "c", vec![], &accounts[0], 9).await;
+    assert!(result.is_ok());
+}
+
 #[tokio::test]
 async fn test_certificate_deny() {
     let (network_config, state) = setup_test(TransactionDenyConfig::default()).await;
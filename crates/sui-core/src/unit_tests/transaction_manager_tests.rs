@@ -37,6 +37,7 @@ fn make_transaction_manager(
         &state.epoch_store_for_testing(),
         tx_ready_certificates,
         state.metrics.clone(),
+        *state.transaction_tracer(),
     );
 
     (transaction_manager, rx_ready_certificates)
@@ -715,3 +716,70 @@ async fn transaction_manager_receiving_object_ready_if_current_version_greater()
     rx_ready_certificates.recv().await.unwrap();
     assert!(rx_ready_certificates.try_recv().is_err());
 }
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn transaction_manager_snapshot() {
+    // Initialize an authority state.
+    let (owner, _keypair) = deterministic_random_account_key();
+    let gas_objects: Vec<Object> = (0..10)
+        .map(|_| {
+            let gas_object_id = ObjectID::random();
+            Object::with_id_owner_for_testing(gas_object_id, owner)
+        })
+        .collect();
+    let state = init_state_with_objects(gas_objects.clone()).await;
+    let (transaction_manager, mut rx_ready_certificates) = make_transaction_manager(&state);
+
+    // Enqueue two transactions waiting on the same missing object, one second apart.
+    let missing_object =
+        Object::with_id_owner_version_for_testing(ObjectID::random(), 0.into(), owner);
+    let missing_object_arg = CallArg::Object(ObjectArg::ImmOrOwnedObject(
+        missing_object.compute_object_reference(),
+    ));
+    let transaction_0 = make_transaction(gas_objects[0].clone(), vec![missing_object_arg.clone()]);
+    let transaction_1 = make_transaction(gas_objects[1].clone(), vec![missing_object_arg]);
+    transaction_manager.enqueue(
+        vec![transaction_0.clone()],
+        &state.epoch_store_for_testing(),
+    );
+    sleep(Duration::from_secs(1)).await;
+    transaction_manager.enqueue(
+        vec![transaction_1.clone()],
+        &state.epoch_store_for_testing(),
+    );
+
+    // And one transaction that is ready to execute.
+    let transaction_2 = make_transaction(gas_objects[2].clone(), vec![]);
+    transaction_manager.enqueue(
+        vec![transaction_2.clone()],
+        &state.epoch_store_for_testing(),
+    );
+    rx_ready_certificates.recv().await.unwrap();
+
+    let snapshot = transaction_manager.snapshot(10);
+    assert_eq!(snapshot.num_pending, 2);
+    assert_eq!(snapshot.num_executing, 1);
+    // Pending transactions are listed oldest first.
+    assert_eq!(
+        snapshot
+            .pending
+            .iter()
+            .map(|tx| tx.digest)
+            .collect::<Vec<_>>(),
+        vec![*transaction_0.digest(), *transaction_1.digest()]
+    );
+    assert!(snapshot.pending[0].age_ms >= 1000);
+    assert_eq!(
+        snapshot.pending[0].waiting_input_objects,
+        vec![(missing_object.id(), Some(missing_object.version()))]
+    );
+    assert_eq!(snapshot.executing, vec![*transaction_2.digest()]);
+    assert_eq!(snapshot.hot_objects[0].object_id, missing_object.id());
+    assert_eq!(snapshot.hot_objects[0].queue_len, 2);
+
+    // Lists are truncated to the limit, but counts are not.
+    let snapshot = transaction_manager.snapshot(1);
+    assert_eq!(snapshot.num_pending, 2);
+    assert_eq!(snapshot.pending.len(), 1);
+    assert_eq!(snapshot.pending[0].digest, *transaction_0.digest());
+}
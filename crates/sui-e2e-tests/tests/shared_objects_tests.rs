@@ -11,6 +11,9 @@ use sui_core::authority::EffectsNotifyRead;
 use sui_core::consensus_adapter::position_submit_certificate;
 use sui_json_rpc_types::SuiTransactionBlockEffectsAPI;
 use sui_macros::{register_fail_point_async, sim_test};
+use sui_protocol_config::{
+    ExecutionTimeEstimateParams, PerObjectCongestionControlMode, ProtocolConfig,
+};
 use sui_swarm_config::genesis_config::{AccountConfig, DEFAULT_GAS_AMOUNT};
 use sui_test_transaction_builder::{
     publish_basics_package, publish_basics_package_and_make_counter, TestTransactionBuilder,
@@ -653,3 +656,175 @@ async fn replay_shared_object_transaction() {
         version = Some(curr);
     }
 }
+
+/// Validators share the execution times they measure on a shared object through consensus, and
+/// all of them derive the same estimate for it.
+#[sim_test]
+async fn shared_object_execution_time_estimate_agreed() {
+    let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut config| {
+        config.set_per_object_congestion_control_mode(
+            PerObjectCongestionControlMode::ExecutionTimeEstimate(ExecutionTimeEstimateParams {
+                default_estimate_micros: 1_000,
+                max_observed_objects_per_authority: 1_000,
+                max_generation_lead_ms: 10_000,
+            }),
+        );
+        // Large enough for no transaction to be deferred.
+        config.set_max_accumulated_txn_cost_per_object_in_checkpoint(1_000_000_000);
+        config
+    });
+    let test_cluster = TestClusterBuilder::new().build().await;
+
+    let (package, counter) = publish_basics_package_and_make_counter(&test_cluster.wallet).await;
+    let (package_id, counter_id, counter_initial_shared_version) =
+        (package.0, counter.0, counter.1);
+    for _ in 0..5 {
+        let transaction = test_cluster
+            .test_transaction_builder()
+            .await
+            .call_counter_increment(package_id, counter_id, counter_initial_shared_version)
+            .build();
+        let effects = test_cluster
+            .sign_and_execute_transaction(&transaction)
+            .await
+            .effects
+            .unwrap();
+        assert!(effects.status().is_ok());
+    }
+
+    // Every validator executed the transactions and shares its own measurements, but estimates
+    // are only derived from the observations sequenced by consensus, so they end up equal.
+    let validators = test_cluster.swarm.validator_node_handles();
+    tokio::time::timeout(Duration::from_secs(120), async {
+        loop {
+            let estimates: Vec<_> = validators
+                .iter()
+                .map(|handle| {
+                    handle.with(|node| {
+                        node.state()
+                            .epoch_store_for_testing()
+                            .get_agreed_execution_time_estimate(&counter_id)
+                    })
+                })
+                .collect();
+            if estimates[0].is_some() && estimates.iter().all(|e| *e == estimates[0]) {
+                break;
+            }
+            sleep(Duration::from_secs(1)).await;
+        }
+    })
+    .await
+    .expect("Validators did not agree on an execution time estimate for the counter");
+}
+
+/// Sends `num_txns` concurrent increments of a single shared counter, and returns how long they
+/// took to execute, along with the number of congestion deferrals on each validator.
+async fn run_contended_counter_load(num_txns: usize) -> (Duration, Vec<i64>) {
+    let test_cluster = TestClusterBuilder::new()
+        .with_accounts(vec![AccountConfig {
+            address: None,
+            gas_amounts: vec![DEFAULT_GAS_AMOUNT; num_txns + 2],
+        }])
+        .build()
+        .await;
+
+    let (package, counter) = publish_basics_package_and_make_counter(&test_cluster.wallet).await;
+    let (package_id, counter_id, counter_initial_shared_version) =
+        (package.0, counter.0, counter.1);
+
+    let accounts_and_gas = test_cluster
+        .wallet
+        .get_all_accounts_and_gas_objects()
+        .await
+        .unwrap();
+    let sender = accounts_and_gas[0].0;
+    let mut txs = vec![];
+    for coin_ref in accounts_and_gas[0].1.iter().take(num_txns) {
+        let transaction = test_cluster
+            .test_transaction_builder_with_gas_object(sender, *coin_ref)
+            .await
+            .call_counter_increment(package_id, counter_id, counter_initial_shared_version)
+            .build();
+        txs.push(test_cluster.sign_transaction(&transaction));
+    }
+    assert_eq!(txs.len(), num_txns);
+
+    let validators = test_cluster.get_validator_pubkeys();
+    let start = tokio::time::Instant::now();
+    let submissions = txs.iter().map(|tx| async {
+        let (effects, _) = test_cluster
+            .submit_transaction_to_validators(tx.clone(), &validators)
+            .await
+            .unwrap();
+        assert!(effects.status().is_ok());
+    });
+    tokio::time::timeout(Duration::from_secs(120), join_all(submissions))
+        .await
+        .expect("Contended counter increments did not all execute");
+    let elapsed = start.elapsed();
+
+    let deferrals = test_cluster
+        .swarm
+        .validator_node_handles()
+        .iter()
+        .map(|handle| {
+            handle.with(|node| {
+                node.state()
+                    .epoch_store_for_testing()
+                    .metrics()
+                    .epoch_shared_object_congestion_deferral_count
+                    .get()
+            })
+        })
+        .collect();
+    (elapsed, deferrals)
+}
+
+/// Under contended load on a shared object, execution time based congestion control defers the
+/// transactions that exceed the per-commit budget of the object, and they all still execute.
+#[sim_test]
+async fn shared_object_congestion_control_with_execution_time_estimates() {
+    let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut config| {
+        config.set_per_object_congestion_control_mode(
+            PerObjectCongestionControlMode::ExecutionTimeEstimate(ExecutionTimeEstimateParams {
+                default_estimate_micros: 1_000,
+                max_observed_objects_per_authority: 1_000,
+                max_generation_lead_ms: 10_000,
+            }),
+        );
+        // Budget for at most 3 transactions on the counter per commit, until validators agree
+        // on an estimate for it.
+        config.set_max_accumulated_txn_cost_per_object_in_checkpoint(3_000);
+        config
+    });
+
+    let (elapsed, deferrals) = run_contended_counter_load(50).await;
+    assert!(
+        deferrals.iter().all(|count| *count > 0),
+        "Every validator should have deferred contended transactions: {deferrals:?}"
+    );
+    assert!(
+        elapsed < Duration::from_secs(60),
+        "Deferred transactions took too long to execute: {elapsed:?}"
+    );
+}
+
+/// The same contended load is never deferred without congestion control.
+#[sim_test]
+async fn shared_object_contention_without_congestion_control() {
+    let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut config| {
+        config.set_per_object_congestion_control_mode(PerObjectCongestionControlMode::None);
+        config.set_max_accumulated_txn_cost_per_object_in_checkpoint(3_000);
+        config
+    });
+
+    let (elapsed, deferrals) = run_contended_counter_load(50).await;
+    assert!(
+        deferrals.iter().all(|count| *count == 0),
+        "No transaction should be deferred without congestion control: {deferrals:?}"
+    );
+    assert!(
+        elapsed < Duration::from_secs(60),
+        "Contended transactions took too long to execute: {elapsed:?}"
+    );
+}
@@ -7,7 +7,7 @@ use async_trait::async_trait;
 use diesel::{
     query_builder::{BoxedSelectStatement, FromClause, QueryFragment, QueryId},
     query_dsl::{methods::LimitDsl, LoadQuery},
-    QueryResult,
+    QueryResult, RunQueryDsl,
 };
 
 use crate::error::Error;
@@ -83,6 +83,14 @@ pub(crate) trait DbConnection {
         Q: LoadQuery<'static, Self::Connection, U>,
         Q: QueryId + QueryFragment<Self::Backend>;
 
+    /// The database's estimate of the cost of running a query, or `None` if it could not be
+    /// estimated. `query` is a thunk that returns a query when called.
+    fn estimated_cost<Q>(&mut self, query: impl Fn() -> Q) -> Option<f64>
+    where
+        Q: diesel::query_builder::Query,
+        Q: QueryId + QueryFragment<Self::Backend>,
+        Q: RunQueryDsl<Self::Connection>;
+
     /// Helper to limit a query that fetches multiple values to return only its first value. `query`
     /// is a thunk that returns a query when called.
     fn first<Q: LimitDsl, U>(&mut self, query: impl Fn() -> Q) -> QueryResult<U>
@@ -112,6 +112,15 @@ impl<'c> super::DbConnection for PgConnection<'c> {
         query_cost::log(self.conn, self.max_cost, query());
         query().get_results(self.conn)
     }
+
+    fn estimated_cost<Q>(&mut self, query: impl Fn() -> Q) -> Option<f64>
+    where
+        Q: diesel::query_builder::Query,
+        Q: QueryId + QueryFragment<Self::Backend>,
+        Q: RunQueryDsl<Self::Connection>,
+    {
+        query_cost::explain(self.conn, query())
+    }
 }
 
 /// Support for calculating estimated query cost using EXPLAIN and then logging it.
@@ -27,7 +27,7 @@ use fastcrypto::encoding::{Base58, Encoding};
 use serde::{Deserialize, Serialize};
 use sui_indexer::{
     models::checkpoints::StoredCheckpoint,
-    schema::{checkpoints, objects_snapshot},
+    schema::{checkpoints, objects_snapshot, pruner_watermark},
 };
 use sui_types::messages_checkpoint::CheckpointDigest;
 
@@ -404,7 +404,7 @@ impl Checkpoint {
             lhs.union(rhs)
         })?;
 
-        Ok(match checkpoint_range.as_slice() {
+        let (lhs, rhs) = match checkpoint_range.as_slice() {
             [] => (0, 0),
             [single_value] => (0, *single_value as u64),
             values => {
@@ -412,7 +412,20 @@ impl Checkpoint {
                 let max_value = *values.iter().max().unwrap();
                 (min_value as u64, max_value as u64)
             }
-        })
+        };
+
+        // Data below the pruner's watermark may already be gone, so it cannot be served, even if
+        // the snapshot has not caught up to it yet.
+        let watermark: Option<i64> = conn
+            .result(move || {
+                pruner_watermark::dsl::pruner_watermark
+                    .select(pruner_watermark::dsl::checkpoint_sequence_number)
+                    .limit(1)
+            })
+            .optional()?;
+
+        let lhs = lhs.max(watermark.unwrap_or_default() as u64).min(rhs);
+        Ok((lhs, rhs))
     }
 }
 
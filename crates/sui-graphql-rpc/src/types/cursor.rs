@@ -350,6 +350,18 @@ impl<C: CursorType + Eq + Clone + Send + Sync + 'static> Page<C> {
         ))
     }
 
+    /// A page with the same limit and outer bound as this one, over the part of its range past
+    /// `cursor`, in the direction that entries are taken from. Used to keep scanning a range from
+    /// where a query for this page left off.
+    pub(crate) fn continue_from(&self, cursor: C) -> Self {
+        let mut page = self.clone();
+        match self.end {
+            End::Front => page.after = Some(cursor),
+            End::Back => page.before = Some(cursor),
+        }
+        page
+    }
+
     /// Given the results of a database query, determine whether the result set has a previous and
     /// next page and is consistent with the provided cursors.
     ///
@@ -762,6 +774,54 @@ mod tests {
         expect.assert_eq(&format!("{page:#?}"));
     }
 
+    #[test]
+    fn test_continue_page() {
+        let config = ServiceConfig::default();
+        let front: Page<JsonCursor<u64>> = Page::from_params(
+            &config,
+            Some(10),
+            Some(JsonCursor::new(40)),
+            None,
+            Some(JsonCursor::new(80)),
+        )
+        .unwrap();
+
+        let back: Page<JsonCursor<u64>> = Page::from_params(
+            &config,
+            None,
+            Some(JsonCursor::new(40)),
+            Some(10),
+            Some(JsonCursor::new(80)),
+        )
+        .unwrap();
+
+        let expect = expect![[r#"
+            Page {
+                after: Some(
+                    50,
+                ),
+                before: Some(
+                    80,
+                ),
+                limit: 10,
+                end: Front,
+            }"#]];
+        expect.assert_eq(&format!("{:#?}", front.continue_from(JsonCursor::new(50))));
+
+        let expect = expect![[r#"
+            Page {
+                after: Some(
+                    40,
+                ),
+                before: Some(
+                    70,
+                ),
+                limit: 10,
+                end: Back,
+            }"#]];
+        expect.assert_eq(&format!("{:#?}", back.continue_from(JsonCursor::new(70))));
+    }
+
     #[test]
     fn test_err_first_and_last() {
         let config = ServiceConfig::default();
@@ -78,6 +78,7 @@ impl GasInput {
         };
 
         Object::paginate(
+            ctx.data_unchecked(),
             ctx.data_unchecked(),
             page,
             filter,
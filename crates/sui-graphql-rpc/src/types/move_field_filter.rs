@@ -0,0 +1,661 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use async_graphql::*;
+use move_core_types::{
+    account_address::AccountAddress,
+    annotated_value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue},
+    language_storage::StructTag,
+    u256::U256,
+};
+use sui_package_resolver::Resolver;
+use sui_types::{
+    base_types::MoveObjectType, object::bounded_visitor::BoundedVisitor, object::Data,
+    parse_sui_address, TypeTag,
+};
+
+use super::object::{Object, ObjectFilter};
+use super::type_filter::TypeFilter;
+use crate::context_data::package_cache::PackageCache;
+use crate::error::Error;
+use crate::filter;
+use crate::raw_query::RawQuery;
+
+/// Constrains objects by the value of a field in their Move contents. The field is identified by a
+/// path from the object's top-level struct, and its value is compared against `value`, which is
+/// interpreted according to the field's type:
+///
+/// - Integers (`u8` through `u256`) are written in decimal,
+/// - Booleans are written as `true` or `false`,
+/// - Addresses (including `ID`s) are written in hex, with or without a leading `0x`,
+/// - `0x1::string::String` and `0x1::ascii::String` are written as-is.
+///
+/// Booleans only support the `EQ` and `NE` comparisons.
+#[derive(InputObject, Debug, Clone, Eq, PartialEq)]
+pub(crate) struct MoveFieldFilter {
+    /// Dot-separated path to the field, starting from the object's struct, e.g. `price` or
+    /// `balance.value`.
+    pub path: String,
+
+    /// How to compare the field's value against `value`.
+    pub op: MoveFieldComparison,
+
+    /// The value to compare the field against.
+    pub value: String,
+}
+
+/// Comparisons that can be used to constrain a field in a `MoveFieldFilter`.
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum MoveFieldComparison {
+    /// The field's value is equal to the filter's value.
+    Eq,
+    /// The field's value is not equal to the filter's value.
+    Ne,
+    /// The field's value is strictly less than the filter's value.
+    Lt,
+    /// The field's value is less than or equal to the filter's value.
+    Le,
+    /// The field's value is strictly greater than the filter's value.
+    Gt,
+    /// The field's value is greater than or equal to the filter's value.
+    Ge,
+}
+
+/// `MoveFieldFilter`s that have been resolved against the layout of the type they are filtering,
+/// ready to be applied to a database query, and to the objects it returns.
+#[derive(Clone, Debug)]
+pub(crate) struct FieldPredicates {
+    layout: MoveStructLayout,
+    predicates: Vec<FieldPredicate>,
+}
+
+#[derive(Clone, Debug)]
+struct FieldPredicate {
+    path: Vec<String>,
+    op: MoveFieldComparison,
+    value: FieldValue,
+
+    /// If the field can be found at a fixed offset in the object's serialized representation, the
+    /// offset and BCS bytes of `value`, so that the comparison can be pushed down to the database.
+    pushdown: Option<(usize, Vec<u8>)>,
+}
+
+/// Values that fields can be compared against. Values are only ever compared against other
+/// values of the same variant, because both sides are derived from the same layout.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum FieldValue {
+    Bool(bool),
+    Number(U256),
+    Address(AccountAddress),
+    String(String),
+}
+
+impl FieldPredicates {
+    /// Resolve the field filters in `filter` (if there are any) against the layout of the type
+    /// that `filter` constrains objects to. Field filters can only be used alongside a filter on a
+    /// fully-qualified struct type, so that the layout is unambiguous.
+    pub(crate) async fn resolve(
+        filter: &ObjectFilter,
+        resolver: &Resolver<PackageCache>,
+    ) -> Result<Option<Self>, Error> {
+        let Some(fields) = &filter.fields else {
+            return Ok(None);
+        };
+
+        let Some(TypeFilter::ByType(TypeTag::Struct(tag))) = &filter.type_ else {
+            return Err(Error::Client(
+                "Filtering on fields requires a 'type' filter on a fully-qualified struct type"
+                    .to_string(),
+            ));
+        };
+
+        let tag: StructTag = (**tag).clone();
+        let layout = resolver
+            .type_layout(TypeTag::Struct(Box::new(tag.clone())))
+            .await
+            .map_err(|e| {
+                Error::Client(format!(
+                    "Cannot filter on fields of {}: {e}",
+                    tag.to_canonical_display(/* with_prefix */ true),
+                ))
+            })?;
+
+        let MoveTypeLayout::Struct(layout) = layout else {
+            return Err(Error::Internal(format!(
+                "Expected a struct layout for {}",
+                tag.to_canonical_display(/* with_prefix */ true),
+            )));
+        };
+
+        // Pushing down comparisons relies on knowing exactly where the field's bytes are in the
+        // serialized object, which is only possible if the struct has a fixed size.
+        let contents_offset = struct_size(&layout).map(|size| contents_offset(&tag, size));
+
+        let predicates = fields
+            .iter()
+            .map(|field| FieldPredicate::resolve(&layout, contents_offset, field))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Some(Self { layout, predicates }))
+    }
+
+    /// Whether some of the predicates cannot be checked by the database, so the objects it returns
+    /// still need to be checked by `matches`.
+    pub(crate) fn has_residual(&self) -> bool {
+        self.predicates.iter().any(|p| p.pushdown.is_none())
+    }
+
+    /// Modify `query` to push down any comparisons that can be checked by the database, returning
+    /// the new query. The remaining comparisons are checked by `matches`.
+    pub(crate) fn apply(&self, mut query: RawQuery) -> RawQuery {
+        use MoveFieldComparison as C;
+
+        for predicate in &self.predicates {
+            let Some((offset, bytes)) = &predicate.pushdown else {
+                continue;
+            };
+
+            let op = match predicate.op {
+                C::Eq => "=",
+                C::Ne => "<>",
+                C::Lt => "<",
+                C::Le => "<=",
+                C::Gt => ">",
+                C::Ge => ">=",
+            };
+
+            // `bytea`s are ordered lexicographically, which matches the order of addresses, but
+            // numbers are serialized little-endian, so their bytes are reversed to order them.
+            let (field, value) = match (&predicate.value, predicate.op) {
+                (FieldValue::Number(_), C::Lt | C::Le | C::Gt | C::Ge) => {
+                    let field = (1..=bytes.len())
+                        .rev()
+                        .map(|i| format!("substring(serialized_object FROM {} FOR 1)", offset + i))
+                        .collect::<Vec<_>>()
+                        .join(" || ");
+
+                    let value: Vec<_> = bytes.iter().rev().copied().collect();
+                    (format!("({field})"), value)
+                }
+
+                _ => (
+                    format!(
+                        "substring(serialized_object FROM {} FOR {})",
+                        offset + 1,
+                        bytes.len(),
+                    ),
+                    bytes.clone(),
+                ),
+            };
+
+            query = filter!(
+                query,
+                format!("{field} {op} '\\x{}'::bytea", hex::encode(value))
+            );
+        }
+
+        query
+    }
+
+    /// Whether `object` is a Move object of the filtered type whose fields satisfy all the
+    /// predicates.
+    pub(crate) fn matches(&self, object: &Object) -> Result<bool, Error> {
+        let Some(Data::Move(move_object)) = object.native_impl().map(|native| &native.data) else {
+            return Ok(false);
+        };
+
+        if StructTag::from(move_object.type_().clone()) != self.layout.type_ {
+            return Ok(false);
+        }
+
+        let contents = BoundedVisitor::deserialize_struct(move_object.contents(), &self.layout)
+            .map_err(|e| {
+                Error::Internal(format!(
+                    "Error deserializing move struct for type {}: {e}",
+                    self.layout
+                        .type_
+                        .to_canonical_display(/* with_prefix */ true),
+                ))
+            })?;
+
+        for predicate in &self.predicates {
+            if !predicate.matches(&contents)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl FieldPredicate {
+    fn resolve(
+        layout: &MoveStructLayout,
+        contents_offset: Option<usize>,
+        filter: &MoveFieldFilter,
+    ) -> Result<Self, Error> {
+        let path: Vec<_> = filter.path.split('.').map(str::to_string).collect();
+
+        let mut struct_ = layout;
+        let mut field_offset = contents_offset;
+        let mut leaf = None;
+
+        for (i, name) in path.iter().enumerate() {
+            let Some(ix) = struct_.fields.iter().position(|f| f.name.as_str() == name) else {
+                return Err(Error::Client(format!(
+                    "No field '{name}' in {} (filtering on '{}')",
+                    struct_.type_.to_canonical_display(/* with_prefix */ true),
+                    filter.path,
+                )));
+            };
+
+            // The offset of a field is the sum of the sizes of the fields that precede it, which
+            // are all known to be fixed size if the struct is.
+            field_offset = field_offset.and_then(|offset| {
+                let preceding: Option<usize> = struct_.fields[..ix]
+                    .iter()
+                    .map(|f| type_size(&f.layout))
+                    .sum();
+                Some(offset + preceding?)
+            });
+
+            let field = &struct_.fields[ix].layout;
+            match field {
+                MoveTypeLayout::Struct(inner) if i + 1 < path.len() && !is_string(&inner.type_) => {
+                    struct_ = inner;
+                }
+
+                _ if i + 1 < path.len() => {
+                    return Err(Error::Client(format!(
+                        "Field '{name}' is not a struct (filtering on '{}')",
+                        filter.path,
+                    )));
+                }
+
+                _ => leaf = Some(field),
+            }
+        }
+
+        let Some(leaf) = leaf else {
+            return Err(Error::Client("Field path cannot be empty".to_string()));
+        };
+
+        let value = FieldValue::parse(leaf, &filter.value).ok_or_else(|| {
+            Error::Client(format!(
+                "Cannot compare field '{}' against '{}'",
+                filter.path, filter.value,
+            ))
+        })?;
+
+        if matches!(value, FieldValue::Bool(_))
+            && !matches!(filter.op, MoveFieldComparison::Eq | MoveFieldComparison::Ne)
+        {
+            return Err(Error::Client(format!(
+                "Boolean field '{}' can only be compared for equality",
+                filter.path,
+            )));
+        }
+
+        let pushdown = field_offset.zip(value.to_bcs(leaf));
+
+        Ok(Self {
+            path,
+            op: filter.op,
+            value,
+            pushdown,
+        })
+    }
+
+    fn matches(&self, contents: &MoveStruct) -> Result<bool, Error> {
+        let mut struct_ = contents;
+        let mut leaf = None;
+
+        for (i, name) in self.path.iter().enumerate() {
+            let Some((_, value)) = struct_.fields.iter().find(|(n, _)| n.as_str() == name) else {
+                return Err(Error::Internal(format!(
+                    "Field '{name}' missing from contents"
+                )));
+            };
+
+            match value {
+                MoveValue::Struct(inner) if i + 1 < self.path.len() => struct_ = inner,
+                _ => leaf = Some(value),
+            }
+        }
+
+        let Some(actual) = leaf.and_then(FieldValue::extract) else {
+            return Err(Error::Internal(format!(
+                "Unexpected value for field '{}'",
+                self.path.join("."),
+            )));
+        };
+
+        use MoveFieldComparison as C;
+        Ok(match self.op {
+            C::Eq => actual == self.value,
+            C::Ne => actual != self.value,
+            C::Lt => actual < self.value,
+            C::Le => actual <= self.value,
+            C::Gt => actual > self.value,
+            C::Ge => actual >= self.value,
+        })
+    }
+}
+
+impl FieldValue {
+    /// Interpret `value` as a value of the type described by `layout`, returning `None` if that is
+    /// not possible, or if fields of that type cannot be filtered on.
+    fn parse(layout: &MoveTypeLayout, value: &str) -> Option<Self> {
+        use MoveTypeLayout as L;
+
+        let number = || U256::from_str(value).ok();
+        Some(match layout {
+            L::Bool => Self::Bool(bool::from_str(value).ok()?),
+            L::U8 => Self::Number(number().filter(|n| u8::try_from(*n).is_ok())?),
+            L::U16 => Self::Number(number().filter(|n| u16::try_from(*n).is_ok())?),
+            L::U32 => Self::Number(number().filter(|n| u32::try_from(*n).is_ok())?),
+            L::U64 => Self::Number(number().filter(|n| u64::try_from(*n).is_ok())?),
+            L::U128 => Self::Number(number().filter(|n| u128::try_from(*n).is_ok())?),
+            L::U256 => Self::Number(number()?),
+            L::Address | L::Signer => Self::Address(parse_sui_address(value).ok()?.into()),
+            L::Struct(s) if is_string(&s.type_) => Self::String(value.to_string()),
+            L::Struct(s) if is_id(&s.type_) => Self::Address(parse_sui_address(value).ok()?.into()),
+            L::Vector(_) | L::Struct(_) => return None,
+        })
+    }
+
+    /// Extract a value that can be compared against a filter from a field's contents.
+    fn extract(value: &MoveValue) -> Option<Self> {
+        use MoveValue as V;
+
+        Some(match value {
+            V::Bool(b) => Self::Bool(*b),
+            V::U8(n) => Self::Number(U256::from(*n)),
+            V::U16(n) => Self::Number(U256::from(*n)),
+            V::U32(n) => Self::Number(U256::from(*n)),
+            V::U64(n) => Self::Number(U256::from(*n)),
+            V::U128(n) => Self::Number(U256::from(*n)),
+            V::U256(n) => Self::Number(*n),
+            V::Address(a) | V::Signer(a) => Self::Address(*a),
+
+            V::Struct(s) if is_string(&s.type_) => {
+                let [(_, V::Vector(bytes))] = &s.fields[..] else {
+                    return None;
+                };
+
+                let bytes: Option<Vec<u8>> = bytes
+                    .iter()
+                    .map(|b| if let V::U8(b) = b { Some(*b) } else { None })
+                    .collect();
+
+                Self::String(String::from_utf8(bytes?).ok()?)
+            }
+
+            V::Struct(s) if is_id(&s.type_) => {
+                let [(_, V::Address(a))] = &s.fields[..] else {
+                    return None;
+                };
+
+                Self::Address(*a)
+            }
+
+            V::Vector(_) | V::Struct(_) => return None,
+        })
+    }
+
+    /// The BCS representation of this value, as a value of the type described by `layout`, if it
+    /// is a fixed-size value.
+    fn to_bcs(&self, layout: &MoveTypeLayout) -> Option<Vec<u8>> {
+        let size = type_size(layout)?;
+        Some(match self {
+            Self::Bool(b) => vec![*b as u8],
+            Self::Number(n) => n.to_le_bytes()[..size].to_vec(),
+            Self::Address(a) => a.to_vec(),
+            Self::String(_) => return None,
+        })
+    }
+}
+
+/// Whether `tag` is `0x1::string::String` or `0x1::ascii::String`, which are compared as strings.
+fn is_string(tag: &StructTag) -> bool {
+    tag.address == AccountAddress::ONE
+        && (tag.module.as_str() == "string" || tag.module.as_str() == "ascii")
+        && tag.name.as_str() == "String"
+}
+
+/// Whether `tag` is `0x2::object::ID`, which is compared as an address.
+fn is_id(tag: &StructTag) -> bool {
+    tag.address == AccountAddress::TWO
+        && tag.module.as_str() == "object"
+        && tag.name.as_str() == "ID"
+}
+
+/// The size of a value with this `layout`, in bytes, when serialized with BCS, if all values with
+/// this layout have the same size.
+fn type_size(layout: &MoveTypeLayout) -> Option<usize> {
+    use MoveTypeLayout as L;
+
+    Some(match layout {
+        L::Bool | L::U8 => 1,
+        L::U16 => 2,
+        L::U32 => 4,
+        L::U64 => 8,
+        L::U128 => 16,
+        L::U256 => 32,
+        L::Address | L::Signer => AccountAddress::LENGTH,
+        L::Struct(s) => struct_size(s)?,
+        L::Vector(_) => return None,
+    })
+}
+
+fn struct_size(layout: &MoveStructLayout) -> Option<usize> {
+    layout.fields.iter().map(|f| type_size(&f.layout)).sum()
+}
+
+/// The offset of a Move object's contents within the BCS representation of an `Object` with type
+/// `tag`, given the size of its contents (the objects table stores objects in this form).
+fn contents_offset(tag: &StructTag, size: usize) -> usize {
+    // SAFETY: Serializing a `MoveObjectType` cannot fail.
+    let type_ = bcs::to_bytes(&MoveObjectType::from(tag.clone())).unwrap();
+
+    // `Data::Move` variant tag, `type_`, `has_public_transfer`, `version`, then the length of the
+    // contents as a ULEB128.
+    1 + type_.len() + 1 + 8 + uleb128_len(size)
+}
+
+fn uleb128_len(mut n: usize) -> usize {
+    let mut len = 1;
+    while n >= 0x80 {
+        n >>= 7;
+        len += 1;
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::annotated_value::MoveFieldLayout;
+    use move_core_types::identifier::Identifier;
+    use sui_types::base_types::{ObjectID, SequenceNumber, SuiAddress as NativeSuiAddress};
+    use sui_types::digests::TransactionDigest;
+    use sui_types::object::{MoveObject as NativeMoveObject, Object as NativeObject, Owner};
+
+    fn listing_layout() -> MoveStructLayout {
+        let tag = StructTag::from_str("0x42::market::Listing").unwrap();
+        MoveStructLayout {
+            type_: tag,
+            fields: vec![
+                MoveFieldLayout::new(Identifier::new("id").unwrap(), MoveTypeLayout::Address),
+                MoveFieldLayout::new(Identifier::new("price").unwrap(), MoveTypeLayout::U64),
+                MoveFieldLayout::new(Identifier::new("active").unwrap(), MoveTypeLayout::Bool),
+            ],
+        }
+    }
+
+    fn listing(id: ObjectID, price: u64, active: bool) -> NativeObject {
+        let tag = listing_layout().type_;
+        let contents = bcs::to_bytes(&(id, price, active)).unwrap();
+
+        // SAFETY: `Listing` does not have `store`, so it does not have public transfer.
+        let move_object = unsafe {
+            NativeMoveObject::new_from_execution_with_limit(
+                MoveObjectType::from(tag),
+                /* has_public_transfer */ false,
+                SequenceNumber::from_u64(1),
+                contents,
+                u64::MAX,
+            )
+            .unwrap()
+        };
+
+        NativeObject::new_move(
+            move_object,
+            Owner::AddressOwner(NativeSuiAddress::ZERO),
+            TransactionDigest::ZERO,
+        )
+    }
+
+    fn predicates(filters: &[(&str, MoveFieldComparison, &str)]) -> Result<FieldPredicates, Error> {
+        let layout = listing_layout();
+        let offset = struct_size(&layout).map(|size| contents_offset(&layout.type_, size));
+        let predicates = filters
+            .iter()
+            .map(|(path, op, value)| {
+                let filter = MoveFieldFilter {
+                    path: path.to_string(),
+                    op: *op,
+                    value: value.to_string(),
+                };
+                FieldPredicate::resolve(&layout, offset, &filter)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(FieldPredicates { layout, predicates })
+    }
+
+    #[test]
+    fn test_pushdown_offset() {
+        use MoveFieldComparison as C;
+
+        let id = ObjectID::random();
+        let native = listing(id, 42, true);
+        let bytes = bcs::to_bytes(&native).unwrap();
+
+        let preds = predicates(&[("price", C::Eq, "42"), ("active", C::Ne, "false")]).unwrap();
+        for predicate in &preds.predicates {
+            let Some((offset, expect)) = &predicate.pushdown else {
+                panic!("Expected {:?} to be pushed down", predicate.path);
+            };
+
+            assert_eq!(&bytes[*offset..*offset + expect.len()], &expect[..]);
+        }
+    }
+
+    #[test]
+    fn test_pushdown_range() {
+        use MoveFieldComparison as C;
+
+        let preds = predicates(&[("price", C::Ge, "258"), ("id", C::Lt, "0x2")]).unwrap();
+        assert!(!preds.has_residual());
+
+        let Some((offset, _)) = &preds.predicates[0].pushdown else {
+            panic!("Expected price to be pushed down");
+        };
+
+        let (sql, _) = preds
+            .apply(RawQuery::new("SELECT * FROM objects", vec![]))
+            .finish();
+
+        // The price's bytes are compared most significant byte first.
+        let price = (1..=8)
+            .rev()
+            .map(|i| format!("substring(serialized_object FROM {} FOR 1)", offset + i))
+            .collect::<Vec<_>>()
+            .join(" || ");
+        assert!(sql.contains(&format!("({price}) >= '\\x0000000000000102'::bytea")));
+
+        // Addresses are compared as they are serialized.
+        assert!(sql.contains(&format!(
+            "substring(serialized_object FROM {} FOR 32) < '\\x{}'::bytea",
+            offset - AccountAddress::LENGTH + 1,
+            "0".repeat(63) + "2",
+        )));
+    }
+
+    #[test]
+    fn test_residual() {
+        use MoveFieldComparison as C;
+
+        let layout = MoveStructLayout {
+            type_: StructTag::from_str("0x42::market::Named").unwrap(),
+            fields: vec![
+                MoveFieldLayout::new(Identifier::new("price").unwrap(), MoveTypeLayout::U64),
+                MoveFieldLayout::new(
+                    Identifier::new("name").unwrap(),
+                    MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+                ),
+            ],
+        };
+
+        // The struct is not fixed size, so nothing can be pushed down.
+        let offset = struct_size(&layout).map(|size| contents_offset(&layout.type_, size));
+        let filter = MoveFieldFilter {
+            path: "price".to_string(),
+            op: C::Gt,
+            value: "1".to_string(),
+        };
+
+        let predicate = FieldPredicate::resolve(&layout, offset, &filter).unwrap();
+        let preds = FieldPredicates {
+            layout,
+            predicates: vec![predicate],
+        };
+
+        assert!(preds.has_residual());
+        let (sql, _) = preds
+            .apply(RawQuery::new("SELECT * FROM objects", vec![]))
+            .finish();
+        assert!(!sql.contains("serialized_object"));
+    }
+
+    #[test]
+    fn test_matches() {
+        use MoveFieldComparison as C;
+
+        let object = Object::from_native(
+            NativeSuiAddress::ZERO.into(),
+            listing(ObjectID::random(), 100, true),
+            None,
+        );
+
+        let matches = |filters: &[(&str, MoveFieldComparison, &str)]| {
+            predicates(filters).unwrap().matches(&object).unwrap()
+        };
+
+        assert!(matches(&[("price", C::Eq, "100")]));
+        assert!(matches(&[("price", C::Le, "100"), ("price", C::Gt, "99")]));
+        assert!(matches(&[("active", C::Eq, "true")]));
+        assert!(!matches(&[("price", C::Lt, "100")]));
+        assert!(!matches(&[
+            ("price", C::Ge, "1000"),
+            ("active", C::Eq, "true")
+        ]));
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        use MoveFieldComparison as C;
+
+        // Field doesn't exist.
+        assert!(predicates(&[("cost", C::Eq, "1")]).is_err());
+        // Field is not a struct.
+        assert!(predicates(&[("price.value", C::Eq, "1")]).is_err());
+        // Value is out of range for the field's type.
+        assert!(predicates(&[("price", C::Eq, "18446744073709551616")]).is_err());
+        // Booleans can't be ordered.
+        assert!(predicates(&[("active", C::Lt, "true")]).is_err());
+    }
+}
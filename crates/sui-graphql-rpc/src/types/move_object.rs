@@ -19,12 +19,14 @@ use super::suins_registration::{DomainFormat, SuinsRegistration, SuinsRegistrati
 use super::transaction_block::{self, TransactionBlock, TransactionBlockFilter};
 use super::type_filter::ExactTypeFilter;
 use super::{coin::Coin, object::Object};
+use crate::context_data::package_cache::PackageCache;
 use crate::data::Db;
 use crate::error::Error;
 use crate::types::stake::StakedSui;
 use async_graphql::connection::Connection;
 use async_graphql::*;
 use sui_json_rpc::name_service::NameServiceConfig;
+use sui_package_resolver::Resolver;
 use sui_types::object::{Data, MoveObject as NativeMoveObject};
 use sui_types::TypeTag;
 
@@ -440,7 +442,8 @@ impl MoveObject {
         }
     }
 
-    /// Query the database for a `page` of Move objects, optionally `filter`-ed.
+    /// Query the database for a `page` of Move objects, optionally `filter`-ed, including by the
+    /// values of their `fields`.
     ///
     /// `checkpoint_viewed_at` represents the checkpoint sequence number at which this page was
     /// queried for, or `None` if the data was requested at the latest checkpoint. Each entity
@@ -448,11 +451,12 @@ impl MoveObject {
     /// state, it will be as if it was read at the same checkpoint.
     pub(crate) async fn paginate(
         db: &Db,
+        resolver: &Resolver<PackageCache>,
         page: Page<object::Cursor>,
         filter: ObjectFilter,
         checkpoint_viewed_at: Option<u64>,
     ) -> Result<Connection<String, MoveObject>, Error> {
-        Object::paginate_subtype(db, page, filter, checkpoint_viewed_at, |object| {
+        Object::paginate_subtype(db, resolver, page, filter, checkpoint_viewed_at, |object| {
             let address = object.address;
             MoveObject::try_from(&object).map_err(|_| {
                 Error::Internal(format!(
@@ -13,6 +13,7 @@ use super::cursor::{self, Page, Paginated, RawPaginated, Target};
 use super::digest::Digest;
 use super::display::{Display, DisplayEntry};
 use super::dynamic_field::{DynamicField, DynamicFieldName};
+use super::move_field_filter::{FieldPredicates, MoveFieldFilter};
 use super::move_object::MoveObject;
 use super::move_package::MovePackage;
 use super::owner::OwnerImpl;
@@ -102,7 +103,8 @@ pub(crate) struct ObjectRef {
 ///
 /// - Type matches the `type` filter,
 /// - AND, whose owner matches the `owner` filter,
-/// - AND, whose ID is in `objectIds` OR whose ID and version is in `objectKeys`.
+/// - AND, whose ID is in `objectIds` OR whose ID and version is in `objectKeys`,
+/// - AND, whose fields satisfy all the `fields` filters.
 #[derive(InputObject, Default, Debug, Clone, Eq, PartialEq)]
 pub(crate) struct ObjectFilter {
     /// This field is used to specify the type of objects that should be included in the query
@@ -123,6 +125,17 @@ pub(crate) struct ObjectFilter {
 
     /// Filter for live or potentially historical objects by their ID and version.
     pub object_keys: Option<Vec<ObjectKey>>,
+
+    /// Filter for objects by the values of fields in their Move contents. Can only be used
+    /// alongside a `type` filter on a fully-qualified struct type (including its type parameters,
+    /// if it has any).
+    ///
+    /// Comparisons on fields at a fixed offset in the object (i.e. not following a vector or
+    /// string) are checked by the database. Other comparisons are checked against the objects it
+    /// returns, scanning further to fill the page, up to the query's cost limit: Pages may contain
+    /// fewer objects than requested if that limit is reached, and the query fails if it is
+    /// reached before any object matches.
+    pub fields: Option<Vec<MoveFieldFilter>>,
 }
 
 #[derive(InputObject, Debug, Clone, Eq, PartialEq)]
@@ -705,7 +718,8 @@ impl Object {
         }
     }
 
-    /// Query the database for a `page` of objects, optionally `filter`-ed.
+    /// Query the database for a `page` of objects, optionally `filter`-ed. Filters on fields are
+    /// resolved against the layout of the filtered type using `resolver`.
     ///
     /// `checkpoint_viewed_at` represents the checkpoint sequence number at which this page was
     /// queried for, or `None` if the data was requested at the latest checkpoint. Each entity
@@ -713,17 +727,20 @@ impl Object {
     /// state, it will be as if it was read at the same checkpoint.
     pub(crate) async fn paginate(
         db: &Db,
+        resolver: &Resolver<PackageCache>,
         page: Page<Cursor>,
         filter: ObjectFilter,
         checkpoint_viewed_at: Option<u64>,
     ) -> Result<Connection<String, Object>, Error> {
-        Self::paginate_subtype(db, page, filter, checkpoint_viewed_at, Ok).await
+        Self::paginate_subtype(db, resolver, page, filter, checkpoint_viewed_at, Ok).await
     }
 
     /// Query the database for a `page` of some sub-type of Object. The page uses the bytes of an
     /// Object ID and the checkpoint when the query was made as the cursor, and can optionally be
     /// further `filter`-ed. The subtype is created using the `downcast` function, which is allowed
-    /// to fail, if the downcast has failed.
+    /// to fail, if the downcast has failed. Filters on fields in `filter` are resolved using
+    /// `resolver`, and objects that do not satisfy them are dropped from the page, and replaced by
+    /// scanning further, within the database's query cost limit.
     ///
     /// `checkpoint_viewed_at` represents the checkpoint sequence number at which this page was
     /// queried for, or `None` if the data was requested at the latest checkpoint. Each entity
@@ -736,6 +753,7 @@ impl Object {
     /// created the cursor.
     pub(crate) async fn paginate_subtype<T: OutputType>(
         db: &Db,
+        resolver: &Resolver<PackageCache>,
         page: Page<Cursor>,
         filter: ObjectFilter,
         checkpoint_viewed_at: Option<u64>,
@@ -745,39 +763,154 @@ impl Object {
         // consistent. Otherwise, use the value from the parameter, or set to None. This is so that
         // paginated queries are consistent with the previous query that created the cursor.
         let cursor_viewed_at = page.validate_cursor_consistency()?;
-        let checkpoint_viewed_at: Option<u64> = cursor_viewed_at.or(checkpoint_viewed_at);
+        let mut checkpoint_viewed_at: Option<u64> = cursor_viewed_at.or(checkpoint_viewed_at);
+
+        let fields = FieldPredicates::resolve(&filter, resolver).await?;
+
+        // Field comparisons that could not be pushed down to the database are checked against the
+        // objects it returns. To avoid returning short (or empty) pages while there are still
+        // objects to come, the range is scanned again from where the last query left off, until
+        // the page is full (with one extra object to detect the next page), the range runs out,
+        // or the estimated cost of the scan would exceed the database's query cost limit.
+        let residual = fields.clone().filter(FieldPredicates::has_residual);
+        let max_cost = db.limits.max_db_query_cost as f64;
+        let mut spent = 0.0;
+
+        let mut scan = page.clone();
+        let mut matched: Vec<(Cursor, Object)> = vec![];
+        let mut outer_page = None;
+        let mut inner_page = false;
+        let mut exhausted = false;
+
+        loop {
+            let filter = filter.clone();
+            let pushdown = fields.clone();
+            let query_page = scan.clone();
+            let budget = outer_page.is_some().then_some(max_cost - spent);
+
+            let response = db
+                .execute_repeatable(move |conn| {
+                    let Some((lhs, rhs)) = consistent_range(conn, checkpoint_viewed_at)? else {
+                        return Ok::<_, diesel::result::Error>(None);
+                    };
+
+                    let query = objects_query(
+                        &filter,
+                        pushdown.as_ref(),
+                        lhs as i64,
+                        rhs as i64,
+                        &query_page,
+                    );
+
+                    let cost = conn.estimated_cost(|| {
+                        query_page
+                            .apply::<StoredHistoryObject>(query.clone())
+                            .into_boxed()
+                    });
+
+                    // Every query after the first needs to fit in what remains of the budget.
+                    if let Some(budget) = budget {
+                        if !cost.is_some_and(|cost| cost <= budget) {
+                            return Ok(Some((rhs, cost, None)));
+                        }
+                    }
+
+                    let (prev, next, results) =
+                        query_page.paginate_raw_query::<StoredHistoryObject>(conn, rhs, query)?;
+
+                    Ok(Some((
+                        rhs,
+                        cost,
+                        Some((prev, next, results.collect::<Vec<_>>())),
+                    )))
+                })
+                .await?;
 
-        let response = db
-            .execute_repeatable(move |conn| {
-                let Some((lhs, rhs)) = consistent_range(conn, checkpoint_viewed_at)? else {
-                    return Ok::<_, diesel::result::Error>(None);
-                };
+            let Some((rhs, cost, result)) = response else {
+                return Err(Error::Client(
+                    "Requested data is outside the available range".to_string(),
+                ));
+            };
 
-                let result = page.paginate_raw_query::<StoredHistoryObject>(
-                    conn,
-                    rhs,
-                    objects_query(&filter, lhs as i64, rhs as i64, &page),
-                )?;
+            // Later queries must see the same snapshot of the data as the first.
+            checkpoint_viewed_at = Some(rhs);
+            spent += cost.unwrap_or(max_cost);
 
-                Ok(Some((result, rhs)))
-            })
-            .await?;
+            let Some((prev, next, mut results)) = result else {
+                exhausted = true;
+                break;
+            };
+
+            // Whether there are entries beyond the page's outer bound is only known from the first
+            // query, and whether there are entries beyond the last one scanned, from the last.
+            let (outer, inner) = if page.is_from_front() {
+                (prev, next)
+            } else {
+                results.reverse();
+                (next, prev)
+            };
+
+            outer_page.get_or_insert(outer);
+            let Some(last) = results.last() else {
+                break;
+            };
 
-        let Some(((prev, next, results), checkpoint_viewed_at)) = response else {
+            inner_page = inner;
+            let last = last.cursor(rhs);
+            let scanned = results.len();
+
+            for stored in results {
+                // To maintain consistency, the returned cursor should have the same upper-bound as
+                // the checkpoint found on the cursor.
+                let cursor = stored.cursor(rhs);
+                let object = Object::try_from_stored_history_object(stored, Some(rhs))?;
+                if let Some(fields) = &residual {
+                    if !fields.matches(&object)? {
+                        continue;
+                    }
+                }
+
+                matched.push((cursor, object));
+            }
+
+            if residual.is_none() || matched.len() > page.limit() || scanned < page.limit() {
+                break;
+            }
+
+            scan = scan.continue_from(last);
+        }
+
+        if exhausted && matched.is_empty() {
             return Err(Error::Client(
-                "Requested data is outside the available range".to_string(),
+                "No objects matched the field filters within the query's cost limit, try a more \
+                 selective filter"
+                    .to_string(),
             ));
+        }
+
+        // If the scan stopped early, or found more than a page's worth of objects, then there are
+        // more objects past the last one in the page.
+        if matched.len() > page.limit() {
+            matched.truncate(page.limit());
+            inner_page = true;
+        } else if exhausted {
+            inner_page = true;
+        }
+
+        let outer_page = outer_page.unwrap_or(false) && !matched.is_empty();
+        let inner_page = inner_page && !matched.is_empty();
+
+        let (prev, next) = if page.is_from_front() {
+            (outer_page, inner_page)
+        } else {
+            matched.reverse();
+            (inner_page, outer_page)
         };
 
         let mut conn: Connection<String, T> = Connection::new(prev, next);
-
-        for stored in results {
-            // To maintain consistency, the returned cursor should have the same upper-bound as the
-            // checkpoint found on the cursor.
-            let cursor = stored.cursor(checkpoint_viewed_at).encode_cursor();
-            let object =
-                Object::try_from_stored_history_object(stored, Some(checkpoint_viewed_at))?;
-            conn.edges.push(Edge::new(cursor, downcast(object)?));
+        for (cursor, object) in matched {
+            conn.edges
+                .push(Edge::new(cursor.encode_cursor(), downcast(object)?));
         }
 
         Ok(conn)
@@ -1126,6 +1259,8 @@ impl ObjectFilter {
             owner: intersect!(owner, intersect::by_eq)?,
             object_ids,
             object_keys,
+            // Field filters are conjunctive, so their intersection is their concatenation.
+            fields: intersect!(fields, |f, g| Some([f, g].concat()))?,
         })
     }
 
@@ -1367,10 +1502,15 @@ pub(crate) async fn deserialize_move_struct(
 
 /// Constructs a raw query to fetch objects from the database. Objects are filtered out if they
 /// satisfy the criteria but have a later version in the same checkpoint. If object keys are
-/// provided, or no filters are specified at all, then this final condition is not applied.
-fn objects_query(filter: &ObjectFilter, lhs: i64, rhs: i64, page: &Page<Cursor>) -> RawQuery
-where
-{
+/// provided, or no filters are specified at all, then this final condition is not applied. Any
+/// field comparisons that can be checked by the database are pushed down into the query.
+fn objects_query(
+    filter: &ObjectFilter,
+    fields: Option<&FieldPredicates>,
+    lhs: i64,
+    rhs: i64,
+    page: &Page<Cursor>,
+) -> RawQuery {
     let view = if filter.object_keys.is_some() || !filter.has_filters() {
         View::Historical
     } else {
@@ -1382,7 +1522,13 @@ where
         lhs,
         rhs,
         page,
-        move |query| filter.apply(query),
+        move |query| {
+            let query = filter.apply(query);
+            match fields {
+                Some(fields) => fields.apply(query),
+                None => query,
+            }
+        },
         move |newer| newer,
     )
 }
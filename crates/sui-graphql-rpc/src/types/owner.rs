@@ -10,6 +10,7 @@ use super::move_package::MovePackage;
 use super::object::ObjectLookupKey;
 use super::stake::StakedSui;
 use super::suins_registration::{DomainFormat, NameService, SuinsRegistration};
+use crate::context_data::package_cache::PackageCache;
 use crate::data::Db;
 use crate::types::balance::{self, Balance};
 use crate::types::coin::Coin;
@@ -21,6 +22,7 @@ use crate::types::type_filter::ExactTypeFilter;
 use async_graphql::connection::Connection;
 use async_graphql::*;
 use sui_json_rpc::name_service::NameServiceConfig;
+use sui_package_resolver::Resolver;
 use sui_types::dynamic_field::DynamicFieldType;
 use sui_types::gas_coin::GAS;
 
@@ -322,6 +324,7 @@ impl OwnerImpl {
         };
 
         MoveObject::paginate(
+            ctx.data_unchecked(),
             ctx.data_unchecked(),
             page,
             filter,
@@ -398,6 +401,7 @@ impl OwnerImpl {
     ) -> Result<Connection<String, StakedSui>> {
         let page = Page::from_params(ctx.data_unchecked(), first, after, last, before)?;
         StakedSui::paginate(
+            ctx.data_unchecked(),
             ctx.data_unchecked(),
             page,
             self.address,
@@ -431,6 +435,7 @@ impl OwnerImpl {
         let page = Page::from_params(ctx.data_unchecked(), first, after, last, before)?;
         SuinsRegistration::paginate(
             ctx.data_unchecked::<Db>(),
+            ctx.data_unchecked::<Resolver<PackageCache>>(),
             ctx.data_unchecked::<NameServiceConfig>(),
             page,
             self.address,
@@ -392,6 +392,7 @@ impl Query {
 
         let page = Page::from_params(ctx.data_unchecked(), first, after, last, before)?;
         Object::paginate(
+            ctx.data_unchecked(),
             ctx.data_unchecked(),
             page,
             filter.unwrap_or_default(),
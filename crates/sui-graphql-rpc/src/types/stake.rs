@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::context_data::package_cache::PackageCache;
 use crate::error::Error;
 use crate::{context_data::db_data_provider::PgManager, data::Db};
 
@@ -24,6 +25,7 @@ use async_graphql::connection::Connection;
 use async_graphql::*;
 use move_core_types::language_storage::StructTag;
 use sui_json_rpc_types::{Stake as RpcStakedSui, StakeStatus as RpcStakeStatus};
+use sui_package_resolver::Resolver;
 use sui_types::base_types::MoveObjectType;
 use sui_types::governance::StakedSui as NativeStakedSui;
 
@@ -367,6 +369,7 @@ impl StakedSui {
     /// state, it will be as if it was read at the same checkpoint.
     pub(crate) async fn paginate(
         db: &Db,
+        resolver: &Resolver<PackageCache>,
         page: Page<object::Cursor>,
         owner: SuiAddress,
         checkpoint_viewed_at: Option<u64>,
@@ -379,7 +382,7 @@ impl StakedSui {
             ..Default::default()
         };
 
-        Object::paginate_subtype(db, page, filter, checkpoint_viewed_at, |object| {
+        Object::paginate_subtype(db, resolver, page, filter, checkpoint_viewed_at, |object| {
             let address = object.address;
             let move_object = MoveObject::try_from(&object).map_err(|_| {
                 Error::Internal(format!(
@@ -24,6 +24,7 @@ use super::{
 };
 use crate::{
     consistency::{build_objects_query, consistent_range, View},
+    context_data::package_cache::PackageCache,
     data::{Db, DbConnection, QueryExecutor},
     error::Error,
 };
@@ -34,6 +35,7 @@ use sui_indexer::models::objects::StoredHistoryObject;
 use sui_json_rpc::name_service::{
     Domain as NativeDomain, NameRecord, NameServiceConfig, NameServiceError,
 };
+use sui_package_resolver::Resolver;
 use sui_types::{base_types::SuiAddress as NativeSuiAddress, dynamic_field::Field, id::UID};
 
 const MOD_REGISTRATION: &IdentStr = ident_str!("suins_registration");
@@ -545,6 +547,7 @@ impl SuinsRegistration {
     /// state, it will be as if it was read at the same checkpoint.
     pub(crate) async fn paginate(
         db: &Db,
+        resolver: &Resolver<PackageCache>,
         config: &NameServiceConfig,
         page: Page<object::Cursor>,
         owner: SuiAddress,
@@ -558,7 +561,7 @@ impl SuinsRegistration {
             ..Default::default()
         };
 
-        Object::paginate_subtype(db, page, filter, checkpoint_viewed_at, |object| {
+        Object::paginate_subtype(db, resolver, page, filter, checkpoint_viewed_at, |object| {
             let address = object.address;
             let move_object = MoveObject::try_from(&object).map_err(|_| {
                 Error::Internal(format!(
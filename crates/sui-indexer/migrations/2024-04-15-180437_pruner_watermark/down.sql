@@ -0,0 +1,2 @@
+DROP PROCEDURE IF EXISTS drop_partition;
+DROP TABLE IF EXISTS pruner_watermark;
@@ -0,0 +1,20 @@
+-- The lower bound of the data retained by the pruner. Transactions, events, transaction indices
+-- and object history from before `epoch` (equivalently, from before `checkpoint_sequence_number`
+-- and `tx_sequence_number`) may have been pruned. The table has at most one row, and no rows if
+-- nothing has been pruned yet.
+CREATE TABLE pruner_watermark
+(
+    -- Always 0, to restrict the table to a single row.
+    id                          SMALLINT     PRIMARY KEY DEFAULT 0 CHECK (id = 0),
+    epoch                       BIGINT       NOT NULL,
+    checkpoint_sequence_number  BIGINT       NOT NULL,
+    tx_sequence_number          BIGINT       NOT NULL
+);
+
+CREATE OR REPLACE PROCEDURE drop_partition(table_name TEXT, epoch BIGINT)
+LANGUAGE plpgsql
+AS $$
+BEGIN
+    EXECUTE format('DROP TABLE IF EXISTS %I_partition_%s', table_name, epoch);
+END;
+$$;
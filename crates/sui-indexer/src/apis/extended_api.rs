@@ -78,6 +78,14 @@ impl ExtendedApiServer for ExtendedApi {
             .await?;
         Ok(latest_checkpoint.network_total_transactions.into())
     }
+
+    async fn get_lowest_available_checkpoint(&self) -> RpcResult<BigInt<u64>> {
+        let checkpoint = self
+            .inner
+            .spawn_blocking(|this| this.get_lowest_available_checkpoint())
+            .await?;
+        Ok(checkpoint.into())
+    }
 }
 
 impl SuiRpcModule for ExtendedApi {
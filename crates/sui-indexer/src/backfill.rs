@@ -0,0 +1,478 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Backfills tables and columns that were added after the indexer started, by re-indexing a range
+//! of checkpoints fetched from a remote checkpoint store.
+//!
+//! Backfills are idempotent, and safe to run alongside a live indexer: tables are only ever
+//! inserted into when a row does not already exist, and columns are only written for rows that
+//! already exist. Tables that hold the latest state of objects (`objects`, `objects_snapshot`,
+//! `display`) cannot be backfilled, as replaying old checkpoints would regress them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use clap::{Args, ValueEnum};
+use futures::{StreamExt, TryStreamExt};
+use prometheus::Registry;
+use tokio::sync::oneshot;
+use tracing::info;
+
+use sui_data_ingestion_core::{
+    DataIngestionMetrics, IndexerExecutor, ProgressStore, ReaderOptions, Worker, WorkerPool,
+};
+use sui_types::full_checkpoint_content::CheckpointData;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+
+use crate::errors::IndexerError;
+use crate::handlers::checkpoint_handler::index_checkpoint;
+use crate::handlers::CheckpointDataToCommit;
+use crate::metrics::IndexerMetrics;
+use crate::store::{IndexerStore, PgIndexerStore};
+
+const BACKFILL_TASK_NAME: &str = "backfill";
+const BACKFILL_WORKERS_PER_CHUNK: usize = 5;
+
+#[derive(Args, Clone, Debug)]
+pub struct BackfillConfig {
+    /// First checkpoint to backfill.
+    #[clap(long)]
+    pub start_checkpoint: u64,
+    /// Last checkpoint to backfill, inclusive.
+    #[clap(long)]
+    pub end_checkpoint: u64,
+    /// Remote store to fetch checkpoints from, e.g. https://checkpoints.mainnet.sui.io
+    #[clap(long)]
+    pub remote_store_url: String,
+    /// Tables to backfill. Rows that already exist are left untouched.
+    #[clap(long, value_enum, value_delimiter = ',')]
+    pub tables: Vec<BackfillTable>,
+    /// Columns to backfill on rows that already exist, as `table.column`.
+    #[clap(long, value_delimiter = ',')]
+    pub columns: Vec<BackfillColumn>,
+    /// Number of checkpoints in each chunk of the range.
+    #[clap(long, default_value = "1000")]
+    pub chunk_size: u64,
+    /// Number of chunks backfilled concurrently.
+    #[clap(long, default_value = "10")]
+    pub concurrency: usize,
+}
+
+impl BackfillConfig {
+    fn validate(&self) -> Result<(), IndexerError> {
+        if self.start_checkpoint > self.end_checkpoint {
+            return Err(IndexerError::InvalidArgumentError(format!(
+                "Start checkpoint {} is after end checkpoint {}",
+                self.start_checkpoint, self.end_checkpoint
+            )));
+        }
+        if self.chunk_size == 0 || self.concurrency == 0 {
+            return Err(IndexerError::InvalidArgumentError(
+                "Chunk size and concurrency must be positive".to_string(),
+            ));
+        }
+        if self.tables.is_empty() && self.columns.is_empty() {
+            return Err(IndexerError::InvalidArgumentError(
+                "Nothing to backfill, at least one of --tables or --columns is required"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The ranges of checkpoints, inclusive at both ends, that the backfill is split into.
+    fn chunks(&self) -> impl Iterator<Item = (u64, u64)> {
+        let (end, chunk_size) = (self.end_checkpoint, self.chunk_size);
+        (self.start_checkpoint..=end)
+            .step_by(chunk_size as usize)
+            .map(move |start| (start, end.min(start.saturating_add(chunk_size - 1))))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[value(rename_all = "snake_case")]
+pub enum BackfillTable {
+    Checkpoints,
+    Transactions,
+    Events,
+    TxIndices,
+    Packages,
+    Epochs,
+    ObjectsHistory,
+}
+
+impl fmt::Display for BackfillTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Value names are the table names, and no variant is skipped.
+        let value = self.to_possible_value().unwrap();
+        write!(f, "{}", value.get_name())
+    }
+}
+
+impl BackfillTable {
+    /// The columns identifying a row of the table, if its columns can be backfilled.
+    pub fn primary_key(&self) -> Option<&'static [&'static str]> {
+        match self {
+            Self::Checkpoints => Some(&["sequence_number"]),
+            Self::Transactions => Some(&["tx_sequence_number", "checkpoint_sequence_number"]),
+            Self::Events => Some(&["tx_sequence_number", "event_sequence_number"]),
+            Self::ObjectsHistory => {
+                Some(&["checkpoint_sequence_number", "object_id", "object_version"])
+            }
+            // These span several tables, or are upserted by the indexer already.
+            Self::TxIndices | Self::Packages | Self::Epochs => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BackfillColumn {
+    pub table: BackfillTable,
+    pub column: String,
+}
+
+impl FromStr for BackfillColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((table, column)) = s.split_once('.') else {
+            return Err(format!("Expected `table.column`, got `{s}`"));
+        };
+        let table = <BackfillTable as ValueEnum>::from_str(table, true)?;
+        let Some(primary_key) = table.primary_key() else {
+            return Err(format!(
+                "Backfilling columns of table {table} is not supported"
+            ));
+        };
+        // The column is interpolated into SQL, so only plain identifiers are accepted.
+        if column.is_empty()
+            || !column
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(format!("Invalid column name `{column}`"));
+        }
+        if primary_key.contains(&column) {
+            return Err(format!(
+                "Column `{column}` is part of the primary key of table {table}"
+            ));
+        }
+        Ok(Self {
+            table,
+            column: column.to_string(),
+        })
+    }
+}
+
+/// Backfills the configured tables and columns for every checkpoint in the configured range.
+pub async fn run_backfill(
+    config: BackfillConfig,
+    store: PgIndexerStore,
+    metrics: IndexerMetrics,
+) -> Result<(), IndexerError> {
+    config.validate()?;
+
+    let mut tables = config.tables.clone();
+    tables.sort();
+    tables.dedup();
+    let mut columns: BTreeMap<BackfillTable, Vec<String>> = BTreeMap::new();
+    for BackfillColumn { table, column } in &config.columns {
+        let table_columns = columns.entry(*table).or_default();
+        if !table_columns.contains(column) {
+            table_columns.push(column.clone());
+        }
+    }
+    let worker = BackfillWorker {
+        store,
+        metrics,
+        tables,
+        columns,
+        end_checkpoint: config.end_checkpoint,
+    };
+
+    info!(
+        "Backfilling checkpoints {} to {} with tables {:?} and columns {:?}",
+        config.start_checkpoint, config.end_checkpoint, worker.tables, worker.columns
+    );
+    futures::stream::iter(config.chunks())
+        .map(|(start, end)| backfill_chunk(worker.clone(), &config.remote_store_url, start, end))
+        .buffer_unordered(config.concurrency)
+        .try_collect::<Vec<_>>()
+        .await?;
+    info!(
+        "Backfilled checkpoints {} to {}",
+        config.start_checkpoint, config.end_checkpoint
+    );
+    Ok(())
+}
+
+/// Runs an ingestion pipeline over checkpoints `start..=end`, until all of them are backfilled.
+async fn backfill_chunk(
+    worker: BackfillWorker,
+    remote_store_url: &str,
+    start: CheckpointSequenceNumber,
+    end: CheckpointSequenceNumber,
+) -> Result<(), IndexerError> {
+    let (exit_sender, exit_receiver) = oneshot::channel();
+    let progress_store = BackfillProgressStore {
+        start,
+        end,
+        exit_sender: Some(exit_sender),
+    };
+    let mut executor = IndexerExecutor::new(
+        progress_store,
+        1,
+        DataIngestionMetrics::new(&Registry::new()),
+    );
+    executor
+        .register(WorkerPool::new(
+            worker,
+            BACKFILL_TASK_NAME.to_string(),
+            BACKFILL_WORKERS_PER_CHUNK,
+        ))
+        .await?;
+    let local_dir = tempfile::tempdir().map_err(anyhow::Error::from)?;
+    executor
+        .run(
+            local_dir.path().to_path_buf(),
+            Some(remote_store_url.to_string()),
+            vec![],
+            ReaderOptions::default(),
+            exit_receiver,
+        )
+        .await?;
+    info!("Backfilled checkpoints {} to {}", start, end);
+    Ok(())
+}
+
+#[derive(Clone)]
+struct BackfillWorker {
+    store: PgIndexerStore,
+    metrics: IndexerMetrics,
+    tables: Vec<BackfillTable>,
+    columns: BTreeMap<BackfillTable, Vec<String>>,
+    end_checkpoint: CheckpointSequenceNumber,
+}
+
+impl BackfillWorker {
+    async fn backfill(&self, data: CheckpointDataToCommit) -> Result<(), IndexerError> {
+        for (table, columns) in &self.columns {
+            self.store
+                .backfill_columns(*table, columns.clone(), &data)
+                .await?;
+        }
+
+        let CheckpointDataToCommit {
+            checkpoint,
+            transactions,
+            events,
+            tx_indices,
+            object_history_changes,
+            packages,
+            epoch,
+            ..
+        } = data;
+        let mut checkpoint = Some(checkpoint);
+        let mut transactions = Some(transactions);
+        let mut events = Some(events);
+        let mut tx_indices = Some(tx_indices);
+        let mut object_history_changes = Some(object_history_changes);
+        let mut packages = Some(packages);
+        let mut epoch = epoch;
+        // Tables are deduplicated, so each field is taken at most once.
+        for table in &self.tables {
+            match table {
+                BackfillTable::Checkpoints => {
+                    let checkpoints = checkpoint.take().into_iter().collect();
+                    self.store.persist_checkpoints(checkpoints).await?
+                }
+                BackfillTable::Transactions => {
+                    let transactions = transactions.take().unwrap_or_default();
+                    self.store.persist_transactions(transactions).await?
+                }
+                BackfillTable::Events => {
+                    let events = events.take().unwrap_or_default();
+                    self.store.persist_events(events).await?
+                }
+                BackfillTable::TxIndices => {
+                    let tx_indices = tx_indices.take().unwrap_or_default();
+                    self.store.persist_tx_indices(tx_indices).await?
+                }
+                BackfillTable::Packages => {
+                    let packages = packages.take().unwrap_or_default();
+                    self.store.persist_packages(packages).await?
+                }
+                BackfillTable::Epochs => {
+                    if let Some(epoch) = epoch.take() {
+                        self.store.persist_epoch(epoch).await?
+                    }
+                }
+                BackfillTable::ObjectsHistory => {
+                    let changes = object_history_changes.take().into_iter().collect();
+                    self.store.persist_object_history(changes).await?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for BackfillWorker {
+    async fn process_checkpoint(&self, checkpoint: CheckpointData) -> anyhow::Result<()> {
+        // The reader fetches ahead of the chunk, these checkpoints belong to another one.
+        if checkpoint.checkpoint_summary.sequence_number > self.end_checkpoint {
+            return Ok(());
+        }
+        let data = index_checkpoint(self.store.clone(), checkpoint, self.metrics.clone()).await?;
+        self.backfill(data).await?;
+        Ok(())
+    }
+}
+
+/// Starts the pipeline at the beginning of a chunk, and stops it once the whole chunk is processed.
+struct BackfillProgressStore {
+    start: CheckpointSequenceNumber,
+    end: CheckpointSequenceNumber,
+    exit_sender: Option<oneshot::Sender<()>>,
+}
+
+#[async_trait]
+impl ProgressStore for BackfillProgressStore {
+    async fn load(&mut self, _task_name: String) -> anyhow::Result<CheckpointSequenceNumber> {
+        Ok(self.start)
+    }
+
+    async fn save(
+        &mut self,
+        _task_name: String,
+        checkpoint_number: CheckpointSequenceNumber,
+    ) -> anyhow::Result<()> {
+        // `checkpoint_number` is the next checkpoint to process.
+        if checkpoint_number > self.end {
+            if let Some(exit_sender) = self.exit_sender.take() {
+                let _ = exit_sender.send(());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(start: u64, end: u64, chunk_size: u64) -> BackfillConfig {
+        BackfillConfig {
+            start_checkpoint: start,
+            end_checkpoint: end,
+            remote_store_url: "https://checkpoints.testnet.sui.io".to_string(),
+            tables: vec![BackfillTable::Checkpoints],
+            columns: vec![],
+            chunk_size,
+            concurrency: 1,
+        }
+    }
+
+    #[test]
+    fn test_table_names() {
+        assert_eq!(BackfillTable::ObjectsHistory.to_string(), "objects_history");
+        assert_eq!(BackfillTable::TxIndices.to_string(), "tx_indices");
+        assert_eq!(
+            <BackfillTable as ValueEnum>::from_str("objects_history", true).unwrap(),
+            BackfillTable::ObjectsHistory
+        );
+    }
+
+    #[test]
+    fn test_parse_column() {
+        let column: BackfillColumn = "transactions.success_command_count".parse().unwrap();
+        assert_eq!(column.table, BackfillTable::Transactions);
+        assert_eq!(column.column, "success_command_count");
+
+        let column: BackfillColumn = "objects_history.df_kind".parse().unwrap();
+        assert_eq!(column.table, BackfillTable::ObjectsHistory);
+        assert_eq!(column.column, "df_kind");
+    }
+
+    #[test]
+    fn test_parse_column_errors() {
+        let err = |s: &str| BackfillColumn::from_str(s).unwrap_err();
+
+        assert!(err("transactions").contains("Expected `table.column`"));
+        // Tables outside of the backfillable ones, including the live object tables.
+        err("objects.owner_id");
+        err("no_such_table.column");
+        assert!(err("tx_indices.sender").contains("not supported"));
+        assert!(err("epochs.epoch_total_transactions").contains("not supported"));
+        assert!(err("transactions.").contains("Invalid column name"));
+        assert!(err("transactions.Sender").contains("Invalid column name"));
+        assert!(err("transactions.x; DROP TABLE transactions").contains("Invalid column name"));
+        assert!(err("transactions.a.b").contains("Invalid column name"));
+        assert!(err("transactions.tx_sequence_number").contains("primary key"));
+        assert!(err("events.event_sequence_number").contains("primary key"));
+    }
+
+    #[test]
+    fn test_validate_config() {
+        config(0, 0, 1).validate().unwrap();
+        config(5, 10, 100).validate().unwrap();
+        assert!(config(10, 5, 100).validate().is_err());
+        assert!(config(0, 10, 0).validate().is_err());
+
+        let mut no_concurrency = config(0, 10, 1);
+        no_concurrency.concurrency = 0;
+        assert!(no_concurrency.validate().is_err());
+
+        let mut nothing = config(0, 10, 1);
+        nothing.tables.clear();
+        assert!(nothing.validate().is_err());
+        nothing.columns = vec!["checkpoints.checkpoint_digest".parse().unwrap()];
+        nothing.validate().unwrap();
+    }
+
+    #[test]
+    fn test_chunks() {
+        let chunks: Vec<_> = config(0, 9, 5).chunks().collect();
+        assert_eq!(chunks, vec![(0, 4), (5, 9)]);
+
+        let chunks: Vec<_> = config(3, 10, 4).chunks().collect();
+        assert_eq!(chunks, vec![(3, 6), (7, 10)]);
+
+        let chunks: Vec<_> = config(3, 11, 4).chunks().collect();
+        assert_eq!(chunks, vec![(3, 6), (7, 10), (11, 11)]);
+
+        let chunks: Vec<_> = config(7, 7, 1000).chunks().collect();
+        assert_eq!(chunks, vec![(7, 7)]);
+
+        let chunks: Vec<_> = config(u64::MAX - 2, u64::MAX, 2).chunks().collect();
+        assert_eq!(
+            chunks,
+            vec![(u64::MAX - 2, u64::MAX - 1), (u64::MAX, u64::MAX)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_progress_store_exits_after_chunk() {
+        let (exit_sender, mut exit_receiver) = oneshot::channel();
+        let mut store = BackfillProgressStore {
+            start: 10,
+            end: 20,
+            exit_sender: Some(exit_sender),
+        };
+        let task = BACKFILL_TASK_NAME.to_string();
+
+        assert_eq!(store.load(task.clone()).await.unwrap(), 10);
+        store.save(task.clone(), 15).await.unwrap();
+        store.save(task.clone(), 20).await.unwrap();
+        assert!(exit_receiver.try_recv().is_err());
+
+        // The last checkpoint of the chunk was processed.
+        store.save(task.clone(), 21).await.unwrap();
+        exit_receiver.try_recv().unwrap();
+        // Saving again does not send twice.
+        store.save(task, 22).await.unwrap();
+    }
+}
@@ -42,7 +42,6 @@ use crate::errors::IndexerError;
 use crate::framework::interface::Handler;
 use crate::metrics::IndexerMetrics;
 
-use crate::db::PgConnectionPool;
 use crate::store::package_resolver::{IndexerStorePackageResolver, InterimPackageResolver};
 use crate::store::{IndexerStore, PgIndexerStore};
 use crate::types::{
@@ -141,8 +140,7 @@ where
         let packages = Self::index_packages(checkpoints, &self.metrics);
         let package_objects = Self::get_package_objects(checkpoints);
 
-        let pg_blocking_cp = self.pg_blocking_cp()?;
-        let package_db_resolver = IndexerStorePackageResolver::new(pg_blocking_cp);
+        let package_db_resolver = package_db_resolver(&self.state)?;
         let in_mem_package_resolver = InterimPackageResolver::new(
             package_db_resolver,
             self.package_buffer.clone(),
@@ -715,16 +713,50 @@ where
             })
             .collect()
     }
+}
 
-    fn pg_blocking_cp(&self) -> Result<PgConnectionPool, IndexerError> {
-        let state_as_any = self.state.as_any();
-        if let Some(pg_state) = state_as_any.downcast_ref::<PgIndexerStore>() {
-            return Ok(pg_state.blocking_cp());
-        }
-        Err(IndexerError::UncategorizedError(anyhow::anyhow!(
-            "Failed to downcast state to PgIndexerStore"
-        )))
+/// Index a single checkpoint outside of the main indexing pipeline, e.g. to backfill tables.
+/// Packages are resolved from the store, and from the checkpoint itself.
+pub(crate) async fn index_checkpoint<S>(
+    state: S,
+    data: CheckpointData,
+    metrics: IndexerMetrics,
+) -> Result<CheckpointDataToCommit, IndexerError>
+where
+    S: IndexerStore + Clone + Sync + Send + 'static,
+{
+    let checkpoints = [data];
+    let packages = CheckpointHandler::<S>::index_packages(&checkpoints, &metrics);
+    let package_objects = CheckpointHandler::<S>::get_package_objects(&checkpoints);
+    let in_mem_package_resolver = InterimPackageResolver::new(
+        package_db_resolver(&state)?,
+        Arc::new(Mutex::new(IndexingPackageBuffer::default())),
+        &package_objects,
+        metrics.clone(),
+    );
+    let package_resolver = Arc::new(Resolver::new(in_mem_package_resolver));
+
+    let [data] = checkpoints;
+    CheckpointHandler::index_one_checkpoint(
+        Arc::new(state),
+        data,
+        Arc::new(metrics),
+        packages,
+        package_resolver,
+    )
+    .await
+}
+
+fn package_db_resolver<S: IndexerStore>(
+    state: &S,
+) -> Result<IndexerStorePackageResolver, IndexerError> {
+    let state_as_any = state.as_any();
+    if let Some(pg_state) = state_as_any.downcast_ref::<PgIndexerStore>() {
+        return Ok(IndexerStorePackageResolver::new(pg_state.blocking_cp()));
     }
+    Err(IndexerError::UncategorizedError(anyhow::anyhow!(
+        "Failed to downcast state to PgIndexerStore"
+    )))
 }
 
 async fn get_move_struct_layout_map(
@@ -0,0 +1,77 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::ops::Range;
+use std::time::Duration;
+
+use tracing::{error, info};
+
+use crate::types::IndexerResult;
+use crate::{metrics::IndexerMetrics, store::IndexerStore};
+
+const PRUNER_SLEEP_DURATION_SECS: u64 = 60;
+
+pub struct Pruner<S> {
+    pub store: S,
+    pub epochs_to_keep: u64,
+    metrics: IndexerMetrics,
+}
+
+impl<S> Pruner<S>
+where
+    S: IndexerStore + Clone + Sync + Send + 'static,
+{
+    pub fn new(store: S, epochs_to_keep: u64, metrics: IndexerMetrics) -> Self {
+        Self {
+            store,
+            epochs_to_keep,
+            metrics,
+        }
+    }
+
+    // The pruner keeps the latest `epochs_to_keep` epochs (including the current one), and prunes
+    // everything older, one epoch at a time, starting from the lowest available epoch. An epoch
+    // is only pruned once it has ended and the objects snapshot has caught up past its last
+    // checkpoint, so that consistent reads over `objects_snapshot` and `objects_history` never
+    // reach into pruned data.
+    pub async fn start(&self) -> IndexerResult<()> {
+        info!(
+            epochs_to_keep = self.epochs_to_keep,
+            "Starting indexer pruner..."
+        );
+        loop {
+            let Some((min_epoch, max_epoch)) = self.store.get_available_epoch_range().await? else {
+                tokio::time::sleep(Duration::from_secs(PRUNER_SLEEP_DURATION_SECS)).await;
+                continue;
+            };
+
+            let snapshot_cp = self
+                .store
+                .get_latest_object_snapshot_checkpoint_sequence_number()
+                .await?
+                .unwrap_or_default();
+
+            for epoch in epochs_to_prune(min_epoch, max_epoch, self.epochs_to_keep) {
+                match self.store.get_epoch_last_checkpoint(epoch).await? {
+                    Some(last_cp) if last_cp <= snapshot_cp => {}
+                    _ => break,
+                }
+
+                if let Err(e) = self.store.prune_epoch(epoch).await {
+                    error!("Failed to prune epoch {}: {}", epoch, e);
+                    break;
+                }
+                self.metrics.latest_pruned_epoch.set(epoch as i64);
+                info!("Pruned epoch {}", epoch);
+            }
+
+            tokio::time::sleep(Duration::from_secs(PRUNER_SLEEP_DURATION_SECS)).await;
+        }
+    }
+}
+
+/// The epochs between `min_epoch` and `max_epoch` (inclusive) that fall outside a retention window
+/// of the latest `epochs_to_keep` epochs.
+pub(crate) fn epochs_to_prune(min_epoch: u64, max_epoch: u64, epochs_to_keep: u64) -> Range<u64> {
+    min_epoch..(max_epoch + 1).saturating_sub(epochs_to_keep)
+}
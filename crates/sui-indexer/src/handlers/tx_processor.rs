@@ -116,6 +116,7 @@ impl IndexingModuleBuffer {
     }
 }
 
+#[derive(Default)]
 pub struct IndexingPackageBuffer {
     packages: HashMap<
         ObjectID,
@@ -14,6 +14,7 @@ use crate::errors::IndexerError;
 use crate::framework::fetcher::CheckpointFetcher;
 use crate::handlers::checkpoint_handler::new_handlers;
 use crate::handlers::objects_snapshot_processor::{ObjectsSnapshotProcessor, SnapshotLagConfig};
+use crate::handlers::pruner::Pruner;
 use crate::indexer_reader::IndexerReader;
 use crate::metrics::IndexerMetrics;
 use crate::store::IndexerStore;
@@ -79,6 +80,15 @@ impl Indexer {
         );
         spawn_monitored_task!(objects_snapshot_processor.start());
 
+        if let Some(epochs_to_keep) = config.epochs_to_keep {
+            info!(
+                "Starting indexer pruner with epochs to keep: {}",
+                epochs_to_keep
+            );
+            let pruner = Pruner::new(store.clone(), epochs_to_keep, metrics.clone());
+            spawn_monitored_task!(pruner.start());
+        }
+
         let checkpoint_handler = new_handlers(store, metrics.clone()).await?;
         crate::framework::runner::run(
             mysten_metrics::metered_channel::ReceiverStream::new(
@@ -54,7 +54,10 @@ use crate::{
         transactions::{tx_events_to_sui_tx_events, StoredTransaction},
         tx_indices::TxSequenceNumber,
     },
-    schema::{checkpoints, display, epochs, events, objects, objects_snapshot, transactions},
+    schema::{
+        checkpoints, display, epochs, events, objects, objects_snapshot, pruner_watermark,
+        transactions,
+    },
     store::package_resolver::IndexerStorePackageResolver,
     types::{IndexerResult, OwnerType},
 };
@@ -441,6 +444,18 @@ impl IndexerReader {
         Ok(stored_checkpoint)
     }
 
+    /// Lowest checkpoint whose data has not been pruned, or 0 if nothing has been pruned yet.
+    pub fn get_lowest_available_checkpoint(&self) -> Result<u64, IndexerError> {
+        let checkpoint = self.run_query(|conn| {
+            pruner_watermark::table
+                .select(pruner_watermark::checkpoint_sequence_number)
+                .first::<i64>(conn)
+                .optional()
+        })?;
+
+        Ok(checkpoint.unwrap_or_default() as u64)
+    }
+
     pub fn get_checkpoint(
         &self,
         checkpoint_id: CheckpointId,
@@ -1175,6 +1190,7 @@ impl IndexerReader {
                     unreachable!()
                 }
                 EventFilter::MoveEventField { .. }
+                | EventFilter::MoveEventFieldPredicate { .. }
                 | EventFilter::All(_)
                 | EventFilter::Any(_)
                 | EventFilter::And(_, _)
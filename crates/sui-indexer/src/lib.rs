@@ -5,7 +5,7 @@
 use std::net::SocketAddr;
 
 use anyhow::{anyhow, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use jsonrpsee::http_client::{HeaderMap, HeaderValue, HttpClient, HttpClientBuilder};
 use metrics::IndexerMetrics;
 use prometheus::Registry;
@@ -21,10 +21,13 @@ use crate::apis::{
     CoinReadApi, ExtendedApi, GovernanceReadApi, IndexerApi, MoveUtilsApi, ReadApi,
     TransactionBuilderApi, WriteApi,
 };
+use crate::backfill::BackfillConfig;
 use crate::indexer_reader::IndexerReader;
+use crate::verify::VerifyConfig;
 use errors::IndexerError;
 
 pub mod apis;
+pub mod backfill;
 pub mod db;
 pub mod errors;
 pub mod framework;
@@ -37,6 +40,7 @@ pub mod schema;
 pub mod store;
 pub mod test_utils;
 pub mod types;
+pub mod verify;
 
 #[derive(Parser, Clone, Debug)]
 #[clap(
@@ -73,6 +77,19 @@ pub struct IndexerConfig {
     pub fullnode_sync_worker: bool,
     #[clap(long)]
     pub rpc_server_worker: bool,
+    /// Number of most recent epochs to retain. Older epochs are pruned. Retains all data if unset.
+    #[clap(long)]
+    pub epochs_to_keep: Option<u64>,
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Backfill tables or columns for a range of checkpoints, alongside the live indexer.
+    Backfill(BackfillConfig),
+    /// Verify the DB against the fullnode at `--rpc-client-url`, and optionally repair it.
+    Verify(VerifyConfig),
 }
 
 impl IndexerConfig {
@@ -121,6 +138,8 @@ impl Default for IndexerConfig {
             reset_db: false,
             fullnode_sync_worker: true,
             rpc_server_worker: true,
+            epochs_to_keep: None,
+            command: None,
         }
     }
 }
@@ -4,13 +4,15 @@
 use clap::Parser;
 use tracing::{error, info};
 
+use sui_indexer::backfill::run_backfill;
 use sui_indexer::db::{get_pg_pool_connection, new_pg_connection_pool, reset_database};
 use sui_indexer::errors::IndexerError;
 use sui_indexer::indexer::Indexer;
 use sui_indexer::metrics::start_prometheus_server;
 use sui_indexer::metrics::IndexerMetrics;
 use sui_indexer::store::PgIndexerStore;
-use sui_indexer::IndexerConfig;
+use sui_indexer::verify::run_verify;
+use sui_indexer::{Command, IndexerConfig};
 
 #[tokio::main]
 async fn main() -> Result<(), IndexerError> {
@@ -86,6 +88,21 @@ async fn main() -> Result<(), IndexerError> {
         }
     });
 
+    match indexer_config.command.clone() {
+        Some(Command::Backfill(backfill_config)) => {
+            let store = PgIndexerStore::new(blocking_cp, indexer_metrics.clone());
+            return run_backfill(backfill_config, store, indexer_metrics).await;
+        }
+        Some(Command::Verify(verify_config)) => {
+            let store = PgIndexerStore::new(blocking_cp, indexer_metrics.clone());
+            let rpc_client_url = indexer_config.rpc_client_url.as_str();
+            return run_verify(verify_config, rpc_client_url, store, indexer_metrics)
+                .await
+                .map(|_| ());
+        }
+        None => {}
+    }
+
     if indexer_config.fullnode_sync_worker {
         let store = PgIndexerStore::new(blocking_cp, indexer_metrics.clone());
         return Indexer::start_writer(&indexer_config, store, indexer_metrics).await;
@@ -0,0 +1,27 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::pruner_watermark;
+
+use diesel::prelude::*;
+
+/// The lowest epoch, checkpoint and transaction whose data has not been pruned.
+#[derive(Queryable, Insertable, Selectable, Clone, Debug, Identifiable)]
+#[diesel(table_name = pruner_watermark, primary_key(id))]
+pub struct StoredPrunerWatermark {
+    pub id: i16,
+    pub epoch: i64,
+    pub checkpoint_sequence_number: i64,
+    pub tx_sequence_number: i64,
+}
+
+impl StoredPrunerWatermark {
+    pub fn new(epoch: u64, checkpoint_sequence_number: u64, tx_sequence_number: u64) -> Self {
+        Self {
+            id: 0,
+            epoch: epoch as i64,
+            checkpoint_sequence_number: checkpoint_sequence_number as i64,
+            tx_sequence_number: tx_sequence_number as i64,
+        }
+    }
+}
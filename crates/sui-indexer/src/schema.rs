@@ -157,6 +157,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    pruner_watermark (id) {
+        id -> Int2,
+        epoch -> Int8,
+        checkpoint_sequence_number -> Int8,
+        tx_sequence_number -> Int8,
+    }
+}
+
 diesel::table! {
     transactions (tx_sequence_number, checkpoint_sequence_number) {
         tx_sequence_number -> Int8,
@@ -236,6 +245,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     objects_history_partition_0,
     objects_snapshot,
     packages,
+    pruner_watermark,
     transactions,
     transactions_partition_0,
     tx_calls,
@@ -68,5 +68,16 @@ pub trait IndexerStore: Any + Clone + Sync + Send + 'static {
         epoch: u64,
     ) -> Result<u64, IndexerError>;
 
+    /// The range of epochs that the store has data for, as the lowest epoch that has not been
+    /// pruned and the latest epoch, or `None` if no epochs have been persisted yet.
+    async fn get_available_epoch_range(&self) -> Result<Option<(u64, u64)>, IndexerError>;
+
+    /// The last checkpoint of `epoch`, or `None` if the epoch has not ended yet.
+    async fn get_epoch_last_checkpoint(&self, epoch: u64) -> Result<Option<u64>, IndexerError>;
+
+    /// Remove transactions, events, transaction indices and object history from `epoch` and all
+    /// epochs before it, and advance the pruner watermark past it. `epoch` must have ended.
+    async fn prune_epoch(&self, epoch: u64) -> Result<(), IndexerError>;
+
     fn as_any(&self) -> &dyn Any;
 }
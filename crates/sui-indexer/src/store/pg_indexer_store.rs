@@ -10,21 +10,23 @@ use std::time::Instant;
 
 use async_trait::async_trait;
 use core::result::Result::Ok;
-use diesel::dsl::max;
+use diesel::dsl::{max, min};
 use diesel::upsert::excluded;
 use diesel::ExpressionMethods;
 use diesel::OptionalExtension;
+use diesel::QueryableByName;
 use diesel::{QueryDsl, RunQueryDsl};
-use itertools::Itertools;
+use itertools::{Either, Itertools};
 use tap::Tap;
 use tracing::info;
 
 use sui_types::base_types::ObjectID;
 
+use crate::backfill::BackfillTable;
 use crate::db::PgConnectionPool;
 use crate::errors::{Context, IndexerError};
-use crate::handlers::EpochToCommit;
 use crate::handlers::TransactionObjectChangesToCommit;
+use crate::handlers::{CheckpointDataToCommit, EpochToCommit};
 use crate::metrics::IndexerMetrics;
 use crate::models::checkpoints::StoredCheckpoint;
 use crate::models::display::StoredDisplay;
@@ -34,10 +36,12 @@ use crate::models::objects::{
     StoredDeletedHistoryObject, StoredDeletedObject, StoredHistoryObject, StoredObject,
 };
 use crate::models::packages::StoredPackage;
+use crate::models::pruner_watermark::StoredPrunerWatermark;
 use crate::models::transactions::StoredTransaction;
 use crate::schema::{
     checkpoints, display, epochs, events, objects, objects_history, objects_snapshot, packages,
-    transactions, tx_calls, tx_changed_objects, tx_input_objects, tx_recipients, tx_senders,
+    pruner_watermark, transactions, tx_calls, tx_changed_objects, tx_input_objects, tx_recipients,
+    tx_senders,
 };
 use crate::store::diesel_macro::{read_only_blocking, transactional_blocking_with_retry};
 use crate::types::{IndexedCheckpoint, IndexedEvent, IndexedPackage, IndexedTransaction, TxIndex};
@@ -70,6 +74,19 @@ const PG_COMMIT_PARALLEL_CHUNK_SIZE: usize = 100;
 // optimistic locking.
 const PG_COMMIT_OBJECTS_PARALLEL_CHUNK_SIZE: usize = 500;
 
+// The number of transactions whose rows are pruned from a table in one DB transaction
+const PG_PRUNE_CHUNK_SIZE_TX: u64 = 10_000;
+
+// Tables that are not partitioned by epoch, whose rows are pruned by transaction sequence number.
+const PRUNED_TX_TABLES: [&str; 6] = [
+    "events",
+    "tx_senders",
+    "tx_recipients",
+    "tx_input_objects",
+    "tx_changed_objects",
+    "tx_calls",
+];
+
 // with rn = 1, we only select the latest version of each object,
 // so that we don't have to update the same object multiple times.
 const UPDATE_OBJECTS_SNAPSHOT_QUERY: &str = r"
@@ -767,7 +784,507 @@ impl PgIndexerStore {
         .map(|v| v as u64)
     }
 
-    async fn execute_in_blocking_worker<F, R>(&self, f: F) -> Result<R, IndexerError>
+    fn get_available_epoch_range(&self) -> Result<Option<(u64, u64)>, IndexerError> {
+        let watermark = read_only_blocking!(&self.blocking_cp, |conn| {
+            pruner_watermark::table
+                .select(pruner_watermark::epoch)
+                .first::<i64>(conn)
+                .optional()
+        })
+        .context("Failed reading pruner watermark from PostgresDB")?;
+
+        let range = read_only_blocking!(&self.blocking_cp, |conn| {
+            epochs::table
+                .select((min(epochs::epoch), max(epochs::epoch)))
+                .first::<(Option<i64>, Option<i64>)>(conn)
+        })
+        .context("Failed reading epoch range from PostgresDB")?;
+
+        let (Some(lo), Some(hi)) = range else {
+            return Ok(None);
+        };
+
+        Ok(Some((watermark.unwrap_or(lo).max(lo) as u64, hi as u64)))
+    }
+
+    fn get_epoch_last_checkpoint(&self, epoch: u64) -> Result<Option<u64>, IndexerError> {
+        read_only_blocking!(&self.blocking_cp, |conn| {
+            epochs::table
+                .filter(epochs::epoch.eq(epoch as i64))
+                .select(epochs::last_checkpoint_id)
+                .first::<Option<i64>>(conn)
+                .optional()
+        })
+        .context("Failed reading epoch's last checkpoint from PostgresDB")
+        .map(|cp| cp.flatten().map(|cp| cp as u64))
+    }
+
+    fn prune_epoch(&self, epoch: u64) -> Result<(), IndexerError> {
+        let Some(last_checkpoint) = self.get_epoch_last_checkpoint(epoch)? else {
+            return Err(IndexerError::PostgresWriteError(format!(
+                "Cannot prune epoch {epoch} before it has ended"
+            )));
+        };
+
+        let guard = self.metrics.prune_epoch_latency.start_timer();
+
+        // Transaction sequence numbers start at 0, so the number of transactions at the end of
+        // the epoch is also the sequence number of the first transaction after it.
+        let next_tx = self.get_network_total_transactions_by_end_of_epoch(epoch)?;
+        let watermark = StoredPrunerWatermark::new(epoch + 1, last_checkpoint + 1, next_tx);
+
+        // Advance the watermark first, so that readers stop serving data from the epoch before it
+        // starts to disappear.
+        transactional_blocking_with_retry!(
+            &self.blocking_cp,
+            |conn| {
+                diesel::insert_into(pruner_watermark::table)
+                    .values(watermark.clone())
+                    .on_conflict(pruner_watermark::id)
+                    .do_update()
+                    .set((
+                        pruner_watermark::epoch.eq(excluded(pruner_watermark::epoch)),
+                        pruner_watermark::checkpoint_sequence_number
+                            .eq(excluded(pruner_watermark::checkpoint_sequence_number)),
+                        pruner_watermark::tx_sequence_number
+                            .eq(excluded(pruner_watermark::tx_sequence_number)),
+                    ))
+                    .execute(conn)
+            },
+            Duration::from_secs(60)
+        )?;
+
+        // Deleting everything below the watermark (rather than just the epoch's range) keeps
+        // pruning idempotent, and cleans up after any previous attempt that was interrupted.
+        for table in PRUNED_TX_TABLES {
+            let deleted = self.prune_tx_table(table, next_tx)?;
+            info!(epoch, "Pruned {} rows from table {}", deleted, table);
+        }
+
+        for table in self.partition_manager.get_table_partitions()?.into_keys() {
+            self.partition_manager.drop_table_partition(table, epoch)?;
+        }
+
+        let elapsed = guard.stop_and_record();
+        info!(elapsed, epoch, "Pruned epoch");
+        Ok(())
+    }
+
+    /// Deletes the rows of `table` whose transaction sequence number is below `next_tx`, starting
+    /// from the lowest one still in the table, one range of `PG_PRUNE_CHUNK_SIZE_TX` transactions
+    /// per DB transaction, so that no single statement has to lock or delete the whole epoch.
+    /// Returns the number of rows deleted.
+    fn prune_tx_table(&self, table: &str, next_tx: u64) -> Result<usize, IndexerError> {
+        #[derive(QueryableByName)]
+        struct MinTx {
+            #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+            min_tx: Option<i64>,
+        }
+
+        let min_tx = read_only_blocking!(&self.blocking_cp, |conn| {
+            diesel::sql_query(format!(
+                "SELECT MIN(tx_sequence_number) AS min_tx FROM {table}"
+            ))
+            .get_result::<MinTx>(conn)
+        })
+        .context(&format!("Failed reading lowest transaction in {table}"))?;
+
+        let Some(mut lo) = min_tx.min_tx.map(|tx| tx as u64) else {
+            return Ok(0);
+        };
+
+        let mut deleted = 0;
+        while lo < next_tx {
+            let hi = next_tx.min(lo + PG_PRUNE_CHUNK_SIZE_TX);
+            deleted += transactional_blocking_with_retry!(
+                &self.blocking_cp,
+                |conn| {
+                    diesel::sql_query(format!(
+                        "DELETE FROM {table} WHERE tx_sequence_number >= $1 AND tx_sequence_number < $2"
+                    ))
+                    .bind::<diesel::sql_types::BigInt, _>(lo as i64)
+                    .bind::<diesel::sql_types::BigInt, _>(hi as i64)
+                    .execute(conn)
+                },
+                Duration::from_secs(60)
+            )?;
+            lo = hi;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Overwrites `columns` of the rows of `table` that already exist in the DB with their values
+    /// in `data`, matching rows on the table's primary key. Rows that do not exist are skipped.
+    ///
+    /// Rows are staged in a temporary table that shadows `table` for the duration of the DB
+    /// transaction, so that they can be written with the same inserts as the regular commit path.
+    pub async fn backfill_columns(
+        &self,
+        table: BackfillTable,
+        columns: Vec<String>,
+        data: &CheckpointDataToCommit,
+    ) -> Result<(), IndexerError> {
+        let Some(primary_key) = table.primary_key() else {
+            return Err(IndexerError::NotSupportedError(format!(
+                "Backfilling columns of table {table} is not supported"
+            )));
+        };
+        let rows = StagedRows::new(table, data);
+        self.execute_in_blocking_worker(move |this| {
+            this.backfill_columns_blocking(table, primary_key, &columns, rows)
+        })
+        .await
+    }
+
+    fn backfill_columns_blocking(
+        &self,
+        table: BackfillTable,
+        primary_key: &[&str],
+        columns: &[String],
+        rows: StagedRows,
+    ) -> Result<(), IndexerError> {
+        let name = table.to_string();
+        let create_staging_table =
+            format!("CREATE TEMP TABLE {name} (LIKE public.{name}) ON COMMIT DROP");
+        let update_columns = format!(
+            "UPDATE public.{name} t SET {} FROM pg_temp.{name} s WHERE {}",
+            columns.iter().map(|c| format!("{c} = s.{c}")).join(", "),
+            primary_key
+                .iter()
+                .map(|k| format!("t.{k} = s.{k}"))
+                .join(" AND "),
+        );
+
+        let updated = transactional_blocking_with_retry!(
+            &self.blocking_cp,
+            |conn| {
+                diesel::sql_query(&create_staging_table).execute(conn)?;
+                // The inserts below resolve `table` to the staging table, as temporary tables
+                // take precedence over the public schema.
+                match &rows {
+                    StagedRows::Checkpoints(rows) => {
+                        for chunk in rows.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                            diesel::insert_into(checkpoints::table)
+                                .values(chunk)
+                                .execute(conn)?;
+                        }
+                    }
+                    StagedRows::Transactions(rows) => {
+                        for chunk in rows.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                            diesel::insert_into(transactions::table)
+                                .values(chunk)
+                                .execute(conn)?;
+                        }
+                    }
+                    StagedRows::Events(rows) => {
+                        for chunk in rows.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                            diesel::insert_into(events::table)
+                                .values(chunk)
+                                .execute(conn)?;
+                        }
+                    }
+                    StagedRows::ObjectsHistory(mutated, deleted) => {
+                        for chunk in mutated.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                            diesel::insert_into(objects_history::table)
+                                .values(chunk)
+                                .execute(conn)?;
+                        }
+                        for chunk in deleted.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                            diesel::insert_into(objects_history::table)
+                                .values(chunk)
+                                .execute(conn)?;
+                        }
+                    }
+                }
+                diesel::sql_query(&update_columns).execute(conn)
+            },
+            Duration::from_secs(60)
+        )?;
+        info!(
+            "Backfilled columns {:?} of {} rows in table {}",
+            columns, updated, name
+        );
+        Ok(())
+    }
+
+    /// Returns the lowest and highest checkpoints in the DB, if any.
+    pub fn get_checkpoint_range(&self) -> Result<Option<(u64, u64)>, IndexerError> {
+        let range = read_only_blocking!(&self.blocking_cp, |conn| {
+            checkpoints::table
+                .select((
+                    min(checkpoints::sequence_number),
+                    max(checkpoints::sequence_number),
+                ))
+                .first::<(Option<i64>, Option<i64>)>(conn)
+        })
+        .context("Failed reading checkpoint range from PostgresDB")?;
+        Ok(match range {
+            (Some(lo), Some(hi)) => Some((lo as u64, hi as u64)),
+            _ => None,
+        })
+    }
+
+    /// Returns the rows written for checkpoint `seq`: the checkpoint itself, its transactions,
+    /// and the events of the transactions in `tx_range`.
+    pub fn get_checkpoint_rows(
+        &self,
+        seq: u64,
+        tx_range: Option<(i64, i64)>,
+    ) -> Result<
+        (
+            Option<StoredCheckpoint>,
+            Vec<StoredTransaction>,
+            Vec<StoredEvent>,
+        ),
+        IndexerError,
+    > {
+        read_only_blocking!(&self.blocking_cp, |conn| {
+            let checkpoint = checkpoints::table
+                .filter(checkpoints::sequence_number.eq(seq as i64))
+                .first::<StoredCheckpoint>(conn)
+                .optional()?;
+            let transactions = transactions::table
+                .filter(transactions::checkpoint_sequence_number.eq(seq as i64))
+                .order(transactions::tx_sequence_number.asc())
+                .load::<StoredTransaction>(conn)?;
+            let events = match tx_range {
+                Some((lo, hi)) => events::table
+                    .filter(events::tx_sequence_number.between(lo, hi))
+                    .order((
+                        events::tx_sequence_number.asc(),
+                        events::event_sequence_number.asc(),
+                    ))
+                    .load::<StoredEvent>(conn)?,
+                None => vec![],
+            };
+            Ok::<_, diesel::result::Error>((checkpoint, transactions, events))
+        })
+        .context("Failed reading checkpoint rows from PostgresDB")
+    }
+
+    /// Returns up to `limit` live objects with IDs greater than `cursor`, ordered by ID.
+    pub fn get_objects_page(
+        &self,
+        cursor: Option<Vec<u8>>,
+        limit: i64,
+    ) -> Result<Vec<StoredObject>, IndexerError> {
+        read_only_blocking!(&self.blocking_cp, |conn| {
+            let mut query = objects::table
+                .order(objects::object_id.asc())
+                .limit(limit)
+                .into_boxed();
+            if let Some(cursor) = &cursor {
+                query = query.filter(objects::object_id.gt(cursor.clone()));
+            }
+            query.load::<StoredObject>(conn)
+        })
+        .context("Failed reading objects from PostgresDB")
+    }
+
+    /// Returns the checkpoint of the transaction with the given digest, if it has been indexed.
+    pub fn get_transaction_checkpoint(&self, digest: Vec<u8>) -> Result<Option<u64>, IndexerError> {
+        let seq = read_only_blocking!(&self.blocking_cp, |conn| {
+            transactions::table
+                .filter(transactions::transaction_digest.eq(digest.clone()))
+                .select(transactions::checkpoint_sequence_number)
+                .first::<i64>(conn)
+                .optional()
+        })
+        .context("Failed reading transaction checkpoint from PostgresDB")?;
+        Ok(seq.map(|s| s as u64))
+    }
+
+    /// Returns the version of each of the objects in `object_ids` that is live in the DB.
+    pub fn get_live_object_versions(
+        &self,
+        object_ids: Vec<Vec<u8>>,
+    ) -> Result<Vec<(Vec<u8>, i64)>, IndexerError> {
+        read_only_blocking!(&self.blocking_cp, |conn| {
+            objects::table
+                .filter(objects::object_id.eq_any(object_ids.clone()))
+                .select((objects::object_id, objects::object_version))
+                .load::<(Vec<u8>, i64)>(conn)
+        })
+        .context("Failed reading live object versions from PostgresDB")
+    }
+
+    /// Replaces the rows of a checkpoint, its transactions, their events, transaction indices and
+    /// object history with `data`, and removes the objects that were deleted or wrapped in the
+    /// checkpoint from the live objects, in a single DB transaction.
+    pub fn repair_checkpoint(&self, data: &CheckpointDataToCommit) -> Result<(), IndexerError> {
+        let seq = data.checkpoint.sequence_number as i64;
+        let checkpoint = StoredCheckpoint::from(&data.checkpoint);
+        let transactions = data
+            .transactions
+            .iter()
+            .map(StoredTransaction::from)
+            .collect::<Vec<_>>();
+        let events = data
+            .events
+            .iter()
+            .cloned()
+            .map(StoredEvent::from)
+            .collect::<Vec<_>>();
+        let (senders, recipients, input_objects, changed_objects, calls) =
+            data.tx_indices.iter().cloned().map(TxIndex::split).fold(
+                (vec![], vec![], vec![], vec![], vec![]),
+                |(
+                    mut senders,
+                    mut recipients,
+                    mut input_objects,
+                    mut changed_objects,
+                    mut calls,
+                ),
+                 index| {
+                    senders.extend(index.0);
+                    recipients.extend(index.1);
+                    input_objects.extend(index.2);
+                    changed_objects.extend(index.3);
+                    calls.extend(index.4);
+                    (senders, recipients, input_objects, changed_objects, calls)
+                },
+            );
+        let (mutated_history, deleted_history): (Vec<_>, Vec<_>) =
+            make_objects_history_to_commit(vec![data.object_history_changes.clone()])
+                .into_iter()
+                .partition_map(|change| match change {
+                    ObjectChangeToCommit::MutatedObject(o) => {
+                        Either::Left(StoredHistoryObject::from(o))
+                    }
+                    ObjectChangeToCommit::DeletedObject(o) => {
+                        Either::Right(StoredDeletedHistoryObject::from(o))
+                    }
+                });
+        let deleted_objects = data
+            .object_changes
+            .deleted_objects
+            .iter()
+            .map(|o| (o.object_id.to_vec(), o.object_version as i64))
+            .collect::<Vec<_>>();
+        let tx_range = transactions
+            .iter()
+            .map(|t| t.tx_sequence_number)
+            .minmax()
+            .into_option();
+
+        transactional_blocking_with_retry!(
+            &self.blocking_cp,
+            |conn| {
+                diesel::delete(checkpoints::table.filter(checkpoints::sequence_number.eq(seq)))
+                    .execute(conn)?;
+                diesel::delete(
+                    transactions::table.filter(transactions::checkpoint_sequence_number.eq(seq)),
+                )
+                .execute(conn)?;
+                if let Some((lo, hi)) = tx_range {
+                    diesel::delete(
+                        events::table.filter(events::tx_sequence_number.between(lo, hi)),
+                    )
+                    .execute(conn)?;
+                    diesel::delete(
+                        tx_senders::table.filter(tx_senders::tx_sequence_number.between(lo, hi)),
+                    )
+                    .execute(conn)?;
+                    diesel::delete(
+                        tx_recipients::table
+                            .filter(tx_recipients::tx_sequence_number.between(lo, hi)),
+                    )
+                    .execute(conn)?;
+                    diesel::delete(
+                        tx_input_objects::table
+                            .filter(tx_input_objects::tx_sequence_number.between(lo, hi)),
+                    )
+                    .execute(conn)?;
+                    diesel::delete(
+                        tx_changed_objects::table
+                            .filter(tx_changed_objects::tx_sequence_number.between(lo, hi)),
+                    )
+                    .execute(conn)?;
+                    diesel::delete(
+                        tx_calls::table.filter(tx_calls::tx_sequence_number.between(lo, hi)),
+                    )
+                    .execute(conn)?;
+                }
+                diesel::delete(
+                    objects_history::table
+                        .filter(objects_history::checkpoint_sequence_number.eq(seq)),
+                )
+                .execute(conn)?;
+
+                // An object that was deleted or wrapped in this checkpoint is only still live if
+                // the DB missed the deletion: versions written after it are left alone.
+                for (object_id, version) in &deleted_objects {
+                    diesel::delete(
+                        objects::table
+                            .filter(objects::object_id.eq(object_id.clone()))
+                            .filter(objects::object_version.lt(*version)),
+                    )
+                    .execute(conn)?;
+                }
+
+                diesel::insert_into(checkpoints::table)
+                    .values(&checkpoint)
+                    .execute(conn)?;
+                for chunk in transactions.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                    diesel::insert_into(transactions::table)
+                        .values(chunk)
+                        .execute(conn)?;
+                }
+                for chunk in events.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                    diesel::insert_into(events::table)
+                        .values(chunk)
+                        .execute(conn)?;
+                }
+                for chunk in senders.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                    diesel::insert_into(tx_senders::table)
+                        .values(chunk)
+                        .execute(conn)?;
+                }
+                for chunk in recipients.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                    diesel::insert_into(tx_recipients::table)
+                        .values(chunk)
+                        .execute(conn)?;
+                }
+                for chunk in input_objects.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                    diesel::insert_into(tx_input_objects::table)
+                        .values(chunk)
+                        .execute(conn)?;
+                }
+                for chunk in changed_objects.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                    diesel::insert_into(tx_changed_objects::table)
+                        .values(chunk)
+                        .execute(conn)?;
+                }
+                for chunk in calls.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                    diesel::insert_into(tx_calls::table)
+                        .values(chunk)
+                        .execute(conn)?;
+                }
+                for chunk in mutated_history.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                    diesel::insert_into(objects_history::table)
+                        .values(chunk)
+                        .execute(conn)?;
+                }
+                for chunk in deleted_history.chunks(PG_COMMIT_CHUNK_SIZE_INTRA_DB_TX) {
+                    diesel::insert_into(objects_history::table)
+                        .values(chunk)
+                        .execute(conn)?;
+                }
+                Ok::<(), IndexerError>(())
+            },
+            Duration::from_secs(60)
+        )?;
+        info!(seq, "Repaired checkpoint");
+        Ok(())
+    }
+
+    /// Overwrites the row of a live object.
+    pub fn repair_object(&self, object: StoredObject) -> Result<(), IndexerError> {
+        self.persist_objects_chunk(vec![ObjectChangeToCommit::MutatedObject(object)])
+    }
+
+    pub(crate) async fn execute_in_blocking_worker<F, R>(&self, f: F) -> Result<R, IndexerError>
     where
         F: FnOnce(Self) -> Result<R, IndexerError> + Send + 'static,
         R: Send + 'static,
@@ -1079,6 +1596,21 @@ impl IndexerStore for PgIndexerStore {
         .await
     }
 
+    async fn get_available_epoch_range(&self) -> Result<Option<(u64, u64)>, IndexerError> {
+        self.execute_in_blocking_worker(|this| this.get_available_epoch_range())
+            .await
+    }
+
+    async fn get_epoch_last_checkpoint(&self, epoch: u64) -> Result<Option<u64>, IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.get_epoch_last_checkpoint(epoch))
+            .await
+    }
+
+    async fn prune_epoch(&self, epoch: u64) -> Result<(), IndexerError> {
+        self.execute_in_blocking_worker(move |this| this.prune_epoch(epoch))
+            .await
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -1128,6 +1660,49 @@ fn make_final_list_of_objects_to_commit(
         .collect()
 }
 
+/// Rows of a table whose columns are being backfilled, see `PgIndexerStore::backfill_columns`.
+enum StagedRows {
+    Checkpoints(Vec<StoredCheckpoint>),
+    Transactions(Vec<StoredTransaction>),
+    Events(Vec<StoredEvent>),
+    ObjectsHistory(Vec<StoredHistoryObject>, Vec<StoredDeletedHistoryObject>),
+}
+
+impl StagedRows {
+    fn new(table: BackfillTable, data: &CheckpointDataToCommit) -> Self {
+        match table {
+            BackfillTable::Checkpoints => {
+                Self::Checkpoints(vec![StoredCheckpoint::from(&data.checkpoint)])
+            }
+            BackfillTable::Transactions => Self::Transactions(
+                data.transactions
+                    .iter()
+                    .map(StoredTransaction::from)
+                    .collect(),
+            ),
+            BackfillTable::Events => {
+                Self::Events(data.events.iter().cloned().map(StoredEvent::from).collect())
+            }
+            BackfillTable::ObjectsHistory => {
+                let mut mutated = vec![];
+                let mut deleted = vec![];
+                for object in
+                    make_objects_history_to_commit(vec![data.object_history_changes.clone()])
+                {
+                    match object {
+                        ObjectChangeToCommit::MutatedObject(o) => mutated.push(o.into()),
+                        ObjectChangeToCommit::DeletedObject(o) => deleted.push(o.into()),
+                    }
+                }
+                Self::ObjectsHistory(mutated, deleted)
+            }
+            BackfillTable::TxIndices | BackfillTable::Packages | BackfillTable::Epochs => {
+                unreachable!("Table {table} has no primary key to backfill columns on")
+            }
+        }
+    }
+}
+
 fn make_objects_history_to_commit(
     tx_object_changes: Vec<TransactionObjectChangesToCommit>,
 ) -> Vec<ObjectChangeToCommit> {
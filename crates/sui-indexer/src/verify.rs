@@ -0,0 +1,423 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verifies the data written by the indexer against a fullnode.
+//!
+//! Checkpoints are re-indexed from the fullnode's REST API, and the rows they produce are compared
+//! with the checkpoint, transaction and event rows in the DB. Objects that the checkpoint deleted
+//! or wrapped are reported if an older version of them is still live in the DB. Live objects (and
+//! with them, coin balances) are compared with the fullnode's copy of the object at the same
+//! version, and are reported as stale if the fullnode has a newer version written by a transaction
+//! the indexer has already indexed.
+//!
+//! Rows written concurrently by a live indexer can be reported as transient discrepancies.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use clap::Args;
+use futures::{StreamExt, TryStreamExt};
+use itertools::Itertools;
+use rand::Rng;
+use tracing::{info, warn};
+
+use sui_types::base_types::{ObjectID, SequenceNumber};
+
+use crate::errors::IndexerError;
+use crate::handlers::checkpoint_handler::index_checkpoint;
+use crate::metrics::IndexerMetrics;
+use crate::models::checkpoints::StoredCheckpoint;
+use crate::models::events::StoredEvent;
+use crate::models::objects::StoredObject;
+use crate::models::transactions::StoredTransaction;
+use crate::store::PgIndexerStore;
+use crate::types::IndexedObject;
+
+const OBJECTS_PAGE_SIZE: i64 = 1000;
+
+#[derive(Args, Clone, Debug)]
+pub struct VerifyConfig {
+    /// First checkpoint to verify. Defaults to the lowest checkpoint in the DB.
+    #[clap(long)]
+    pub start_checkpoint: Option<u64>,
+    /// Last checkpoint to verify, inclusive. Defaults to the highest checkpoint in the DB.
+    #[clap(long)]
+    pub end_checkpoint: Option<u64>,
+    /// Fraction of checkpoints and objects to verify, 1.0 verifies all of them.
+    #[clap(long, default_value = "1.0")]
+    pub sample_rate: f64,
+    /// Skip verifying checkpoints, transactions and events.
+    #[clap(long)]
+    pub skip_checkpoints: bool,
+    /// Skip verifying objects and coin balances.
+    #[clap(long)]
+    pub skip_objects: bool,
+    /// Rewrite the rows that differ from the fullnode.
+    #[clap(long)]
+    pub repair: bool,
+    /// Number of checkpoints or objects verified concurrently.
+    #[clap(long, default_value = "50")]
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct VerificationReport {
+    pub checkpoints_verified: u64,
+    pub objects_verified: u64,
+    /// Objects the fullnode could not return at the version in the DB, e.g. as it was pruned.
+    pub objects_skipped: u64,
+    pub discrepancies: Vec<String>,
+    pub repaired: u64,
+}
+
+enum Outcome {
+    Consistent,
+    Unverifiable,
+    Inconsistent {
+        discrepancies: Vec<String>,
+        repaired: bool,
+    },
+}
+
+impl VerificationReport {
+    fn record_checkpoint(&mut self, outcome: Outcome) {
+        self.checkpoints_verified += 1;
+        self.record(outcome);
+    }
+
+    fn record_object(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Unverifiable => self.objects_skipped += 1,
+            outcome => {
+                self.objects_verified += 1;
+                self.record(outcome);
+            }
+        }
+    }
+
+    fn record(&mut self, outcome: Outcome) {
+        if let Outcome::Inconsistent {
+            discrepancies,
+            repaired,
+        } = outcome
+        {
+            for discrepancy in &discrepancies {
+                warn!("{}", discrepancy);
+            }
+            self.discrepancies.extend(discrepancies);
+            self.repaired += repaired as u64;
+        }
+    }
+}
+
+struct Verifier {
+    config: VerifyConfig,
+    client: sui_rest_api::Client,
+    store: PgIndexerStore,
+    metrics: IndexerMetrics,
+}
+
+/// Verifies the DB against the fullnode at `rpc_client_url`, and repairs it if configured to.
+/// Fails if any discrepancy was left unrepaired.
+pub async fn run_verify(
+    config: VerifyConfig,
+    rpc_client_url: &str,
+    store: PgIndexerStore,
+    metrics: IndexerMetrics,
+) -> Result<VerificationReport, IndexerError> {
+    if !(config.sample_rate > 0.0 && config.sample_rate <= 1.0) {
+        return Err(IndexerError::InvalidArgumentError(format!(
+            "Sample rate must be in (0, 1], got {}",
+            config.sample_rate
+        )));
+    }
+    if config.concurrency == 0 {
+        return Err(IndexerError::InvalidArgumentError(
+            "Concurrency must be positive".to_string(),
+        ));
+    }
+    let verifier = Verifier {
+        config,
+        client: sui_rest_api::Client::new(format!("{}/rest", rpc_client_url)),
+        store,
+        metrics,
+    };
+
+    let mut report = VerificationReport::default();
+    if !verifier.config.skip_checkpoints {
+        verifier.verify_checkpoints(&mut report).await?;
+    }
+    if !verifier.config.skip_objects {
+        verifier.verify_objects(&mut report).await?;
+    }
+
+    info!(
+        "Verified {} checkpoints and {} objects ({} skipped), found {} discrepancies, repaired {}",
+        report.checkpoints_verified,
+        report.objects_verified,
+        report.objects_skipped,
+        report.discrepancies.len(),
+        report.repaired,
+    );
+    if !verifier.config.repair && !report.discrepancies.is_empty() {
+        return Err(IndexerError::PersistentStorageDataCorruptionError(format!(
+            "Found {} discrepancies with the fullnode",
+            report.discrepancies.len()
+        )));
+    }
+    Ok(report)
+}
+
+impl Verifier {
+    fn sampled(&self) -> bool {
+        self.config.sample_rate >= 1.0 || rand::thread_rng().gen_bool(self.config.sample_rate)
+    }
+
+    async fn verify_checkpoints(
+        &self,
+        report: &mut VerificationReport,
+    ) -> Result<(), IndexerError> {
+        let Some((lowest, highest)) = self
+            .store
+            .execute_in_blocking_worker(|this| this.get_checkpoint_range())
+            .await?
+        else {
+            info!("No checkpoints to verify");
+            return Ok(());
+        };
+        let start = self.config.start_checkpoint.unwrap_or(lowest).max(lowest);
+        let end = self.config.end_checkpoint.unwrap_or(highest).min(highest);
+        info!("Verifying checkpoints {} to {}", start, end);
+
+        let checkpoints = (start..=end).filter(|_| self.sampled()).collect::<Vec<_>>();
+        let outcomes = futures::stream::iter(checkpoints)
+            .map(|seq| self.verify_checkpoint(seq))
+            .buffer_unordered(self.config.concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+        for outcome in outcomes {
+            report.record_checkpoint(outcome);
+        }
+        Ok(())
+    }
+
+    async fn verify_checkpoint(&self, seq: u64) -> Result<Outcome, IndexerError> {
+        let checkpoint = self.client.get_full_checkpoint(seq).await?;
+        let expected =
+            index_checkpoint(self.store.clone(), checkpoint, self.metrics.clone()).await?;
+
+        let expected_checkpoint = StoredCheckpoint::from(&expected.checkpoint);
+        let expected_transactions = expected
+            .transactions
+            .iter()
+            .map(StoredTransaction::from)
+            .collect::<Vec<_>>();
+        let expected_events = expected
+            .events
+            .iter()
+            .cloned()
+            .map(StoredEvent::from)
+            .collect::<Vec<_>>();
+        let tx_range = expected_transactions
+            .iter()
+            .map(|t| t.tx_sequence_number)
+            .minmax()
+            .into_option();
+        let (checkpoint, transactions, events) = self
+            .store
+            .execute_in_blocking_worker(move |this| this.get_checkpoint_rows(seq, tx_range))
+            .await?;
+
+        let deleted = expected
+            .object_changes
+            .deleted_objects
+            .iter()
+            .map(|o| (o.object_id, o.object_version))
+            .collect::<BTreeMap<_, _>>();
+        let ids = deleted.keys().map(|id| id.to_vec()).collect::<Vec<_>>();
+        let live = if ids.is_empty() {
+            vec![]
+        } else {
+            self.store
+                .execute_in_blocking_worker(move |this| this.get_live_object_versions(ids))
+                .await?
+        };
+
+        let mut discrepancies = vec![];
+        match checkpoint {
+            None => discrepancies.push(format!("checkpoint {seq} is missing")),
+            Some(checkpoint) if checkpoint != expected_checkpoint => {
+                discrepancies.push(format!("checkpoint {seq} differs"))
+            }
+            Some(_) => {}
+        }
+        diff_rows(
+            "transaction",
+            transactions,
+            expected_transactions,
+            |t| t.tx_sequence_number,
+            &mut discrepancies,
+        );
+        diff_rows(
+            "event",
+            events,
+            expected_events,
+            |e| (e.tx_sequence_number, e.event_sequence_number),
+            &mut discrepancies,
+        );
+        for (object_id, live_version) in live {
+            let object_id = ObjectID::from_bytes(&object_id)?;
+            let live_version = live_version as u64;
+            let Some(&version) = deleted.get(&object_id) else {
+                continue;
+            };
+            if live_version < version {
+                discrepancies.push(format!(
+                    "object {object_id} was deleted or wrapped at version {version}, but \
+                     version {live_version} is still live"
+                ));
+            }
+        }
+        if discrepancies.is_empty() {
+            return Ok(Outcome::Consistent);
+        }
+        let discrepancies = discrepancies
+            .into_iter()
+            .map(|d| format!("In checkpoint {seq}: {d}"))
+            .collect();
+
+        if self.config.repair {
+            self.store
+                .execute_in_blocking_worker(move |this| this.repair_checkpoint(&expected))
+                .await?;
+        }
+        Ok(Outcome::Inconsistent {
+            discrepancies,
+            repaired: self.config.repair,
+        })
+    }
+
+    async fn verify_objects(&self, report: &mut VerificationReport) -> Result<(), IndexerError> {
+        info!("Verifying live objects");
+        let mut cursor = None;
+        loop {
+            let page = self
+                .store
+                .execute_in_blocking_worker(move |this| {
+                    this.get_objects_page(cursor, OBJECTS_PAGE_SIZE)
+                })
+                .await?;
+            let Some(last) = page.last() else {
+                return Ok(());
+            };
+            cursor = Some(last.object_id.clone());
+
+            let objects = page.into_iter().filter(|_| self.sampled());
+            let outcomes = futures::stream::iter(objects)
+                .map(|object| self.verify_object(object))
+                .buffer_unordered(self.config.concurrency)
+                .try_collect::<Vec<_>>()
+                .await?;
+            for outcome in outcomes {
+                report.record_object(outcome);
+            }
+        }
+    }
+
+    async fn verify_object(&self, row: StoredObject) -> Result<Outcome, IndexerError> {
+        let object_id = ObjectID::from_bytes(&row.object_id)?;
+        let version = row.object_version as u64;
+        let Ok(object) = self
+            .client
+            .get_object_with_version(object_id, SequenceNumber::from_u64(version))
+            .await
+        else {
+            return Ok(Outcome::Unverifiable);
+        };
+
+        let mut discrepancies = vec![];
+        let mut repair = None;
+        let expected = expected_object_row(&row, object, row.checkpoint_sequence_number as u64);
+        if expected != row {
+            discrepancies.push(format!("object {object_id} at version {version} differs"));
+            repair = Some(expected);
+        }
+
+        // The fullnode is usually ahead of the indexer, so a newer version is only a discrepancy
+        // if the transaction that wrote it has been indexed.
+        if let Ok(latest) = self.client.get_object(object_id).await {
+            let latest_version = latest.version().value();
+            if latest_version > version {
+                let digest = latest.previous_transaction.into_inner().to_vec();
+                let written_at = self
+                    .store
+                    .execute_in_blocking_worker(move |this| this.get_transaction_checkpoint(digest))
+                    .await?;
+                if let Some(checkpoint) = written_at {
+                    discrepancies.push(format!(
+                        "object {object_id} is at version {version}, but version \
+                         {latest_version} was written in checkpoint {checkpoint}"
+                    ));
+                    repair = Some(expected_object_row(&row, latest, checkpoint));
+                }
+            }
+        }
+
+        if discrepancies.is_empty() {
+            return Ok(Outcome::Consistent);
+        }
+        if let Some(object) = repair.filter(|_| self.config.repair) {
+            self.store
+                .execute_in_blocking_worker(move |this| this.repair_object(object))
+                .await?;
+        }
+        Ok(Outcome::Inconsistent {
+            discrepancies,
+            repaired: self.config.repair,
+        })
+    }
+}
+
+/// The row the indexer would write for `object`. Dynamic field info needs the package resolver
+/// to derive, so it is taken from `row` rather than verified.
+fn expected_object_row(
+    row: &StoredObject,
+    object: sui_types::object::Object,
+    checkpoint_sequence_number: u64,
+) -> StoredObject {
+    let mut expected = StoredObject::from(IndexedObject::from_object(
+        checkpoint_sequence_number,
+        object,
+        None,
+    ));
+    expected.df_kind = row.df_kind;
+    expected.df_name = row.df_name.clone();
+    expected.df_object_type = row.df_object_type.clone();
+    expected.df_object_id = row.df_object_id.clone();
+    expected
+}
+
+/// Records a discrepancy for every row that is missing, differs, or is not expected.
+fn diff_rows<T: PartialEq, K: Ord + Debug>(
+    kind: &str,
+    actual: Vec<T>,
+    expected: Vec<T>,
+    key: impl Fn(&T) -> K,
+    discrepancies: &mut Vec<String>,
+) {
+    let mut actual = actual
+        .into_iter()
+        .map(|row| (key(&row), row))
+        .collect::<BTreeMap<_, _>>();
+    for row in expected {
+        let k = key(&row);
+        match actual.remove(&k) {
+            None => discrepancies.push(format!("{kind} {k:?} is missing")),
+            Some(actual) if actual != row => discrepancies.push(format!("{kind} {k:?} differs")),
+            Some(_) => {}
+        }
+    }
+    for k in actual.into_keys() {
+        discrepancies.push(format!("{kind} {k:?} is unexpected"));
+    }
+}
@@ -43,4 +43,8 @@ pub trait ExtendedApi {
 
     #[method(name = "getTotalTransactions")]
     async fn get_total_transactions(&self) -> RpcResult<BigInt<u64>>;
+
+    /// Return the sequence number of the lowest checkpoint whose data has not been pruned.
+    #[method(name = "getLowestAvailableCheckpoint")]
+    async fn get_lowest_available_checkpoint(&self) -> RpcResult<BigInt<u64>>;
 }
@@ -10,6 +10,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use serde_with::{serde_as, DisplayFromStr};
+use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::Display;
 use sui_types::base_types::{ObjectID, SuiAddress, TransactionDigest};
@@ -248,6 +249,15 @@ pub enum EventFilter {
         path: String,
         value: Value,
     },
+    /// Return events with a field of their parsed Move value, designated by a JSON pointer into
+    /// `parsedJson` such as `/amount`, that compares to `value` with `op`. Integers, including
+    /// the large ones rendered as strings, are compared numerically, and addresses and object IDs
+    /// regardless of their hex formatting. Only supported by subscriptions.
+    MoveEventFieldPredicate {
+        path: String,
+        op: EventFieldComparison,
+        value: Value,
+    },
     /// Return events emitted in [start_time, end_time] interval
     #[serde(rename_all = "camelCase")]
     TimeRange {
@@ -274,6 +284,10 @@ impl EventFilter {
             EventFilter::MoveEventField { path, value } => {
                 matches!(item.parsed_json.pointer(path), Some(v) if v == value)
             }
+            EventFilter::MoveEventFieldPredicate { path, op, value } => item
+                .parsed_json
+                .pointer(path)
+                .is_some_and(|field| op.matches(field, value)),
             EventFilter::Sender(sender) => &item.sender == sender,
             EventFilter::Package(object_id) => &item.package_id == object_id,
             EventFilter::MoveModule { package, module } => {
@@ -305,6 +319,35 @@ impl EventFilter {
         })
     }
 
+    /// Cost of evaluating the filter against an event: one per filter, plus the number of
+    /// segments of the paths followed into the fields of the event.
+    pub fn cost(&self) -> usize {
+        match self {
+            EventFilter::MoveEventField { path, .. }
+            | EventFilter::MoveEventFieldPredicate { path, .. } => 1 + path.matches('/').count(),
+            EventFilter::All(filters) | EventFilter::Any(filters) => {
+                1 + filters.iter().map(EventFilter::cost).sum::<usize>()
+            }
+            EventFilter::And(f1, f2) | EventFilter::Or(f1, f2) => 1 + f1.cost() + f2.cost(),
+            _ => 1,
+        }
+    }
+
+    /// Whether the filter compares fields of events with `MoveEventFieldPredicate`. Plain
+    /// `MoveEventField` equality filters predate the limit on such subscriptions and don't count.
+    pub fn has_field_predicates(&self) -> bool {
+        match self {
+            EventFilter::MoveEventFieldPredicate { .. } => true,
+            EventFilter::All(filters) | EventFilter::Any(filters) => {
+                filters.iter().any(EventFilter::has_field_predicates)
+            }
+            EventFilter::And(f1, f2) | EventFilter::Or(f1, f2) => {
+                f1.has_field_predicates() || f2.has_field_predicates()
+            }
+            _ => false,
+        }
+    }
+
     pub fn and(self, other_filter: EventFilter) -> Self {
         Self::All(vec![self, other_filter])
     }
@@ -313,6 +356,71 @@ impl EventFilter {
     }
 }
 
+/// Comparison of `EventFilter::MoveEventFieldPredicate`, between the field of an event and the
+/// value of the filter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum EventFieldComparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl EventFieldComparison {
+    fn matches(&self, field: &Value, value: &Value) -> bool {
+        let ordering = compare_field(field, value);
+        match self {
+            EventFieldComparison::Eq => ordering == Some(Ordering::Equal),
+            EventFieldComparison::Ne => ordering != Some(Ordering::Equal),
+            EventFieldComparison::Lt => ordering == Some(Ordering::Less),
+            EventFieldComparison::Le => {
+                matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+            }
+            EventFieldComparison::Gt => ordering == Some(Ordering::Greater),
+            EventFieldComparison::Ge => {
+                matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+            }
+        }
+    }
+}
+
+/// Orders two JSON values of Move fields, or returns None if they are not comparable. Only
+/// unsigned integers and addresses are ordered, other values are only compared for equality.
+fn compare_field(field: &Value, value: &Value) -> Option<Ordering> {
+    if let (Some(field), Some(value)) = (as_unsigned_integer(field), as_unsigned_integer(value)) {
+        // Without leading zeros, a longer integer is a larger one.
+        return Some(
+            field
+                .len()
+                .cmp(&value.len())
+                .then_with(|| field.cmp(&value)),
+        );
+    }
+    if let (Some(field), Some(value)) = (field.as_str(), value.as_str()) {
+        if let (Ok(field), Ok(value)) = (
+            ObjectID::from_hex_literal(field),
+            ObjectID::from_hex_literal(value),
+        ) {
+            return Some(field.cmp(&value));
+        }
+    }
+    (field == value).then_some(Ordering::Equal)
+}
+
+/// Decimal digits of an unsigned integer without leading zeros, from a JSON number or from the
+/// string Move integers larger than u32 are rendered as.
+fn as_unsigned_integer(value: &Value) -> Option<String> {
+    let digits = match value {
+        Value::Number(n) => n.as_u64()?.to_string(),
+        Value::String(s) if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) => s.clone(),
+        _ => return None,
+    };
+    let trimmed = digits.trim_start_matches('0');
+    Some(if trimmed.is_empty() { "0" } else { trimmed }.to_string())
+}
+
 impl Filter<SuiEvent> for EventFilter {
     fn matches(&self, item: &SuiEvent) -> bool {
         let _scope = monitored_scope("EventFilter::matches");
@@ -16,7 +16,9 @@ use sui_types::gas_coin::GasCoin;
 use sui_types::object::{MoveObject, Owner};
 use sui_types::{parse_sui_struct_tag, MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS};
 
-use crate::{ObjectChange, SuiMoveStruct, SuiMoveValue};
+use crate::{
+    EventFieldComparison, EventFilter, ObjectChange, SuiEvent, SuiMoveStruct, SuiMoveValue,
+};
 
 #[test]
 fn test_move_value_to_sui_coin() {
@@ -196,3 +198,87 @@ fn test_type_tag_struct_tag_devnet_inc_222() {
         assert_eq!(oc, deser);
     }
 }
+
+#[test]
+fn test_event_field_predicates() {
+    let pool_id = ObjectID::random();
+    let mut event = SuiEvent::random_for_testing();
+    event.parsed_json = json!({
+        "amount": "1500",
+        "pool": { "id": pool_id.to_hex_uncompressed() },
+        "flag": true,
+    });
+    let predicate = |path: &str, op, value| EventFilter::MoveEventFieldPredicate {
+        path: path.to_string(),
+        op,
+        value,
+    };
+    let matches = |filter: &EventFilter| filter.try_matches(&event).unwrap();
+
+    // u64 amounts are rendered as strings, and compared as integers.
+    assert!(matches(&predicate(
+        "/amount",
+        EventFieldComparison::Gt,
+        json!(1000)
+    )));
+    assert!(matches(&predicate(
+        "/amount",
+        EventFieldComparison::Le,
+        json!("1500")
+    )));
+    assert!(!matches(&predicate(
+        "/amount",
+        EventFieldComparison::Lt,
+        json!("999")
+    )));
+    assert!(!matches(&predicate(
+        "/amount",
+        EventFieldComparison::Gt,
+        json!("01500")
+    )));
+
+    // Addresses compare equal regardless of their hex formatting.
+    let short_id = format!("0x{}", pool_id.to_hex().trim_start_matches('0'));
+    assert!(matches(&predicate(
+        "/pool/id",
+        EventFieldComparison::Eq,
+        json!(short_id)
+    )));
+    assert!(matches(&predicate(
+        "/pool/id",
+        EventFieldComparison::Ne,
+        json!(ObjectID::random())
+    )));
+
+    // Other values are only compared for equality, and missing fields never match.
+    assert!(matches(&predicate(
+        "/flag",
+        EventFieldComparison::Eq,
+        json!(true)
+    )));
+    assert!(!matches(&predicate(
+        "/flag",
+        EventFieldComparison::Gt,
+        json!(false)
+    )));
+    assert!(!matches(&predicate(
+        "/missing",
+        EventFieldComparison::Ne,
+        json!(0)
+    )));
+
+    let filter = EventFilter::All(vec![
+        predicate("/amount", EventFieldComparison::Gt, json!(1000)),
+        predicate("/pool/id", EventFieldComparison::Eq, json!(pool_id)),
+    ]);
+    assert!(matches(&filter));
+    assert_eq!(filter.cost(), 1 + 2 + 3);
+    assert!(filter.has_field_predicates());
+    assert_eq!(EventFilter::Sender(event.sender).cost(), 1);
+    assert!(!EventFilter::Sender(event.sender).has_field_predicates());
+    assert!(!EventFilter::MoveEventField {
+        path: "/amount".to_string(),
+        value: json!("1000"),
+    }
+    .has_field_predicates());
+}
@@ -292,13 +292,12 @@ impl<R: ReadApiServer> IndexerApiServer for IndexerApi<R> {
     #[instrument(skip(self))]
     fn subscribe_event(&self, sink: SubscriptionSink, filter: EventFilter) -> SubscriptionResult {
         let permit = self.acquire_subscribe_permit()?;
-        spawn_subscription(
-            sink,
-            self.state
-                .get_subscription_handler()
-                .subscribe_events(filter),
-            Some(permit),
-        );
+        let stream = self
+            .state
+            .get_subscription_handler()
+            .subscribe_events(filter)
+            .map_err(anyhow::Error::from)?;
+        spawn_subscription(sink, stream, Some(permit));
         Ok(())
     }
 
@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{
-    metrics::Metrics, server::Server, Discovery, DiscoveryEventLoop, DiscoveryServer, State,
+    metrics::Metrics, server::Server, Discovery, DiscoveryEventLoop, DiscoveryServer, PeerScores,
+    State,
 };
 use crate::discovery::TrustedPeerChangeEvent;
 use anemo::codegen::InboundRequestLayer;
@@ -22,6 +23,7 @@ use tokio::{
 pub struct Builder {
     config: Option<P2pConfig>,
     metrics: Option<Metrics>,
+    peer_scores: Option<PeerScores>,
     trusted_peer_change_rx: watch::Receiver<TrustedPeerChangeEvent>,
 }
 
@@ -31,6 +33,7 @@ impl Builder {
         Self {
             config: None,
             metrics: None,
+            peer_scores: None,
             trusted_peer_change_rx,
         }
     }
@@ -45,6 +48,14 @@ impl Builder {
         self
     }
 
+    /// Sets the peer scores that discovery uses to choose and ban peers, so that they can be
+    /// shared with the subsystems reporting on peers. If unset, scores are created from the
+    /// discovery config.
+    pub fn peer_scores(mut self, peer_scores: PeerScores) -> Self {
+        self.peer_scores = Some(peer_scores);
+        self
+    }
+
     pub fn build(self) -> (UnstartedDiscovery, DiscoveryServer<impl Discovery>) {
         let discovery_config = self
             .config
@@ -70,14 +81,18 @@ impl Builder {
         let Builder {
             config,
             metrics,
+            peer_scores,
             trusted_peer_change_rx,
         } = self;
         let config = config.unwrap();
         let metrics = metrics.unwrap_or_else(Metrics::disabled);
+        let peer_scores = peer_scores
+            .unwrap_or_else(|| PeerScores::new(&config.discovery.clone().unwrap_or_default()));
         let (sender, receiver) = oneshot::channel();
 
         let handle = Handle {
             _shutdown_handle: Arc::new(sender),
+            peer_scores: peer_scores.clone(),
         };
 
         let state = State {
@@ -99,6 +114,7 @@ impl Builder {
                 shutdown_handle: receiver,
                 state,
                 trusted_peer_change_rx,
+                peer_scores,
                 metrics,
             },
             server,
@@ -113,6 +129,7 @@ pub struct UnstartedDiscovery {
     pub(super) shutdown_handle: oneshot::Receiver<()>,
     pub(super) state: Arc<RwLock<State>>,
     pub(super) trusted_peer_change_rx: watch::Receiver<TrustedPeerChangeEvent>,
+    pub(super) peer_scores: PeerScores,
     pub(super) metrics: Metrics,
 }
 
@@ -124,6 +141,7 @@ impl UnstartedDiscovery {
             shutdown_handle,
             state,
             trusted_peer_change_rx,
+            peer_scores,
             metrics,
         } = self;
 
@@ -152,6 +170,7 @@ impl UnstartedDiscovery {
                 shutdown_handle,
                 state,
                 trusted_peer_change_rx,
+                peer_scores,
                 metrics,
             },
             handle,
@@ -170,4 +189,11 @@ impl UnstartedDiscovery {
 /// been dropped.
 pub struct Handle {
     _shutdown_handle: Arc<oneshot::Sender<()>>,
+    peer_scores: PeerScores,
+}
+
+impl Handle {
+    pub fn peer_scores(&self) -> &PeerScores {
+        &self.peer_scores
+    }
 }
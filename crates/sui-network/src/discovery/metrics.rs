@@ -1,7 +1,11 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use prometheus::{register_int_gauge_with_registry, IntGauge, Registry};
+use anemo::PeerId;
+use prometheus::{
+    register_gauge_vec_with_registry, register_int_counter_with_registry,
+    register_int_gauge_with_registry, GaugeVec, IntCounter, IntGauge, Registry,
+};
 use std::sync::Arc;
 use tap::Pipe;
 
@@ -34,10 +38,38 @@ impl Metrics {
             inner.num_peers_with_external_address.dec();
         }
     }
+
+    pub fn inc_peer_bans(&self, count: usize) {
+        if let Some(inner) = &self.0 {
+            inner.peer_bans.inc_by(count as u64);
+        }
+    }
+
+    /// Replaces the reported peer scores, so that peers which are no longer scored stop being
+    /// reported.
+    pub fn set_peer_scores(&self, scores: impl Iterator<Item = (PeerId, f64, bool)>) {
+        if let Some(inner) = &self.0 {
+            inner.peer_score.reset();
+            let mut num_banned_peers = 0;
+            for (peer_id, score, banned) in scores {
+                inner
+                    .peer_score
+                    .with_label_values(&[&peer_id.to_string()])
+                    .set(score);
+                if banned {
+                    num_banned_peers += 1;
+                }
+            }
+            inner.num_banned_peers.set(num_banned_peers);
+        }
+    }
 }
 
 struct Inner {
     num_peers_with_external_address: IntGauge,
+    peer_score: GaugeVec,
+    num_banned_peers: IntGauge,
+    peer_bans: IntCounter,
 }
 
 impl Inner {
@@ -49,6 +81,25 @@ impl Inner {
                 registry
             )
             .unwrap(),
+            peer_score: register_gauge_vec_with_registry!(
+                "peer_score",
+                "Score of a peer, based on the success rate and latency of our requests to it and its rate limit violations",
+                &["peer_id"],
+                registry
+            )
+            .unwrap(),
+            num_banned_peers: register_int_gauge_with_registry!(
+                "num_banned_peers",
+                "Number of peers currently banned because of their low score",
+                registry
+            )
+            .unwrap(),
+            peer_bans: register_int_counter_with_registry!(
+                "peer_bans",
+                "Number of times a peer was banned because of its low score",
+                registry
+            )
+            .unwrap(),
         }
         .pipe(Arc::new)
     }
@@ -1,7 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anemo::types::PeerInfo;
+use anemo::types::{PeerAffinity, PeerInfo};
 use anemo::{types::PeerEvent, Network, Peer, PeerId, Request, Response};
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
@@ -19,7 +19,7 @@ use tokio::{
     sync::oneshot,
     task::{AbortHandle, JoinSet},
 };
-use tracing::{debug, info, trace};
+use tracing::{debug, info, trace, warn};
 
 const TIMEOUT: Duration = Duration::from_secs(1);
 const ONE_DAY_MILLISECONDS: u64 = 24 * 60 * 60 * 1_000;
@@ -29,6 +29,7 @@ mod generated {
 }
 mod builder;
 mod metrics;
+mod peer_scores;
 mod server;
 #[cfg(test)]
 mod tests;
@@ -38,6 +39,7 @@ pub use generated::{
     discovery_client::DiscoveryClient,
     discovery_server::{Discovery, DiscoveryServer},
 };
+pub use peer_scores::{PeerScore, PeerScores, RateLimitViolationLayer};
 pub use server::GetKnownPeersResponse;
 
 use self::metrics::Metrics;
@@ -83,6 +85,7 @@ struct DiscoveryEventLoop {
     shutdown_handle: oneshot::Receiver<()>,
     state: Arc<RwLock<State>>,
     trusted_peer_change_rx: watch::Receiver<TrustedPeerChangeEvent>,
+    peer_scores: PeerScores,
     metrics: Metrics,
 }
 
@@ -212,6 +215,15 @@ impl DiscoveryEventLoop {
     fn handle_peer_event(&mut self, peer_event: Result<PeerEvent, RecvError>) {
         match peer_event {
             Ok(PeerEvent::NewPeer(peer_id)) => {
+                // Don't keep connections initiated by banned peers.
+                if self.peer_scores.is_banned(&peer_id) {
+                    debug!(
+                        "disconnecting from banned peer {}",
+                        peer_id.short_display(4)
+                    );
+                    let _ = self.network.disconnect(peer_id);
+                    return;
+                }
                 if let Some(peer) = self.network.peer(peer_id) {
                     self.state
                         .write()
@@ -242,8 +254,9 @@ impl DiscoveryEventLoop {
         }
     }
 
-    fn handle_tick(&mut self, _now: std::time::Instant, now_unix: u64) {
+    fn handle_tick(&mut self, now: std::time::Instant, now_unix: u64) {
         self.update_our_info_timestamp(now_unix);
+        self.update_peer_bans(now);
 
         self.tasks
             .spawn(query_connected_peers_for_their_known_peers(
@@ -280,6 +293,7 @@ impl DiscoveryEventLoop {
                 !info.addresses.is_empty() // Peer has addresses we can dial
                 && !state.connected_peers.contains_key(peer_id) // We're not already connected
                 && !self.pending_dials.contains_key(peer_id) // There is no pending dial to this node
+                && !self.peer_scores.is_banned(peer_id) // The peer isn't banned
             })
             .collect::<Vec<_>>();
 
@@ -292,12 +306,14 @@ impl DiscoveryEventLoop {
                 .saturating_sub(number_of_connections),
         );
 
-        // randomize the order
-        for (peer_id, info) in rand::seq::SliceRandom::choose_multiple(
-            eligible.as_slice(),
-            &mut rand::thread_rng(),
-            number_to_dial,
-        ) {
+        // randomize the order, then prefer peers with higher scores
+        let mut eligible = eligible
+            .into_iter()
+            .map(|(peer_id, info)| (self.peer_scores.score(&peer_id), peer_id, info))
+            .collect::<Vec<_>>();
+        rand::seq::SliceRandom::shuffle(eligible.as_mut_slice(), &mut rand::thread_rng());
+        eligible.sort_by(|(score_a, _, _), (score_b, _, _)| score_b.total_cmp(score_a));
+        for (_score, peer_id, info) in eligible.iter().take(number_to_dial) {
             let abort_handle = self.tasks.spawn(try_to_connect_to_peer(
                 self.network.clone(),
                 info.to_owned(),
@@ -321,6 +337,50 @@ impl DiscoveryEventLoop {
             self.dial_seed_peers_task = Some(abort_handle);
         }
     }
+
+    fn update_peer_bans(&mut self, now: std::time::Instant) {
+        // Allowlisted, seed and high-affinity (e.g. committee) peers are never banned, as the
+        // network would keep reconnecting to them.
+        let known_peers = self.network.known_peers();
+        let newly_banned = self.peer_scores.update_bans(now, |peer_id| {
+            self.allowlisted_peers.contains_key(peer_id)
+                || self
+                    .config
+                    .seed_peers
+                    .iter()
+                    .any(|seed| seed.peer_id.as_ref() == Some(peer_id))
+                || known_peers
+                    .get(peer_id)
+                    .is_some_and(|info| matches!(info.affinity, PeerAffinity::High))
+        });
+        self.metrics.inc_peer_bans(newly_banned.len());
+        for peer_id in newly_banned {
+            warn!(
+                "banning peer {} with score {:.1}",
+                peer_id.short_display(4),
+                self.peer_scores.score(&peer_id)
+            );
+            if let Some(abort_handle) = self.pending_dials.remove(&peer_id) {
+                abort_handle.abort();
+            }
+            let _ = self.network.disconnect(peer_id);
+        }
+
+        // Stop tracking scores of peers we no longer know about
+        {
+            let state = self.state.read().unwrap();
+            self.peer_scores.retain(|peer_id| {
+                state.known_peers.contains_key(peer_id)
+                    || state.connected_peers.contains_key(peer_id)
+            });
+        }
+        self.metrics.set_peer_scores(
+            self.peer_scores
+                .snapshot()
+                .into_iter()
+                .map(|score| (score.peer_id, score.score, score.banned_for_ms.is_some())),
+        );
+    }
 }
 
 async fn try_to_connect_to_peer(network: Network, info: NodeInfo) {
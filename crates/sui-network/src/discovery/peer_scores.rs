@@ -0,0 +1,343 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Peer scoring.
+//!
+//! Peers are scored on how well they serve our requests: the rate of successful requests, the
+//! latency of those requests, and the number of times they violated our rate limits. A peer
+//! starts with the maximum score, `100`, and loses points as it fails requests, responds slowly
+//! or gets rate limited. Timed out requests weigh less than failures, as timeouts are often caused
+//! by the network rather than by the peer. Discovery prefers to dial peers with high scores, and temporarily bans
+//! peers whose score drops below the configured threshold.
+
+use anemo::{rpc::Status, types::response::StatusCode, PeerId, Request};
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use sui_config::p2p::DiscoveryConfig;
+
+const MAX_SCORE: f64 = 100.0;
+/// Weight of new samples in the moving averages of success rate and latency.
+const SAMPLE_WEIGHT: f64 = 0.1;
+/// A timed out request counts as this fraction of a failed request.
+const TIMEOUT_WEIGHT: f64 = 0.5;
+/// Points lost per 100ms of average request latency, capped at `MAX_LATENCY_PENALTY`.
+const LATENCY_PENALTY_PER_100MS: f64 = 5.0;
+const MAX_LATENCY_PENALTY: f64 = 25.0;
+/// Points lost per rate limit violation. The penalty halves every
+/// `VIOLATION_PENALTY_HALF_LIFE`.
+const VIOLATION_PENALTY: f64 = 20.0;
+const VIOLATION_PENALTY_HALF_LIFE: Duration = Duration::from_secs(60);
+/// Peers are not banned before we have this many samples from them, so that a couple of
+/// unlucky requests don't get a peer banned.
+const MIN_SAMPLES_FOR_BAN: u64 = 10;
+
+/// Scores of the peers we've exchanged requests with. Cloning returns a handle to the same
+/// scores, so that the subsystems that observe peers and discovery share them.
+#[derive(Clone)]
+pub struct PeerScores {
+    inner: Arc<Mutex<Inner>>,
+    ban_score_threshold: f64,
+    ban_duration: Duration,
+}
+
+struct Inner {
+    peers: HashMap<PeerId, PeerStats>,
+    last_decay: Option<Instant>,
+}
+
+#[derive(Clone, Debug)]
+struct PeerStats {
+    requests: u64,
+    failures: u64,
+    timeouts: u64,
+    rate_limit_violations: u64,
+    success_rate: f64,
+    latency: Duration,
+    violation_penalty: f64,
+    banned_until: Option<Instant>,
+}
+
+impl Default for PeerStats {
+    fn default() -> Self {
+        Self {
+            requests: 0,
+            failures: 0,
+            timeouts: 0,
+            rate_limit_violations: 0,
+            success_rate: 1.0,
+            latency: Duration::ZERO,
+            violation_penalty: 0.0,
+            banned_until: None,
+        }
+    }
+}
+
+impl PeerStats {
+    fn score(&self) -> f64 {
+        let latency_penalty = (self.latency.as_secs_f64() * 10.0 * LATENCY_PENALTY_PER_100MS)
+            .min(MAX_LATENCY_PENALTY);
+        MAX_SCORE * self.success_rate - latency_penalty - self.violation_penalty
+    }
+
+    fn record_request(&mut self, outcome: RequestOutcome) {
+        self.requests += 1;
+        let sample = match outcome {
+            RequestOutcome::Success => 1.0,
+            RequestOutcome::Failure => {
+                self.failures += 1;
+                0.0
+            }
+            RequestOutcome::Timeout => {
+                self.timeouts += 1;
+                1.0 - TIMEOUT_WEIGHT
+            }
+        };
+        self.success_rate = self.success_rate * (1.0 - SAMPLE_WEIGHT) + sample * SAMPLE_WEIGHT;
+    }
+}
+
+enum RequestOutcome {
+    Success,
+    Failure,
+    Timeout,
+}
+
+/// The score of a peer, as exposed through the admin API.
+#[derive(Clone, Debug, Serialize)]
+pub struct PeerScore {
+    pub peer_id: PeerId,
+    pub score: f64,
+    pub requests: u64,
+    pub failures: u64,
+    pub timeouts: u64,
+    pub rate_limit_violations: u64,
+    pub average_latency_ms: u64,
+    /// Time left until the peer is unbanned, if it is banned.
+    pub banned_for_ms: Option<u64>,
+}
+
+impl Default for PeerScores {
+    fn default() -> Self {
+        Self::new(&DiscoveryConfig::default())
+    }
+}
+
+impl PeerScores {
+    pub fn new(config: &DiscoveryConfig) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                peers: HashMap::new(),
+                last_decay: None,
+            })),
+            ban_score_threshold: config.peer_ban_score_threshold(),
+            ban_duration: config.peer_ban_duration(),
+        }
+    }
+
+    /// Records a request to `peer` which succeeded after `latency`.
+    pub fn record_success(&self, peer: PeerId, latency: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        let stats = inner.peers.entry(peer).or_default();
+        stats.record_request(RequestOutcome::Success);
+        stats.latency = stats.latency.mul_f64(1.0 - SAMPLE_WEIGHT) + latency.mul_f64(SAMPLE_WEIGHT);
+    }
+
+    /// Records a request to `peer` which failed, or returned an invalid response.
+    pub fn record_failure(&self, peer: PeerId) {
+        let mut inner = self.inner.lock().unwrap();
+        inner
+            .peers
+            .entry(peer)
+            .or_default()
+            .record_request(RequestOutcome::Failure);
+    }
+
+    /// Records a request to `peer` which timed out.
+    pub fn record_timeout(&self, peer: PeerId) {
+        let mut inner = self.inner.lock().unwrap();
+        inner
+            .peers
+            .entry(peer)
+            .or_default()
+            .record_request(RequestOutcome::Timeout);
+    }
+
+    /// Records a request to `peer` which returned `status`, as a timeout or a failure.
+    pub fn record_error(&self, peer: PeerId, status: &Status) {
+        if status.status() == StatusCode::RequestTimeout {
+            self.record_timeout(peer);
+        } else {
+            self.record_failure(peer);
+        }
+    }
+
+    /// Records a request from `peer` which was rejected by one of our rate limits.
+    pub fn record_rate_limit_violation(&self, peer: PeerId) {
+        let mut inner = self.inner.lock().unwrap();
+        let stats = inner.peers.entry(peer).or_default();
+        stats.rate_limit_violations += 1;
+        stats.violation_penalty += VIOLATION_PENALTY;
+    }
+
+    /// Returns the score of `peer`. Peers we have no observations of have the maximum score.
+    pub fn score(&self, peer: &PeerId) -> f64 {
+        self.inner
+            .lock()
+            .unwrap()
+            .peers
+            .get(peer)
+            .map_or(MAX_SCORE, PeerStats::score)
+    }
+
+    pub fn is_banned(&self, peer: &PeerId) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .peers
+            .get(peer)
+            .is_some_and(|stats| stats.banned_until.is_some())
+    }
+
+    /// Returns the scores of all peers, sorted from the highest score.
+    pub fn snapshot(&self) -> Vec<PeerScore> {
+        let now = Instant::now();
+        let mut scores: Vec<_> = self
+            .inner
+            .lock()
+            .unwrap()
+            .peers
+            .iter()
+            .map(|(peer_id, stats)| PeerScore {
+                peer_id: *peer_id,
+                score: stats.score(),
+                requests: stats.requests,
+                failures: stats.failures,
+                timeouts: stats.timeouts,
+                rate_limit_violations: stats.rate_limit_violations,
+                average_latency_ms: stats.latency.as_millis() as u64,
+                banned_for_ms: stats
+                    .banned_until
+                    .map(|until| until.saturating_duration_since(now).as_millis() as u64),
+            })
+            .collect();
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scores
+    }
+
+    /// Decays rate limit violation penalties, lifts expired bans, and bans peers whose score
+    /// dropped below the threshold, unless `is_exempt` returns true for them. Peers are unbanned
+    /// with a clean slate. Returns the newly banned peers.
+    pub(super) fn update_bans(
+        &self,
+        now: Instant,
+        is_exempt: impl Fn(&PeerId) -> bool,
+    ) -> Vec<PeerId> {
+        let mut inner = self.inner.lock().unwrap();
+        let decay = inner.last_decay.map_or(1.0, |last_decay| {
+            0.5_f64.powf(
+                now.saturating_duration_since(last_decay).as_secs_f64()
+                    / VIOLATION_PENALTY_HALF_LIFE.as_secs_f64(),
+            )
+        });
+        inner.last_decay = Some(now);
+
+        let mut newly_banned = vec![];
+        for (peer_id, stats) in inner.peers.iter_mut() {
+            stats.violation_penalty *= decay;
+            match stats.banned_until {
+                Some(until) if until <= now => *stats = PeerStats::default(),
+                Some(_) => {}
+                None => {
+                    if stats.requests + stats.rate_limit_violations >= MIN_SAMPLES_FOR_BAN
+                        && stats.score() < self.ban_score_threshold
+                        && !is_exempt(peer_id)
+                    {
+                        stats.banned_until = Some(now + self.ban_duration);
+                        newly_banned.push(*peer_id);
+                    }
+                }
+            }
+        }
+        newly_banned
+    }
+
+    /// Forgets peers for which `keep` returns false, unless they are banned.
+    pub(super) fn retain(&self, keep: impl Fn(&PeerId) -> bool) {
+        self.inner
+            .lock()
+            .unwrap()
+            .peers
+            .retain(|peer_id, stats| stats.banned_until.is_some() || keep(peer_id));
+    }
+}
+
+/// [`Layer`] recording requests rejected with `TooManyRequests` by the rate limits it wraps as
+/// rate limit violations of the requesting peer.
+///
+/// [`Layer`]: tower::layer::Layer
+#[derive(Clone)]
+pub struct RateLimitViolationLayer {
+    peer_scores: PeerScores,
+}
+
+impl RateLimitViolationLayer {
+    pub fn new(peer_scores: PeerScores) -> Self {
+        Self { peer_scores }
+    }
+}
+
+impl<S> tower::layer::Layer<S> for RateLimitViolationLayer {
+    type Service = RateLimitViolation<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitViolation {
+            inner,
+            peer_scores: self.peer_scores.clone(),
+        }
+    }
+}
+
+/// Middleware recording requests rejected with `TooManyRequests` as rate limit violations.
+#[derive(Clone)]
+pub struct RateLimitViolation<S> {
+    inner: S,
+    peer_scores: PeerScores,
+}
+
+impl<S, T> tower::Service<Request<T>> for RateLimitViolation<S>
+where
+    S: tower::Service<Request<T>, Error = Status> + 'static + Clone + Send,
+    S::Future: Send,
+    S::Response: Send + 'static,
+    Request<T>: 'static + Send,
+{
+    type Response = S::Response;
+    type Error = Status;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<T>) -> Self::Future {
+        let peer_id = req.peer_id().copied();
+        let peer_scores = self.peer_scores.clone();
+        let fut = self.inner.call(req);
+        Box::pin(async move {
+            let result = fut.await;
+            if let (Err(status), Some(peer_id)) = (&result, peer_id) {
+                if status.status() == StatusCode::TooManyRequests {
+                    peer_scores.record_rate_limit_violation(peer_id);
+                }
+            }
+            result
+        })
+    }
+}
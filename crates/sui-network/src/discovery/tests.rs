@@ -3,6 +3,8 @@
 
 use super::*;
 use crate::utils::{build_network, build_network_with_anemo_config};
+use anemo::rpc::Status;
+use anemo::types::response::StatusCode;
 use anemo::types::PeerAffinity;
 use anemo::Result;
 use fastcrypto::ed25519::Ed25519PublicKey;
@@ -715,3 +717,123 @@ fn create_test_channel() -> (
     let (tx, rx) = watch::channel(TrustedPeerChangeEvent { new_peers: vec![] });
     (tx, rx)
 }
+
+#[test]
+fn peer_scores_drop_with_failures_and_latency() {
+    let scores = PeerScores::default();
+    let peer = |i| PeerId([i; 32]);
+    assert_eq!(scores.score(&peer(1)), 100.0);
+
+    for _ in 0..10 {
+        scores.record_success(peer(1), Duration::from_millis(10));
+        scores.record_success(peer(2), Duration::from_millis(500));
+        scores.record_failure(peer(3));
+    }
+    assert!(scores.score(&peer(1)) > scores.score(&peer(2)));
+    assert!(scores.score(&peer(2)) > scores.score(&peer(3)));
+
+    let snapshot = scores.snapshot();
+    assert_eq!(
+        snapshot.iter().map(|s| s.peer_id).collect::<Vec<_>>(),
+        vec![peer(1), peer(2), peer(3)]
+    );
+    assert_eq!(snapshot[2].failures, 10);
+}
+
+#[test]
+fn peer_scores_timeouts_weigh_less_than_failures() {
+    let scores = PeerScores::default();
+    let peer = |i| PeerId([i; 32]);
+
+    for _ in 0..10 {
+        scores.record_timeout(peer(1));
+        scores.record_failure(peer(2));
+    }
+    assert!(scores.score(&peer(1)) < 100.0);
+    assert!(scores.score(&peer(1)) > scores.score(&peer(2)));
+
+    scores.record_error(peer(3), &Status::new(StatusCode::RequestTimeout));
+    scores.record_error(peer(3), &Status::new(StatusCode::InternalServerError));
+    let snapshot = scores.snapshot();
+    let peer_3 = snapshot.iter().find(|s| s.peer_id == peer(3)).unwrap();
+    assert_eq!((peer_3.timeouts, peer_3.failures), (1, 1));
+}
+
+#[test]
+fn peer_scores_ban_and_unban() {
+    let scores = PeerScores::default();
+    let peer = |i| PeerId([i; 32]);
+    let ban_duration = DiscoveryConfig::default().peer_ban_duration();
+    let now = std::time::Instant::now();
+
+    // Peers aren't banned until there are 10 samples from them.
+    for _ in 0..9 {
+        scores.record_failure(peer(1));
+    }
+    assert!(scores.update_bans(now, |_| false).is_empty());
+
+    scores.record_failure(peer(1));
+    for _ in 0..10 {
+        scores.record_failure(peer(2));
+        scores.record_success(peer(3), Duration::from_millis(10));
+    }
+    // Peer 2 is exempt, e.g. because it is allowlisted.
+    assert_eq!(scores.update_bans(now, |p| p == &peer(2)), vec![peer(1)]);
+    assert!(scores.is_banned(&peer(1)));
+    assert!(!scores.is_banned(&peer(2)));
+    assert!(!scores.is_banned(&peer(3)));
+
+    // Banned peers are not forgotten.
+    scores.retain(|_| false);
+    assert!(scores.is_banned(&peer(1)));
+    assert_eq!(scores.snapshot().len(), 1);
+
+    // Peers are unbanned with a clean slate.
+    scores.update_bans(now + ban_duration, |_| false);
+    assert!(!scores.is_banned(&peer(1)));
+    assert_eq!(scores.score(&peer(1)), 100.0);
+}
+
+#[tokio::test]
+async fn high_affinity_peers_are_never_banned() {
+    let (builder, network) = set_up_network(P2pConfig::default());
+    let (mut event_loop, _handle, _state) = start_network(builder, network.clone());
+    let peer = |i| PeerId([i; 32]);
+
+    // Peer 1 is a committee member, which the network keeps reconnecting to.
+    network.known_peers().insert(PeerInfo {
+        peer_id: peer(1),
+        affinity: PeerAffinity::High,
+        address: vec![],
+    });
+    for _ in 0..10 {
+        event_loop.peer_scores.record_failure(peer(1));
+        event_loop.peer_scores.record_failure(peer(2));
+    }
+
+    event_loop.update_peer_bans(std::time::Instant::now());
+    assert!(!event_loop.peer_scores.is_banned(&peer(1)));
+    assert!(event_loop.peer_scores.is_banned(&peer(2)));
+}
+
+#[test]
+fn peer_scores_rate_limit_violations_decay() {
+    let scores = PeerScores::default();
+    let peer = PeerId([1; 32]);
+    let now = std::time::Instant::now();
+    scores.update_bans(now, |_| false);
+
+    scores.record_rate_limit_violation(peer);
+    assert_eq!(scores.score(&peer), 80.0);
+
+    // The penalty halves every minute.
+    let later = now + Duration::from_secs(60);
+    scores.update_bans(later, |_| false);
+    assert!((scores.score(&peer) - 90.0).abs() < 1e-6);
+
+    // Repeated violations get a peer banned.
+    for _ in 0..10 {
+        scores.record_rate_limit_violation(peer);
+    }
+    assert_eq!(scores.update_bans(later, |_| false), vec![peer]);
+}
@@ -16,8 +16,15 @@ use tokio::{
     sync::{broadcast, mpsc},
     task::JoinSet,
 };
+use tracing::warn;
+
+use crate::discovery::{PeerScores, RateLimitViolationLayer};
 
 use super::{
+    content_source::{
+        ArchiveContentSource, CheckpointContentSource, CheckpointContentSources,
+        ObjectStoreContentSource, PeerContentSource,
+    },
     metrics::Metrics,
     server::{CheckpointContentsDownloadLimitLayer, Server},
     Handle, PeerHeights, StateSync, StateSyncEventLoop, StateSyncMessage, StateSyncServer,
@@ -29,6 +36,8 @@ pub struct Builder<S> {
     config: Option<StateSyncConfig>,
     metrics: Option<Metrics>,
     archive_readers: Option<ArchiveReaderBalancer>,
+    content_sources: Vec<Arc<dyn CheckpointContentSource>>,
+    peer_scores: Option<PeerScores>,
 }
 
 impl Builder<()> {
@@ -39,6 +48,8 @@ impl Builder<()> {
             config: None,
             metrics: None,
             archive_readers: None,
+            content_sources: Vec::new(),
+            peer_scores: None,
         }
     }
 }
@@ -50,6 +61,8 @@ impl<S> Builder<S> {
             config: self.config,
             metrics: self.metrics,
             archive_readers: self.archive_readers,
+            content_sources: self.content_sources,
+            peer_scores: self.peer_scores,
         }
     }
 
@@ -67,6 +80,20 @@ impl<S> Builder<S> {
         self.archive_readers = Some(archive_readers);
         self
     }
+
+    /// Adds a source to download checkpoint contents from, in addition to peers, archive readers
+    /// and the object store from the config.
+    pub fn content_source(mut self, source: Arc<dyn CheckpointContentSource>) -> Self {
+        self.content_sources.push(source);
+        self
+    }
+
+    /// Sets the peer scores that StateSync reports the outcome of requests to peers and rate
+    /// limit violations to, and that it uses to avoid banned peers.
+    pub fn peer_scores(mut self, peer_scores: PeerScores) -> Self {
+        self.peer_scores = Some(peer_scores);
+        self
+    }
 }
 
 impl<S> Builder<S>
@@ -99,9 +126,15 @@ where
             state_sync_server = state_sync_server.add_layer_for_get_checkpoint_contents(
                 InboundRequestLayer::new(rate_limit::RateLimitLayer::new(
                     governor::Quota::per_second(limit),
-                    rate_limit::WaitMode::Block,
+                    rate_limit::WaitMode::ReturnError,
                 )),
             );
+            // Record requests rejected by the per-peer limit above against the requesting peer's
+            // score. The global limits below are added outside of this layer, as hitting them says
+            // nothing about the peer.
+            state_sync_server = state_sync_server.add_layer_for_get_checkpoint_contents(
+                InboundRequestLayer::new(RateLimitViolationLayer::new(builder.peer_scores.clone())),
+            );
         }
         if let Some(limit) = state_sync_config.get_checkpoint_contents_inflight_limit {
             state_sync_server = state_sync_server.add_layer_for_get_checkpoint_contents(
@@ -127,11 +160,26 @@ where
             config,
             metrics,
             archive_readers,
+            mut content_sources,
+            peer_scores,
         } = self;
         let store = store.unwrap();
         let config = config.unwrap_or_default();
         let metrics = metrics.unwrap_or_else(Metrics::disabled);
         let archive_readers = archive_readers.unwrap_or_default();
+        let peer_scores = peer_scores.unwrap_or_default();
+
+        if let Some(object_store_config) = &config.checkpoint_content_object_store {
+            match object_store_config.make() {
+                Ok(object_store) => {
+                    content_sources.push(Arc::new(ObjectStoreContentSource::new(object_store)))
+                }
+                Err(e) => warn!("Failed to create checkpoint content object store: {e:?}"),
+            }
+        }
+        if !archive_readers.is_empty() {
+            content_sources.push(Arc::new(ArchiveContentSource::new(archive_readers)));
+        }
 
         let (sender, mailbox) = mpsc::channel(config.mailbox_capacity());
         let (checkpoint_event_sender, _receiver) =
@@ -166,7 +214,8 @@ where
                 peer_heights,
                 checkpoint_event_sender,
                 metrics,
-                archive_readers,
+                content_sources,
+                peer_scores,
             },
             server,
         )
@@ -182,7 +231,8 @@ pub struct UnstartedStateSync<S> {
     pub(super) peer_heights: Arc<RwLock<PeerHeights>>,
     pub(super) checkpoint_event_sender: broadcast::Sender<VerifiedCheckpoint>,
     pub(super) metrics: Metrics,
-    pub(super) archive_readers: ArchiveReaderBalancer,
+    pub(super) content_sources: Vec<Arc<dyn CheckpointContentSource>>,
+    pub(super) peer_scores: PeerScores,
 }
 
 impl<S> UnstartedStateSync<S>
@@ -199,9 +249,20 @@ where
             peer_heights,
             checkpoint_event_sender,
             metrics,
-            archive_readers,
+            mut content_sources,
+            peer_scores,
         } = self;
 
+        // Peers are always a source of checkpoint contents.
+        content_sources.insert(
+            0,
+            Arc::new(PeerContentSource::new(
+                network.clone(),
+                peer_heights.clone(),
+                peer_scores.clone(),
+            )),
+        );
+
         (
             StateSyncEventLoop {
                 config,
@@ -216,8 +277,8 @@ where
                 checkpoint_event_sender,
                 network,
                 metrics,
-                archive_readers,
-                sync_checkpoint_from_archive_task: None,
+                peer_scores,
+                content_sources: Arc::new(CheckpointContentSources::new(content_sources)),
             },
             handle,
         )
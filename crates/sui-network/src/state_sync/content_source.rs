@@ -0,0 +1,418 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sources that checkpoint contents can be downloaded from.
+//!
+//! StateSync downloads the contents of verified checkpoints from a list of
+//! [CheckpointContentSource]s: our p2p peers, archive readers and an object store holding
+//! checkpoint blobs, along with any source plugged in through [Builder::content_source]. Sources
+//! are tried in order of their cost, and sources of the same cost in order of their observed
+//! latency. Contents are verified against the digest in the synced checkpoint summary no matter
+//! which source they come from.
+//!
+//! [Builder::content_source]: super::Builder::content_source
+
+use anemo::{Request, Response};
+use async_trait::async_trait;
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
+use object_store::{path::Path, DynObjectStore};
+use std::{
+    collections::BTreeMap,
+    ops::Range,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+use sui_archival::reader::ArchiveReaderBalancer;
+use sui_storage::{blob::Blob, object_store::ObjectStoreGetExt};
+use sui_types::{
+    base_types::ExecutionData,
+    full_checkpoint_content::CheckpointData,
+    messages_checkpoint::{
+        CheckpointSequenceNumber, FullCheckpointContents, VerifiedCheckpoint,
+        VerifiedCheckpointContents,
+    },
+    storage::WriteStore,
+};
+use tap::{TapFallible, TapOptional};
+use tracing::{debug, instrument, trace, warn};
+
+use super::{PeerBalancer, PeerCheckpointRequestType, PeerHeights};
+use crate::discovery::PeerScores;
+
+/// A source of checkpoint contents.
+#[async_trait]
+pub trait CheckpointContentSource: Send + Sync + 'static {
+    /// Name of the source, used in logs.
+    fn name(&self) -> &str;
+
+    /// Relative cost of downloading from this source. Sources with a lower cost are tried first.
+    fn cost(&self) -> u64;
+
+    /// Downloads the contents of `checkpoint`, or returns `None` if the source is unable to
+    /// provide them. The returned contents do not need to be verified.
+    async fn get_checkpoint_contents(
+        &self,
+        checkpoint: &VerifiedCheckpoint,
+        timeout: Duration,
+    ) -> Option<FullCheckpointContents>;
+}
+
+/// Downloads checkpoint contents from our p2p peers, preferring peers with a lower RTT.
+pub(super) struct PeerContentSource {
+    network: anemo::Network,
+    peer_heights: Arc<RwLock<PeerHeights>>,
+    peer_scores: PeerScores,
+}
+
+impl PeerContentSource {
+    const NAME: &'static str = "peers";
+
+    pub(super) fn new(
+        network: anemo::Network,
+        peer_heights: Arc<RwLock<PeerHeights>>,
+        peer_scores: PeerScores,
+    ) -> Self {
+        Self {
+            network,
+            peer_heights,
+            peer_scores,
+        }
+    }
+}
+
+#[async_trait]
+impl CheckpointContentSource for PeerContentSource {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn cost(&self) -> u64 {
+        0
+    }
+
+    async fn get_checkpoint_contents(
+        &self,
+        checkpoint: &VerifiedCheckpoint,
+        timeout: Duration,
+    ) -> Option<FullCheckpointContents> {
+        let peers = PeerBalancer::new(
+            &self.network,
+            self.peer_heights.clone(),
+            &self.peer_scores,
+            PeerCheckpointRequestType::Content,
+        )
+        .with_checkpoint(*checkpoint.sequence_number());
+        let digest = checkpoint.content_digest;
+
+        // Iterate through our selected peers trying each one in turn until we're able to
+        // successfully get the target checkpoint
+        for mut peer in peers {
+            let peer_id = peer.inner().peer_id();
+            debug!(?timeout, "requesting checkpoint contents from {peer_id}");
+            let request = Request::new(digest).with_timeout(timeout);
+            let start = Instant::now();
+            if let Some(contents) = peer
+                .get_checkpoint_contents(request)
+                .await
+                .tap_err(|e| {
+                    trace!("{e:?}");
+                    self.peer_scores.record_error(peer_id, e);
+                })
+                .ok()
+                .and_then(Response::into_inner)
+                .tap_none(|| trace!("peer unable to help sync"))
+            {
+                if contents.verify_digests(digest).is_ok() {
+                    self.peer_scores.record_success(peer_id, start.elapsed());
+                    return Some(contents);
+                }
+                self.peer_scores.record_failure(peer_id);
+            }
+        }
+        debug!("no peers had checkpoint contents");
+        None
+    }
+}
+
+/// A download of an archive content file, shared by concurrent requests for checkpoints in the
+/// file. Resolves to the contents of each checkpoint in the file, in order.
+type ContentsFile = Shared<BoxFuture<'static, Option<Arc<Vec<FullCheckpointContents>>>>>;
+
+/// Downloads checkpoint contents from archive readers. Each archive file holds the contents of a
+/// range of checkpoints, so the last few downloaded files are kept to serve the following
+/// checkpoints.
+pub(super) struct ArchiveContentSource {
+    archive_readers: ArchiveReaderBalancer,
+    // Downloaded and in progress files along with their checkpoint range, keyed by the first
+    // checkpoint in the file. The lock is only held to look up and insert files, never across a
+    // download.
+    files:
+        Mutex<BTreeMap<CheckpointSequenceNumber, (Range<CheckpointSequenceNumber>, ContentsFile)>>,
+}
+
+impl ArchiveContentSource {
+    const MAX_CACHED_FILES: usize = 2;
+
+    pub(super) fn new(archive_readers: ArchiveReaderBalancer) -> Self {
+        Self {
+            archive_readers,
+            files: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn cached_file(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Option<(CheckpointSequenceNumber, ContentsFile)> {
+        let files = self.files.lock().unwrap();
+        let (range, file) = files.range(..=sequence_number).next_back()?.1;
+        range
+            .contains(&sequence_number)
+            .then(|| (range.start, file.clone()))
+    }
+
+    /// Starts downloading the file holding `sequence_number`, unless a concurrent request already
+    /// started it.
+    async fn download_file(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Option<(CheckpointSequenceNumber, ContentsFile)> {
+        let archive_reader = self
+            .archive_readers
+            .pick_one_random(sequence_number..sequence_number + 1)
+            .await
+            .tap_none(|| trace!("no archive reader has checkpoint {sequence_number}"))?;
+        let range = archive_reader
+            .contents_file_range(sequence_number)
+            .await
+            .tap_err(|e| trace!("{e:?}"))
+            .ok()?;
+        let start = range.start;
+
+        let mut files = self.files.lock().unwrap();
+        let file = match files.get(&start) {
+            // Archives may split checkpoints into files differently, so a concurrent download is
+            // only shared if it is of the same range.
+            Some((cached_range, file)) if *cached_range == range => file.clone(),
+            _ => {
+                let expected_range = range.clone();
+                let file = async move {
+                    let (file_range, contents) = archive_reader
+                        .read_contents_file(sequence_number)
+                        .await
+                        .tap_err(|e| {
+                            warn!("Failed to read checkpoint contents from archive: {e:?}")
+                        })
+                        .ok()?;
+                    if file_range != expected_range {
+                        warn!(
+                            "Archive file range changed from {expected_range:?} to {file_range:?}"
+                        );
+                        return None;
+                    }
+                    Some(Arc::new(contents))
+                }
+                .boxed()
+                .shared();
+                files.insert(start, (range, file.clone()));
+                file
+            }
+        };
+        while files.len() > Self::MAX_CACHED_FILES {
+            files.pop_first();
+        }
+        Some((start, file))
+    }
+}
+
+#[async_trait]
+impl CheckpointContentSource for ArchiveContentSource {
+    fn name(&self) -> &str {
+        "archive"
+    }
+
+    fn cost(&self) -> u64 {
+        2
+    }
+
+    // Archive files are much larger than the contents of a single checkpoint, so downloads are
+    // not bounded by the per-checkpoint timeout.
+    async fn get_checkpoint_contents(
+        &self,
+        checkpoint: &VerifiedCheckpoint,
+        _timeout: Duration,
+    ) -> Option<FullCheckpointContents> {
+        let sequence_number = *checkpoint.sequence_number();
+        let (start, file) = match self.cached_file(sequence_number) {
+            Some(file) => file,
+            None => self.download_file(sequence_number).await?,
+        };
+        let Some(contents) = file.clone().await else {
+            // Forget the failed download so that the next request tries again.
+            let mut files = self.files.lock().unwrap();
+            if files.get(&start).is_some_and(|(_, f)| f.ptr_eq(&file)) {
+                files.remove(&start);
+            }
+            return None;
+        };
+        contents.get((sequence_number - start) as usize).cloned()
+    }
+}
+
+/// Downloads checkpoint contents from `{sequence_number}.chk` blobs of `CheckpointData` in an
+/// object store, as written by the data ingestion blob worker.
+pub struct ObjectStoreContentSource {
+    object_store: Arc<DynObjectStore>,
+}
+
+impl ObjectStoreContentSource {
+    pub fn new(object_store: Arc<DynObjectStore>) -> Self {
+        Self { object_store }
+    }
+}
+
+#[async_trait]
+impl CheckpointContentSource for ObjectStoreContentSource {
+    fn name(&self) -> &str {
+        "object_store"
+    }
+
+    fn cost(&self) -> u64 {
+        1
+    }
+
+    async fn get_checkpoint_contents(
+        &self,
+        checkpoint: &VerifiedCheckpoint,
+        timeout: Duration,
+    ) -> Option<FullCheckpointContents> {
+        let path = Path::from(format!("{}.chk", checkpoint.sequence_number()));
+        let bytes = tokio::time::timeout(timeout, self.object_store.get_bytes(&path))
+            .await
+            .tap_err(|_| trace!("timed out reading {path} from object store"))
+            .ok()?
+            .tap_err(|e| trace!("failed to read {path} from object store: {e:?}"))
+            .ok()?;
+        let checkpoint_data: CheckpointData = Blob::from_bytes(&bytes)
+            .tap_err(|e| warn!("Malformed checkpoint blob {path} in object store: {e:?}"))
+            .ok()?;
+        Some(FullCheckpointContents::from_contents_and_execution_data(
+            checkpoint_data.checkpoint_contents,
+            checkpoint_data
+                .transactions
+                .into_iter()
+                .map(|tx| ExecutionData {
+                    transaction: tx.transaction,
+                    effects: tx.effects,
+                }),
+        ))
+    }
+}
+
+struct RankedSource {
+    source: Arc<dyn CheckpointContentSource>,
+    // Moving average of the time taken to download contents, where failures count as taking
+    // the full timeout.
+    latency: Mutex<Duration>,
+}
+
+impl RankedSource {
+    fn record_latency(&self, sample: Duration) {
+        let mut latency = self.latency.lock().unwrap();
+        *latency = latency.mul_f64(0.8) + sample.mul_f64(0.2);
+    }
+}
+
+/// The list of sources that StateSync downloads checkpoint contents from.
+pub(super) struct CheckpointContentSources {
+    sources: Vec<RankedSource>,
+}
+
+impl CheckpointContentSources {
+    pub(super) fn new(sources: Vec<Arc<dyn CheckpointContentSource>>) -> Self {
+        Self {
+            sources: sources
+                .into_iter()
+                .map(|source| RankedSource {
+                    source,
+                    latency: Mutex::new(Duration::ZERO),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns true if there are sources other than our p2p peers.
+    pub(super) fn has_non_peer_sources(&self) -> bool {
+        self.sources
+            .iter()
+            .any(|s| s.source.name() != PeerContentSource::NAME)
+    }
+
+    /// Sources in the order they should be tried.
+    fn ranked(&self) -> Vec<&RankedSource> {
+        let mut sources: Vec<_> = self
+            .sources
+            .iter()
+            .map(|s| ((s.source.cost(), *s.latency.lock().unwrap()), s))
+            .collect();
+        sources.sort_by_key(|(rank, _)| *rank);
+        sources.into_iter().map(|(_, s)| s).collect()
+    }
+
+    /// Returns the contents of `checkpoint`, from the local store if available, or downloaded
+    /// from the first source able to provide contents matching the checkpoint's content digest.
+    /// Downloaded contents are inserted into the store.
+    #[instrument(level = "debug", skip_all)]
+    pub(super) async fn get_full_checkpoint_contents<S>(
+        &self,
+        store: S,
+        checkpoint: &VerifiedCheckpoint,
+        timeout: Duration,
+    ) -> Option<FullCheckpointContents>
+    where
+        S: WriteStore,
+    {
+        let digest = checkpoint.content_digest;
+        if let Some(contents) = store
+            .get_full_checkpoint_contents_by_sequence_number(*checkpoint.sequence_number())
+            .expect("store operation should not fail")
+            .or_else(|| {
+                store
+                    .get_full_checkpoint_contents(&digest)
+                    .expect("store operation should not fail")
+            })
+        {
+            debug!("store already contains checkpoint contents");
+            return Some(contents);
+        }
+
+        for ranked in self.ranked() {
+            let source = &ranked.source;
+            let start = Instant::now();
+            let Some(contents) = source.get_checkpoint_contents(checkpoint, timeout).await else {
+                ranked.record_latency(timeout);
+                continue;
+            };
+            if let Err(e) = contents.verify_digests(digest) {
+                warn!(
+                    "Checkpoint contents from {} failed verification: {e:?}",
+                    source.name()
+                );
+                ranked.record_latency(timeout);
+                continue;
+            }
+            ranked.record_latency(start.elapsed());
+            debug!("downloaded checkpoint contents from {}", source.name());
+            let verified_contents = VerifiedCheckpointContents::new_unchecked(contents.clone());
+            store
+                .insert_checkpoint_contents(checkpoint, verified_contents)
+                .expect("store operation should not fail");
+            return Some(contents);
+        }
+        debug!("no source had checkpoint contents");
+        None
+    }
+}
@@ -50,11 +50,10 @@
 use anemo::{types::PeerEvent, PeerId, Request, Response, Result};
 use futures::{stream::FuturesOrdered, FutureExt, StreamExt};
 use rand::Rng;
-use std::sync::atomic::{AtomicU64, Ordering};
 use std::{
     collections::{HashMap, VecDeque},
     sync::{Arc, RwLock},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use sui_config::p2p::StateSyncConfig;
 use sui_types::{
@@ -62,7 +61,7 @@ use sui_types::{
     digests::CheckpointDigest,
     messages_checkpoint::{
         CertifiedCheckpointSummary as Checkpoint, CheckpointSequenceNumber, EndOfEpochData,
-        FullCheckpointContents, VerifiedCheckpoint, VerifiedCheckpointContents,
+        VerifiedCheckpoint,
     },
     storage::WriteStore,
 };
@@ -78,22 +77,28 @@ mod generated {
     include!(concat!(env!("OUT_DIR"), "/sui.StateSync.rs"));
 }
 mod builder;
+mod content_source;
 mod metrics;
 mod server;
 #[cfg(test)]
 mod tests;
 
 pub use builder::{Builder, UnstartedStateSync};
+pub use content_source::{CheckpointContentSource, ObjectStoreContentSource};
 pub use generated::{
     state_sync_client::StateSyncClient,
     state_sync_server::{StateSync, StateSyncServer},
 };
 pub use server::GetCheckpointAvailabilityResponse;
 pub use server::GetCheckpointSummaryRequest;
-use sui_archival::reader::ArchiveReaderBalancer;
 use sui_storage::verify_checkpoint;
 
-use self::{metrics::Metrics, server::CheckpointContentsDownloadLimitLayer};
+use crate::discovery::PeerScores;
+
+use self::{
+    content_source::CheckpointContentSources, metrics::Metrics,
+    server::CheckpointContentsDownloadLimitLayer,
+};
 
 /// A handle to the StateSync subsystem.
 ///
@@ -284,12 +289,15 @@ impl PeerBalancer {
     pub fn new(
         network: &anemo::Network,
         peer_heights: Arc<RwLock<PeerHeights>>,
+        peer_scores: &PeerScores,
         request_type: PeerCheckpointRequestType,
     ) -> Self {
         let mut peers: Vec<_> = peer_heights
             .read()
             .unwrap()
             .peers_on_same_chain()
+            // Filter out any peers who are banned for misbehaving.
+            .filter(|(peer_id, _info)| !peer_scores.is_banned(peer_id))
             // Filter out any peers who we aren't connected with.
             .filter_map(|(peer_id, info)| network.peer(*peer_id).map(|peer| (peer, *info)))
             .collect();
@@ -366,9 +374,9 @@ struct StateSyncEventLoop<S> {
     checkpoint_event_sender: broadcast::Sender<VerifiedCheckpoint>,
     network: anemo::Network,
     metrics: Metrics,
+    peer_scores: PeerScores,
 
-    archive_readers: ArchiveReaderBalancer,
-    sync_checkpoint_from_archive_task: Option<AbortHandle>,
+    content_sources: Arc<CheckpointContentSources>,
 }
 
 impl<S> StateSyncEventLoop<S>
@@ -407,7 +415,7 @@ where
 
         // Start checkpoint contents sync loop.
         let task = sync_checkpoint_contents(
-            self.network.clone(),
+            self.content_sources.clone(),
             self.store.clone(),
             self.peer_heights.clone(),
             self.weak_sender.clone(),
@@ -420,22 +428,6 @@ where
         let task_handle = self.tasks.spawn(task);
         self.sync_checkpoint_contents_task = Some(task_handle);
 
-        // Start archive based checkpoint content sync loop.
-        // TODO: Consider switching to sync from archive only on startup.
-        // Right now because the peer set is fixed at startup, a node may eventually
-        // end up with peers who have all purged their local state. In such a scenario it will be
-        // stuck until restart when it ends up with a different set of peers. Once the discovery
-        // mechanism can dynamically identify and connect to other peers on the network, we will rely
-        // on sync from archive as a fall back.
-        let task = sync_checkpoint_contents_from_archive(
-            self.network.clone(),
-            self.archive_readers.clone(),
-            self.store.clone(),
-            self.peer_heights.clone(),
-        );
-        let task_handle = self.tasks.spawn(task);
-        self.sync_checkpoint_from_archive_task = Some(task_handle);
-
         // Start main loop.
         loop {
             tokio::select! {
@@ -476,10 +468,6 @@ where
                     if matches!(&self.sync_checkpoint_summaries_task, Some(t) if t.is_finished()) {
                         self.sync_checkpoint_summaries_task = None;
                     }
-
-                    if matches!(&self.sync_checkpoint_from_archive_task, Some(t) if t.is_finished()) {
-                        panic!("sync_checkpoint_from_archive task unexpectedly terminated")
-                    }
                 },
             }
 
@@ -679,6 +667,7 @@ where
                 self.network.clone(),
                 self.store.clone(),
                 self.peer_heights.clone(),
+                self.peer_scores.clone(),
                 self.metrics.clone(),
                 self.config.pinned_checkpoints.clone(),
                 self.config.checkpoint_header_download_concurrency(),
@@ -712,13 +701,15 @@ where
 
         if highest_verified_checkpoint.sequence_number()
             > highest_synced_checkpoint.sequence_number()
-            // skip if we aren't connected to any peers that can help
-            && self
-                .peer_heights
-                .read()
-                .unwrap()
-                .highest_known_checkpoint_sequence_number()
-                > Some(*highest_synced_checkpoint.sequence_number())
+            // skip if we aren't connected to any peers that can help, and there are no other
+            // sources of checkpoint contents
+            && (self.content_sources.has_non_peer_sources()
+                || self
+                    .peer_heights
+                    .read()
+                    .unwrap()
+                    .highest_known_checkpoint_sequence_number()
+                    > Some(*highest_synced_checkpoint.sequence_number()))
         {
             let _ = target_sequence_channel.send_if_modified(|num| {
                 let new_num = *highest_verified_checkpoint.sequence_number();
@@ -936,6 +927,7 @@ async fn sync_to_checkpoint<S>(
     network: anemo::Network,
     store: S,
     peer_heights: Arc<RwLock<PeerHeights>>,
+    peer_scores: PeerScores,
     metrics: Metrics,
     pinned_checkpoints: Vec<(CheckpointSequenceNumber, CheckpointDigest)>,
     checkpoint_header_download_concurrency: usize,
@@ -961,6 +953,7 @@ where
     let peer_balancer = PeerBalancer::new(
         &network,
         peer_heights.clone(),
+        &peer_scores,
         PeerCheckpointRequestType::Summary,
     );
     // range of the next sequence_numbers to fetch
@@ -970,6 +963,7 @@ where
             let peers = peer_balancer.clone().with_checkpoint(next);
             let peer_heights = peer_heights.clone();
             let pinned_checkpoints = &pinned_checkpoints;
+            let peer_scores = &peer_scores;
             async move {
                 if let Some(checkpoint) = peer_heights
                     .read()
@@ -982,12 +976,17 @@ where
                 // Iterate through peers trying each one in turn until we're able to
                 // successfully get the target checkpoint
                 for mut peer in peers {
+                    let peer_id = peer.inner().peer_id();
                     let request = Request::new(GetCheckpointSummaryRequest::BySequenceNumber(next))
                         .with_timeout(timeout);
+                    let start = Instant::now();
                     if let Some(checkpoint) = peer
                         .get_checkpoint_summary(request)
                         .await
-                        .tap_err(|e| trace!("{e:?}"))
+                        .tap_err(|e| {
+                            trace!("{e:?}");
+                            peer_scores.record_error(peer_id, e);
+                        })
                         .ok()
                         .and_then(Response::into_inner)
                         .tap_none(|| trace!("peer unable to help sync"))
@@ -998,6 +997,7 @@ where
                                 "peer returned checkpoint with wrong sequence number: expected {next}, got {}",
                                 checkpoint.sequence_number()
                             );
+                            peer_scores.record_failure(peer_id);
                             continue;
                         }
 
@@ -1013,16 +1013,18 @@ where
                                     pinned_checkpoints[pinned_digest_index].1,
                                     checkpoint_digest
                                 );
+                                peer_scores.record_failure(peer_id);
                                 continue;
                             }
                         }
 
+                        peer_scores.record_success(peer_id, start.elapsed());
                         // Insert in our store in the event that things fail and we need to retry
                         peer_heights
                             .write()
                             .unwrap()
                             .insert_checkpoint(checkpoint.clone());
-                        return (Some(checkpoint), next, Some(peer.inner().peer_id()));
+                        return (Some(checkpoint), next, Some(peer_id));
                     }
                 }
                 (None, next, None)
@@ -1063,6 +1065,7 @@ where
                     // Mark peer as not on the same chain as us
                     if let Some(peer_id) = maybe_peer_id {
                         peer_heights.mark_peer_as_not_on_same_chain(peer_id);
+                        peer_scores.record_failure(peer_id);
                     }
 
                     return Err(anyhow::anyhow!(
@@ -1093,71 +1096,8 @@ where
     Ok(())
 }
 
-async fn sync_checkpoint_contents_from_archive<S>(
-    network: anemo::Network,
-    archive_readers: ArchiveReaderBalancer,
-    store: S,
-    peer_heights: Arc<RwLock<PeerHeights>>,
-) where
-    S: WriteStore + Clone + Send + Sync + 'static,
-{
-    loop {
-        let peers: Vec<_> = peer_heights
-            .read()
-            .unwrap()
-            .peers_on_same_chain()
-            // Filter out any peers who we aren't connected with.
-            .filter_map(|(peer_id, info)| network.peer(*peer_id).map(|peer| (peer, *info)))
-            .collect();
-        let lowest_checkpoint_on_peers = peers
-            .iter()
-            .map(|(_p, state_sync_info)| state_sync_info.lowest)
-            .min();
-        let highest_synced = store
-            .get_highest_synced_checkpoint()
-            .expect("store operation should not fail")
-            .sequence_number;
-        let sync_from_archive = if let Some(lowest_checkpoint_on_peers) = lowest_checkpoint_on_peers
-        {
-            highest_synced < lowest_checkpoint_on_peers
-        } else {
-            false
-        };
-        if sync_from_archive {
-            let start = highest_synced
-                .checked_add(1)
-                .expect("Checkpoint seq num overflow");
-            let checkpoint_range = start..lowest_checkpoint_on_peers.unwrap();
-            if let Some(archive_reader) = archive_readers
-                .pick_one_random(checkpoint_range.clone())
-                .await
-            {
-                let txn_counter = Arc::new(AtomicU64::new(0));
-                let checkpoint_counter = Arc::new(AtomicU64::new(0));
-                if let Err(err) = archive_reader
-                    .read(
-                        store.clone(),
-                        checkpoint_range,
-                        txn_counter.clone(),
-                        checkpoint_counter.clone(),
-                        true,
-                    )
-                    .await
-                {
-                    warn!("State sync from archive failed with error: {:?}", err);
-                } else {
-                    info!("State sync from archive is complete. Checkpoints downloaded = {:?}, Txns downloaded = {:?}", checkpoint_counter.load(Ordering::Relaxed), txn_counter.load(Ordering::Relaxed));
-                }
-            } else {
-                warn!("Failed to find an archive reader to complete the state sync request");
-            }
-        }
-        tokio::time::sleep(Duration::from_secs(5)).await;
-    }
-}
-
 async fn sync_checkpoint_contents<S>(
-    network: anemo::Network,
+    content_sources: Arc<CheckpointContentSources>,
     store: S,
     peer_heights: Arc<RwLock<PeerHeights>>,
     sender: mpsc::WeakSender<StateSyncMessage>,
@@ -1220,7 +1160,7 @@ async fn sync_checkpoint_contents<S>(
                         }
                         // Retry contents sync on failure.
                         checkpoint_contents_tasks.push_front(sync_one_checkpoint_contents(
-                            network.clone(),
+                            content_sources.clone(),
                             &store,
                             peer_heights.clone(),
                             timeout,
@@ -1253,7 +1193,7 @@ async fn sync_checkpoint_contents<S>(
             highest_started_network_total_transactions = next_checkpoint.network_total_transactions;
             current_sequence += 1;
             checkpoint_contents_tasks.push_back(sync_one_checkpoint_contents(
-                network.clone(),
+                content_sources.clone(),
                 &store,
                 peer_heights.clone(),
                 timeout,
@@ -1275,7 +1215,7 @@ async fn sync_checkpoint_contents<S>(
 
 #[instrument(level = "debug", skip_all, fields(sequence_number = ?checkpoint.sequence_number()))]
 async fn sync_one_checkpoint_contents<S>(
-    network: anemo::Network,
+    content_sources: Arc<CheckpointContentSources>,
     store: S,
     peer_heights: Arc<RwLock<PeerHeights>>,
     timeout: Duration,
@@ -1298,14 +1238,10 @@ where
         return Ok(checkpoint);
     }
 
-    // Request checkpoint contents from peers.
-    let peers = PeerBalancer::new(
-        &network,
-        peer_heights.clone(),
-        PeerCheckpointRequestType::Content,
-    )
-    .with_checkpoint(*checkpoint.sequence_number());
-    let Some(_contents) = get_full_checkpoint_contents(peers, &store, &checkpoint, timeout).await
+    // Request checkpoint contents from peers and other sources.
+    let Some(_contents) = content_sources
+        .get_full_checkpoint_contents(&store, &checkpoint, timeout)
+        .await
     else {
         // Delay completion in case of error so we don't hammer the network with retries.
         let duration = peer_heights
@@ -1320,60 +1256,6 @@ where
     Ok(checkpoint)
 }
 
-#[instrument(level = "debug", skip_all)]
-async fn get_full_checkpoint_contents<S>(
-    peers: PeerBalancer,
-    store: S,
-    checkpoint: &VerifiedCheckpoint,
-    timeout: Duration,
-) -> Option<FullCheckpointContents>
-where
-    S: WriteStore,
-{
-    let digest = checkpoint.content_digest;
-    if let Some(contents) = store
-        .get_full_checkpoint_contents_by_sequence_number(*checkpoint.sequence_number())
-        .expect("store operation should not fail")
-        .or_else(|| {
-            store
-                .get_full_checkpoint_contents(&digest)
-                .expect("store operation should not fail")
-        })
-    {
-        debug!("store already contains checkpoint contents");
-        return Some(contents);
-    }
-
-    // Iterate through our selected peers trying each one in turn until we're able to
-    // successfully get the target checkpoint
-    for mut peer in peers {
-        debug!(
-            ?timeout,
-            "requesting checkpoint contents from {}",
-            peer.inner().peer_id(),
-        );
-        let request = Request::new(digest).with_timeout(timeout);
-        if let Some(contents) = peer
-            .get_checkpoint_contents(request)
-            .await
-            .tap_err(|e| trace!("{e:?}"))
-            .ok()
-            .and_then(Response::into_inner)
-            .tap_none(|| trace!("peer unable to help sync"))
-        {
-            if contents.verify_digests(digest).is_ok() {
-                let verified_contents = VerifiedCheckpointContents::new_unchecked(contents.clone());
-                store
-                    .insert_checkpoint_contents(checkpoint, verified_contents)
-                    .expect("store operation should not fail");
-                return Some(contents);
-            }
-        }
-    }
-    debug!("no peers had checkpoint contents");
-    None
-}
-
 async fn update_checkpoint_watermark_metrics<S>(
     mut recv: oneshot::Receiver<()>,
     store: S,
@@ -2,25 +2,32 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    discovery::PeerScores,
     state_sync::{
-        Builder, GetCheckpointSummaryRequest, PeerStateSyncInfo, StateSync, StateSyncMessage,
-        UnstartedStateSync,
+        Builder, CheckpointContentSource, GetCheckpointSummaryRequest, PeerStateSyncInfo,
+        StateSync, StateSyncClient, StateSyncMessage, UnstartedStateSync,
     },
     utils::build_network,
 };
-use anemo::{PeerId, Request};
+use anemo::{types::response::StatusCode, PeerId, Request};
 use anyhow::anyhow;
+use async_trait::async_trait;
 use prometheus::Registry;
-use std::num::NonZeroUsize;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::{collections::HashMap, time::Duration};
 use sui_archival::reader::ArchiveReaderBalancer;
 use sui_archival::writer::ArchiveWriter;
 use sui_config::node::ArchiveReaderConfig;
 use sui_config::object_storage_config::{ObjectStoreConfig, ObjectStoreType};
+use sui_config::p2p::StateSyncConfig;
 use sui_storage::{FileCompression, StorageFormat};
 use sui_swarm_config::test_utils::{empty_contents, CommitteeFixture};
 use sui_types::{
-    messages_checkpoint::CheckpointDigest,
+    messages_checkpoint::{
+        CheckpointContentsDigest, CheckpointDigest, FullCheckpointContents, VerifiedCheckpoint,
+    },
     storage::{ReadStore, SharedInMemoryStore, WriteStore},
 };
 use tempfile::tempdir;
@@ -265,6 +272,81 @@ async fn isolated_sync_job() {
     }
 }
 
+/// Starts a node serving state sync with `config`, which records rate limit violations in the
+/// returned scores, and a client node connected to it.
+async fn connect_to_limited_server(
+    config: StateSyncConfig,
+) -> (
+    PeerScores,
+    anemo::Network,
+    anemo::Network,
+    StateSyncClient<anemo::Peer>,
+) {
+    let peer_scores = PeerScores::default();
+    let (_, server) = Builder::new()
+        .store(SharedInMemoryStore::default())
+        .config(config)
+        .peer_scores(peer_scores.clone())
+        .build();
+    let server_network = build_network(|router| router.add_rpc_service(server));
+    let client_network = build_network(|router| router);
+    let server_id = client_network
+        .connect(server_network.local_addr())
+        .await
+        .unwrap();
+    let client = StateSyncClient::new(client_network.peer(server_id).unwrap());
+    (peer_scores, server_network, client_network, client)
+}
+
+#[tokio::test]
+async fn global_limits_do_not_count_against_peer_scores() {
+    // No checkpoint contents can be downloaded at all, so every request hits the global limit,
+    // whatever the peer asking for them does.
+    let (peer_scores, _server_network, _client_network, mut client) =
+        connect_to_limited_server(StateSyncConfig {
+            get_checkpoint_contents_rate_limit: NonZeroU32::new(1000),
+            get_checkpoint_contents_per_checkpoint_limit: Some(0),
+            ..Default::default()
+        })
+        .await;
+
+    for _ in 0..20 {
+        let status = client
+            .get_checkpoint_contents(Request::new(CheckpointContentsDigest::random()))
+            .await
+            .unwrap_err();
+        assert_eq!(status.status(), StatusCode::TooManyRequests);
+    }
+
+    // The honest peer is not scored, so it can't get banned.
+    assert!(peer_scores.snapshot().is_empty());
+}
+
+#[tokio::test]
+async fn per_peer_limits_count_against_peer_scores() {
+    let (peer_scores, _server_network, client_network, mut client) =
+        connect_to_limited_server(StateSyncConfig {
+            get_checkpoint_contents_rate_limit: NonZeroU32::new(1),
+            ..Default::default()
+        })
+        .await;
+
+    client
+        .get_checkpoint_contents(Request::new(CheckpointContentsDigest::random()))
+        .await
+        .unwrap();
+    let status = client
+        .get_checkpoint_contents(Request::new(CheckpointContentsDigest::random()))
+        .await
+        .unwrap_err();
+    assert_eq!(status.status(), StatusCode::TooManyRequests);
+
+    let scores = peer_scores.snapshot();
+    assert_eq!(scores.len(), 1);
+    assert_eq!(scores[0].peer_id, client_network.peer_id());
+    assert_eq!(scores[0].rate_limit_violations, 1);
+}
+
 #[tokio::test]
 async fn test_state_sync_using_archive() -> anyhow::Result<()> {
     let committee = CommitteeFixture::generate(rand::rngs::OsRng, 0, 4);
@@ -446,6 +528,109 @@ async fn test_state_sync_using_archive() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Serves empty checkpoint contents, counting the checkpoints it was asked for.
+#[derive(Default)]
+struct EmptyContentSource {
+    requests: AtomicUsize,
+}
+
+#[async_trait]
+impl CheckpointContentSource for EmptyContentSource {
+    fn name(&self) -> &str {
+        "empty"
+    }
+
+    fn cost(&self) -> u64 {
+        1
+    }
+
+    async fn get_checkpoint_contents(
+        &self,
+        _checkpoint: &VerifiedCheckpoint,
+        _timeout: Duration,
+    ) -> Option<FullCheckpointContents> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        Some(empty_contents().into_inner())
+    }
+}
+
+#[tokio::test]
+async fn sync_contents_from_plugged_in_source() {
+    let committee = CommitteeFixture::generate(rand::rngs::OsRng, 0, 4);
+    let (ordered_checkpoints, _, _sequence_number_to_digest, _checkpoints) =
+        committee.make_empty_checkpoints(20, None);
+    let oldest_checkpoint_to_keep: u64 = 10;
+
+    // Node 1 can download contents from the plugged in source, Node 2 only has contents of
+    // checkpoints starting at 10.
+    let content_source = Arc::new(EmptyContentSource::default());
+    let (builder, server) = Builder::new()
+        .store(SharedInMemoryStore::default())
+        .content_source(content_source.clone())
+        .build();
+    let network_1 = build_network(|router| router.add_rpc_service(server));
+    let (event_loop_1, _handle_1) = builder.build(network_1.clone());
+    let (builder, server) = Builder::new().store(SharedInMemoryStore::default()).build();
+    let network_2 = build_network(|router| router.add_rpc_service(server));
+    let (event_loop_2, _handle_2) = builder.build(network_2.clone());
+    network_1.connect(network_2.local_addr()).await.unwrap();
+
+    for event_loop in [&event_loop_1, &event_loop_2] {
+        event_loop.store.inner_mut().insert_genesis_state(
+            ordered_checkpoints.first().cloned().unwrap(),
+            empty_contents(),
+            committee.committee().to_owned(),
+        );
+    }
+    {
+        let mut store = event_loop_2.store.inner_mut();
+        for checkpoint in ordered_checkpoints.clone() {
+            store.insert_checkpoint(&checkpoint);
+            store.insert_checkpoint_contents(&checkpoint, empty_contents());
+            store.update_highest_synced_checkpoint(&checkpoint);
+        }
+        for checkpoint in &ordered_checkpoints[0..(oldest_checkpoint_to_keep as usize)] {
+            store
+                .delete_checkpoint_content_test_only(checkpoint.sequence_number)
+                .unwrap();
+        }
+    }
+    event_loop_1.peer_heights.write().unwrap().peers.insert(
+        network_2.peer_id(),
+        PeerStateSyncInfo {
+            genesis_checkpoint_digest: *ordered_checkpoints[0].digest(),
+            on_same_chain_as_us: true,
+            height: *ordered_checkpoints.last().unwrap().sequence_number(),
+            lowest: oldest_checkpoint_to_keep,
+        },
+    );
+
+    let store_1 = event_loop_1.store.clone();
+    tokio::spawn(event_loop_1.start());
+    tokio::spawn(event_loop_2.start());
+
+    let last_checkpoint = ordered_checkpoints.last().unwrap().sequence_number;
+    timeout(Duration::from_secs(60), async {
+        loop {
+            if store_1
+                .inner()
+                .get_highest_synced_checkpoint()
+                .is_some_and(|c| c.sequence_number == last_checkpoint)
+            {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    // Contents of checkpoints that Node 2 pruned came from the plugged in source.
+    assert!(
+        content_source.requests.load(Ordering::Relaxed) >= oldest_checkpoint_to_keep as usize - 1
+    );
+}
+
 #[tokio::test]
 async fn sync_with_checkpoints_being_inserted() {
     telemetry_subscribers::init_for_testing();
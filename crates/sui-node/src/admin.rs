@@ -9,9 +9,12 @@ use axum::{
     Router,
 };
 use humantime::parse_duration;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
 use std::sync::Arc;
+use sui_config::transaction_deny_config::{TransactionDenyConfig, TransactionDenyConfigPatch};
+use sui_config::{Config, NodeConfig};
 use sui_types::error::SuiError;
 use telemetry_subscribers::TracingHandle;
 use tracing::info;
@@ -47,6 +50,47 @@ use tracing::info;
 // Reset tracing to the TRACE_FILTER env var.
 //
 //   $ curl -X POST 'http://127.0.0.1:1337/reset-tracing'
+//
+// View the transaction deny config currently in effect:
+//
+//   $ curl 'http://127.0.0.1:1337/transaction-deny-config'
+//
+// Patch the transaction deny config, e.g. to deny an address and disable package publishing. The
+// patch is a YAML (or JSON) `TransactionDenyConfigPatch`:
+//
+//   $ curl -X POST 'http://127.0.0.1:1337/transaction-deny-config' \
+//       --data-binary $'deny-addresses: ["0x..."]\npackage-publish-disabled: true'
+//
+// Reload the transaction deny config from the node config file, discarding any patches:
+//
+//   $ curl -X POST 'http://127.0.0.1:1337/reload-transaction-deny-config'
+//
+// Changes to the transaction deny config are logged under the `audit` tracing target.
+//
+// The endpoints below dump the state of transaction processing as JSON, to debug stuck
+// transactions. None of them change any state.
+//
+// View the oldest transactions pending in the transaction manager, the transactions being
+// executed, and the objects with the most transactions waiting on them (100 of each by default):
+//
+//   $ curl 'http://127.0.0.1:1337/transaction-manager?limit=20'
+//
+// View the shared object costs accumulated in the last consensus commit, used to defer
+// transactions on congested objects:
+//
+//   $ curl 'http://127.0.0.1:1337/congestion'
+//
+// View whether the authority is overloaded, and the percentage of transactions being shed:
+//
+//   $ curl 'http://127.0.0.1:1337/overload'
+//
+// View the transactions submitted to consensus by this validator that are still inflight:
+//
+//   $ curl 'http://127.0.0.1:1337/consensus-submissions'
+//
+// View the scores of p2p peers, and which of them are banned:
+//
+//   $ curl 'http://127.0.0.1:1337/peer-scores'
 
 const LOGGING_ROUTE: &str = "/logging";
 const TRACING_ROUTE: &str = "/enable-tracing";
@@ -56,24 +100,51 @@ const CLEAR_BUFFER_STAKE_ROUTE: &str = "/clear-override-buffer-stake";
 const FORCE_CLOSE_EPOCH: &str = "/force-close-epoch";
 const CAPABILITIES: &str = "/capabilities";
 const NODE_CONFIG: &str = "/node-config";
+const TRANSACTION_DENY_CONFIG: &str = "/transaction-deny-config";
+const RELOAD_TRANSACTION_DENY_CONFIG: &str = "/reload-transaction-deny-config";
+const TRANSACTION_MANAGER: &str = "/transaction-manager";
+const CONGESTION: &str = "/congestion";
+const OVERLOAD: &str = "/overload";
+const CONSENSUS_SUBMISSIONS: &str = "/consensus-submissions";
+const PEER_SCORES: &str = "/peer-scores";
+
+const DEFAULT_TRANSACTION_MANAGER_LIMIT: usize = 100;
 
 struct AppState {
     node: Arc<SuiNode>,
     tracing_handle: TracingHandle,
+    config_path: PathBuf,
 }
 
-pub async fn run_admin_server(node: Arc<SuiNode>, port: u16, tracing_handle: TracingHandle) {
+pub async fn run_admin_server(
+    node: Arc<SuiNode>,
+    port: u16,
+    tracing_handle: TracingHandle,
+    config_path: PathBuf,
+) {
     let filter = tracing_handle.get_log().unwrap();
 
     let app_state = AppState {
         node,
         tracing_handle,
+        config_path,
     };
 
     let app = Router::new()
         .route(LOGGING_ROUTE, get(get_filter))
         .route(CAPABILITIES, get(capabilities))
         .route(NODE_CONFIG, get(node_config))
+        .route(TRANSACTION_DENY_CONFIG, get(transaction_deny_config))
+        .route(TRANSACTION_DENY_CONFIG, post(patch_transaction_deny_config))
+        .route(
+            RELOAD_TRANSACTION_DENY_CONFIG,
+            post(reload_transaction_deny_config),
+        )
+        .route(TRANSACTION_MANAGER, get(transaction_manager))
+        .route(CONGESTION, get(congestion))
+        .route(OVERLOAD, get(overload))
+        .route(CONSENSUS_SUBMISSIONS, get(consensus_submissions))
+        .route(PEER_SCORES, get(peer_scores))
         .route(LOGGING_ROUTE, post(set_filter))
         .route(
             SET_BUFFER_STAKE_ROUTE,
@@ -216,6 +287,113 @@ async fn node_config(State(state): State<Arc<AppState>>) -> (StatusCode, String)
     (StatusCode::OK, format!("{:#?}\n", node_config))
 }
 
+async fn transaction_deny_config(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    match serde_yaml::to_string(state.node.transaction_deny_config().as_ref()) {
+        Ok(config) => (StatusCode::OK, config),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+async fn patch_transaction_deny_config(
+    State(state): State<Arc<AppState>>,
+    patch: String,
+) -> (StatusCode, String) {
+    let patch: TransactionDenyConfigPatch = match serde_yaml::from_str(&patch) {
+        Ok(patch) => patch,
+        Err(err) => return (StatusCode::BAD_REQUEST, format!("invalid patch: {err}\n")),
+    };
+
+    let previous = state.node.patch_transaction_deny_config(&patch);
+    audit_transaction_deny_config_change(
+        &format!("patch {:?}", patch),
+        &previous,
+        &state.node.transaction_deny_config(),
+    );
+    transaction_deny_config(State(state)).await
+}
+
+async fn reload_transaction_deny_config(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, String) {
+    let config = match NodeConfig::load(&state.config_path) {
+        Ok(config) => config.transaction_deny_config,
+        Err(err) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("can't load config from {:?}: {err}\n", state.config_path),
+            )
+        }
+    };
+
+    let previous = state.node.set_transaction_deny_config(config);
+    audit_transaction_deny_config_change(
+        &format!("reload from {:?}", state.config_path),
+        &previous,
+        &state.node.transaction_deny_config(),
+    );
+    transaction_deny_config(State(state)).await
+}
+
+fn json_response<T: Serialize>(value: &T) -> (StatusCode, String) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => (StatusCode::OK, json),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+struct TransactionManagerQuery {
+    limit: Option<usize>,
+}
+
+async fn transaction_manager(
+    State(state): State<Arc<AppState>>,
+    query: Query<TransactionManagerQuery>,
+) -> (StatusCode, String) {
+    let limit = query.limit.unwrap_or(DEFAULT_TRANSACTION_MANAGER_LIMIT);
+    json_response(&state.node.state().transaction_manager().snapshot(limit))
+}
+
+async fn congestion(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    let epoch_store = state.node.state().load_epoch_store_one_call_per_task();
+    json_response(&epoch_store.last_commit_congestion())
+}
+
+async fn overload(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    json_response(&state.node.state().overload_info.snapshot())
+}
+
+async fn consensus_submissions(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    match state.node.inflight_consensus_submissions().await {
+        Some(submissions) => json_response(&submissions),
+        None => (
+            StatusCode::NOT_FOUND,
+            "Node is not a validator, it does not submit to consensus\n".to_string(),
+        ),
+    }
+}
+
+async fn peer_scores(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    json_response(&state.node.peer_scores())
+}
+
+fn audit_transaction_deny_config_change(
+    change: &str,
+    previous: &TransactionDenyConfig,
+    current: &TransactionDenyConfig,
+) {
+    let to_yaml = |config: &TransactionDenyConfig| {
+        serde_yaml::to_string(config).unwrap_or_else(|err| err.to_string())
+    };
+    info!(
+        target: "audit",
+        change,
+        previous = %to_yaml(previous),
+        current = %to_yaml(current),
+        "Transaction deny config changed"
+    );
+}
+
 #[derive(Deserialize)]
 struct Epoch {
     epoch: u64,
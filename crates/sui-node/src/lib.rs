@@ -58,11 +58,13 @@ use sui_archival::writer::ArchiveWriter;
 use sui_config::node::{ConsensusProtocol, DBCheckpointConfig, RunWithRange};
 use sui_config::node_config_metrics::NodeConfigMetrics;
 use sui_config::object_storage_config::{ObjectStoreConfig, ObjectStoreType};
+use sui_config::transaction_deny_config::{TransactionDenyConfig, TransactionDenyConfigPatch};
 use sui_config::{ConsensusConfig, NodeConfig};
 use sui_core::authority::authority_per_epoch_store::AuthorityPerEpochStore;
 use sui_core::authority::authority_store_tables::AuthorityPerpetualTables;
 use sui_core::authority::epoch_start_configuration::EpochStartConfigTrait;
 use sui_core::authority::epoch_start_configuration::EpochStartConfiguration;
+use sui_core::authority::execution_time_estimator::MAX_ESTIMATES_PER_OBSERVATION;
 use sui_core::authority_aggregator::AuthorityAggregator;
 use sui_core::authority_server::{ValidatorService, ValidatorServiceMetrics};
 use sui_core::checkpoints::checkpoint_executor::{CheckpointExecutor, StopReason};
@@ -72,6 +74,7 @@ use sui_core::checkpoints::{
 };
 use sui_core::consensus_adapter::{
     CheckConnection, ConnectionMonitorStatus, ConsensusAdapter, ConsensusAdapterMetrics,
+    InflightSubmissionSnapshot,
 };
 use sui_core::consensus_manager::{ConsensusManager, ConsensusManagerTrait};
 use sui_core::consensus_throughput_calculator::{
@@ -90,6 +93,7 @@ use sui_core::signature_verifier::SignatureVerifierMetrics;
 use sui_core::state_accumulator::StateAccumulator;
 use sui_core::storage::RocksDbStore;
 use sui_core::transaction_orchestrator::TransactiondOrchestrator;
+use sui_core::transaction_tracing::TransactionTracer;
 use sui_core::{
     authority::{AuthorityState, AuthorityStore},
     authority_client::NetworkAuthorityClient,
@@ -106,9 +110,11 @@ use sui_macros::fail_point;
 use sui_macros::{fail_point_async, replay_log};
 use sui_network::api::ValidatorServer;
 use sui_network::discovery;
-use sui_network::discovery::TrustedPeerChangeEvent;
+use sui_network::discovery::{PeerScore, PeerScores, TrustedPeerChangeEvent};
 use sui_network::state_sync;
-use sui_protocol_config::{Chain, ProtocolConfig, SupportedProtocolVersions};
+use sui_protocol_config::{
+    Chain, PerObjectCongestionControlMode, ProtocolConfig, SupportedProtocolVersions,
+};
 use sui_snapshot::uploader::StateSnapshotUploader;
 use sui_storage::{
     http_key_value_store::HttpKVStore,
@@ -121,7 +127,7 @@ use sui_types::committee::Committee;
 use sui_types::crypto::KeypairTraits;
 use sui_types::error::{SuiError, SuiResult};
 use sui_types::messages_consensus::{
-    check_total_jwk_size, AuthorityCapabilities, ConsensusTransaction,
+    check_total_jwk_size, AuthorityCapabilities, ConsensusTransaction, ExecutionTimeObservation,
 };
 use sui_types::quorum_driver_types::QuorumDriverEffectsQueueResult;
 use sui_types::sui_system_state::epoch_start_sui_system_state::EpochStartSystemState;
@@ -219,8 +225,9 @@ pub struct SuiNode {
     transaction_orchestrator: Option<Arc<TransactiondOrchestrator<NetworkAuthorityClient>>>,
     registry_service: RegistryService,
     metrics: Arc<SuiNodeMetrics>,
+    config_metrics: Arc<NodeConfigMetrics>,
 
-    _discovery: discovery::Handle,
+    discovery: discovery::Handle,
     state_sync_handle: state_sync::Handle,
     randomness_handle: randomness::Handle,
     checkpoint_store: Arc<CheckpointStore>,
@@ -255,6 +262,9 @@ impl fmt::Debug for SuiNode {
 
 static MAX_JWK_KEYS_PER_FETCH: usize = 100;
 
+/// How often validators share changed execution time estimates of shared objects through consensus.
+const EXECUTION_TIME_OBSERVATION_SHARE_INTERVAL: Duration = Duration::from_secs(10);
+
 impl SuiNode {
     pub async fn start(
         config: NodeConfig,
@@ -399,13 +409,59 @@ impl SuiNode {
         }
     }
 
+    fn start_execution_time_observation_sharer(
+        state: Arc<AuthorityState>,
+        epoch_store: Arc<AuthorityPerEpochStore>,
+        consensus_adapter: Arc<ConsensusAdapter>,
+    ) {
+        let epoch = epoch_store.epoch();
+        info!("Starting execution time observation sharing task");
+
+        spawn_monitored_task!(epoch_store.clone().within_alive_epoch(
+            async move {
+                // Observations are recorded per epoch, so everything needs to be shared again.
+                let observer = state.execution_time_observer();
+                observer.reset_shared();
+                let mut interval =
+                    tokio::time::interval(EXECUTION_TIME_OBSERVATION_SHARE_INTERVAL);
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                loop {
+                    interval.tick().await;
+                    let estimates =
+                        observer.take_estimates_to_share(MAX_ESTIMATES_PER_OBSERVATION);
+                    if estimates.is_empty() {
+                        continue;
+                    }
+                    debug!(
+                        "Submitting execution time observation of {} objects to consensus",
+                        estimates.len()
+                    );
+                    let transaction = ConsensusTransaction::new_execution_time_observation(
+                        ExecutionTimeObservation::new(state.name, estimates),
+                    );
+                    consensus_adapter
+                        .submit(transaction, None, &epoch_store)
+                        .tap_err(|e| {
+                            warn!(
+                                "Error when submitting execution time observation to consensus {:?}",
+                                e
+                            )
+                        })
+                        .ok();
+                }
+            }
+            .instrument(error_span!("execution_time_observation_task", epoch)),
+        ));
+    }
+
     pub async fn start_async(
         config: NodeConfig,
         registry_service: RegistryService,
         custom_rpc_runtime: Option<Handle>,
         software_version: &'static str,
     ) -> Result<Arc<SuiNode>> {
-        NodeConfigMetrics::new(&registry_service.default_registry()).record_metrics(&config);
+        let config_metrics = NodeConfigMetrics::new(&registry_service.default_registry());
+        config_metrics.record_metrics(&config);
         let mut config = config.clone();
         if config.supported_protocol_versions.is_none() {
             info!(
@@ -442,7 +498,7 @@ impl SuiNode {
         let perpetual_tables = Arc::new(AuthorityPerpetualTables::open(
             &config.db_path().join("store"),
             Some(perpetual_options.options),
-        ));
+        )?);
         let is_genesis = perpetual_tables
             .database_is_empty()
             .expect("Database read should not fail at init.");
@@ -621,7 +677,9 @@ impl SuiNode {
             config.indirect_objects_threshold,
             config.state_debug_dump_config.clone(),
             config.authority_overload_config.clone(),
+            config.event_subscription_config.clone().unwrap_or_default(),
             archive_readers,
+            TransactionTracer::new(config.transaction_tracing_sample_rate.unwrap_or(0.0)),
         )
         .await;
         // ensure genesis txn was executed
@@ -745,8 +803,9 @@ impl SuiNode {
             transaction_orchestrator,
             registry_service,
             metrics: sui_node_metrics,
+            config_metrics,
 
-            _discovery: discovery_handle,
+            discovery: discovery_handle,
             state_sync_handle,
             randomness_handle,
             checkpoint_store,
@@ -812,6 +871,50 @@ impl SuiNode {
             .clear_override_protocol_upgrade_buffer_stake(epoch)
     }
 
+    pub fn transaction_deny_config(&self) -> Arc<TransactionDenyConfig> {
+        self.state.transaction_deny_config()
+    }
+
+    /// Replaces the transaction deny config read from `NodeConfig` at startup. Returns the
+    /// previous config.
+    pub fn set_transaction_deny_config(
+        &self,
+        config: TransactionDenyConfig,
+    ) -> Arc<TransactionDenyConfig> {
+        let previous = self.state.set_transaction_deny_config(config);
+        self.config_metrics
+            .record_transaction_deny_config(&self.state.transaction_deny_config());
+        previous
+    }
+
+    /// Applies `patch` to the current transaction deny config. Returns the previous config.
+    pub fn patch_transaction_deny_config(
+        &self,
+        patch: &TransactionDenyConfigPatch,
+    ) -> Arc<TransactionDenyConfig> {
+        let previous = self
+            .state
+            .update_transaction_deny_config(|config| config.patched(patch));
+        self.config_metrics
+            .record_transaction_deny_config(&self.state.transaction_deny_config());
+        previous
+    }
+
+    /// Returns the scores of the p2p peers this node has exchanged requests with.
+    pub fn peer_scores(&self) -> Vec<PeerScore> {
+        self.discovery.peer_scores().snapshot()
+    }
+
+    /// Returns the transactions this validator submitted to consensus that have not been
+    /// processed yet, or None if the node is not a validator.
+    pub async fn inflight_consensus_submissions(&self) -> Option<Vec<InflightSubmissionSnapshot>> {
+        self.validator_components
+            .lock()
+            .await
+            .as_ref()
+            .map(|components| components.consensus_adapter.inflight_submissions())
+    }
+
     pub fn set_override_protocol_upgrade_buffer_stake(
         &self,
         epoch: EpochId,
@@ -893,7 +996,12 @@ impl SuiNode {
         let db_checkpoint_config = if config.db_checkpoint_config.checkpoint_path.is_none() {
             DBCheckpointConfig {
                 checkpoint_path,
-                perform_db_checkpoints_at_epoch_end: if state_snapshot_enabled {
+                perform_db_checkpoints_at_epoch_end: if state_snapshot_enabled
+                    || config
+                        .db_checkpoint_config
+                        .backup_object_store_config
+                        .is_some()
+                {
                     true
                 } else {
                     config
@@ -913,12 +1021,16 @@ impl SuiNode {
             // If db checkpoint config object store not specified but
             // state snapshot object store is specified, create handler
             // anyway for marking db checkpoints as completed so that they
-            // can be uploaded as state snapshots.
-            (None, false) => Ok((db_checkpoint_config, None)),
+            // can be uploaded as state snapshots, or backed up.
+            (None, false) if db_checkpoint_config.backup_object_store_config.is_none() => {
+                Ok((db_checkpoint_config, None))
+            }
             (_, _) => {
                 let handler = DBCheckpointHandler::new(
                     &db_checkpoint_config.checkpoint_path.clone().unwrap(),
                     db_checkpoint_config.object_store_config.as_ref(),
+                    db_checkpoint_config.backup_object_store_config.as_ref(),
+                    db_checkpoint_config.num_backups_to_keep,
                     60,
                     db_checkpoint_config
                         .prune_and_compact_before_upload
@@ -950,15 +1062,21 @@ impl SuiNode {
         state_sync::Handle,
         randomness::Handle,
     )> {
+        // Peers are scored by state sync, and discovery uses the scores to choose peers.
+        let peer_scores = PeerScores::new(&config.p2p_config.discovery.clone().unwrap_or_default());
+
         let (state_sync, state_sync_server) = state_sync::Builder::new()
             .config(config.p2p_config.state_sync.clone().unwrap_or_default())
             .store(state_sync_store)
             .archive_readers(archive_readers)
+            .peer_scores(peer_scores.clone())
             .with_metrics(prometheus_registry)
             .build();
 
         let (discovery, discovery_server) = discovery::Builder::new(trusted_peer_change_rx)
             .config(config.p2p_config.clone())
+            .peer_scores(peer_scores)
+            .with_metrics(prometheus_registry)
             .build();
 
         let (randomness, randomness_router) =
@@ -1125,6 +1243,7 @@ impl SuiNode {
                     Arc::new(LazyNarwhalClient::new(
                         consensus_config.address().to_owned(),
                     )),
+                    *state.transaction_tracer(),
                 ));
                 let consensus_manager =
                     ConsensusManager::new_narwhal(&config, consensus_config, registry_service);
@@ -1141,6 +1260,7 @@ impl SuiNode {
                     &registry_service.default_registry(),
                     epoch_store.protocol_config().clone(),
                     client.clone(),
+                    *state.transaction_tracer(),
                 ));
                 let consensus_manager = ConsensusManager::new_mysticeti(
                     &config,
@@ -1303,6 +1423,19 @@ impl SuiNode {
             );
         }
 
+        if matches!(
+            epoch_store
+                .protocol_config()
+                .per_object_congestion_control_mode(),
+            PerObjectCongestionControlMode::ExecutionTimeEstimate(_)
+        ) {
+            Self::start_execution_time_observation_sharer(
+                state.clone(),
+                epoch_store.clone(),
+                consensus_adapter.clone(),
+            );
+        }
+
         Ok(ValidatorComponents {
             validator_server_handle,
             validator_overload_monitor_handle,
@@ -1372,6 +1505,7 @@ impl SuiNode {
         prometheus_registry: &Registry,
         protocol_config: ProtocolConfig,
         consensus_client: Arc<dyn SubmitToConsensus>,
+        transaction_tracer: TransactionTracer,
     ) -> ConsensusAdapter {
         let ca_metrics = ConsensusAdapterMetrics::new(prometheus_registry);
         // The consensus adapter allows the authority to send user certificates through consensus.
@@ -1387,6 +1521,7 @@ impl SuiNode {
             ca_metrics,
             protocol_config,
         )
+        .with_transaction_tracer(transaction_tracer)
     }
 
     async fn start_grpc_validator_service(
@@ -121,6 +121,7 @@ fn main() {
     let is_validator = config.consensus_config().is_some();
 
     let admin_interface_port = config.admin_interface_port;
+    let config_path = args.config_path.clone();
 
     // Run node in a separate runtime so that admin/monitoring functions continue to work
     // if it deadlocks.
@@ -181,7 +182,8 @@ fn main() {
             ))
             .unwrap();
 
-        sui_node::admin::run_admin_server(node, admin_interface_port, filter_handle).await
+        sui_node::admin::run_admin_server(node, admin_interface_port, filter_handle, config_path)
+            .await
     });
 
     runtimes.metrics.spawn(async move {
@@ -398,6 +398,12 @@ struct FeatureFlags {
     // Controls the behavior of per object congestion control in consensus handler.
     #[serde(skip_serializing_if = "PerObjectCongestionControlMode::is_none")]
     per_object_congestion_control_mode: PerObjectCongestionControlMode,
+
+    // If true, Mysticeti scores authorities on their votes for committed leaders, and swaps
+    // the leaders with the lowest scores out of the leader schedule.
+    #[serde(skip_serializing_if = "is_false")]
+    mysticeti_leader_scoring_and_schedule: bool,
+
     // Set the upper bound allowed for max_epoch in zklogin signature.
     #[serde(skip_serializing_if = "Option::is_none")]
     zklogin_max_epoch_upper_bound_delta: Option<u64>,
@@ -433,6 +439,23 @@ pub enum PerObjectCongestionControlMode {
     #[default]
     None, // No congestion control.
     TotalGasBudget, // Use txn gas budget as execution cost.
+    // Use execution times observed by validators, in microseconds, as execution cost.
+    ExecutionTimeEstimate(ExecutionTimeEstimateParams),
+}
+
+// Parameters of the `ExecutionTimeEstimate` per object congestion control mode.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Debug)]
+pub struct ExecutionTimeEstimateParams {
+    // The execution time estimate (in microseconds) used for a shared object that validators
+    // have not yet agreed on an observed execution time for.
+    pub default_estimate_micros: u64,
+    // The max number of shared objects each validator can report execution times for in an
+    // epoch. Observations of further objects are ignored.
+    pub max_observed_objects_per_authority: u64,
+    // How far (in milliseconds) the generation of an observation, a timestamp set by the sending
+    // validator, can be ahead of the timestamp of the consensus commit that sequences it.
+    // Observations further ahead are ignored.
+    pub max_generation_lead_ms: u64,
 }
 
 impl PerObjectCongestionControlMode {
@@ -962,6 +985,10 @@ pub struct ProtocolConfig {
     // above 33 (f) will not be allowed.
     consensus_bad_nodes_stake_threshold: Option<u64>,
 
+    // Number of commits after which Mysticeti updates its leader schedule, from the reputation
+    // scores of authorities over these commits.
+    mysticeti_leader_schedule_update_interval: Option<u64>,
+
     max_jwk_votes_per_validator_per_epoch: Option<u64>,
     // The maximum age of a JWK in epochs before it is removed from the AuthenticatorState object.
     // Applied at the end of an epoch as a delta from the new epoch value, so setting this to 1
@@ -990,6 +1017,9 @@ pub struct ProtocolConfig {
 
     // The max accumulated txn execution cost per object in a checkpoint. Transactions
     // in a checkpoint will be deferred once their touch shared objects hit this limit.
+    // The unit depends on `per_object_congestion_control_mode`: gas units with
+    // `TotalGasBudget`, and microseconds of estimated execution time with
+    // `ExecutionTimeEstimate`.
     max_accumulated_txn_cost_per_object_in_checkpoint: Option<u64>,
 }
 
@@ -1220,6 +1250,10 @@ impl ProtocolConfig {
     pub fn per_object_congestion_control_mode(&self) -> PerObjectCongestionControlMode {
         self.feature_flags.per_object_congestion_control_mode
     }
+
+    pub fn mysticeti_leader_scoring_and_schedule(&self) -> bool {
+        self.feature_flags.mysticeti_leader_scoring_and_schedule
+    }
 }
 
 #[cfg(not(msim))]
@@ -1649,6 +1683,7 @@ impl ProtocolConfig {
             max_event_emit_size_total: None,
 
             consensus_bad_nodes_stake_threshold: None,
+            mysticeti_leader_schedule_update_interval: None,
 
             max_jwk_votes_per_validator_per_epoch: None,
 
@@ -2141,6 +2176,13 @@ impl ProtocolConfig {
     pub fn set_consensus_bad_nodes_stake_threshold(&mut self, val: u64) {
         self.consensus_bad_nodes_stake_threshold = Some(val);
     }
+
+    pub fn set_mysticeti_leader_scoring_and_schedule_for_testing(&mut self, val: bool) {
+        self.feature_flags.mysticeti_leader_scoring_and_schedule = val;
+    }
+    pub fn set_mysticeti_leader_schedule_update_interval_for_testing(&mut self, val: u64) {
+        self.mysticeti_leader_schedule_update_interval = Some(val);
+    }
     pub fn set_receive_object_for_testing(&mut self, val: bool) {
         self.feature_flags.receive_objects = val
     }
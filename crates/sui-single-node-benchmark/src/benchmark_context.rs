@@ -319,6 +319,7 @@ impl BenchmarkContext {
         &self,
         mut transactions: Vec<Transaction>,
         checkpoint_size: usize,
+        catch_up: bool,
     ) {
         self.execute_sample_transaction(transactions.pop().unwrap())
             .await;
@@ -340,7 +341,8 @@ impl BenchmarkContext {
             .await;
         info!("Built {} checkpoints", checkpoints.len());
         let last_checkpoint_seq = *checkpoints.last().unwrap().0.sequence_number();
-        let (mut checkpoint_executor, checkpoint_sender) = validator.create_checkpoint_executor();
+        let (mut checkpoint_executor, checkpoint_sender) =
+            validator.create_checkpoint_executor(catch_up);
         for (checkpoint, contents) in checkpoints {
             let state = validator.get_validator();
             state
@@ -69,6 +69,9 @@ pub enum Component {
     /// Benchmark the checkpoint executor by constructing a full epoch of checkpoints, execute
     /// all transactions in them and measure time.
     CheckpointExecutor,
+    /// Same as CheckpointExecutor, but executes checkpoints in catch-up mode, in batches of
+    /// checkpoints within which non-conflicting transactions are executed in parallel.
+    CheckpointExecutorCatchUp,
 }
 
 #[derive(Subcommand, Clone)]
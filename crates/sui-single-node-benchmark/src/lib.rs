@@ -40,7 +40,11 @@ pub async fn run_benchmark(
                 .await;
         }
         Component::CheckpointExecutor => {
-            ctx.benchmark_checkpoint_executor(transactions, checkpoint_size)
+            ctx.benchmark_checkpoint_executor(transactions, checkpoint_size, false)
+                .await;
+        }
+        Component::CheckpointExecutorCatchUp => {
+            ctx.benchmark_checkpoint_executor(transactions, checkpoint_size, true)
                 .await;
         }
         Component::ExecutionOnly => {
@@ -4,8 +4,10 @@
 use crate::command::Component;
 use crate::mock_consensus::{ConsensusMode, MockConsensusClient};
 use crate::mock_storage::InMemoryObjectStore;
+use prometheus::Registry;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
+use sui_config::node::{CheckpointCatchUpConfig, CheckpointExecutorConfig};
 use sui_core::authority::authority_per_epoch_store::AuthorityPerEpochStore;
 use sui_core::authority::authority_store_tables::LiveObject;
 use sui_core::authority::test_authority_builder::TestAuthorityBuilder;
@@ -160,7 +162,10 @@ impl SingleValidator {
                     .into_inner();
                 response.signed_effects.into_data()
             }
-            Component::TxnSigning | Component::CheckpointExecutor | Component::ExecutionOnly => {
+            Component::TxnSigning
+            | Component::CheckpointExecutor
+            | Component::CheckpointExecutorCatchUp
+            | Component::ExecutionOnly => {
                 unreachable!()
             }
         };
@@ -247,14 +252,26 @@ impl SingleValidator {
 
     pub fn create_checkpoint_executor(
         &self,
+        catch_up: bool,
     ) -> (CheckpointExecutor, broadcast::Sender<VerifiedCheckpoint>) {
         let validator = self.get_validator();
         let (ckpt_sender, ckpt_receiver) = broadcast::channel(1000000);
-        let checkpoint_executor = CheckpointExecutor::new_for_tests(
+        let config = CheckpointExecutorConfig {
+            // All checkpoints are synced before execution starts, so catch-up mode is used
+            // for the whole run.
+            catch_up: catch_up.then(|| CheckpointCatchUpConfig {
+                min_lag: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let checkpoint_executor = CheckpointExecutor::new(
             ckpt_receiver,
             validator.get_checkpoint_store().clone(),
             validator.clone(),
             Arc::new(StateAccumulator::new(validator.get_execution_cache())),
+            config,
+            &Registry::new(),
         );
         (checkpoint_executor, ckpt_sender)
     }
@@ -129,7 +129,7 @@ impl StateSnapshotUploader {
                 let db = Arc::new(AuthorityPerpetualTables::open(
                     &path_to_filesystem(self.db_checkpoint_path.clone(), &db_path.child("store"))?,
                     None,
-                ));
+                )?);
                 let commitments = self
                     .checkpoint_store
                     .get_epoch_state_commitments(*epoch)
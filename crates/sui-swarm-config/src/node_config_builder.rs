@@ -196,6 +196,8 @@ impl ValidatorConfigBuilder {
             authority_overload_config: self.authority_overload_config.unwrap_or_default(),
             run_with_range: None,
             websocket_only: false,
+            transaction_tracing_sample_rate: None,
+            event_subscription_config: None,
         }
     }
 
@@ -449,6 +451,8 @@ impl FullnodeConfigBuilder {
             authority_overload_config: Default::default(),
             run_with_range: self.run_with_range,
             websocket_only: false,
+            transaction_tracing_sample_rate: None,
+            event_subscription_config: None,
         }
     }
 }
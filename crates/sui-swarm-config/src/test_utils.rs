@@ -152,11 +152,26 @@ impl CommitteeFixture {
         self.make_checkpoints(number_of_checkpoints, previous_checkpoint, empty_contents)
     }
 
-    fn make_checkpoints<F: Fn() -> VerifiedCheckpointContents>(
+    /// Makes a checkpoint for each of the given contents, after the given previous checkpoint or
+    /// the root checkpoint.
+    pub fn make_checkpoints_with_contents(
+        &self,
+        contents: Vec<VerifiedCheckpointContents>,
+        previous_checkpoint: Option<VerifiedCheckpoint>,
+    ) -> MakeCheckpointResults {
+        let number_of_checkpoints = contents.len() + previous_checkpoint.is_none() as usize;
+        let mut contents = contents.into_iter();
+        // The generator is called once more than needed, for a checkpoint that is never returned.
+        self.make_checkpoints(number_of_checkpoints, previous_checkpoint, || {
+            contents.next().unwrap_or_else(empty_contents)
+        })
+    }
+
+    fn make_checkpoints<F: FnMut() -> VerifiedCheckpointContents>(
         &self,
         number_of_checkpoints: usize,
         previous_checkpoint: Option<VerifiedCheckpoint>,
-        content_generator: F,
+        mut content_generator: F,
     ) -> MakeCheckpointResults {
         // Only skip the first one if it was supplied
         let skip = previous_checkpoint.is_some() as usize;
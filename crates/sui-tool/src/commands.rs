@@ -9,7 +9,7 @@ use crate::{
     restore_from_db_checkpoint, verify_archive, verify_archive_by_checksum, ConciseObjectOutput,
     GroupedObjectOutput, VerboseObjectOutput,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::env;
 use std::path::PathBuf;
 use sui_config::genesis::Genesis;
@@ -26,10 +26,12 @@ use sui_archival::{read_manifest_as_json, write_manifest_from_json};
 use sui_config::object_storage_config::{ObjectStoreConfig, ObjectStoreType};
 use sui_config::Config;
 use sui_core::authority_aggregator::AuthorityAggregatorBuilder;
+use sui_core::checkpoints::CheckpointForkEvidence;
 use sui_types::messages_checkpoint::{
     CheckpointRequest, CheckpointResponse, CheckpointSequenceNumber,
 };
 use sui_types::transaction::{SenderSignedData, Transaction};
+use typed_store::rocks::backup::BackupEngine;
 
 #[derive(Parser, Clone, ValueEnum)]
 pub enum Verbosity {
@@ -242,6 +244,16 @@ pub enum ToolCommand {
         sequence_number: Option<CheckpointSequenceNumber>,
     },
 
+    /// Compare the checkpoint fork evidence bundles written by two validators, found in the
+    /// `fork_evidence` directory next to their databases.
+    #[command(name = "diff-fork-evidence")]
+    DiffForkEvidence {
+        #[arg(help = "Evidence bundle of the first validator")]
+        first: PathBuf,
+        #[arg(help = "Evidence bundle of the second validator")]
+        second: PathBuf,
+    },
+
     #[command(name = "anemo")]
     Anemo {
         #[command(next_help_heading = "foo", flatten)]
@@ -256,6 +268,46 @@ pub enum ToolCommand {
         db_checkpoint_path: PathBuf,
     },
 
+    /// Upload an incremental backup of a database to an object store. The database must either
+    /// be a RocksDB checkpoint or not be open by a running node.
+    #[command(name = "create-db-backup")]
+    CreateDbBackup {
+        #[arg(long = "db-path")]
+        db_path: PathBuf,
+        #[command(flatten)]
+        object_store_config: ObjectStoreConfig,
+        /// Path under which the backups of this database are stored in the object store
+        #[arg(long = "backup-prefix", default_value = "")]
+        backup_prefix: String,
+        /// If set, delete all but the latest `num_to_keep` backups once the backup is uploaded
+        #[arg(long = "num-to-keep")]
+        num_to_keep: Option<usize>,
+    },
+
+    /// List the backups of a database in an object store
+    #[command(name = "list-db-backups")]
+    ListDbBackups {
+        #[command(flatten)]
+        object_store_config: ObjectStoreConfig,
+        #[arg(long = "backup-prefix", default_value = "")]
+        backup_prefix: String,
+    },
+
+    /// Restore a backup of a database from an object store
+    #[command(name = "restore-db-backup")]
+    RestoreDbBackup {
+        /// Directory to restore the database into. It must not exist or be empty.
+        #[arg(long = "db-path")]
+        db_path: PathBuf,
+        #[command(flatten)]
+        object_store_config: ObjectStoreConfig,
+        #[arg(long = "backup-prefix", default_value = "")]
+        backup_prefix: String,
+        /// Backup to restore. Defaults to the latest backup.
+        #[arg(long = "backup-id")]
+        backup_id: Option<u64>,
+    },
+
     #[clap(
         name = "download-db-snapshot",
         about = "Downloads the legacy database snapshot via cloud object store, outputs to local disk"
@@ -537,6 +589,11 @@ impl ToolCommand {
                 let genesis = Genesis::load(genesis)?;
                 println!("{:#?}", genesis);
             }
+            ToolCommand::DiffForkEvidence { first, second } => {
+                let first = CheckpointForkEvidence::read(&first)?;
+                let second = CheckpointForkEvidence::read(&second)?;
+                println!("{}", first.diff(&second));
+            }
             ToolCommand::FetchCheckpoint {
                 genesis,
                 sequence_number,
@@ -572,6 +629,73 @@ impl ToolCommand {
                 let config = sui_config::NodeConfig::load(config_path)?;
                 restore_from_db_checkpoint(&config, &db_checkpoint_path).await?;
             }
+            ToolCommand::CreateDbBackup {
+                db_path,
+                object_store_config,
+                backup_prefix,
+                num_to_keep,
+            } => {
+                let engine = BackupEngine::new(object_store_config.make()?, &backup_prefix);
+                let stats = engine
+                    .backup_dir(&db_path)
+                    .await
+                    .map_err(|err| anyhow!(err.to_string()))?;
+                println!(
+                    "Created backup {}: uploaded {} of {} files, {} of {} bytes",
+                    stats.backup_id,
+                    stats.num_uploaded_files,
+                    stats.num_files,
+                    stats.uploaded_bytes,
+                    stats.total_bytes
+                );
+                if let Some(num_to_keep) = num_to_keep {
+                    let deleted = engine
+                        .purge_old_backups(num_to_keep)
+                        .await
+                        .map_err(|err| anyhow!(err.to_string()))?;
+                    println!("Deleted backups: {:?}", deleted);
+                }
+            }
+            ToolCommand::ListDbBackups {
+                object_store_config,
+                backup_prefix,
+            } => {
+                let engine = BackupEngine::new(object_store_config.make()?, &backup_prefix);
+                let backup_ids = engine
+                    .list_backups()
+                    .await
+                    .map_err(|err| anyhow!(err.to_string()))?;
+                for backup_id in backup_ids {
+                    let manifest = engine
+                        .read_manifest(backup_id)
+                        .await
+                        .map_err(|err| anyhow!(err.to_string()))?;
+                    println!(
+                        "Backup {}: timestamp_ms {}, {} files, {} bytes",
+                        backup_id,
+                        manifest.timestamp_ms,
+                        manifest.files.len(),
+                        manifest.files.iter().map(|file| file.size).sum::<u64>()
+                    );
+                }
+            }
+            ToolCommand::RestoreDbBackup {
+                db_path,
+                object_store_config,
+                backup_prefix,
+                backup_id,
+            } => {
+                let engine = BackupEngine::new(object_store_config.make()?, &backup_prefix);
+                let manifest = engine
+                    .restore(backup_id, &db_path)
+                    .await
+                    .map_err(|err| anyhow!(err.to_string()))?;
+                println!(
+                    "Restored backup {} into {}",
+                    manifest.backup_id,
+                    db_path.display()
+                );
+            }
             ToolCommand::DownloadFormalSnapshot {
                 epoch,
                 genesis,
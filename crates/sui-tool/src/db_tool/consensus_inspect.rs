@@ -0,0 +1,142 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::anyhow;
+use clap::{Parser, ValueEnum};
+use consensus_core::{blocks_to_dot, BlockSummary, StoreInspector};
+use std::path::Path;
+
+#[derive(Parser)]
+#[command(rename_all = "kebab-case")]
+pub enum ConsensusInspectCommand {
+    /// List blocks in a round range, optionally from a single author
+    ListBlocks(ListBlocksOptions),
+    /// Print a block and its ancestors
+    Ancestry(AncestryOptions),
+    /// Print commits with their leaders and blocks in linearized order
+    ListCommits(ListCommitsOptions),
+    /// Export blocks in a round range as a graph
+    ExportGraph(ExportGraphOptions),
+}
+
+#[derive(Parser)]
+#[command(rename_all = "kebab-case")]
+pub struct ListBlocksOptions {
+    #[arg(long, help = "Only list blocks from this authority index")]
+    author: Option<u32>,
+    #[arg(long, help = "First round to list (inclusive)")]
+    start_round: u32,
+    #[arg(long, help = "Last round to list (exclusive)")]
+    end_round: u32,
+}
+
+#[derive(Parser)]
+#[command(rename_all = "kebab-case")]
+pub struct AncestryOptions {
+    #[arg(long, help = "Authority index of the block author")]
+    author: u32,
+    #[arg(long, help = "Round of the block")]
+    round: u32,
+    #[arg(
+        long,
+        default_value = "",
+        help = "Prefix of the base64 block digest, to pick among equivocating blocks"
+    )]
+    digest: String,
+    #[arg(
+        long,
+        default_value_t = 3,
+        help = "Number of rounds of ancestors to print"
+    )]
+    depth: u32,
+}
+
+#[derive(Parser)]
+#[command(rename_all = "kebab-case")]
+pub struct ListCommitsOptions {
+    #[arg(long, help = "First commit index to list (inclusive)")]
+    start: u32,
+    #[arg(long, help = "Last commit index to list (exclusive)")]
+    end: u32,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+#[derive(Parser)]
+#[command(rename_all = "kebab-case")]
+pub struct ExportGraphOptions {
+    #[arg(long, help = "First round to export (inclusive)")]
+    start_round: u32,
+    #[arg(long, help = "Last round to export (exclusive)")]
+    end_round: u32,
+    #[arg(long, value_enum, default_value = "dot")]
+    format: GraphFormat,
+}
+
+/// Inspects the consensus store of an epoch at `path`, i.e. `<consensus db-path>/<epoch>`.
+pub fn execute_consensus_inspect_command(
+    path: &Path,
+    cmd: ConsensusInspectCommand,
+) -> anyhow::Result<()> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| anyhow!("Invalid consensus store path {:?}", path))?;
+    let inspector = StoreInspector::open(path)?;
+    match cmd {
+        ConsensusInspectCommand::ListBlocks(opt) => {
+            for block in inspector.blocks(opt.author, opt.start_round..opt.end_round)? {
+                print_block(&block);
+            }
+        }
+        ConsensusInspectCommand::Ancestry(opt) => {
+            let blocks = inspector.find_blocks(opt.author, opt.round, &opt.digest)?;
+            if blocks.is_empty() {
+                println!(
+                    "No block from authority {} at round {} with digest prefix {:?}",
+                    opt.author, opt.round, opt.digest
+                );
+            }
+            for block in blocks {
+                for ancestor in inspector.ancestry(&block, opt.depth)? {
+                    let indent = (block.round - ancestor.round) as usize * 2;
+                    print!("{:indent$}", "");
+                    print_block(&ancestor);
+                }
+            }
+        }
+        ConsensusInspectCommand::ListCommits(opt) => {
+            for commit in inspector.commits(opt.start..opt.end)? {
+                println!(
+                    "Commit {} ({}) leader {} previous {}",
+                    commit.index, commit.digest, commit.leader, commit.previous_digest
+                );
+                for block in commit.blocks {
+                    println!("  {}", block);
+                }
+            }
+        }
+        ConsensusInspectCommand::ExportGraph(opt) => {
+            let blocks = inspector.blocks(None, opt.start_round..opt.end_round)?;
+            match opt.format {
+                GraphFormat::Dot => print!("{}", blocks_to_dot(&blocks)),
+                GraphFormat::Json => println!("{}", serde_json::to_string_pretty(&blocks)?),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_block(block: &BlockSummary) {
+    println!(
+        "{} timestamp {} transactions {} commit votes {:?} ancestors [{}]",
+        block.reference,
+        block.timestamp_ms,
+        block.num_transactions,
+        block.commit_votes,
+        block.ancestors.join(", ")
+    );
+}
@@ -187,13 +187,16 @@ pub fn duplicate_objects_summary(db_path: PathBuf) -> (usize, usize, usize, usiz
 }
 
 pub fn compact(db_path: PathBuf) -> anyhow::Result<()> {
-    let perpetual = Arc::new(AuthorityPerpetualTables::open(&db_path, None));
+    let perpetual = Arc::new(AuthorityPerpetualTables::open(&db_path, None)?);
     AuthorityStorePruner::compact(&perpetual)?;
     Ok(())
 }
 
 pub async fn prune_objects(db_path: PathBuf) -> anyhow::Result<()> {
-    let perpetual_db = Arc::new(AuthorityPerpetualTables::open(&db_path.join("store"), None));
+    let perpetual_db = Arc::new(AuthorityPerpetualTables::open(
+        &db_path.join("store"),
+        None,
+    )?);
     let checkpoint_store = Arc::new(CheckpointStore::open_tables_read_write(
         db_path.join("checkpoints"),
         MetricConf::default(),
@@ -228,7 +231,10 @@ pub async fn prune_objects(db_path: PathBuf) -> anyhow::Result<()> {
 }
 
 pub async fn prune_checkpoints(db_path: PathBuf) -> anyhow::Result<()> {
-    let perpetual_db = Arc::new(AuthorityPerpetualTables::open(&db_path.join("store"), None));
+    let perpetual_db = Arc::new(AuthorityPerpetualTables::open(
+        &db_path.join("store"),
+        None,
+    )?);
     let checkpoint_store = Arc::new(CheckpointStore::open_tables_read_write(
         db_path.join("checkpoints"),
         MetricConf::default(),
@@ -312,7 +318,7 @@ mod test {
 
         // Open the DB for writing
         let _: AuthorityEpochTables = AuthorityEpochTables::open(0, &primary_path, None);
-        let _: AuthorityPerpetualTables = AuthorityPerpetualTables::open(&primary_path, None);
+        let _: AuthorityPerpetualTables = AuthorityPerpetualTables::open(&primary_path, None)?;
 
         // Get all the tables for AuthorityEpochTables
         let tables = {
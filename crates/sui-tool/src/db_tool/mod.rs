@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use self::consensus_inspect::{execute_consensus_inspect_command, ConsensusInspectCommand};
 use self::db_dump::{dump_table, duplicate_objects_summary, list_tables, table_summary, StoreName};
 use self::index_search::{search_index, SearchRange};
 use crate::db_tool::db_dump::{compact, print_table_metadata, prune_checkpoints, prune_objects};
@@ -17,6 +18,7 @@ use sui_types::effects::TransactionEffectsAPI;
 use sui_types::messages_checkpoint::{CheckpointDigest, CheckpointSequenceNumber};
 use sui_types::storage::ObjectStore;
 use typed_store::rocks::MetricConf;
+mod consensus_inspect;
 pub mod db_dump;
 mod index_search;
 
@@ -42,6 +44,12 @@ pub enum DbToolCommand {
     PruneObjects,
     PruneCheckpoints,
     SetCheckpointWatermark(SetCheckpointWatermarkOptions),
+    /// Run the pending schema migrations of the perpetual tables.
+    Migrate(MigrateOptions),
+    /// Inspect blocks and commits in a consensus store. Pass the path of the epoch's consensus
+    /// store as --db-path.
+    #[command(subcommand)]
+    ConsensusInspect(ConsensusInspectCommand),
 }
 
 #[derive(Parser)]
@@ -178,6 +186,14 @@ pub struct SetCheckpointWatermarkOptions {
     highest_synced: Option<CheckpointSequenceNumber>,
 }
 
+#[derive(Parser)]
+#[command(rename_all = "kebab-case")]
+pub struct MigrateOptions {
+    /// Report what the migrations would change without writing anything.
+    #[arg(long)]
+    dry_run: bool,
+}
+
 pub async fn execute_db_tool_command(db_path: PathBuf, cmd: DbToolCommand) -> anyhow::Result<()> {
     match cmd {
         DbToolCommand::ListTables => print_db_all_tables(db_path),
@@ -234,6 +250,8 @@ pub async fn execute_db_tool_command(db_path: PathBuf, cmd: DbToolCommand) -> an
             Ok(())
         }
         DbToolCommand::SetCheckpointWatermark(d) => set_checkpoint_watermark(&db_path, d),
+        DbToolCommand::Migrate(d) => migrate(&db_path, d),
+        DbToolCommand::ConsensusInspect(c) => execute_consensus_inspect_command(&db_path, c),
     }
 }
 
@@ -264,6 +282,50 @@ pub fn print_last_consensus_index(path: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub fn migrate(path: &Path, opt: MigrateOptions) -> anyhow::Result<()> {
+    let path = AuthorityPerpetualTables::path(&path.join("store"));
+    let migrations = AuthorityPerpetualTables::migrations();
+    // A dry run opens the tables as a secondary, so that it can run next to a node using them.
+    let (versions, reports) = if opt.dry_run {
+        let perpetual_db =
+            AuthorityPerpetualTables::get_read_only_handle(path, None, None, MetricConf::default());
+        (
+            perpetual_db.schema_versions()?,
+            perpetual_db.dry_run_migrations(&migrations)?,
+        )
+    } else {
+        let perpetual_db = AuthorityPerpetualTables::open_tables_read_write(
+            path,
+            MetricConf::default(),
+            None,
+            None,
+        );
+        (
+            perpetual_db.schema_versions()?,
+            perpetual_db.run_migrations(&migrations, false)?,
+        )
+    };
+    for (table, version) in versions {
+        println!("{}: schema version {}", table, version.version);
+    }
+    if reports.is_empty() {
+        println!("No pending migrations");
+    }
+    for report in reports {
+        println!(
+            "{}{} from schema version {} ({}): {} entries scanned, {} updated, {} deleted",
+            if opt.dry_run { "[dry run] " } else { "" },
+            report.table,
+            report.from_version,
+            report.description,
+            report.scanned,
+            report.updated,
+            report.deleted
+        );
+    }
+    Ok(())
+}
+
 pub fn print_consensus_commit(path: &Path, opt: PrintConsensusCommitOptions) -> anyhow::Result<()> {
     let consensus_db = NodeStorage::reopen(path, None);
     let consensus_commit = consensus_db
@@ -277,7 +339,7 @@ pub fn print_consensus_commit(path: &Path, opt: PrintConsensusCommitOptions) ->
 }
 
 pub fn print_transaction(path: &Path, opt: PrintTransactionOptions) -> anyhow::Result<()> {
-    let perpetual_db = AuthorityPerpetualTables::open(&path.join("store"), None);
+    let perpetual_db = AuthorityPerpetualTables::open(&path.join("store"), None)?;
     if let Some((epoch, checkpoint_seq_num)) =
         perpetual_db.get_checkpoint_sequence_number(&opt.digest)?
     {
@@ -297,7 +359,7 @@ pub fn print_transaction(path: &Path, opt: PrintTransactionOptions) -> anyhow::R
 }
 
 pub fn print_object(path: &Path, opt: PrintObjectOptions) -> anyhow::Result<()> {
-    let perpetual_db = AuthorityPerpetualTables::open(&path.join("store"), None);
+    let perpetual_db = AuthorityPerpetualTables::open(&path.join("store"), None)?;
 
     let obj = if let Some(version) = opt.version {
         perpetual_db.get_object_by_key(&opt.id, version.into())?
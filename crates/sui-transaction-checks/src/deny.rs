@@ -2,13 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use fastcrypto_zkp::bn254::zk_login::OIDCProvider;
-use sui_config::transaction_deny_config::TransactionDenyConfig;
+use move_core_types::language_storage::StructTag;
+use sui_config::transaction_deny_config::{DeniedFunction, TransactionDenyConfig};
 use sui_types::{
-    base_types::ObjectRef,
+    base_types::{ObjectID, ObjectRef},
     error::{SuiError, SuiResult, UserInputError},
+    object::Object,
     signature::GenericSignature,
     storage::BackingPackageStore,
     transaction::{Command, InputObjectKind, TransactionData, TransactionDataAPI},
+    TypeTag,
 };
 macro_rules! deny_if_true {
     ($cond:expr, $msg:expr) => {
@@ -40,11 +43,41 @@ pub fn check_transaction_for_signing(
 
     check_package_dependencies(filter_config, tx_data, package_store)?;
 
+    check_type_arguments(filter_config, tx_data, package_store)?;
+
     check_receiving_objects(filter_config, receiving_objects)?;
 
     Ok(())
 }
 
+/// Check that the types of the loaded input objects of a transaction, including the objects it
+/// receives, are allowed by the deny config. Unlike the other checks, this needs the objects
+/// themselves, so it can only run once they have been read.
+pub fn check_input_object_types<'a>(
+    filter_config: &TransactionDenyConfig,
+    input_objects: impl IntoIterator<Item = &'a Object>,
+) -> SuiResult {
+    let denied_types = filter_config.type_deny_list();
+    if denied_types.is_empty() {
+        return Ok(());
+    }
+    for object in input_objects {
+        let Some(type_) = object.type_() else {
+            continue;
+        };
+        let tag = StructTag::from(type_.clone());
+        deny_if_true!(
+            is_struct_denied(denied_types, &tag),
+            format!(
+                "Access to input object {:?} of type {} is temporarily disabled",
+                object.id(),
+                tag
+            )
+        );
+    }
+    Ok(())
+}
+
 fn check_receiving_objects(
     filter_config: &TransactionDenyConfig,
     receiving_objects: &[ObjectRef],
@@ -154,10 +187,29 @@ fn check_package_dependencies(
     package_store: &dyn BackingPackageStore,
 ) -> SuiResult {
     let deny_map = filter_config.get_package_deny_set();
-    if deny_map.is_empty() {
+    let family_deny_map = filter_config.get_package_family_deny_set();
+    let function_deny_map = filter_config.get_function_deny_set();
+    if deny_map.is_empty() && family_deny_map.is_empty() && function_deny_map.is_empty() {
         return Ok(());
     }
     let mut dependencies = vec![];
+    // Original IDs of the packages used, i.e. the upgrade families they belong to.
+    let mut families = vec![];
+    // Publish and Upgrade only list the IDs of the packages they use, so the packages need to be
+    // loaded to find out their families. Packages that don't exist are skipped, as the
+    // transaction will fail to execute anyway.
+    let original_ids = |ids: &[ObjectID]| -> SuiResult<Vec<ObjectID>> {
+        if family_deny_map.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut original_ids = vec![];
+        for id in ids {
+            if let Some(package) = package_store.get_package_object(id)? {
+                original_ids.push(package.move_package().original_package_id());
+            }
+        }
+        Ok(original_ids)
+    };
     for command in tx_data.kind().iter_commands() {
         match command {
             Command::Publish(_, deps) => {
@@ -165,12 +217,15 @@ fn check_package_dependencies(
                 // by the user. But that's OK because this publish transaction will fail
                 // to execute in the end. Similar reasoning for Upgrade.
                 dependencies.extend(deps.iter().copied());
+                families.extend(original_ids(deps)?);
             }
             Command::Upgrade(_, deps, package_id, _) => {
                 dependencies.extend(deps.iter().copied());
                 // It's crucial that we don't allow upgrading a package in the deny list,
                 // otherwise one can bypass the deny list by upgrading a package.
                 dependencies.push(*package_id);
+                families.extend(original_ids(deps)?);
+                families.extend(original_ids(&[*package_id])?);
             }
             Command::MoveCall(call) => {
                 let package = package_store.get_package_object(&call.package)?.ok_or(
@@ -194,6 +249,28 @@ fn check_package_dependencies(
                         .map(|upgrade_info| upgrade_info.upgraded_id),
                 );
                 dependencies.push(package.move_package().id());
+                // The linkage table is keyed by the original ID of each dependency, which
+                // identifies its upgrade family regardless of the version in use.
+                let original_id = package.move_package().original_package_id();
+                families.extend(package.move_package().linkage_table().keys().copied());
+                families.push(original_id);
+
+                if !function_deny_map.is_empty() {
+                    let function = DeniedFunction {
+                        package: call.package,
+                        module: call.module.clone(),
+                        function: call.function.clone(),
+                    };
+                    let function_in_family = DeniedFunction {
+                        package: original_id,
+                        ..function.clone()
+                    };
+                    deny_if_true!(
+                        function_deny_map.contains(&function)
+                            || function_deny_map.contains(&function_in_family),
+                        format!("Calling function {} is temporarily disabled", function)
+                    );
+                }
             }
             Command::TransferObjects(..)
             | &Command::SplitCoins(..)
@@ -207,5 +284,108 @@ fn check_package_dependencies(
             format!("Access to package {:?} is temporarily disabled", dep)
         );
     }
+    for family in families {
+        deny_if_true!(
+            family_deny_map.contains(&family),
+            format!(
+                "Access to package upgrade family {:?} is temporarily disabled",
+                family
+            )
+        );
+    }
+    Ok(())
+}
+
+fn check_type_arguments(
+    filter_config: &TransactionDenyConfig,
+    tx_data: &TransactionData,
+    package_store: &dyn BackingPackageStore,
+) -> SuiResult {
+    let denied_types = filter_config.type_deny_list();
+    let family_deny_map = filter_config.get_package_family_deny_set();
+    if denied_types.is_empty() && family_deny_map.is_empty() {
+        return Ok(());
+    }
+    for command in tx_data.kind().iter_commands() {
+        let type_arguments = match command {
+            Command::MoveCall(call) => call.type_arguments.as_slice(),
+            Command::MakeMoveVec(Some(tag), _) => std::slice::from_ref(tag),
+            _ => continue,
+        };
+        for tag in type_arguments {
+            // Type arguments can name a type through the ID of any version of its package, so
+            // they are also checked with every package ID resolved to its original ID, to stop
+            // an upgraded version from being used to get around the deny config.
+            let mut families = vec![];
+            let original = resolve_original_ids(tag, package_store, &mut families)?;
+            deny_if_true!(
+                is_type_denied(denied_types, tag) || is_type_denied(denied_types, &original),
+                format!("Usage of type {} is temporarily disabled", tag)
+            );
+            for family in families {
+                deny_if_true!(
+                    family_deny_map.contains(&family),
+                    format!(
+                        "Access to package upgrade family {:?} is temporarily disabled",
+                        family
+                    )
+                );
+            }
+        }
+    }
     Ok(())
 }
+
+/// `tag` with the package ID of every struct type in it replaced by the original ID of that
+/// package, which is also added to `families`. Packages that don't exist are left as they are, as
+/// the transaction will fail to execute anyway.
+fn resolve_original_ids(
+    tag: &TypeTag,
+    package_store: &dyn BackingPackageStore,
+    families: &mut Vec<ObjectID>,
+) -> SuiResult<TypeTag> {
+    Ok(match tag {
+        TypeTag::Struct(tag) => {
+            let mut tag = (**tag).clone();
+            if let Some(package) = package_store.get_package_object(&ObjectID::from(tag.address))? {
+                let original_id = package.move_package().original_package_id();
+                families.push(original_id);
+                tag.address = original_id.into();
+            }
+            tag.type_params = tag
+                .type_params
+                .iter()
+                .map(|param| resolve_original_ids(param, package_store, families))
+                .collect::<SuiResult<_>>()?;
+            TypeTag::Struct(Box::new(tag))
+        }
+        TypeTag::Vector(tag) => TypeTag::Vector(Box::new(resolve_original_ids(
+            tag,
+            package_store,
+            families,
+        )?)),
+        tag => tag.clone(),
+    })
+}
+
+/// Whether `tag` is, or is instantiated with, one of the denied types.
+fn is_type_denied(denied_types: &[StructTag], tag: &TypeTag) -> bool {
+    match tag {
+        TypeTag::Struct(tag) => is_struct_denied(denied_types, tag),
+        TypeTag::Vector(tag) => is_type_denied(denied_types, tag),
+        _ => false,
+    }
+}
+
+fn is_struct_denied(denied_types: &[StructTag], tag: &StructTag) -> bool {
+    // A denied type without type parameters matches all of its instantiations.
+    denied_types.iter().any(|denied| {
+        denied.address == tag.address
+            && denied.module == tag.module
+            && denied.name == tag.name
+            && (denied.type_params.is_empty() || denied.type_params == tag.type_params)
+    }) || tag
+        .type_params
+        .iter()
+        .any(|param| is_type_denied(denied_types, param))
+}
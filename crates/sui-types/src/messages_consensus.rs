@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::base_types::ConciseableName;
-use crate::base_types::{AuthorityName, ObjectRef, TransactionDigest};
+use crate::base_types::{AuthorityName, ObjectID, ObjectRef, TransactionDigest};
 use crate::digests::ConsensusCommitDigest;
 use crate::messages_checkpoint::{
     CheckpointSequenceNumber, CheckpointSignatureMessage, CheckpointTimestamp,
@@ -71,6 +71,7 @@ pub enum ConsensusTransactionKey {
     NewJWKFetched(Box<(AuthorityName, JwkId, JWK)>),
     RandomnessDkgMessage(AuthorityName),
     RandomnessDkgConfirmation(AuthorityName),
+    ExecutionTimeObservation(AuthorityName, u64 /* generation */),
 }
 
 impl Debug for ConsensusTransactionKey {
@@ -103,6 +104,12 @@ impl Debug for ConsensusTransactionKey {
             Self::RandomnessDkgConfirmation(name) => {
                 write!(f, "RandomnessDkgConfirmation({:?})", name.concise())
             }
+            Self::ExecutionTimeObservation(name, generation) => write!(
+                f,
+                "ExecutionTimeObservation({:?}, {:?})",
+                name.concise(),
+                generation
+            ),
         }
     }
 }
@@ -163,6 +170,48 @@ impl AuthorityCapabilities {
     }
 }
 
+/// Used to share the execution times of transactions touching shared objects, as measured
+/// locally by each authority, via narwhal. Validators agree on a per-object estimate from these
+/// observations, which is used as transaction cost for per-object congestion control.
+#[derive(Serialize, Deserialize, Clone, Hash)]
+pub struct ExecutionTimeObservation {
+    /// Originating authority - must match narwhal transaction source.
+    pub authority: AuthorityName,
+    /// Generation number set by sending authority. Used to determine which of multiple
+    /// observations of the same object from the same authority is the most recent.
+    ///
+    /// (Like `AuthorityCapabilities::generation`, this is the current time in milliseconds.)
+    pub generation: u64,
+    /// Locally measured execution time estimate, in microseconds, for each shared object.
+    pub estimates: Vec<(ObjectID, u64)>,
+}
+
+impl Debug for ExecutionTimeObservation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExecutionTimeObservation")
+            .field("authority", &self.authority.concise())
+            .field("generation", &self.generation)
+            .field("estimates", &self.estimates)
+            .finish()
+    }
+}
+
+impl ExecutionTimeObservation {
+    pub fn new(authority: AuthorityName, estimates: Vec<(ObjectID, u64)>) -> Self {
+        let generation = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Sui did not exist prior to 1970")
+            .as_millis()
+            .try_into()
+            .expect("This build of sui is not supported in the year 500,000,000");
+        Self {
+            authority,
+            generation,
+            estimates,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum ConsensusTransactionKind {
     UserTransaction(Box<CertifiedTransaction>),
@@ -179,6 +228,7 @@ pub enum ConsensusTransactionKind {
     // `RandomnessDkgMessages` have been received locally, to complete the key generation process.
     // Contents are a serialized `fastcrypto_tbls::dkg::Confirmation`.
     RandomnessDkgConfirmation(AuthorityName, Vec<u8>),
+    ExecutionTimeObservation(ExecutionTimeObservation),
 }
 
 impl ConsensusTransactionKind {
@@ -293,6 +343,16 @@ impl ConsensusTransaction {
         }
     }
 
+    pub fn new_execution_time_observation(observation: ExecutionTimeObservation) -> Self {
+        let mut hasher = DefaultHasher::new();
+        observation.hash(&mut hasher);
+        let tracking_id = hasher.finish().to_le_bytes();
+        Self {
+            tracking_id,
+            kind: ConsensusTransactionKind::ExecutionTimeObservation(observation),
+        }
+    }
+
     pub fn get_tracking_id(&self) -> u64 {
         (&self.tracking_id[..])
             .read_u64::<BigEndian>()
@@ -332,6 +392,12 @@ impl ConsensusTransaction {
             ConsensusTransactionKind::RandomnessDkgConfirmation(authority, _) => {
                 ConsensusTransactionKey::RandomnessDkgConfirmation(*authority)
             }
+            ConsensusTransactionKind::ExecutionTimeObservation(observation) => {
+                ConsensusTransactionKey::ExecutionTimeObservation(
+                    observation.authority,
+                    observation.generation,
+                )
+            }
         }
     }
 
@@ -3,7 +3,9 @@
 
 use atomic_float::AtomicF64;
 use crossterm::tty::IsTty;
+use futures::{future::BoxFuture, FutureExt};
 use once_cell::sync::Lazy;
+use opentelemetry::sdk::export::trace::{ExportResult, SpanData, SpanExporter};
 use opentelemetry::sdk::trace::Sampler;
 use opentelemetry::sdk::{
     self, runtime,
@@ -12,7 +14,10 @@ use opentelemetry::sdk::{
 };
 use opentelemetry::trace::TracerProvider as _;
 use opentelemetry_api::{
-    trace::{Link, SamplingResult, SpanKind, TraceId},
+    trace::{
+        Link, SamplingResult, SpanContext, SpanId, SpanKind, TraceContextExt, TraceFlags, TraceId,
+        TraceState,
+    },
     Context, Key, OrderMap, Value,
 };
 use opentelemetry_otlp::WithExportConfig;
@@ -29,6 +34,7 @@ use std::{
 use tracing::metadata::LevelFilter;
 use tracing::{error, info, Level};
 use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use tracing_subscriber::{filter, fmt, layer::SubscriberExt, reload, EnvFilter, Layer, Registry};
 
 use crate::file_exporter::{CachedOpenFile, FileExporter};
@@ -526,6 +532,23 @@ impl ShouldSample for SamplingFilter {
     }
 }
 
+/// Exports `span` as part of the trace `trace_id`, regardless of the sampling rate of OTLP tracing.
+/// The span gets a synthetic remote parent, so spans recorded on different tasks or nodes for the
+/// same entity end up in a single trace when they use the same id, e.g. one derived from a
+/// transaction digest. Has no effect if OTLP tracing is not enabled.
+pub fn set_span_trace_id(span: &tracing::Span, trace_id: [u8; 16]) {
+    let mut span_id = [0u8; 8];
+    span_id.copy_from_slice(&trace_id[..8]);
+    let span_context = SpanContext::new(
+        TraceId::from_bytes(trace_id),
+        SpanId::from_bytes(span_id),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    span.set_parent(Context::new().with_remote_span_context(span_context));
+}
+
 /// Globally set a tracing subscriber suitable for testing environments
 pub fn init_for_testing() {
     static LOGGER: Lazy<()> = Lazy::new(|| {
@@ -546,6 +569,49 @@ pub fn init_for_testing() {
     Lazy::force(&LOGGER);
 }
 
+/// Returns a subscriber that exports every span to memory through OpenTelemetry, for tests that
+/// check which trace spans end up in. Install it with `tracing::subscriber::set_default`.
+pub fn otel_subscriber_for_testing() -> (impl tracing::Subscriber + Send + Sync, ExportedSpans) {
+    let exporter = TestExporter::default();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let subscriber = Registry::default()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+    (subscriber, ExportedSpans { provider, exporter })
+}
+
+/// Spans exported by the subscriber returned from [otel_subscriber_for_testing].
+pub struct ExportedSpans {
+    provider: TracerProvider,
+    exporter: TestExporter,
+}
+
+impl ExportedSpans {
+    /// Trace ids of the finished spans named `name`, in the order they finished.
+    pub fn trace_ids(&self, name: &str) -> Vec<[u8; 16]> {
+        self.provider.force_flush();
+        self.exporter
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|span| span.name == name)
+            .map(|span| span.span_context.trace_id().to_bytes())
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct TestExporter(Arc<Mutex<Vec<SpanData>>>);
+
+impl SpanExporter for TestExporter {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        self.0.lock().unwrap().extend(batch);
+        futures::future::ready(Ok(())).boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
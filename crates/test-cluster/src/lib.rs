@@ -831,6 +831,8 @@ impl TestClusterBuilder {
             object_store_config: None,
             perform_index_db_checkpoints_at_epoch_end: None,
             prune_and_compact_before_upload: None,
+            backup_object_store_config: None,
+            num_backups_to_keep: None,
         };
         self
     }
@@ -842,6 +844,8 @@ impl TestClusterBuilder {
             object_store_config: None,
             perform_index_db_checkpoints_at_epoch_end: None,
             prune_and_compact_before_upload: Some(true),
+            backup_object_store_config: None,
+            num_backups_to_keep: None,
         };
         self
     }
@@ -180,7 +180,8 @@ fn extract_generics_names(generics: &Generics) -> Vec<Ident> {
 /// 2. Auto-generated `open` routine
 /// 3. Auto-generated `read_only_mode` handle
 /// 4. Auto-generated memory stats method
-/// 5. Other convenience features
+/// 5. Auto-generated schema migration routines, see `typed_store::rocks::migration`
+/// 6. Other convenience features
 ///
 /// 1. Flexible configuration:
 /// a. Static options specified at struct definition
@@ -325,6 +326,11 @@ pub fn derive_dbmap_utils_general(input: TokenStream) -> TokenStream {
         .map(|q| (q.args.first().unwrap(), q.args.last().unwrap()))
         .unzip();
 
+    // All the tables share the same DB, so its handle is taken from the first table
+    let first_field_name = field_names
+        .first()
+        .expect("Struct must have at least one table");
+
     // This is the actual name of the type which was found
     let post_process_fn_str = allowed_types_with_post_process_fn
         .get(&simple_field_type_name_str.as_str())
@@ -508,6 +514,41 @@ pub fn derive_dbmap_utils_general(input: TokenStream) -> TokenStream {
                 }
             }
 
+            /// Opens a set of tables in read-write mode, with their data kept in memory instead of
+            /// on disk. The data is lost when the tables are dropped. This is mainly intended for tests
+            pub fn open_tables_in_memory() -> Self {
+                Self::open_tables_read_write(typed_store::rocks::in_memory_db_path(), typed_store::rocks::MetricConf::default(), Some(typed_store::rocks::in_memory_db_options()), None)
+            }
+
+            /// Opens a set of tables in read-write mode like `open_tables_read_write`, then runs the
+            /// pending schema migrations of the tables from `migrations`
+            pub fn open_tables_read_write_with_migrations(
+                path: std::path::PathBuf,
+                metric_conf: typed_store::rocks::MetricConf,
+                global_db_options_override: Option<rocksdb::Options>,
+                tables_db_options_override: Option<typed_store::rocks::DBMapTableConfigMap>,
+                migrations: &typed_store::rocks::migration::MigrationRegistry,
+            ) -> Result<Self, typed_store::TypedStoreError> {
+                let tables = Self::open_tables_read_write(path, metric_conf, global_db_options_override, tables_db_options_override);
+                tables.run_migrations(migrations, false)?;
+                Ok(tables)
+            }
+
+            /// Runs the pending schema migrations of the tables from `migrations`
+            /// If `dry_run` is set, nothing is written and the reports describe what would change
+            pub fn run_migrations(
+                &self,
+                migrations: &typed_store::rocks::migration::MigrationRegistry,
+                dry_run: bool,
+            ) -> Result<Vec<typed_store::rocks::migration::MigrationReport>, typed_store::TypedStoreError> {
+                migrations.run(&self.#first_field_name.rocksdb, &[#(stringify!(#cf_names)),*], dry_run)
+            }
+
+            /// Returns the schema version recorded for each table
+            pub fn schema_versions(&self) -> Result<std::collections::BTreeMap<String, typed_store::rocks::migration::SchemaVersion>, typed_store::TypedStoreError> {
+                typed_store::rocks::migration::schema_versions(&self.#first_field_name.rocksdb, &[#(stringify!(#cf_names)),*])
+            }
+
             /// Returns a list of the tables name and type pairs
             pub fn describe_tables() -> std::collections::BTreeMap<String, (String, String)> {
                 vec![#(
@@ -641,6 +682,23 @@ pub fn derive_dbmap_utils_general(input: TokenStream) -> TokenStream {
                 )*
                 Ok(())
             }
+
+            /// Returns the schema version recorded for each table
+            /// Tables must be opened in read only mode using `open_tables_read_only`
+            pub fn schema_versions(&self) -> Result<std::collections::BTreeMap<String, typed_store::rocks::migration::SchemaVersion>, typed_store::TypedStoreError> {
+                typed_store::traits::Map::try_catch_up_with_primary(&self.#first_field_name)?;
+                typed_store::rocks::migration::schema_versions(&self.#first_field_name.rocksdb, &[#(stringify!(#cf_names)),*])
+            }
+
+            /// Reports what the pending schema migrations of the tables from `migrations` would change, without writing anything
+            /// Tables must be opened in read only mode using `open_tables_read_only`
+            pub fn dry_run_migrations(
+                &self,
+                migrations: &typed_store::rocks::migration::MigrationRegistry,
+            ) -> Result<Vec<typed_store::rocks::migration::MigrationReport>, typed_store::TypedStoreError> {
+                typed_store::traits::Map::try_catch_up_with_primary(&self.#first_field_name)?;
+                migrations.run(&self.#first_field_name.rocksdb, &[#(stringify!(#cf_names)),*], true)
+            }
         }
 
         impl <
@@ -799,12 +857,14 @@ pub fn derive_sallydb_general(input: TokenStream) -> TokenStream {
             pub fn init(db_options: typed_store::sally::SallyDBOptions) -> Self {
                 match db_options {
                     typed_store::sally::SallyDBOptions::TestDB => {
+                        // All the tables share a database, so that they can be written in the same batch
+                        let db = typed_store::test_db::InMemoryDB::new();
                         let (
                             #(
                                 #field_names
                             ),*
                         ) = (#(
-                            SallyColumn::TestDB((typed_store::test_db::TestDB::#inner_types::open(), typed_store::sally::SallyConfig::default()))
+                            SallyColumn::TestDB((typed_store::test_db::TestDB::#inner_types::reopen(&db, stringify!(#field_names)), typed_store::sally::SallyConfig::default()))
                             ),*);
 
                         Self {
@@ -0,0 +1,382 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Incremental backups of RocksDB databases to an object store.
+//!
+//! A backup is made from a RocksDB checkpoint of the database. SST and blob files are immutable,
+//! so the ones uploaded by an earlier backup are shared with it instead of being uploaded again.
+//! Each backup only uploads the files created since the previous backup, and the small files
+//! describing the database (`MANIFEST-*`, `OPTIONS-*`, `CURRENT`, ...). The layout of backups in
+//! the object store is:
+//!
+//! ```text
+//! <root>/shared/<db identity>/<file number>_<crc32c>_<size>.sst   files shared between backups
+//! <root>/backups/<backup id>/<file name>                          other files of a backup
+//! <root>/manifests/<backup id>.json                               list of files of a backup
+//! ```
+//!
+//! Shared files are keyed by the identity of the database they come from, since file numbers are
+//! only unique within a database, and, like RocksDB's own BackupEngine does, by their checksum, so
+//! that a different file reusing a number is never mistaken for one already uploaded. The
+//! checksum of every file is recorded in the manifest and verified on restore. The manifest is
+//! written last, so only backups that were fully uploaded are listed and restored.
+
+use bytes::Bytes;
+use eyre::{eyre, Result, WrapErr};
+use futures::{StreamExt, TryStreamExt};
+use object_store::{path::Path as ObjectPath, DynObjectStore};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::info;
+
+use super::RocksDB;
+
+const SHARED_DIR: &str = "shared";
+const BACKUPS_DIR: &str = "backups";
+const MANIFESTS_DIR: &str = "manifests";
+const IDENTITY_FILE: &str = "IDENTITY";
+/// Files of a database directory which are not needed to restore it.
+const SKIPPED_FILE_PREFIXES: &[&str] = &["LOCK", "LOG"];
+const TRANSFER_CONCURRENCY: usize = 8;
+const CHECKSUM_BUFFER_SIZE: usize = 1 << 20;
+
+/// Lists the files of a backup, and where they are stored.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub backup_id: u64,
+    pub timestamp_ms: u64,
+    pub db_identity: String,
+    pub files: Vec<BackupFile>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// Name of the file in the database directory.
+    pub name: String,
+    pub size: u64,
+    /// CRC32C checksum of the content of the file.
+    pub crc32c: u32,
+    /// Location of the file in the object store, relative to the root of the backups.
+    pub location: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BackupStats {
+    pub backup_id: u64,
+    pub num_files: usize,
+    pub total_bytes: u64,
+    pub num_uploaded_files: usize,
+    pub uploaded_bytes: u64,
+}
+
+/// Creates, restores and deletes backups of RocksDB databases under `root` in an object store.
+/// Backups of different databases should use different roots. Only one backup, restore or purge
+/// should run at a time for a given root.
+pub struct BackupEngine {
+    store: Arc<DynObjectStore>,
+    root: String,
+}
+
+impl BackupEngine {
+    pub fn new(store: Arc<DynObjectStore>, root: &str) -> Self {
+        Self {
+            store,
+            root: root.trim_matches('/').to_string(),
+        }
+    }
+
+    /// Backs up `db`. A checkpoint of the database is created in `staging_dir`, which must not
+    /// exist and should be on the same filesystem as the database, so that the checkpoint hard
+    /// links its files rather than copying them. The checkpoint is deleted once uploaded.
+    pub async fn create_backup(&self, db: &RocksDB, staging_dir: &Path) -> Result<BackupStats> {
+        db.checkpoint(staging_dir)?;
+        let result = self.backup_dir(staging_dir).await;
+        if let Err(e) = tokio::fs::remove_dir_all(staging_dir).await {
+            info!("Failed to remove backup staging dir {staging_dir:?}: {e}");
+        }
+        result
+    }
+
+    /// Backs up the database directory `db_dir`, which must either be a RocksDB checkpoint, or
+    /// the directory of a database that is not open.
+    pub async fn backup_dir(&self, db_dir: &Path) -> Result<BackupStats> {
+        let backup_id = self.list_backups().await?.last().map_or(0, |id| id + 1);
+        let db_identity = std::fs::read_to_string(db_dir.join(IDENTITY_FILE))
+            .map(|identity| identity.trim().to_string())
+            .unwrap_or_default();
+        if db_identity.is_empty() {
+            return Err(eyre!("Missing identity of database {db_dir:?}"));
+        }
+
+        let shared_dir = format!("{SHARED_DIR}/{db_identity}");
+        let already_uploaded: HashSet<ObjectPath> = self
+            .store
+            .list_with_delimiter(Some(&self.path(&shared_dir)))
+            .await?
+            .objects
+            .into_iter()
+            .map(|object| object.location)
+            .collect();
+
+        let mut files = vec![];
+        let mut to_upload = vec![];
+        for entry in std::fs::read_dir(db_dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let name = entry
+                .file_name()
+                .into_string()
+                .map_err(|name| eyre!("Invalid file name {name:?} in {db_dir:?}"))?;
+            if SKIPPED_FILE_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+            {
+                continue;
+            }
+            let size = entry.metadata()?.len();
+            let crc32c = file_crc32c(&entry.path())
+                .await
+                .wrap_err_with(|| format!("Failed to compute the checksum of {name}"))?;
+            let location = match shared_file_name(&name, crc32c, size) {
+                Some(shared_name) => format!("{shared_dir}/{shared_name}"),
+                None => format!("{BACKUPS_DIR}/{backup_id}/{name}"),
+            };
+            if !already_uploaded.contains(&self.path(&location)) {
+                to_upload.push((entry.path(), location.clone(), size));
+            }
+            files.push(BackupFile {
+                name,
+                size,
+                crc32c,
+                location,
+            });
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+
+        futures::stream::iter(&to_upload)
+            .map(|(path, location, _size)| self.upload_file(path, location))
+            .buffer_unordered(TRANSFER_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let stats = BackupStats {
+            backup_id,
+            num_files: files.len(),
+            total_bytes: files.iter().map(|file| file.size).sum(),
+            num_uploaded_files: to_upload.len(),
+            uploaded_bytes: to_upload.iter().map(|(_, _, size)| size).sum(),
+        };
+        let manifest = BackupManifest {
+            backup_id,
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+            db_identity,
+            files,
+        };
+        self.store
+            .put(
+                &self.manifest_path(backup_id),
+                Bytes::from(serde_json::to_vec_pretty(&manifest)?),
+            )
+            .await?;
+        info!(
+            "Created backup {backup_id} of {db_dir:?}: uploaded {} of {} files, {} of {} bytes",
+            stats.num_uploaded_files, stats.num_files, stats.uploaded_bytes, stats.total_bytes
+        );
+        Ok(stats)
+    }
+
+    /// Returns the ids of the complete backups, in increasing order.
+    pub async fn list_backups(&self) -> Result<Vec<u64>> {
+        let mut backup_ids: Vec<u64> = self
+            .store
+            .list_with_delimiter(Some(&self.path(MANIFESTS_DIR)))
+            .await?
+            .objects
+            .into_iter()
+            .filter_map(|object| {
+                object
+                    .location
+                    .filename()?
+                    .strip_suffix(".json")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        backup_ids.sort();
+        Ok(backup_ids)
+    }
+
+    pub async fn read_manifest(&self, backup_id: u64) -> Result<BackupManifest> {
+        let bytes = self
+            .store
+            .get(&self.manifest_path(backup_id))
+            .await
+            .wrap_err_with(|| format!("Failed to read manifest of backup {backup_id}"))?
+            .bytes()
+            .await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// Restores backup `backup_id`, or the latest backup if `None`, into `db_dir`, which must not
+    /// exist or be empty.
+    pub async fn restore(&self, backup_id: Option<u64>, db_dir: &Path) -> Result<BackupManifest> {
+        let backup_id = match backup_id {
+            Some(backup_id) => backup_id,
+            None => *self
+                .list_backups()
+                .await?
+                .last()
+                .ok_or_else(|| eyre!("No backups found under {:?}", self.root))?,
+        };
+        if db_dir.exists() && std::fs::read_dir(db_dir)?.next().is_some() {
+            return Err(eyre!("Restore target {db_dir:?} is not empty"));
+        }
+        std::fs::create_dir_all(db_dir)?;
+
+        let manifest = self.read_manifest(backup_id).await?;
+        futures::stream::iter(&manifest.files)
+            .map(|file| self.download_file(file, db_dir.join(&file.name)))
+            .buffer_unordered(TRANSFER_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
+        info!(
+            "Restored backup {backup_id} into {db_dir:?}: {} files",
+            manifest.files.len()
+        );
+        Ok(manifest)
+    }
+
+    /// Deletes all but the latest `num_to_keep` backups, along with the shared files that are
+    /// only used by deleted backups. Returns the ids of the deleted backups.
+    pub async fn purge_old_backups(&self, num_to_keep: usize) -> Result<Vec<u64>> {
+        let backup_ids = self.list_backups().await?;
+        let num_to_delete = backup_ids.len().saturating_sub(num_to_keep);
+        let (to_delete, to_keep) = backup_ids.split_at(num_to_delete);
+
+        let mut used_locations = HashSet::new();
+        for backup_id in to_keep {
+            let manifest = self.read_manifest(*backup_id).await?;
+            used_locations.extend(manifest.files.into_iter().map(|file| file.location));
+        }
+        for backup_id in to_delete {
+            let manifest = self.read_manifest(*backup_id).await?;
+            // Delete the manifest first, so that the backup is not listed once it is incomplete.
+            self.store.delete(&self.manifest_path(*backup_id)).await?;
+            for file in manifest.files {
+                if !used_locations.contains(&file.location) {
+                    self.store.delete(&self.path(&file.location)).await?;
+                }
+            }
+            info!("Deleted backup {backup_id}");
+        }
+        Ok(to_delete.to_vec())
+    }
+
+    /// Streams the file at `path` to `location` with a multipart upload, so that large SST files
+    /// are not loaded in memory.
+    async fn upload_file(&self, path: &PathBuf, location: &str) -> Result<()> {
+        let object_path = self.path(location);
+        let (multipart_id, mut writer) = self
+            .store
+            .put_multipart(&object_path)
+            .await
+            .wrap_err_with(|| format!("Failed to start uploading {path:?} to {location}"))?;
+        let result = async {
+            let mut file = tokio::fs::File::open(path).await?;
+            tokio::io::copy(&mut file, &mut writer).await?;
+            writer.shutdown().await
+        }
+        .await;
+        if let Err(e) = result {
+            // Best effort, parts left behind are eventually cleaned up by the object store.
+            let _ = self
+                .store
+                .abort_multipart(&object_path, &multipart_id)
+                .await;
+            return Err(e).wrap_err_with(|| format!("Failed to upload {path:?} to {location}"));
+        }
+        Ok(())
+    }
+
+    /// Streams the object of `file` to `path`, checking that its size and checksum match the
+    /// manifest.
+    async fn download_file(&self, file: &BackupFile, path: PathBuf) -> Result<()> {
+        let mut stream = self
+            .store
+            .get(&self.path(&file.location))
+            .await
+            .wrap_err_with(|| format!("Failed to download {}", file.location))?
+            .into_stream();
+        let mut writer = tokio::fs::File::create(&path).await?;
+        let mut size = 0;
+        let mut crc32c = 0;
+        while let Some(chunk) = stream
+            .try_next()
+            .await
+            .wrap_err_with(|| format!("Failed to download {}", file.location))?
+        {
+            size += chunk.len() as u64;
+            crc32c = crc32c::crc32c_append(crc32c, &chunk);
+            writer.write_all(&chunk).await?;
+        }
+        writer.flush().await?;
+        if size != file.size {
+            return Err(eyre!(
+                "Size of {} is {size}, expected {}",
+                file.location,
+                file.size
+            ));
+        }
+        if crc32c != file.crc32c {
+            return Err(eyre!(
+                "Checksum of {} is {crc32c:#x}, expected {:#x}",
+                file.location,
+                file.crc32c
+            ));
+        }
+        Ok(())
+    }
+
+    fn path(&self, location: &str) -> ObjectPath {
+        if self.root.is_empty() {
+            ObjectPath::from(location)
+        } else {
+            ObjectPath::from(format!("{}/{location}", self.root))
+        }
+    }
+
+    fn manifest_path(&self, backup_id: u64) -> ObjectPath {
+        self.path(&format!("{MANIFESTS_DIR}/{backup_id}.json"))
+    }
+}
+
+/// Returns the name under which an immutable file is shared between backups, or `None` if the
+/// file can change between backups.
+fn shared_file_name(name: &str, crc32c: u32, size: u64) -> Option<String> {
+    let (number, extension) = name.rsplit_once('.')?;
+    matches!(extension, "sst" | "blob").then(|| format!("{number}_{crc32c}_{size}.{extension}"))
+}
+
+async fn file_crc32c(path: &Path) -> std::io::Result<u32> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut buffer = vec![0; CHECKSUM_BUFFER_SIZE];
+    let mut crc32c = 0;
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(crc32c);
+        }
+        crc32c = crc32c::crc32c_append(crc32c, &buffer[..read]);
+    }
+}
@@ -0,0 +1,365 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Schema versions and migrations of DBMap tables.
+//!
+//! Every table has a schema version, recorded in the default column family of its database under
+//! the name of the table. Tables without a recorded version are at version 0. A [`Migration`]
+//! rewrites the entries of a table from one version to the next, and a [`MigrationRegistry`]
+//! runs the pending migrations of a set of tables in order.
+//!
+//! Migrations are resumable: entries are rewritten in batches, and each batch records the last
+//! key it rewrote along with the rewritten entries. If the process stops in the middle of a
+//! migration, the next run resumes after that key. Migrations can change or delete values, but
+//! not keys.
+
+use bincode::Options;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::info;
+
+use super::{
+    errors::typed_store_err_from_bcs_err, DBMap, ReadWriteOptions, RocksDB, TypedStoreError,
+};
+use crate::traits::Map;
+
+/// Number of entries scanned between two writes of a migration.
+const MIGRATION_BATCH_SIZE: usize = 10_000;
+
+/// The schema version of a table, as recorded in the database.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaVersion {
+    pub version: u64,
+    /// Last key rewritten by the migration from `version` to the next version, if that migration
+    /// is in progress.
+    pub migration_cursor: Option<Vec<u8>>,
+}
+
+/// What a migration does with an entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MigrationAction {
+    Keep,
+    Update(Vec<u8>),
+    Delete,
+}
+
+type MigrateFn =
+    Box<dyn Fn(&[u8], &[u8]) -> Result<MigrationAction, TypedStoreError> + Send + Sync>;
+
+/// Rewrites the entries of `table` from schema version `from_version` to `from_version + 1`.
+pub struct Migration {
+    table: String,
+    from_version: u64,
+    description: String,
+    migrate: MigrateFn,
+}
+
+impl Migration {
+    /// Creates a migration which rewrites the raw, serialized key-value pairs of `table`.
+    pub fn new(
+        table: &str,
+        from_version: u64,
+        description: &str,
+        migrate: impl Fn(&[u8], &[u8]) -> Result<MigrationAction, TypedStoreError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            table: table.to_string(),
+            from_version,
+            description: description.to_string(),
+            migrate: Box::new(migrate),
+        }
+    }
+
+    /// Creates a migration which converts the values of a `DBMap<K, Old>` table to `New`.
+    /// Entries for which `migrate` returns `None` are deleted.
+    pub fn typed<K, Old, New>(
+        table: &str,
+        from_version: u64,
+        description: &str,
+        migrate: impl Fn(K, Old) -> Option<New> + Send + Sync + 'static,
+    ) -> Self
+    where
+        K: DeserializeOwned,
+        Old: DeserializeOwned,
+        New: Serialize,
+    {
+        Self::new(table, from_version, description, move |key, value| {
+            let config = bincode::DefaultOptions::new()
+                .with_big_endian()
+                .with_fixint_encoding();
+            let key: K = config
+                .deserialize(key)
+                .map_err(|e| TypedStoreError::SerializationError(e.to_string()))?;
+            let old: Old = bcs::from_bytes(value).map_err(typed_store_err_from_bcs_err)?;
+            Ok(match migrate(key, old) {
+                Some(new) => {
+                    let new = bcs::to_bytes(&new).map_err(typed_store_err_from_bcs_err)?;
+                    if new == value {
+                        MigrationAction::Keep
+                    } else {
+                        MigrationAction::Update(new)
+                    }
+                }
+                None => MigrationAction::Delete,
+            })
+        })
+    }
+
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    pub fn from_version(&self) -> u64 {
+        self.from_version
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// What a migration changed, or would change in a dry run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub table: String,
+    pub from_version: u64,
+    pub description: String,
+    pub scanned: u64,
+    pub updated: u64,
+    pub deleted: u64,
+}
+
+/// The migrations of the tables of a database. The latest schema version of a table is the
+/// version its last migration migrates to, or 0 if it has none.
+#[derive(Default)]
+pub struct MigrationRegistry {
+    migrations: BTreeMap<String, BTreeMap<u64, Migration>>,
+}
+
+impl MigrationRegistry {
+    /// Registers `migration`. Panics if a migration from the same version of the same table was
+    /// already registered.
+    pub fn register(&mut self, migration: Migration) -> &mut Self {
+        let previous = self
+            .migrations
+            .entry(migration.table.clone())
+            .or_default()
+            .insert(migration.from_version, migration);
+        if let Some(previous) = previous {
+            panic!(
+                "Duplicate migration of table {} from version {}",
+                previous.table, previous.from_version
+            );
+        }
+        self
+    }
+
+    pub fn latest_version(&self, table: &str) -> u64 {
+        self.migrations
+            .get(table)
+            .and_then(|migrations| migrations.keys().last())
+            .map_or(0, |from_version| from_version + 1)
+    }
+
+    /// Runs the pending migrations of `tables` in `db`, in order of version, and records the
+    /// resulting schema versions. With `dry_run`, nothing is written and the reports describe
+    /// what the migrations would change.
+    pub fn run(
+        &self,
+        db: &Arc<RocksDB>,
+        tables: &[&str],
+        dry_run: bool,
+    ) -> Result<Vec<MigrationReport>, TypedStoreError> {
+        let versions = schema_versions_map(db)?;
+        let mut reports = vec![];
+        for table in tables {
+            let recorded = versions.get(&table.to_string())?;
+            let mut version = recorded.clone().unwrap_or_default();
+            let latest_version = self.latest_version(table);
+            if version.version > latest_version {
+                return Err(TypedStoreError::MigrationError(format!(
+                    "table {table} is at schema version {}, newer than the latest known version {latest_version}",
+                    version.version
+                )));
+            }
+            let pending: Vec<&Migration> = (version.version..latest_version)
+                .map(|from_version| {
+                    self.migrations
+                        .get(*table)
+                        .and_then(|migrations| migrations.get(&from_version))
+                        .ok_or_else(|| {
+                            TypedStoreError::MigrationError(format!(
+                                "missing migration of table {table} from version {from_version}"
+                            ))
+                        })
+                })
+                .collect::<Result<_, _>>()?;
+
+            if dry_run {
+                reports.extend(dry_run_migrations(db, table, &version, &pending)?);
+                continue;
+            }
+            if pending.is_empty() && recorded.is_none() {
+                let mut batch = versions.batch();
+                batch.insert_batch(&versions, [(table.to_string(), version.clone())])?;
+                batch.write()?;
+            }
+            for migration in pending {
+                reports.push(run_migration(db, &versions, migration, &mut version)?);
+            }
+        }
+        Ok(reports)
+    }
+}
+
+/// Returns the schema versions recorded in `db` for `tables`.
+pub fn schema_versions(
+    db: &Arc<RocksDB>,
+    tables: &[&str],
+) -> Result<BTreeMap<String, SchemaVersion>, TypedStoreError> {
+    let versions = schema_versions_map(db)?;
+    tables
+        .iter()
+        .map(|table| {
+            let version = versions.get(&table.to_string())?.unwrap_or_default();
+            Ok((table.to_string(), version))
+        })
+        .collect()
+}
+
+fn schema_versions_map(db: &Arc<RocksDB>) -> Result<DBMap<String, SchemaVersion>, TypedStoreError> {
+    DBMap::reopen(db, None, &ReadWriteOptions::default())
+}
+
+fn run_migration(
+    db: &Arc<RocksDB>,
+    versions: &DBMap<String, SchemaVersion>,
+    migration: &Migration,
+    version: &mut SchemaVersion,
+) -> Result<MigrationReport, TypedStoreError> {
+    let table = migration.table.as_str();
+    info!(
+        "Migrating table {table} from schema version {}: {}",
+        migration.from_version, migration.description
+    );
+    let mut report = MigrationReport {
+        table: table.to_string(),
+        from_version: migration.from_version,
+        description: migration.description.clone(),
+        ..Default::default()
+    };
+    let cf = db
+        .cf_handle(table)
+        .ok_or_else(|| TypedStoreError::UnregisteredColumn(table.to_string()))?;
+
+    loop {
+        // Iterators are recreated for every batch, so that they see the entries rewritten by
+        // the previous batches.
+        let mut iter = db.raw_iterator_cf(&cf, ReadWriteOptions::default().readopts());
+        match &version.migration_cursor {
+            Some(cursor) => {
+                iter.seek(cursor);
+                if iter.valid() && iter.key() == Some(cursor.as_slice()) {
+                    iter.next();
+                }
+            }
+            None => iter.seek_to_first(),
+        }
+
+        let mut batch = versions.batch();
+        let mut scanned = 0;
+        while iter.valid() && scanned < MIGRATION_BATCH_SIZE {
+            let (key, value) = (iter.key().unwrap(), iter.value().unwrap());
+            match (migration.migrate)(key, value)? {
+                MigrationAction::Keep => {}
+                MigrationAction::Update(new_value) => {
+                    batch.put_raw_cf(&cf, key, new_value);
+                    report.updated += 1;
+                }
+                MigrationAction::Delete => {
+                    batch.delete_raw_cf(&cf, key);
+                    report.deleted += 1;
+                }
+            }
+            version.migration_cursor = Some(key.to_vec());
+            scanned += 1;
+            iter.next();
+        }
+        iter.status()
+            .map_err(|e| TypedStoreError::RocksDBError(e.to_string()))?;
+        report.scanned += scanned as u64;
+
+        let done = !iter.valid();
+        drop(iter);
+        if done {
+            version.version = migration.from_version + 1;
+            version.migration_cursor = None;
+        }
+        batch.insert_batch(versions, [(table.to_string(), version.clone())])?;
+        batch.write()?;
+        if done {
+            info!(
+                "Migrated table {table} to schema version {}: {} entries scanned, {} updated, {} deleted",
+                version.version, report.scanned, report.updated, report.deleted
+            );
+            return Ok(report);
+        }
+    }
+}
+
+/// Applies `pending` migrations to the entries of `table` in memory, and reports what they would
+/// change. Entries already rewritten by an interrupted migration skip that migration.
+fn dry_run_migrations(
+    db: &Arc<RocksDB>,
+    table: &str,
+    version: &SchemaVersion,
+    pending: &[&Migration],
+) -> Result<Vec<MigrationReport>, TypedStoreError> {
+    let mut reports: Vec<_> = pending
+        .iter()
+        .map(|migration| MigrationReport {
+            table: table.to_string(),
+            from_version: migration.from_version,
+            description: migration.description.clone(),
+            ..Default::default()
+        })
+        .collect();
+    if pending.is_empty() {
+        return Ok(reports);
+    }
+    let cf = db
+        .cf_handle(table)
+        .ok_or_else(|| TypedStoreError::UnregisteredColumn(table.to_string()))?;
+    let mut iter = db.raw_iterator_cf(&cf, ReadWriteOptions::default().readopts());
+    iter.seek_to_first();
+    while iter.valid() {
+        let key = iter.key().unwrap();
+        let mut value = iter.value().unwrap().to_vec();
+        let already_migrated = version
+            .migration_cursor
+            .as_ref()
+            .is_some_and(|cursor| key <= cursor.as_slice());
+        let skip = usize::from(already_migrated);
+        for (migration, report) in pending.iter().zip(reports.iter_mut()).skip(skip) {
+            report.scanned += 1;
+            match (migration.migrate)(key, &value)? {
+                MigrationAction::Keep => {}
+                MigrationAction::Update(new_value) => {
+                    report.updated += 1;
+                    value = new_value;
+                }
+                MigrationAction::Delete => {
+                    report.deleted += 1;
+                    break;
+                }
+            }
+        }
+        iter.next();
+    }
+    iter.status()
+        .map_err(|e| TypedStoreError::RocksDBError(e.to_string()))?;
+    Ok(reports)
+}
@@ -1,8 +1,10 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
+pub mod backup;
 pub mod errors;
 pub(crate) mod iter;
 pub(crate) mod keys;
+pub mod migration;
 pub(crate) mod safe_iter;
 pub mod util;
 pub(crate) mod values;
@@ -39,7 +41,10 @@ use std::{
     marker::PhantomData,
     ops::RangeBounds,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 use std::{collections::HashSet, ffi::CStr};
@@ -515,6 +520,13 @@ impl RocksDB {
         checkpoint
             .create_checkpoint(path)
             .map_err(|e| TypedStoreError::RocksDBError(e.to_string()))?;
+        // RocksDB doesn't include the IDENTITY file in checkpoints, but backups of a checkpoint
+        // need it to tell which database its files come from.
+        let identity = self.path().join("IDENTITY");
+        if identity.exists() {
+            std::fs::copy(&identity, path.join("IDENTITY"))
+                .map_err(|e| TypedStoreError::RocksDBError(e.to_string()))?;
+        }
         Ok(())
     }
 
@@ -1370,6 +1382,16 @@ impl DBBatch {
 
 // TODO: Remove this entire implementation once we switch to sally
 impl DBBatch {
+    /// Puts an already serialized key-value pair in the column family `cf`.
+    pub(crate) fn put_raw_cf(&mut self, cf: &impl AsColumnFamilyRef, key: &[u8], value: Vec<u8>) {
+        self.batch.put_cf(cf, key, value);
+    }
+
+    /// Deletes an already serialized key from the column family `cf`.
+    pub(crate) fn delete_raw_cf(&mut self, cf: &impl AsColumnFamilyRef, key: &[u8]) {
+        self.batch.delete_cf(cf, key);
+    }
+
     pub fn delete_batch<J: Borrow<K>, K: Serialize, V>(
         &mut self,
         db: &DBMap<K, V>,
@@ -2442,6 +2464,26 @@ pub fn default_db_options() -> DBOptions {
     }
 }
 
+/// Creates the default RocksDB options, with the data of the database kept in memory instead of
+/// in files. Databases opened with these options lose their data when dropped, which is mainly
+/// useful for tests.
+pub fn in_memory_db_options() -> rocksdb::Options {
+    let mut options = default_db_options().options;
+    options.set_env(&rocksdb::Env::mem_env().expect("Failed to create in-memory environment"));
+    options
+}
+
+/// Returns a path to open a database with [in_memory_db_options] at. The files of such databases
+/// only exist in their in-memory environment, so nothing is ever created at this path.
+pub fn in_memory_db_path() -> PathBuf {
+    static NEXT_DB_ID: AtomicU64 = AtomicU64::new(0);
+    env::temp_dir().join(format!(
+        "in-memory-db-{}-{}",
+        std::process::id(),
+        NEXT_DB_ID.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
 fn get_block_options(block_cache_size_mb: usize) -> BlockBasedOptions {
     // Set options mostly similar to those used in optimize_for_point_lookup(),
     // except non-default binary and hash index, to hopefully reduce lookup latencies
@@ -2693,7 +2735,7 @@ fn populate_missing_cfs(
 /// Given a vec<u8>, find the value which is one more than the vector
 /// if the vector was a big endian number.
 /// If the vector is already minimum, don't change it.
-fn big_endian_saturating_add_one(v: &mut Vec<u8>) {
+pub(crate) fn big_endian_saturating_add_one(v: &mut Vec<u8>) {
     if is_max(v) {
         return;
     }
@@ -2708,7 +2750,7 @@ fn big_endian_saturating_add_one(v: &mut Vec<u8>) {
 }
 
 /// Check if all the bytes in the vector are 0xFF
-fn is_max(v: &[u8]) -> bool {
+pub(crate) fn is_max(v: &[u8]) -> bool {
     v.iter().all(|&x| x == u8::MAX)
 }
 
@@ -1052,6 +1052,174 @@ async fn test_checkpoint(#[values(true, false)] is_transactional: bool) {
     }
 }
 
+#[tokio::test]
+async fn test_backup_and_restore() {
+    let path_prefix = temp_dir();
+    let store_path = path_prefix.join("store");
+    std::fs::create_dir(&store_path).unwrap();
+    let store = Arc::new(
+        object_store::local::LocalFileSystem::new_with_prefix(&store_path)
+            .expect("Failed to create object store"),
+    );
+    let engine = backup::BackupEngine::new(store, "db");
+
+    let db: DBMap<i32, String> = open_map(path_prefix.join("db"), Some("table"), false);
+    let keys_vals = (0..101).map(|i| (i, i.to_string()));
+    db.multi_insert(keys_vals.clone())
+        .expect("Failed to multi-insert");
+    let first = engine
+        .create_backup(&db.rocksdb, &path_prefix.join("staging"))
+        .await
+        .expect("Failed to create backup");
+    assert_eq!(first.num_uploaded_files, first.num_files);
+
+    let new_keys_vals = (101..201).map(|i| (i, i.to_string()));
+    db.multi_insert(new_keys_vals.clone())
+        .expect("Failed to multi-insert");
+    let second = engine
+        .create_backup(&db.rocksdb, &path_prefix.join("staging"))
+        .await
+        .expect("Failed to create backup");
+    // The SST files of the first backup are shared with the second one.
+    assert!(second.num_uploaded_files < second.num_files);
+    assert_eq!(engine.list_backups().await.unwrap(), vec![0, 1]);
+
+    let first_path = path_prefix.join("restored_first");
+    engine
+        .restore(Some(first.backup_id), &first_path)
+        .await
+        .expect("Failed to restore backup");
+    let latest_path = path_prefix.join("restored_latest");
+    let manifest = engine
+        .restore(None, &latest_path)
+        .await
+        .expect("Failed to restore backup");
+    assert_eq!(manifest.backup_id, second.backup_id);
+    // Restoring into a non-empty directory fails.
+    assert!(engine.restore(None, &latest_path).await.is_err());
+
+    let first_db: DBMap<i32, String> = open_map(first_path, Some("table"), false);
+    let latest_db: DBMap<i32, String> = open_map(latest_path, Some("table"), false);
+    for (k, v) in keys_vals {
+        assert_eq!(Some(v.clone()), first_db.get(&k).unwrap());
+        assert_eq!(Some(v), latest_db.get(&k).unwrap());
+    }
+    for (k, v) in new_keys_vals {
+        assert_eq!(None, first_db.get(&k).unwrap());
+        assert_eq!(Some(v), latest_db.get(&k).unwrap());
+    }
+
+    assert_eq!(engine.purge_old_backups(1).await.unwrap(), vec![0]);
+    assert_eq!(engine.list_backups().await.unwrap(), vec![1]);
+    // Shared files still used by the remaining backup are kept.
+    let restored_path = path_prefix.join("restored_after_purge");
+    engine
+        .restore(None, &restored_path)
+        .await
+        .expect("Failed to restore backup");
+
+    // A shared file corrupted in the object store, even keeping its size, fails the restore.
+    let manifest = engine.read_manifest(second.backup_id).await.unwrap();
+    let shared_file = manifest
+        .files
+        .iter()
+        .find(|file| file.location.starts_with("shared/") && file.size > 0)
+        .expect("Backup should have shared files");
+    assert!(shared_file
+        .location
+        .ends_with(&format!("_{}_{}.sst", shared_file.crc32c, shared_file.size)));
+    std::fs::write(
+        store_path.join("db").join(&shared_file.location),
+        vec![0u8; shared_file.size as usize],
+    )
+    .unwrap();
+    let err = engine
+        .restore(None, &path_prefix.join("restored_corrupted"))
+        .await
+        .expect_err("Restoring a corrupted backup should fail");
+    assert!(err.to_string().contains("Checksum"), "{err}");
+}
+
+#[tokio::test]
+async fn test_migrations() {
+    use crate::rocks::migration::{schema_versions, Migration, MigrationRegistry, SchemaVersion};
+
+    let rocks = open_rocksdb(temp_dir(), &["table"], false);
+    let old_db = DBMap::<i32, u64>::reopen(&rocks, Some("table"), &ReadWriteOptions::default())
+        .expect("Failed to open storage");
+    old_db
+        .multi_insert((0..100).map(|i| (i, i as u64)))
+        .expect("Failed to multi-insert");
+
+    let mut registry = MigrationRegistry::default();
+    registry
+        .register(Migration::typed(
+            "table",
+            0,
+            "store values as strings",
+            |_key: i32, value: u64| Some(value.to_string()),
+        ))
+        .register(Migration::typed(
+            "table",
+            1,
+            "delete odd values",
+            |_key: i32, value: String| (value.parse::<u64>().unwrap() % 2 == 0).then_some(value),
+        ));
+    assert_eq!(registry.latest_version("table"), 2);
+
+    // A dry run reports the changes without making them.
+    let reports = registry
+        .run(&rocks, &["table"], true)
+        .expect("Failed to run migrations");
+    assert_eq!(reports.len(), 2);
+    assert_eq!((reports[0].scanned, reports[0].updated), (100, 100));
+    assert_eq!((reports[1].scanned, reports[1].deleted), (100, 50));
+    assert_eq!(old_db.get(&3).unwrap(), Some(3));
+    assert_eq!(
+        schema_versions(&rocks, &["table"]).unwrap()["table"],
+        SchemaVersion::default()
+    );
+
+    // Simulate an interrupted first migration, which already rewrote the first 50 entries.
+    let new_db = DBMap::<i32, String>::reopen(&rocks, Some("table"), &ReadWriteOptions::default())
+        .expect("Failed to open storage");
+    new_db
+        .multi_insert((0..50).map(|i| (i, i.to_string())))
+        .expect("Failed to multi-insert");
+    let versions =
+        DBMap::<String, SchemaVersion>::reopen(&rocks, None, &ReadWriteOptions::default())
+            .expect("Failed to open storage");
+    versions
+        .insert(
+            &"table".to_string(),
+            &SchemaVersion {
+                version: 0,
+                migration_cursor: Some(be_fix_int_ser(&49).unwrap()),
+            },
+        )
+        .unwrap();
+
+    let reports = registry
+        .run(&rocks, &["table"], false)
+        .expect("Failed to run migrations");
+    assert_eq!((reports[0].scanned, reports[0].updated), (50, 50));
+    assert_eq!((reports[1].scanned, reports[1].deleted), (100, 50));
+    for i in 0..100 {
+        let expected = (i % 2 == 0).then(|| i.to_string());
+        assert_eq!(new_db.get(&i).unwrap(), expected);
+    }
+    assert_eq!(
+        schema_versions(&rocks, &["table"]).unwrap()["table"],
+        SchemaVersion {
+            version: 2,
+            migration_cursor: None
+        }
+    );
+
+    // Migrations already run are not run again.
+    assert!(registry.run(&rocks, &["table"], false).unwrap().is_empty());
+}
+
 #[rstest]
 #[tokio::test]
 async fn test_multi_remove(#[values(true, false)] is_transactional: bool) {
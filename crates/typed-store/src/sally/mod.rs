@@ -51,7 +51,7 @@
 //! ```
 use crate::{
     rocks::{
-        default_db_options, keys::Keys, values::Values, DBBatch, DBMap, DBOptions,
+        default_db_options, iter::Iter, keys::Keys, values::Values, DBBatch, DBMap, DBOptions,
         RocksDBAccessType,
     },
     test_db::{TestDB, TestDBKeys, TestDBValues, TestDBWriteBatch},
@@ -61,12 +61,13 @@ use crate::{
 
 use crate::rocks::safe_iter::{SafeIter as RocksDBIter, SafeRevIter};
 use crate::rocks::{DBMapTableConfigMap, MetricConf};
-use crate::test_db::{TestDBIter, TestDBRevIter};
+use crate::test_db::{TestDBIter, TestDBRevIter, TestDBUnsafeIter};
 use async_trait::async_trait;
 use collectable::TryExtend;
 use rocksdb::Options;
 use serde::{de::DeserializeOwned, Serialize};
 use std::borrow::Borrow;
+use std::ops::RangeBounds;
 use std::{collections::BTreeMap, path::PathBuf};
 
 pub enum SallyRunMode {
@@ -287,6 +288,181 @@ where
     }
 }
 
+/// Synchronous access to a sally column, so that stores built on `DBMap` can also run on the
+/// in-memory backend. Only `FallbackToDB` mode exists, so every call goes to the backing table.
+impl<'a, K, V> Map<'a, K, V> for SallyColumn<K, V>
+where
+    K: Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    type Error = TypedStoreError;
+    type Iterator = SallyUnsafeIter<'a, K, V>;
+    type SafeIterator = SallyIter<'a, K, V>;
+    type Keys = SallyKeys<'a, K>;
+    type Values = SallyValues<'a, V>;
+
+    fn contains_key(&self, key: &K) -> Result<bool, TypedStoreError> {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => db_map.contains_key(key),
+            SallyColumn::TestDB((test_db, _)) => test_db.contains_key(key),
+        }
+    }
+
+    fn multi_contains_keys<J>(
+        &self,
+        keys: impl IntoIterator<Item = J>,
+    ) -> Result<Vec<bool>, TypedStoreError>
+    where
+        J: Borrow<K>,
+    {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => db_map.multi_contains_keys(keys),
+            SallyColumn::TestDB((test_db, _)) => test_db.multi_contains_keys(keys),
+        }
+    }
+
+    fn get(&self, key: &K) -> Result<Option<V>, TypedStoreError> {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => db_map.get(key),
+            SallyColumn::TestDB((test_db, _)) => test_db.get(key),
+        }
+    }
+
+    fn get_raw_bytes(&self, key: &K) -> Result<Option<Vec<u8>>, TypedStoreError> {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => db_map.get_raw_bytes(key),
+            SallyColumn::TestDB((test_db, _)) => test_db.get_raw_bytes(key),
+        }
+    }
+
+    fn insert(&self, key: &K, value: &V) -> Result<(), TypedStoreError> {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => db_map.insert(key, value),
+            SallyColumn::TestDB((test_db, _)) => test_db.insert(key, value),
+        }
+    }
+
+    fn remove(&self, key: &K) -> Result<(), TypedStoreError> {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => db_map.remove(key),
+            SallyColumn::TestDB((test_db, _)) => test_db.remove(key),
+        }
+    }
+
+    fn unsafe_clear(&self) -> Result<(), TypedStoreError> {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => db_map.unsafe_clear(),
+            SallyColumn::TestDB((test_db, _)) => test_db.unsafe_clear(),
+        }
+    }
+
+    fn schedule_delete_all(&self) -> Result<(), TypedStoreError> {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => db_map.schedule_delete_all(),
+            SallyColumn::TestDB((test_db, _)) => test_db.schedule_delete_all(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => db_map.is_empty(),
+            SallyColumn::TestDB((test_db, _)) => test_db.is_empty(),
+        }
+    }
+
+    fn unbounded_iter(&'a self) -> Self::Iterator {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => SallyUnsafeIter::RocksDB(db_map.unbounded_iter()),
+            SallyColumn::TestDB((test_db, _)) => SallyUnsafeIter::TestDB(test_db.unbounded_iter()),
+        }
+    }
+
+    fn iter_with_bounds(
+        &'a self,
+        lower_bound: Option<K>,
+        upper_bound: Option<K>,
+    ) -> Self::Iterator {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => {
+                SallyUnsafeIter::RocksDB(db_map.iter_with_bounds(lower_bound, upper_bound))
+            }
+            SallyColumn::TestDB((test_db, _)) => {
+                SallyUnsafeIter::TestDB(test_db.iter_with_bounds(lower_bound, upper_bound))
+            }
+        }
+    }
+
+    fn range_iter(&'a self, range: impl RangeBounds<K>) -> Self::Iterator {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => SallyUnsafeIter::RocksDB(db_map.range_iter(range)),
+            SallyColumn::TestDB((test_db, _)) => SallyUnsafeIter::TestDB(test_db.range_iter(range)),
+        }
+    }
+
+    fn safe_iter(&'a self) -> Self::SafeIterator {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => SallyIter::RocksDB(db_map.safe_iter()),
+            SallyColumn::TestDB((test_db, _)) => SallyIter::TestDB(test_db.safe_iter()),
+        }
+    }
+
+    fn safe_iter_with_bounds(
+        &'a self,
+        lower_bound: Option<K>,
+        upper_bound: Option<K>,
+    ) -> Self::SafeIterator {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => {
+                SallyIter::RocksDB(db_map.safe_iter_with_bounds(lower_bound, upper_bound))
+            }
+            SallyColumn::TestDB((test_db, _)) => {
+                SallyIter::TestDB(test_db.safe_iter_with_bounds(lower_bound, upper_bound))
+            }
+        }
+    }
+
+    fn safe_range_iter(&'a self, range: impl RangeBounds<K>) -> Self::SafeIterator {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => SallyIter::RocksDB(db_map.safe_range_iter(range)),
+            SallyColumn::TestDB((test_db, _)) => SallyIter::TestDB(test_db.safe_range_iter(range)),
+        }
+    }
+
+    fn keys(&'a self) -> Self::Keys {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => SallyKeys::RocksDB(db_map.keys()),
+            SallyColumn::TestDB((test_db, _)) => SallyKeys::TestDB(test_db.keys()),
+        }
+    }
+
+    fn values(&'a self) -> Self::Values {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => SallyValues::RocksDB(db_map.values()),
+            SallyColumn::TestDB((test_db, _)) => SallyValues::TestDB(test_db.values()),
+        }
+    }
+
+    fn multi_get<J>(
+        &self,
+        keys: impl IntoIterator<Item = J>,
+    ) -> Result<Vec<Option<V>>, TypedStoreError>
+    where
+        J: Borrow<K>,
+    {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => db_map.multi_get(keys),
+            SallyColumn::TestDB((test_db, _)) => test_db.multi_get(keys),
+        }
+    }
+
+    fn try_catch_up_with_primary(&self) -> Result<(), TypedStoreError> {
+        match self {
+            SallyColumn::RocksDB((db_map, _)) => db_map.try_catch_up_with_primary(),
+            SallyColumn::TestDB((test_db, _)) => test_db.try_catch_up_with_primary(),
+        }
+    }
+}
+
 impl<J, K, U, V> TryExtend<(J, U)> for SallyColumn<K, V>
 where
     J: Borrow<K> + std::clone::Clone,
@@ -345,6 +521,11 @@ pub enum SallyWriteBatch {
 
 impl SallyWriteBatch {
     pub async fn write(self) -> Result<(), TypedStoreError> {
+        self.write_sync()
+    }
+    /// Applies the batch without going through the async interface, for callers of the sync
+    /// `Map` interface.
+    pub fn write_sync(self) -> Result<(), TypedStoreError> {
         match self {
             SallyWriteBatch::RocksDB(db_batch) => db_batch.write(),
             SallyWriteBatch::TestDB(write_batch) => write_batch.write(),
@@ -403,6 +584,25 @@ impl SallyWriteBatch {
     }
 }
 
+/// A SallyUnsafeIter provides an iterator over all key values in a sally column, which panics
+/// on deserialization errors
+pub enum SallyUnsafeIter<'a, K, V> {
+    // Iter for a rocksdb backed sally column when `fallback_to_db` is true
+    RocksDB(Iter<'a, K, V>),
+    TestDB(TestDBUnsafeIter<'a, K, V>),
+}
+
+impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iterator for SallyUnsafeIter<'a, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            SallyUnsafeIter::RocksDB(iter) => iter.next(),
+            SallyUnsafeIter::TestDB(iter) => iter.next(),
+        }
+    }
+}
+
 /// A SallyIter provides an iterator over all key values in a sally column
 pub enum SallyIter<'a, K, V> {
     // Iter for a rocksdb backed sally column when `fallback_to_db` is true
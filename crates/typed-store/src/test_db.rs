@@ -1,97 +1,365 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
-#![allow(clippy::await_holding_lock)]
+
+//! An in-memory, multi-version backend for typed-store tables.
+//!
+//! An [`InMemoryDB`] holds named tables, and keeps the successive versions of each value tagged
+//! with the sequence number of the write which created them. Reads at a sequence number see the
+//! state of the database right after that write, which gives [`TestDBSnapshot`]s, iterators and
+//! [`TestDBTransaction`]s the same consistency as their RocksDB counterparts. Versions are
+//! dropped once no live snapshot can read them.
 
 use std::{
     borrow::Borrow,
-    collections::{btree_map::Iter, BTreeMap, HashMap, VecDeque},
+    collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap, VecDeque},
     marker::PhantomData,
-    ops::RangeBounds,
-    sync::{Arc, RwLock},
+    ops::{Bound, RangeBounds},
+    sync::{Arc, Mutex, RwLock, RwLockReadGuard},
 };
 
 use crate::{
-    rocks::{be_fix_int_ser, errors::typed_store_err_from_bcs_err},
+    rocks::{
+        be_fix_int_ser, big_endian_saturating_add_one,
+        errors::{typed_store_err_from_bcs_err, typed_store_err_from_bincode_err},
+        is_max,
+    },
     Map, TypedStoreError,
 };
 use bincode::Options;
 use collectable::TryExtend;
-use ouroboros::self_referencing;
 use rand::distributions::{Alphanumeric, DistString};
 use rocksdb::Direction;
 use serde::{de::DeserializeOwned, Serialize};
-use std::sync::{RwLockReadGuard, RwLockWriteGuard};
 
-/// An interface to a btree map backed sally database. This is mainly intended
-/// for tests and performing benchmark comparisons
+/// Sequence number used to read the latest version of values.
+const LATEST: u64 = u64::MAX;
+
+/// Versions of the value of a key, oldest first. `None` values are deletions.
+type Versions = Vec<(u64, Option<Vec<u8>>)>;
+
+/// Serialized key-value pairs, in key order.
+type Entries = Vec<(Vec<u8>, Vec<u8>)>;
+
+#[derive(Debug, Default)]
+struct State {
+    tables: HashMap<String, BTreeMap<Vec<u8>, Versions>>,
+    /// Sequence number of the last write.
+    seq: u64,
+    /// Sequence numbers of the live snapshots, with the number of snapshots taken at each.
+    snapshots: BTreeMap<u64, usize>,
+    /// Keys with versions only kept for the live snapshots, to collect once those are dropped.
+    retained: BTreeSet<(String, Vec<u8>)>,
+}
+
+impl State {
+    fn get(&self, table: &str, key: &[u8], seq: u64) -> Option<&Vec<u8>> {
+        self.tables
+            .get(table)?
+            .get(key)?
+            .iter()
+            .rev()
+            .find(|(version, _)| *version <= seq)?
+            .1
+            .as_ref()
+    }
+
+    /// Returns the sequence number of the last write to `key`, or 0 if it was never written.
+    fn last_write(&self, table: &str, key: &[u8]) -> u64 {
+        self.tables
+            .get(table)
+            .and_then(|rows| rows.get(key))
+            .and_then(|versions| versions.last())
+            .map_or(0, |(version, _)| *version)
+    }
+
+    /// Returns the entries of `table` within `bounds`, as of `seq`.
+    fn entries(&self, table: &str, bounds: &RawBounds, seq: u64) -> Entries {
+        let Some(rows) = self.tables.get(table) else {
+            return vec![];
+        };
+        if let (Some(lower), Some(upper)) = (&bounds.lower, &bounds.upper) {
+            if lower >= upper {
+                return vec![];
+            }
+        }
+        let lower = bounds
+            .lower
+            .as_ref()
+            .map_or(Bound::Unbounded, Bound::Included);
+        let upper = bounds
+            .upper
+            .as_ref()
+            .map_or(Bound::Unbounded, Bound::Excluded);
+        rows.range::<Vec<u8>, _>((lower, upper))
+            .filter_map(|(key, versions)| {
+                let (_, value) = versions.iter().rev().find(|(version, _)| *version <= seq)?;
+                Some((key.clone(), value.clone()?))
+            })
+            .collect()
+    }
+
+    fn is_empty(&self, table: &str) -> bool {
+        self.tables.get(table).map_or(true, |rows| {
+            rows.values()
+                .all(|versions| matches!(versions.last(), Some((_, None))))
+        })
+    }
+
+    /// Writes a new version of `key` at the current sequence number. `None` deletes the key.
+    fn apply(&mut self, table: &str, key: Vec<u8>, value: Option<Vec<u8>>) {
+        self.tables
+            .entry(table.to_string())
+            .or_default()
+            .entry(key.clone())
+            .or_default()
+            .push((self.seq, value));
+        self.collect_garbage(table, key);
+    }
+
+    /// Drops the versions of `key` that can no longer be read. Each version is read by the
+    /// snapshots taken before the next version was written, and the latest version by the
+    /// current state. Deletions are only kept while a snapshot taken before them is live, so
+    /// that transactions started from that snapshot see the conflict.
+    fn collect_garbage(&mut self, table: &str, key: Vec<u8>) {
+        let Some(rows) = self.tables.get_mut(table) else {
+            return;
+        };
+        let Some(versions) = rows.get_mut(&key) else {
+            return;
+        };
+        let snapshots = &self.snapshots;
+        let latest_is_readable = match versions.last() {
+            Some((_, Some(_))) => true,
+            Some((version, None)) => snapshots.range(..*version).next().is_some(),
+            None => false,
+        };
+        let mut readable = versions
+            .windows(2)
+            .map(|pair| snapshots.range(pair[0].0..pair[1].0).next().is_some())
+            .chain([latest_is_readable])
+            .collect::<Vec<_>>()
+            .into_iter();
+        versions.retain(|_| readable.next().unwrap());
+
+        let retained = versions.len() > 1 || matches!(versions.last(), Some((_, None)));
+        if versions.is_empty() {
+            rows.remove(&key);
+        }
+        let key = (table.to_string(), key);
+        if retained {
+            self.retained.insert(key);
+        } else {
+            self.retained.remove(&key);
+        }
+    }
+
+    fn release_snapshot(&mut self, seq: u64) {
+        let Entry::Occupied(mut entry) = self.snapshots.entry(seq) else {
+            return;
+        };
+        *entry.get_mut() -= 1;
+        if *entry.get() > 0 {
+            return;
+        }
+        entry.remove();
+        for (table, key) in std::mem::take(&mut self.retained) {
+            self.collect_garbage(&table, key);
+        }
+    }
+}
+
+/// Inclusive lower bound and exclusive upper bound of an iteration over serialized keys, as set
+/// in the read options of a `DBMap` iterator.
+#[derive(Debug, Default)]
+struct RawBounds {
+    lower: Option<Vec<u8>>,
+    upper: Option<Vec<u8>>,
+}
+
+impl RawBounds {
+    fn new<K: Serialize>(lower: Option<K>, upper: Option<K>) -> Self {
+        Self {
+            lower: lower.map(|key| be_fix_int_ser(&key).expect("Serialization must not fail")),
+            upper: upper.map(|key| be_fix_int_ser(&key).expect("Serialization must not fail")),
+        }
+    }
+
+    fn from_range<K: Serialize>(range: impl RangeBounds<K>) -> Self {
+        let lower = match range.start_bound() {
+            Bound::Included(lower) => {
+                Some(be_fix_int_ser(lower).expect("Serialization must not fail"))
+            }
+            Bound::Excluded(lower) => {
+                let mut key_buf = be_fix_int_ser(lower).expect("Serialization must not fail");
+                big_endian_saturating_add_one(&mut key_buf);
+                Some(key_buf)
+            }
+            Bound::Unbounded => None,
+        };
+        let upper = match range.end_bound() {
+            Bound::Included(upper) => {
+                let mut key_buf = be_fix_int_ser(upper).expect("Serialization must not fail");
+                // If the key is already at the limit, there's nowhere else to go, so no upper bound
+                (!is_max(&key_buf)).then(|| {
+                    big_endian_saturating_add_one(&mut key_buf);
+                    key_buf
+                })
+            }
+            Bound::Excluded(upper) => {
+                Some(be_fix_int_ser(upper).expect("Serialization must not fail"))
+            }
+            Bound::Unbounded => None,
+        };
+        Self { lower, upper }
+    }
+}
+
+/// An in-memory database holding the tables of one or more `TestDB`s. Tables of the same
+/// database can be written atomically by batches and transactions, and read consistently through
+/// snapshots. Cloning the database gives another handle to the same tables.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryDB {
+    state: Arc<RwLock<State>>,
+}
+
+impl InMemoryDB {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes a snapshot of all the tables of the database.
+    pub fn snapshot(&self) -> TestDBSnapshot {
+        let mut state = self.state.write().unwrap();
+        let seq = state.seq;
+        *state.snapshots.entry(seq).or_default() += 1;
+        TestDBSnapshot {
+            db: self.clone(),
+            seq,
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, State> {
+        self.state.read().unwrap()
+    }
+
+    fn is_same(&self, other: &InMemoryDB) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+/// A table of an in-memory database, with the same semantics as a `DBMap`. This is mainly
+/// intended for tests and performing benchmark comparisons
 #[derive(Clone, Debug)]
 pub struct TestDB<K, V> {
-    pub rows: Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>,
+    pub db: InMemoryDB,
     pub name: String,
     _phantom: PhantomData<fn(K) -> V>,
 }
 
 impl<K, V> TestDB<K, V> {
+    /// Opens a table in a new database of its own.
     pub fn open() -> Self {
+        Self::reopen(
+            &InMemoryDB::new(),
+            &Alphanumeric.sample_string(&mut rand::thread_rng(), 16),
+        )
+    }
+
+    /// Opens the table `name` of `db`, creating it if needed.
+    pub fn reopen(db: &InMemoryDB, name: &str) -> Self {
         TestDB {
-            rows: Arc::new(RwLock::new(BTreeMap::new())),
-            name: Alphanumeric.sample_string(&mut rand::thread_rng(), 16),
+            db: db.clone(),
+            name: name.to_string(),
             _phantom: PhantomData,
         }
     }
+
     pub fn batch(&self) -> TestDBWriteBatch {
         TestDBWriteBatch::default()
     }
+
+    /// Takes a snapshot of all the tables of the database of this table.
+    pub fn snapshot(&self) -> TestDBSnapshot {
+        self.db.snapshot()
+    }
+
+    /// Starts an optimistic transaction on the database of this table. Conflicts are checked
+    /// against the state of the database when the transaction started.
+    pub fn transaction(&self) -> Result<TestDBTransaction, TypedStoreError> {
+        Ok(TestDBTransaction::new(&self.db, true))
+    }
+
+    /// Starts an optimistic transaction on the database of this table. Conflicts on a key are
+    /// checked against the state of the database when the transaction first accessed it.
+    pub fn transaction_without_snapshot(&self) -> Result<TestDBTransaction, TypedStoreError> {
+        Ok(TestDBTransaction::new(&self.db, false))
+    }
+
+    fn iter_at<'a>(&self, bounds: &RawBounds, seq: u64) -> TestDBIter<'a, K, V> {
+        TestDBIter::new(self.db.read().entries(&self.name, bounds, seq))
+    }
 }
 
-#[self_referencing(pub_extras)]
-pub struct TestDBIter<'a, K, V> {
-    pub rows: RwLockReadGuard<'a, BTreeMap<Vec<u8>, Vec<u8>>>,
-    #[borrows(mut rows)]
-    #[covariant]
-    pub iter: Iter<'this, Vec<u8>, Vec<u8>>,
-    phantom: PhantomData<(K, V)>,
-    pub direction: Direction,
+fn deserialize_key<K: DeserializeOwned>(raw_key: &[u8]) -> Result<K, TypedStoreError> {
+    bincode::DefaultOptions::new()
+        .with_big_endian()
+        .with_fixint_encoding()
+        .deserialize(raw_key)
+        .map_err(typed_store_err_from_bincode_err)
 }
 
-#[self_referencing(pub_extras)]
-pub struct TestDBKeys<'a, K> {
-    rows: RwLockReadGuard<'a, BTreeMap<Vec<u8>, Vec<u8>>>,
-    #[borrows(mut rows)]
-    #[covariant]
-    pub iter: Iter<'this, Vec<u8>, Vec<u8>>,
-    phantom: PhantomData<K>,
+fn deserialize_value<V: DeserializeOwned>(raw_value: &[u8]) -> Result<V, TypedStoreError> {
+    bcs::from_bytes(raw_value).map_err(typed_store_err_from_bcs_err)
 }
 
-#[self_referencing(pub_extras)]
-pub struct TestDBValues<'a, V> {
-    rows: RwLockReadGuard<'a, BTreeMap<Vec<u8>, Vec<u8>>>,
-    #[borrows(mut rows)]
-    #[covariant]
-    pub iter: Iter<'this, Vec<u8>, Vec<u8>>,
-    phantom: PhantomData<V>,
+/// An iterator over the entries of a table, as of its creation. Like the iterators of a `DBMap`,
+/// it starts at the first entry unless moved with `skip_to`, `skip_prior_to` or `skip_to_last`.
+pub struct TestDBIter<'a, K, V> {
+    entries: Entries,
+    /// Index of the next entry, which may be out of `entries` once the iteration is over.
+    position: isize,
+    is_initialized: bool,
+    direction: Direction,
+    _phantom: PhantomData<(&'a (), fn() -> (K, V))>,
+}
+
+impl<'a, K, V> TestDBIter<'a, K, V> {
+    fn new(entries: Entries) -> Self {
+        Self {
+            entries,
+            position: 0,
+            is_initialized: false,
+            direction: Direction::Forward,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn next_raw(&mut self) -> Option<(Vec<u8>, Vec<u8>)> {
+        if !self.is_initialized {
+            self.position = 0;
+            self.is_initialized = true;
+        }
+        let entry = usize::try_from(self.position)
+            .ok()
+            .and_then(|position| self.entries.get(position))?
+            .clone();
+        match self.direction {
+            Direction::Forward => self.position += 1,
+            Direction::Reverse => self.position -= 1,
+        }
+        Some(entry)
+    }
 }
 
 impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iterator for TestDBIter<'a, K, V> {
     type Item = Result<(K, V), TypedStoreError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut out: Option<Self::Item> = None;
-        let config = bincode::DefaultOptions::new()
-            .with_big_endian()
-            .with_fixint_encoding();
-        self.with_mut(|fields| {
-            let resp = match fields.direction {
-                Direction::Forward => fields.iter.next(),
-                Direction::Reverse => panic!("Reverse iteration not supported in test db"),
-            };
-            if let Some((raw_key, raw_value)) = resp {
-                let key: K = config.deserialize(raw_key).ok().unwrap();
-                let value: V = bcs::from_bytes(raw_value).ok().unwrap();
-                out = Some(Ok((key, value)));
-            }
-        });
-        out
+        let (raw_key, raw_value) = self.next_raw()?;
+        Some(
+            deserialize_key(&raw_key)
+                .and_then(|key| deserialize_value(&raw_value).map(|value| (key, value))),
+        )
     }
 }
 
@@ -100,20 +368,9 @@ impl<'a, K: Serialize, V> TestDBIter<'a, K, V> {
     /// and either lands on the key or the first one greater than
     /// the key.
     pub fn skip_to(mut self, key: &K) -> Result<Self, TypedStoreError> {
-        self.with_mut(|fields| {
-            let serialized_key = be_fix_int_ser(key).expect("serialization failed");
-            let mut peekable = fields.iter.peekable();
-            let mut peeked = peekable.peek();
-            while peeked.is_some() {
-                let serialized = be_fix_int_ser(peeked.unwrap()).expect("serialization failed");
-                if serialized >= serialized_key {
-                    break;
-                } else {
-                    peekable.next();
-                    peeked = peekable.peek();
-                }
-            }
-        });
+        let raw_key = be_fix_int_ser(key)?;
+        self.position = self.entries.partition_point(|(k, _)| *k < raw_key) as isize;
+        self.is_initialized = true;
         Ok(self)
     }
 
@@ -121,28 +378,16 @@ impl<'a, K: Serialize, V> TestDBIter<'a, K, V> {
     /// the one prior to it if it does not exist. If there is
     /// no element prior to it, it returns an empty iterator.
     pub fn skip_prior_to(mut self, key: &K) -> Result<Self, TypedStoreError> {
-        self.with_mut(|fields| {
-            let serialized_key = be_fix_int_ser(key).expect("serialization failed");
-            let mut peekable = fields.iter.peekable();
-            let mut peeked = peekable.peek();
-            while peeked.is_some() {
-                let serialized = be_fix_int_ser(peeked.unwrap()).expect("serialization failed");
-                if serialized > serialized_key {
-                    break;
-                } else {
-                    peekable.next();
-                    peeked = peekable.peek();
-                }
-            }
-        });
+        let raw_key = be_fix_int_ser(key)?;
+        self.position = self.entries.partition_point(|(k, _)| *k <= raw_key) as isize - 1;
+        self.is_initialized = true;
         Ok(self)
     }
 
     /// Seeks to the last key in the database (at this column family).
     pub fn skip_to_last(mut self) -> Self {
-        self.with_mut(|fields| {
-            fields.iter.last();
-        });
+        self.position = self.entries.len() as isize - 1;
+        self.is_initialized = true;
         self
     }
 
@@ -150,9 +395,7 @@ impl<'a, K: Serialize, V> TestDBIter<'a, K, V> {
     /// create a new `RevIter` to consume. Every call to `next` method
     /// will give the next element from the end.
     pub fn reverse(mut self) -> TestDBRevIter<'a, K, V> {
-        self.with_mut(|fields| {
-            *fields.direction = Direction::Reverse;
-        });
+        self.direction = Direction::Reverse;
         TestDBRevIter::new(self)
     }
 }
@@ -180,36 +423,43 @@ impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iterator for TestDBRevIter<'a
     }
 }
 
+/// An iterator over the entries of a table which, like the unsafe iterators of a `DBMap`, ends
+/// at the first entry that fails to deserialize.
+pub struct TestDBUnsafeIter<'a, K, V> {
+    iter: TestDBIter<'a, K, V>,
+}
+
+impl<'a, K: DeserializeOwned, V: DeserializeOwned> Iterator for TestDBUnsafeIter<'a, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()?.ok()
+    }
+}
+
+pub struct TestDBKeys<'a, K> {
+    iter: TestDBIter<'a, K, ()>,
+}
+
 impl<'a, K: DeserializeOwned> Iterator for TestDBKeys<'a, K> {
     type Item = Result<K, TypedStoreError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut out: Option<Self::Item> = None;
-        self.with_mut(|fields| {
-            let config = bincode::DefaultOptions::new()
-                .with_big_endian()
-                .with_fixint_encoding();
-            if let Some((raw_key, _)) = fields.iter.next() {
-                let key: K = config.deserialize(raw_key).ok().unwrap();
-                out = Some(Ok(key));
-            }
-        });
-        out
+        let (raw_key, _) = self.iter.next_raw()?;
+        Some(deserialize_key(&raw_key))
     }
 }
 
+pub struct TestDBValues<'a, V> {
+    iter: TestDBIter<'a, (), V>,
+}
+
 impl<'a, V: DeserializeOwned> Iterator for TestDBValues<'a, V> {
     type Item = Result<V, TypedStoreError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut out: Option<Self::Item> = None;
-        self.with_mut(|fields| {
-            if let Some((_, raw_value)) = fields.iter.next() {
-                let value: V = bcs::from_bytes(raw_value).ok().unwrap();
-                out = Some(Ok(value));
-            }
-        });
-        out
+        let (_, raw_value) = self.iter.next_raw()?;
+        Some(deserialize_value(&raw_value))
     }
 }
 
@@ -219,117 +469,112 @@ where
     V: Serialize + DeserializeOwned,
 {
     type Error = TypedStoreError;
-    type Iterator = std::iter::Empty<(K, V)>;
+    type Iterator = TestDBUnsafeIter<'a, K, V>;
     type SafeIterator = TestDBIter<'a, K, V>;
     type Keys = TestDBKeys<'a, K>;
     type Values = TestDBValues<'a, V>;
 
     fn contains_key(&self, key: &K) -> Result<bool, Self::Error> {
-        let raw_key = be_fix_int_ser(key)?;
-        let locked = self.rows.read().unwrap();
-        Ok(locked.contains_key(&raw_key))
+        Ok(self.get_raw_bytes(key)?.is_some())
     }
 
     fn get(&self, key: &K) -> Result<Option<V>, Self::Error> {
-        let raw_key = be_fix_int_ser(key)?;
-        let locked = self.rows.read().unwrap();
-        let res = locked.get(&raw_key);
-        Ok(res.map(|raw_value| bcs::from_bytes(raw_value).ok().unwrap()))
+        self.get_raw_bytes(key)?
+            .map(|raw_value| deserialize_value(&raw_value))
+            .transpose()
     }
 
     fn get_raw_bytes(&self, key: &K) -> Result<Option<Vec<u8>>, Self::Error> {
         let raw_key = be_fix_int_ser(key)?;
-        let locked = self.rows.read().unwrap();
-        let res = locked.get(&raw_key);
-        Ok(res.cloned())
+        Ok(self.db.read().get(&self.name, &raw_key, LATEST).cloned())
     }
 
     fn insert(&self, key: &K, value: &V) -> Result<(), Self::Error> {
-        let raw_key = be_fix_int_ser(key)?;
-        let raw_value = bcs::to_bytes(value).map_err(typed_store_err_from_bcs_err)?;
-        let mut locked = self.rows.write().unwrap();
-        locked.insert(raw_key, raw_value);
-        Ok(())
+        let mut batch = self.batch();
+        batch.insert_batch(self, [(key, value)])?;
+        batch.write()
     }
 
     fn remove(&self, key: &K) -> Result<(), Self::Error> {
-        let raw_key = be_fix_int_ser(key)?;
-        let mut locked = self.rows.write().unwrap();
-        locked.remove(&raw_key);
-        Ok(())
+        let mut batch = self.batch();
+        batch.delete_batch(self, [key])?;
+        batch.write()
     }
 
     fn unsafe_clear(&self) -> Result<(), Self::Error> {
-        let mut locked = self.rows.write().unwrap();
-        locked.clear();
-        Ok(())
+        let mut batch = self.batch();
+        batch.ops.push_back(WriteBatchOp::DeleteRange((
+            self.db.clone(),
+            self.name.clone(),
+            (None, None),
+        )));
+        batch.write()
     }
 
     fn schedule_delete_all(&self) -> Result<(), TypedStoreError> {
-        let mut locked = self.rows.write().unwrap();
-        locked.clear();
-        Ok(())
+        self.unsafe_clear()
     }
 
     fn is_empty(&self) -> bool {
-        let locked = self.rows.read().unwrap();
-        locked.is_empty()
+        self.db.read().is_empty(&self.name)
     }
 
     fn unbounded_iter(&'a self) -> Self::Iterator {
-        unimplemented!("unimplemented API");
+        TestDBUnsafeIter {
+            iter: self.safe_iter(),
+        }
     }
 
     fn iter_with_bounds(
         &'a self,
-        _lower_bound: Option<K>,
-        _upper_bound: Option<K>,
+        lower_bound: Option<K>,
+        upper_bound: Option<K>,
     ) -> Self::Iterator {
-        unimplemented!("unimplemented API");
+        TestDBUnsafeIter {
+            iter: self.safe_iter_with_bounds(lower_bound, upper_bound),
+        }
     }
 
-    fn range_iter(&'a self, _range: impl RangeBounds<K>) -> Self::Iterator {
-        unimplemented!("unimplemented API");
+    fn range_iter(&'a self, range: impl RangeBounds<K>) -> Self::Iterator {
+        TestDBUnsafeIter {
+            iter: self.safe_range_iter(range),
+        }
     }
 
     fn safe_iter(&'a self) -> Self::SafeIterator {
-        TestDBIterBuilder {
-            rows: self.rows.read().unwrap(),
-            iter_builder: |rows: &mut RwLockReadGuard<'a, BTreeMap<Vec<u8>, Vec<u8>>>| rows.iter(),
-            phantom: PhantomData,
-            direction: Direction::Forward,
-        }
-        .build()
+        self.iter_at(&RawBounds::default(), LATEST)
     }
 
     fn safe_iter_with_bounds(
         &'a self,
-        _lower_bound: Option<K>,
-        _upper_bound: Option<K>,
+        lower_bound: Option<K>,
+        upper_bound: Option<K>,
     ) -> Self::SafeIterator {
-        unimplemented!("unimplemented API");
+        self.iter_at(&RawBounds::new(lower_bound, upper_bound), LATEST)
     }
 
-    fn safe_range_iter(&'a self, _range: impl RangeBounds<K>) -> Self::SafeIterator {
-        unimplemented!("unimplemented API");
+    fn safe_range_iter(&'a self, range: impl RangeBounds<K>) -> Self::SafeIterator {
+        self.iter_at(&RawBounds::from_range(range), LATEST)
     }
 
     fn keys(&'a self) -> Self::Keys {
-        TestDBKeysBuilder {
-            rows: self.rows.read().unwrap(),
-            iter_builder: |rows: &mut RwLockReadGuard<'a, BTreeMap<Vec<u8>, Vec<u8>>>| rows.iter(),
-            phantom: PhantomData,
+        TestDBKeys {
+            iter: TestDBIter::new(self.db.read().entries(
+                &self.name,
+                &RawBounds::default(),
+                LATEST,
+            )),
         }
-        .build()
     }
 
     fn values(&'a self) -> Self::Values {
-        TestDBValuesBuilder {
-            rows: self.rows.read().unwrap(),
-            iter_builder: |rows: &mut RwLockReadGuard<'a, BTreeMap<Vec<u8>, Vec<u8>>>| rows.iter(),
-            phantom: PhantomData,
+        TestDBValues {
+            iter: TestDBIter::new(self.db.read().entries(
+                &self.name,
+                &RawBounds::default(),
+                LATEST,
+            )),
         }
-        .build()
     }
 
     fn try_catch_up_with_primary(&self) -> Result<(), Self::Error> {
@@ -363,22 +608,11 @@ where
     }
 }
 
-pub type DeleteBatchPayload = (
-    Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>,
-    String,
-    Vec<Vec<u8>>,
-);
-pub type DeleteRangePayload = (
-    Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>,
-    String,
-    (Vec<u8>, Vec<u8>),
-);
-pub type InsertBatchPayload = (
-    Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>,
-    String,
-    Vec<(Vec<u8>, Vec<u8>)>,
-);
-type DBAndName = (Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>, String);
+pub type DeleteBatchPayload = (InMemoryDB, String, Vec<Vec<u8>>);
+/// Deletes the keys from the first bound (inclusive) to the second (non-inclusive). `None` bounds
+/// are open.
+pub type DeleteRangePayload = (InMemoryDB, String, (Option<Vec<u8>>, Option<Vec<u8>>));
+pub type InsertBatchPayload = (InMemoryDB, String, Vec<(Vec<u8>, Vec<u8>)>);
 
 pub enum WriteBatchOp {
     DeleteBatch(DeleteBatchPayload),
@@ -386,80 +620,65 @@ pub enum WriteBatchOp {
     InsertBatch(InsertBatchPayload),
 }
 
+impl WriteBatchOp {
+    fn db(&self) -> &InMemoryDB {
+        match self {
+            WriteBatchOp::DeleteBatch((db, _, _))
+            | WriteBatchOp::DeleteRange((db, _, _))
+            | WriteBatchOp::InsertBatch((db, _, _)) => db,
+        }
+    }
+}
+
+/// A batch of writes to tables of one or more in-memory databases. The writes to each database
+/// are applied atomically, with a single new sequence number.
 #[derive(Default)]
 pub struct TestDBWriteBatch {
     pub ops: VecDeque<WriteBatchOp>,
 }
 
-#[self_referencing]
-pub struct DBLocked {
-    db: Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>,
-    #[borrows(db)]
-    #[covariant]
-    db_guard: RwLockWriteGuard<'this, BTreeMap<Vec<u8>, Vec<u8>>>,
-}
-
 impl TestDBWriteBatch {
     pub fn write(self) -> Result<(), TypedStoreError> {
-        let mut dbs: Vec<DBAndName> = self
-            .ops
+        let mut dbs: Vec<&InMemoryDB> = self.ops.iter().map(WriteBatchOp::db).collect();
+        dbs.sort_by_key(|db| Arc::as_ptr(&db.state));
+        dbs.dedup_by_key(|db| Arc::as_ptr(&db.state));
+        // Lock all databases, always in the same order so that concurrent batches don't deadlock
+        let mut states: Vec<_> = dbs
             .iter()
-            .map(|op| match op {
-                WriteBatchOp::DeleteBatch((db, name, _)) => (db.clone(), name.clone()),
-                WriteBatchOp::DeleteRange((db, name, _)) => (db.clone(), name.clone()),
-                WriteBatchOp::InsertBatch((db, name, _)) => (db.clone(), name.clone()),
+            .map(|db| {
+                let mut state = db.state.write().unwrap();
+                state.seq += 1;
+                state
             })
             .collect();
-        dbs.sort_by_key(|(_k, v)| v.clone());
-        dbs.dedup_by_key(|(_k, v)| v.clone());
-        // lock all databases
-        let mut db_locks = HashMap::new();
-        dbs.iter().for_each(|(db, name)| {
-            if !db_locks.contains_key(name) {
-                db_locks.insert(
-                    name.clone(),
-                    DBLockedBuilder {
-                        db: db.clone(),
-                        db_guard_builder: |db: &Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>| {
-                            db.write().unwrap()
-                        },
+        for op in &self.ops {
+            let index = dbs.iter().position(|db| db.is_same(op.db())).unwrap();
+            let state = &mut states[index];
+            match op {
+                WriteBatchOp::DeleteBatch((_, table, keys)) => {
+                    for key in keys {
+                        state.apply(table, key.clone(), None);
                     }
-                    .build(),
-                );
-            }
-        });
-        self.ops.iter().for_each(|op| match op {
-            WriteBatchOp::DeleteBatch((_, id, keys)) => {
-                let locked = db_locks.get_mut(id).unwrap();
-                locked.with_db_guard_mut(|db| {
-                    keys.iter().for_each(|key| {
-                        db.remove(key);
-                    });
-                });
-            }
-            WriteBatchOp::DeleteRange((_, id, (from, to))) => {
-                let locked = db_locks.get_mut(id).unwrap();
-                locked.with_db_guard_mut(|db| {
-                    db.retain(|k, _| k < from || k >= to);
-                });
-            }
-            WriteBatchOp::InsertBatch((_, id, key_values)) => {
-                let locked = db_locks.get_mut(id).unwrap();
-                locked.with_db_guard_mut(|db| {
-                    key_values.iter().for_each(|(k, v)| {
-                        db.insert(k.clone(), v.clone());
-                    });
-                });
-            }
-        });
-        // unlock in the reverse order
-        dbs.iter().rev().for_each(|(_db, id)| {
-            if db_locks.contains_key(id) {
-                db_locks.remove(id);
+                }
+                WriteBatchOp::DeleteRange((_, table, (from, to))) => {
+                    let bounds = RawBounds {
+                        lower: from.clone(),
+                        upper: to.clone(),
+                    };
+                    for (key, _) in state.entries(table, &bounds, LATEST) {
+                        state.apply(table, key, None);
+                    }
+                }
+                WriteBatchOp::InsertBatch((_, table, key_values)) => {
+                    for (key, value) in key_values {
+                        state.apply(table, key.clone(), Some(value.clone()));
+                    }
+                }
             }
-        });
+        }
         Ok(())
     }
+
     /// Deletes a set of keys given as an iterator
     pub fn delete_batch<J: Borrow<K>, K: Serialize, V>(
         &mut self,
@@ -467,15 +686,16 @@ impl TestDBWriteBatch {
         purged_vals: impl IntoIterator<Item = J>,
     ) -> Result<(), TypedStoreError> {
         self.ops.push_back(WriteBatchOp::DeleteBatch((
-            db.rows.clone(),
+            db.db.clone(),
             db.name.clone(),
             purged_vals
                 .into_iter()
-                .map(|key| be_fix_int_ser(&key.borrow()).unwrap())
-                .collect(),
+                .map(|key| be_fix_int_ser(key.borrow()))
+                .collect::<Result<_, _>>()?,
         )));
         Ok(())
     }
+
     /// Deletes a range of keys between `from` (inclusive) and `to` (non-inclusive)
     pub fn delete_range<K: Serialize, V>(
         &mut self,
@@ -483,15 +703,16 @@ impl TestDBWriteBatch {
         from: &K,
         to: &K,
     ) -> Result<(), TypedStoreError> {
-        let raw_from = be_fix_int_ser(from).unwrap();
-        let raw_to = be_fix_int_ser(to).unwrap();
+        let raw_from = be_fix_int_ser(from)?;
+        let raw_to = be_fix_int_ser(to)?;
         self.ops.push_back(WriteBatchOp::DeleteRange((
-            db.rows.clone(),
+            db.db.clone(),
             db.name.clone(),
-            (raw_from, raw_to),
+            (Some(raw_from), Some(raw_to)),
         )));
         Ok(())
     }
+
     /// inserts a range of (key, value) pairs given as an iterator
     pub fn insert_batch<J: Borrow<K>, K: Serialize, U: Borrow<V>, V: Serialize>(
         &mut self,
@@ -499,25 +720,290 @@ impl TestDBWriteBatch {
         new_vals: impl IntoIterator<Item = (J, U)>,
     ) -> Result<(), TypedStoreError> {
         self.ops.push_back(WriteBatchOp::InsertBatch((
-            db.rows.clone(),
+            db.db.clone(),
             db.name.clone(),
-            new_vals
-                .into_iter()
-                .map(|(key, value)| {
-                    (
-                        be_fix_int_ser(&key.borrow()).unwrap(),
-                        bcs::to_bytes(&value.borrow()).unwrap(),
-                    )
-                })
-                .collect(),
+            serialize_entries(new_vals)?,
         )));
         Ok(())
     }
 }
 
+fn serialize_entries<J: Borrow<K>, K: Serialize, U: Borrow<V>, V: Serialize>(
+    new_vals: impl IntoIterator<Item = (J, U)>,
+) -> Result<Entries, TypedStoreError> {
+    new_vals
+        .into_iter()
+        .map(|(key, value)| {
+            Ok((
+                be_fix_int_ser(key.borrow())?,
+                bcs::to_bytes(value.borrow()).map_err(typed_store_err_from_bcs_err)?,
+            ))
+        })
+        .collect()
+}
+
+/// A consistent view of all the tables of an in-memory database, as of the moment it was taken.
+/// The versions it reads are kept until it is dropped.
+#[derive(Debug)]
+pub struct TestDBSnapshot {
+    db: InMemoryDB,
+    seq: u64,
+}
+
+impl TestDBSnapshot {
+    pub fn get<K: Serialize, V: DeserializeOwned>(
+        &self,
+        table: &TestDB<K, V>,
+        key: &K,
+    ) -> Result<Option<V>, TypedStoreError> {
+        self.check_table(table)?;
+        let raw_key = be_fix_int_ser(key)?;
+        self.db
+            .read()
+            .get(&table.name, &raw_key, self.seq)
+            .map(|raw_value| deserialize_value(raw_value))
+            .transpose()
+    }
+
+    pub fn multi_get<J: Borrow<K>, K: Serialize, V: DeserializeOwned>(
+        &self,
+        table: &TestDB<K, V>,
+        keys: impl IntoIterator<Item = J>,
+    ) -> Result<Vec<Option<V>>, TypedStoreError> {
+        keys.into_iter()
+            .map(|key| self.get(table, key.borrow()))
+            .collect()
+    }
+
+    pub fn safe_iter<'a, K, V>(
+        &self,
+        table: &'a TestDB<K, V>,
+    ) -> Result<TestDBIter<'a, K, V>, TypedStoreError> {
+        self.check_table(table)?;
+        Ok(table.iter_at(&RawBounds::default(), self.seq))
+    }
+
+    pub fn safe_range_iter<'a, K: Serialize, V>(
+        &self,
+        table: &'a TestDB<K, V>,
+        range: impl RangeBounds<K>,
+    ) -> Result<TestDBIter<'a, K, V>, TypedStoreError> {
+        self.check_table(table)?;
+        Ok(table.iter_at(&RawBounds::from_range(range), self.seq))
+    }
+
+    fn check_table<K, V>(&self, table: &TestDB<K, V>) -> Result<(), TypedStoreError> {
+        if !self.db.is_same(&table.db) {
+            return Err(TypedStoreError::UnregisteredColumn(table.name.clone()));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TestDBSnapshot {
+    fn drop(&mut self) {
+        self.db.state.write().unwrap().release_snapshot(self.seq);
+    }
+}
+
+/// An optimistic transaction over the tables of an in-memory database. Its writes are buffered
+/// until `commit`, which fails with `RetryableTransactionError` if a key the transaction wrote or
+/// read with `get_for_update` was written by someone else in the meantime, as with a RocksDB
+/// optimistic transaction.
+pub struct TestDBTransaction {
+    snapshot: TestDBSnapshot,
+    with_snapshot: bool,
+    /// Keys to check for conflicts, with the sequence number after which a write to them is a
+    /// conflict.
+    tracked: Mutex<HashMap<(String, Vec<u8>), u64>>,
+    /// Writes of the transaction, in key order. `None` values are deletions.
+    writes: BTreeMap<(String, Vec<u8>), Option<Vec<u8>>>,
+}
+
+impl TestDBTransaction {
+    fn new(db: &InMemoryDB, with_snapshot: bool) -> Self {
+        Self {
+            snapshot: db.snapshot(),
+            with_snapshot,
+            tracked: Mutex::new(HashMap::new()),
+            writes: BTreeMap::new(),
+        }
+    }
+
+    pub fn insert_batch<J: Borrow<K>, K: Serialize, U: Borrow<V>, V: Serialize>(
+        &mut self,
+        db: &TestDB<K, V>,
+        new_vals: impl IntoIterator<Item = (J, U)>,
+    ) -> Result<&mut Self, TypedStoreError> {
+        self.check_table(db)?;
+        for (raw_key, raw_value) in serialize_entries(new_vals)? {
+            self.track(&db.name, &raw_key);
+            self.writes
+                .insert((db.name.clone(), raw_key), Some(raw_value));
+        }
+        Ok(self)
+    }
+
+    /// Deletes a set of keys given as an iterator
+    pub fn delete_batch<J: Borrow<K>, K: Serialize, V>(
+        &mut self,
+        db: &TestDB<K, V>,
+        purged_vals: impl IntoIterator<Item = J>,
+    ) -> Result<&mut Self, TypedStoreError> {
+        self.check_table(db)?;
+        for key in purged_vals {
+            let raw_key = be_fix_int_ser(key.borrow())?;
+            self.track(&db.name, &raw_key);
+            self.writes.insert((db.name.clone(), raw_key), None);
+        }
+        Ok(self)
+    }
+
+    /// Returns the snapshot the transaction started from.
+    pub fn snapshot(&self) -> &TestDBSnapshot {
+        &self.snapshot
+    }
+
+    /// Reads the value of `key` like `get`, and makes the commit fail if another writer writes
+    /// it before the transaction commits.
+    pub fn get_for_update<K: Serialize, V: DeserializeOwned>(
+        &self,
+        db: &TestDB<K, V>,
+        key: &K,
+    ) -> Result<Option<V>, TypedStoreError> {
+        self.check_table(db)?;
+        let raw_key = be_fix_int_ser(key)?;
+        self.track(&db.name, &raw_key);
+        self.get_raw(&db.name, raw_key)
+            .map(|raw_value| deserialize_value(&raw_value))
+            .transpose()
+    }
+
+    /// Reads the value of `key` as written by the transaction, or else as currently committed.
+    pub fn get<K: Serialize, V: DeserializeOwned>(
+        &self,
+        db: &TestDB<K, V>,
+        key: &K,
+    ) -> Result<Option<V>, TypedStoreError> {
+        self.check_table(db)?;
+        let raw_key = be_fix_int_ser(key)?;
+        self.get_raw(&db.name, raw_key)
+            .map(|raw_value| deserialize_value(&raw_value))
+            .transpose()
+    }
+
+    pub fn multi_get<J: Borrow<K>, K: Serialize, V: DeserializeOwned>(
+        &self,
+        db: &TestDB<K, V>,
+        keys: impl IntoIterator<Item = J>,
+    ) -> Result<Vec<Option<V>>, TypedStoreError> {
+        keys.into_iter()
+            .map(|key| self.get(db, key.borrow()))
+            .collect()
+    }
+
+    /// Iterates over the entries of `db` as currently committed, with the writes of the
+    /// transaction applied.
+    pub fn iter<'a, K, V>(
+        &self,
+        db: &'a TestDB<K, V>,
+    ) -> Result<TestDBIter<'a, K, V>, TypedStoreError> {
+        self.check_table(db)?;
+        let mut entries: BTreeMap<Vec<u8>, Vec<u8>> = self
+            .snapshot
+            .db
+            .read()
+            .entries(&db.name, &RawBounds::default(), LATEST)
+            .into_iter()
+            .collect();
+        for ((table, raw_key), raw_value) in &self.writes {
+            if *table != db.name {
+                continue;
+            }
+            match raw_value {
+                Some(raw_value) => entries.insert(raw_key.clone(), raw_value.clone()),
+                None => entries.remove(raw_key),
+            };
+        }
+        Ok(TestDBIter::new(entries.into_iter().collect()))
+    }
+
+    pub fn keys<'a, K, V>(
+        &self,
+        db: &'a TestDB<K, V>,
+    ) -> Result<TestDBKeys<'a, K>, TypedStoreError> {
+        let iter = self.iter(db)?;
+        Ok(TestDBKeys {
+            iter: TestDBIter::new(iter.entries),
+        })
+    }
+
+    pub fn values<'a, K, V>(
+        &self,
+        db: &'a TestDB<K, V>,
+    ) -> Result<TestDBValues<'a, V>, TypedStoreError> {
+        let iter = self.iter(db)?;
+        Ok(TestDBValues {
+            iter: TestDBIter::new(iter.entries),
+        })
+    }
+
+    /// Applies the writes of the transaction atomically, unless a conflicting write was
+    /// committed since the transaction accessed the keys it tracks.
+    pub fn commit(self) -> Result<(), TypedStoreError> {
+        let mut state = self.snapshot.db.state.write().unwrap();
+        let tracked = self.tracked.lock().unwrap();
+        if tracked
+            .iter()
+            .any(|((table, raw_key), seq)| state.last_write(table, raw_key) > *seq)
+        {
+            return Err(TypedStoreError::RetryableTransactionError);
+        }
+        if self.writes.is_empty() {
+            return Ok(());
+        }
+        state.seq += 1;
+        for ((table, raw_key), raw_value) in &self.writes {
+            state.apply(table, raw_key.clone(), raw_value.clone());
+        }
+        Ok(())
+    }
+
+    fn get_raw(&self, table: &str, raw_key: Vec<u8>) -> Option<Vec<u8>> {
+        let key = (table.to_string(), raw_key);
+        match self.writes.get(&key) {
+            Some(raw_value) => raw_value.clone(),
+            None => self.snapshot.db.read().get(table, &key.1, LATEST).cloned(),
+        }
+    }
+
+    fn track(&self, table: &str, raw_key: &[u8]) {
+        let mut tracked = self.tracked.lock().unwrap();
+        if tracked.contains_key(&(table.to_string(), raw_key.to_vec())) {
+            return;
+        }
+        let seq = if self.with_snapshot {
+            self.snapshot.seq
+        } else {
+            self.snapshot.db.read().seq
+        };
+        tracked.insert((table.to_string(), raw_key.to_vec()), seq);
+    }
+
+    fn check_table<K, V>(&self, db: &TestDB<K, V>) -> Result<(), TypedStoreError> {
+        if !self.snapshot.db.is_same(&db.db) {
+            return Err(TypedStoreError::CrossDBBatch);
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{test_db::TestDB, Map};
+    use crate::{
+        test_db::{InMemoryDB, TestDB},
+        Map, TypedStoreError,
+    };
 
     #[test]
     fn test_contains_key() {
@@ -824,4 +1310,279 @@ mod test {
             assert_eq!(Some(v), val);
         }
     }
+
+    #[test]
+    fn test_iter_with_bounds() {
+        let db: TestDB<u32, String> = TestDB::open();
+        db.multi_insert((0..10).map(|i| (i, i.to_string())))
+            .expect("Failed to multi-insert");
+        db.insert(&u32::MAX, &"max".to_string())
+            .expect("Failed to insert");
+
+        let keys = |iter: crate::test_db::TestDBIter<'_, u32, String>| -> Vec<u32> {
+            iter.map(|entry| entry.unwrap().0).collect()
+        };
+        assert_eq!(keys(db.safe_iter_with_bounds(Some(2), Some(5))), [2, 3, 4]);
+        assert_eq!(keys(db.safe_iter_with_bounds(None, Some(2))), [0, 1]);
+        assert_eq!(
+            keys(db.safe_iter_with_bounds(Some(5), Some(2))),
+            Vec::<u32>::new()
+        );
+        assert_eq!(keys(db.safe_range_iter(2..=5)), [2, 3, 4, 5]);
+        assert_eq!(
+            keys(db.safe_range_iter((std::ops::Bound::Excluded(7), std::ops::Bound::Unbounded))),
+            [8, 9, u32::MAX]
+        );
+        assert_eq!(keys(db.safe_range_iter(9..=u32::MAX)), [9, u32::MAX]);
+
+        let entries: Vec<_> = db.range_iter(..3).collect();
+        assert_eq!(
+            entries,
+            [
+                (0, "0".to_string()),
+                (1, "1".to_string()),
+                (2, "2".to_string())
+            ]
+        );
+        assert_eq!(db.iter_with_bounds(Some(8), None).count(), 3);
+        assert_eq!(db.unbounded_iter().count(), 11);
+    }
+
+    #[test]
+    fn test_iter_skip_and_reverse() {
+        let db: TestDB<i32, String> = TestDB::open();
+        db.multi_insert((0..10).step_by(2).map(|i| (i, i.to_string())))
+            .expect("Failed to multi-insert");
+
+        let keys = |iter: &mut dyn Iterator<Item = Result<(i32, String), TypedStoreError>>| {
+            iter.map(|entry| entry.unwrap().0).collect::<Vec<_>>()
+        };
+        assert_eq!(keys(&mut db.safe_iter().skip_to(&3).unwrap()), [4, 6, 8]);
+        assert_eq!(keys(&mut db.safe_iter().skip_to(&4).unwrap()), [4, 6, 8]);
+        assert_eq!(
+            keys(&mut db.safe_iter().skip_prior_to(&5).unwrap()),
+            [4, 6, 8]
+        );
+        assert_eq!(
+            keys(&mut db.safe_iter().skip_prior_to(&-1).unwrap()),
+            Vec::<i32>::new()
+        );
+        assert_eq!(keys(&mut db.safe_iter().skip_to_last()), [8]);
+        assert_eq!(
+            keys(&mut db.safe_iter().skip_to_last().reverse()),
+            [8, 6, 4, 2, 0]
+        );
+        assert_eq!(
+            keys(&mut db.safe_iter().skip_prior_to(&5).unwrap().reverse()),
+            [4, 2, 0]
+        );
+        assert_eq!(
+            keys(&mut db.safe_iter().skip_to(&20).unwrap().reverse()),
+            Vec::<i32>::new()
+        );
+    }
+
+    #[test]
+    fn test_iter_is_consistent() {
+        let db: TestDB<i32, String> = TestDB::open();
+        db.multi_insert((0..3).map(|i| (i, i.to_string())))
+            .expect("Failed to multi-insert");
+
+        let iter = db.safe_iter();
+        db.remove(&0).expect("Failed to remove");
+        db.insert(&5, &"5".to_string()).expect("Failed to insert");
+        let keys: Vec<_> = iter.map(|entry| entry.unwrap().0).collect();
+        assert_eq!(keys, [0, 1, 2]);
+
+        let keys: Vec<_> = db.keys().map(|key| key.unwrap()).collect();
+        assert_eq!(keys, [1, 2, 5]);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let db = InMemoryDB::new();
+        let table_1: TestDB<i32, String> = TestDB::reopen(&db, "table_1");
+        let table_2: TestDB<i32, String> = TestDB::reopen(&db, "table_2");
+        table_1
+            .insert(&1, &"1".to_string())
+            .expect("Failed to insert");
+        table_2
+            .insert(&1, &"a".to_string())
+            .expect("Failed to insert");
+
+        let snapshot = table_1.snapshot();
+        let mut wb = table_1.batch();
+        wb.insert_batch(&table_1, [(1, "one".to_string()), (2, "two".to_string())])
+            .expect("Failed to batch insert");
+        wb.delete_batch(&table_2, [1])
+            .expect("Failed to batch delete");
+        wb.write().expect("Failed to execute batch");
+
+        assert_eq!(snapshot.get(&table_1, &1).unwrap(), Some("1".to_string()));
+        assert_eq!(snapshot.get(&table_1, &2).unwrap(), None);
+        assert_eq!(snapshot.get(&table_2, &1).unwrap(), Some("a".to_string()));
+        assert_eq!(
+            snapshot.multi_get(&table_1, [1, 2]).unwrap(),
+            [Some("1".to_string()), None]
+        );
+        assert_eq!(snapshot.safe_iter(&table_1).unwrap().count(), 1);
+        assert_eq!(snapshot.safe_range_iter(&table_1, 2..).unwrap().count(), 0);
+
+        assert_eq!(table_1.get(&1).unwrap(), Some("one".to_string()));
+        assert_eq!(table_1.safe_iter().count(), 2);
+        assert!(table_2.is_empty());
+
+        let other: TestDB<i32, String> = TestDB::open();
+        assert!(matches!(
+            snapshot.get(&other, &1),
+            Err(TypedStoreError::UnregisteredColumn(_))
+        ));
+    }
+
+    #[test]
+    fn test_old_versions_are_dropped() {
+        let db: TestDB<i32, String> = TestDB::open();
+        let versions = |db: &TestDB<i32, String>| {
+            let state = db.db.state.read().unwrap();
+            state.tables[&db.name].values().map(Vec::len).sum::<usize>()
+        };
+
+        for i in 0..10 {
+            db.insert(&1, &i.to_string()).expect("Failed to insert");
+        }
+        assert_eq!(versions(&db), 1);
+
+        let snapshot = db.snapshot();
+        for i in 0..10 {
+            db.insert(&1, &i.to_string()).expect("Failed to insert");
+        }
+        assert_eq!(versions(&db), 2);
+        assert_eq!(snapshot.get(&db, &1).unwrap(), Some("9".to_string()));
+
+        // Versions kept for a snapshot are dropped with it, without another write to the key
+        drop(snapshot);
+        assert_eq!(versions(&db), 1);
+        assert!(db.db.state.read().unwrap().retained.is_empty());
+    }
+
+    #[test]
+    fn test_deletions_are_dropped() {
+        let db: TestDB<i32, String> = TestDB::open();
+        let versions = |db: &TestDB<i32, String>| {
+            let state = db.db.state.read().unwrap();
+            state.tables[&db.name].values().map(Vec::len).sum::<usize>()
+        };
+        db.multi_insert((0..10).map(|i| (i, i.to_string())))
+            .expect("Failed to multi-insert");
+
+        // Without snapshots, deleted keys are forgotten right away
+        db.multi_remove(0..5).expect("Failed to multi-remove");
+        assert_eq!(versions(&db), 5);
+
+        // Deletions are kept while a transaction started before them can conflict with them
+        let tx = db.transaction().expect("Failed to start transaction");
+        db.multi_remove(5..10).expect("Failed to multi-remove");
+        assert!(db.is_empty());
+        assert_eq!(versions(&db), 10);
+        tx.get_for_update(&db, &5).unwrap();
+        assert!(matches!(
+            tx.commit(),
+            Err(TypedStoreError::RetryableTransactionError)
+        ));
+        assert_eq!(versions(&db), 0);
+    }
+
+    #[test]
+    fn test_transaction() {
+        let db = InMemoryDB::new();
+        let table_1: TestDB<i32, String> = TestDB::reopen(&db, "table_1");
+        let table_2: TestDB<i32, String> = TestDB::reopen(&db, "table_2");
+        table_1
+            .multi_insert([(1, "1".to_string()), (2, "2".to_string())])
+            .expect("Failed to multi-insert");
+
+        let mut tx = table_1.transaction().expect("Failed to start transaction");
+        tx.insert_batch(&table_1, [(3, "3".to_string())])
+            .expect("Failed to insert")
+            .delete_batch(&table_1, [1])
+            .expect("Failed to delete")
+            .insert_batch(&table_2, [(1, "a".to_string())])
+            .expect("Failed to insert");
+
+        assert_eq!(tx.get(&table_1, &3).unwrap(), Some("3".to_string()));
+        assert_eq!(tx.get(&table_1, &1).unwrap(), None);
+        assert_eq!(tx.get(&table_1, &2).unwrap(), Some("2".to_string()));
+        let keys: Vec<_> = tx.keys(&table_1).unwrap().map(|k| k.unwrap()).collect();
+        assert_eq!(keys, [2, 3]);
+        let values: Vec<_> = tx.values(&table_2).unwrap().map(|v| v.unwrap()).collect();
+        assert_eq!(values, ["a".to_string()]);
+
+        // The writes of the transaction are only visible once committed
+        assert!(table_1.contains_key(&1).unwrap());
+        assert!(table_2.is_empty());
+        tx.commit().expect("Failed to commit");
+        let keys: Vec<_> = table_1.keys().map(|k| k.unwrap()).collect();
+        assert_eq!(keys, [2, 3]);
+        assert_eq!(table_2.get(&1).unwrap(), Some("a".to_string()));
+
+        let other: TestDB<i32, String> = TestDB::open();
+        let mut tx = table_1.transaction().expect("Failed to start transaction");
+        assert!(matches!(
+            tx.insert_batch(&other, [(1, "1".to_string())]),
+            Err(TypedStoreError::CrossDBBatch)
+        ));
+    }
+
+    #[test]
+    fn test_transaction_conflicts() {
+        let db: TestDB<i32, String> = TestDB::open();
+        db.insert(&1, &"1".to_string()).expect("Failed to insert");
+
+        // A key read for update and written by someone else fails the commit
+        let mut tx = db.transaction().expect("Failed to start transaction");
+        let value = tx.get_for_update(&db, &1).unwrap();
+        assert_eq!(value, Some("1".to_string()));
+        tx.insert_batch(&db, [(2, "2".to_string())])
+            .expect("Failed to insert");
+        db.insert(&1, &"one".to_string()).expect("Failed to insert");
+        assert!(matches!(
+            tx.commit(),
+            Err(TypedStoreError::RetryableTransactionError)
+        ));
+        assert!(!db.contains_key(&2).unwrap());
+
+        // So does a key written by both, including deletions
+        let mut tx = db.transaction().expect("Failed to start transaction");
+        tx.insert_batch(&db, [(1, "uno".to_string())])
+            .expect("Failed to insert");
+        db.remove(&1).expect("Failed to remove");
+        assert!(matches!(
+            tx.commit(),
+            Err(TypedStoreError::RetryableTransactionError)
+        ));
+
+        // Writes to other keys don't conflict
+        let mut tx = db.transaction().expect("Failed to start transaction");
+        tx.get_for_update(&db, &1).unwrap();
+        tx.insert_batch(&db, [(1, "1".to_string())])
+            .expect("Failed to insert");
+        db.insert(&3, &"3".to_string()).expect("Failed to insert");
+        tx.commit().expect("Failed to commit");
+        assert_eq!(db.get(&1).unwrap(), Some("1".to_string()));
+
+        // With a snapshot, writes since the start of the transaction conflict, while without one
+        // only writes since the key was first accessed do
+        let tx = db.transaction().expect("Failed to start transaction");
+        let tx_without_snapshot = db
+            .transaction_without_snapshot()
+            .expect("Failed to start transaction");
+        db.insert(&1, &"one".to_string()).expect("Failed to insert");
+        tx.get_for_update(&db, &1).unwrap();
+        tx_without_snapshot.get_for_update(&db, &1).unwrap();
+        assert!(matches!(
+            tx.commit(),
+            Err(TypedStoreError::RetryableTransactionError)
+        ));
+        tx_without_snapshot.commit().expect("Failed to commit");
+    }
 }
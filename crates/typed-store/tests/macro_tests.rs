@@ -381,6 +381,58 @@ struct TablesMemUsage {
     table4: DBMap<i32, String>,
 }
 
+#[tokio::test]
+async fn macro_migrations_test() {
+    use typed_store::rocks::migration::{Migration, MigrationRegistry, SchemaVersion};
+
+    let primary_path = temp_dir();
+    let tbls_primary =
+        Tables::open_tables_read_write(primary_path.clone(), MetricConf::default(), None, None);
+    for i in 0..10 {
+        tbls_primary.table2.insert(&i, &i.to_string()).unwrap();
+    }
+    let mut migrations = MigrationRegistry::default();
+    migrations.register(Migration::typed(
+        "table2",
+        0,
+        "delete odd values",
+        |key: i32, value: String| (key % 2 == 0).then_some(value),
+    ));
+
+    // A dry run through a read only handle reports the changes without making them.
+    let tbls_secondary =
+        Tables::get_read_only_handle(primary_path.clone(), None, None, MetricConf::default());
+    let reports = tbls_secondary.dry_run_migrations(&migrations).unwrap();
+    assert_eq!((reports[0].scanned, reports[0].deleted), (10, 5));
+    assert_eq!(
+        tbls_secondary.schema_versions().unwrap()["table2"],
+        SchemaVersion::default()
+    );
+    assert_eq!(tbls_primary.table2.keys().count(), 10);
+
+    let reports = tbls_primary.run_migrations(&migrations, false).unwrap();
+    assert_eq!((reports[0].scanned, reports[0].deleted), (10, 5));
+    assert_eq!(tbls_primary.schema_versions().unwrap()["table2"].version, 1);
+    assert_eq!(tbls_primary.table2.keys().count(), 5);
+
+    // Failed migrations are returned to the caller.
+    let mut missing_migration = MigrationRegistry::default();
+    missing_migration.register(Migration::typed(
+        "table1",
+        1,
+        "migration without its predecessor",
+        |_key: String, value: String| Some(value),
+    ));
+    assert!(Tables::open_tables_read_write_with_migrations(
+        temp_dir(),
+        MetricConf::default(),
+        None,
+        None,
+        &missing_migration,
+    )
+    .is_err());
+}
+
 #[tokio::test]
 async fn test_sampling() {
     let sampling_interval = SamplingInterval::new(Duration::ZERO, 10);